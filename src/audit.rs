@@ -0,0 +1,116 @@
+//! Reconciling local branches with Jira: branches left behind by finished issues, branches for
+//! issues Jira can't find, and issues in progress that nobody's started a branch for.
+use crate::{
+    git::{branch_names, get_current_repo},
+    jira::{IssueSummary, JiraClient, JiraError, JiraResult},
+    utils::is_issue_key,
+};
+use anyhow::Result;
+use futures::stream::{self, StreamExt};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Default)]
+pub struct Audit {
+    /// Branches whose issue is done, with the issue's status.
+    pub finished: Vec<(String, String)>,
+    /// Branches named after issues that Jira can't find.
+    pub untracked: Vec<String>,
+    /// Branches whose issue couldn't be looked up, with why.
+    pub failed: Vec<(String, String)>,
+    /// Issues assigned to you and in progress without a branch.
+    pub unstarted: Vec<IssueSummary>,
+}
+
+/// The local branches of the repo in the current directory.
+pub fn local_branches() -> Result<Vec<String>> {
+    branch_names(&get_current_repo()?)
+}
+
+/// The issue key a branch is named after, from the start of any of its `/` separated parts, so
+/// both `PROJ-12-fix-crash` and `platform/PROJ-12-fix-crash` give `PROJ-12`.
+pub fn branch_issue_key(branch: &str) -> Option<String> {
+    branch.split('/').find_map(|part| {
+        let (project, rest) = part.split_once('-')?;
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let key = format!("{}-{}", project, &rest[..digits]);
+        Some(key).filter(|key| is_issue_key(key))
+    })
+}
+
+/// Look up the issue behind each of the branches, and which of your issues in progress have none.
+/// The issues are looked up as many at a time as background requests run. A lookup failing is
+/// noted against its branches, other than Jira rejecting the credentials, which stops the audit.
+pub async fn audit(jira: &JiraClient, branches: Vec<String>) -> JiraResult<Audit> {
+    let mut by_key: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for branch in branches {
+        if let Some(key) = branch_issue_key(&branch) {
+            by_key.entry(key).or_default().push(branch);
+        }
+    }
+
+    let lookups: Vec<_> = by_key.keys().map(|key| jira.at(key).issue_status(key)).collect();
+    let statuses: Vec<_> = stream::iter(lookups)
+        .buffered(jira.tasks().parallelism())
+        .collect()
+        .await;
+    let mut audit = Audit::default();
+    for (branches, status) in by_key.values().zip(statuses) {
+        match status {
+            Ok((status, true)) => {
+                let finished = branches
+                    .iter()
+                    .map(|branch| (branch.clone(), status.clone()));
+                audit.finished.extend(finished);
+            }
+            Ok((_, false)) => {}
+            Err(JiraError::NotFound) => audit.untracked.extend(branches.iter().cloned()),
+            Err(JiraError::Unauthorized) => return Err(JiraError::Unauthorized),
+            Err(err) => {
+                let failed = branches
+                    .iter()
+                    .map(|branch| (branch.clone(), err.to_string()));
+                audit.failed.extend(failed);
+            }
+        }
+    }
+
+    let in_progress = jira.my_issues_in_progress().await?;
+    audit.unstarted = in_progress
+        .into_iter()
+        .filter(|issue| !by_key.contains_key(&issue.key))
+        .collect();
+    Ok(audit)
+}
+
+/// The audit as lines of plain text, a heading for each kind of mismatch found.
+pub fn report_lines(audit: &Audit) -> Vec<String> {
+    let mut lines = vec![];
+    if !audit.finished.is_empty() {
+        lines.push("Branches for finished issues:".to_string());
+        for (branch, status) in &audit.finished {
+            lines.push(format!("  {} ({})", branch, status));
+        }
+    }
+    if !audit.untracked.is_empty() {
+        lines.push("Branches for issues Jira can't find:".to_string());
+        for branch in &audit.untracked {
+            lines.push(format!("  {}", branch));
+        }
+    }
+    if !audit.failed.is_empty() {
+        lines.push("Branches whose issue couldn't be looked up:".to_string());
+        for (branch, reason) in &audit.failed {
+            lines.push(format!("  {} ({})", branch, reason));
+        }
+    }
+    if !audit.unstarted.is_empty() {
+        lines.push("Issues in progress without a branch:".to_string());
+        for issue in &audit.unstarted {
+            lines.push(format!("  {} {}", issue.key, issue.summary));
+        }
+    }
+    if lines.is_empty() {
+        lines.push("Nothing to tidy up, every branch and issue in progress match.".to_string());
+    }
+    lines
+}
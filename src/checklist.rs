@@ -0,0 +1,62 @@
+//! Checklists written as bulleted checkboxes, e.g. `* [x] Write the docs`, either in an issue's
+//! description or in a text-based checklist custom field.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChecklistItem {
+    pub checked: bool,
+    pub text: String,
+    /// The line of the source text the item was read from.
+    line: usize,
+}
+
+/// Find the checkbox in a line, returning the byte offset of the mark between the brackets.
+fn checkbox_offset(line: &str) -> Option<usize> {
+    let bullet = line.trim_start();
+    let indent = line.len() - bullet.len();
+    let rest = bullet.trim_start_matches(['-', '*', '#']);
+    if rest.len() == bullet.len() {
+        return None;
+    }
+    let rest_trimmed = rest.trim_start();
+    let offset = indent + (bullet.len() - rest_trimmed.len());
+    let bytes = rest_trimmed.as_bytes();
+    if bytes.len() >= 3 && bytes[0] == b'[' && bytes[2] == b']' && b" xX".contains(&bytes[1]) {
+        Some(offset + 1)
+    } else {
+        None
+    }
+}
+
+pub fn parse_checklist(text: &str) -> Vec<ChecklistItem> {
+    text.lines()
+        .enumerate()
+        .filter_map(|(line, content)| {
+            let offset = checkbox_offset(content)?;
+            Some(ChecklistItem {
+                checked: content.as_bytes()[offset] != b' ',
+                text: content[offset + 2..].trim().to_string(),
+                line,
+            })
+        })
+        .collect()
+}
+
+/// Flip the checkbox of an item, leaving the rest of the text untouched.
+pub fn toggle_item(text: &str, item: &ChecklistItem) -> String {
+    let mark = if item.checked { " " } else { "x" };
+    let toggled: Vec<String> = text
+        .lines()
+        .enumerate()
+        .map(|(line, content)| match checkbox_offset(content) {
+            Some(offset) if line == item.line => {
+                format!("{}{}{}", &content[..offset], mark, &content[offset + 1..])
+            }
+            _ => content.to_string(),
+        })
+        .collect();
+    let mut toggled = toggled.join("\n");
+    if text.ends_with('\n') {
+        toggled.push('\n');
+    }
+    toggled
+}
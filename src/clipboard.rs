@@ -0,0 +1,58 @@
+use crate::utils::extract_issue_key;
+use anyhow::{bail, Result};
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Commands that print the clipboard contents to stdout, tried in order.
+const PASTE_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbpaste", &[]),
+    ("wl-paste", &["--no-newline"]),
+    ("xclip", &["-selection", "clipboard", "-o"]),
+    ("xsel", &["--clipboard", "--output"]),
+    ("powershell", &["-NoProfile", "-Command", "Get-Clipboard"]),
+];
+
+/// Commands that copy their stdin to the clipboard, tried in order.
+const COPY_COMMANDS: &[(&str, &[&str])] = &[
+    ("pbcopy", &[]),
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+    ("clip", &[]),
+];
+
+/// Read the system clipboard by shelling out to whichever paste tool is installed.
+pub fn read_clipboard() -> Option<String> {
+    PASTE_COMMANDS.iter().find_map(|(cmd, args)| {
+        let output = Command::new(cmd).args(*args).output().ok()?;
+        if output.status.success() {
+            String::from_utf8(output.stdout).ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// The issue key referenced by the clipboard, if it holds a key or a Jira link.
+pub fn issue_key_from_clipboard() -> Option<String> {
+    read_clipboard().and_then(|contents| extract_issue_key(&contents))
+}
+
+/// Copy text to the system clipboard with whichever copy tool is installed.
+pub fn write_clipboard(text: &str) -> Result<()> {
+    for (cmd, args) in COPY_COMMANDS {
+        let mut child = match Command::new(cmd).args(*args).stdin(Stdio::piped()).spawn() {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+        if let Some(stdin) = child.stdin.as_mut() {
+            stdin.write_all(text.as_bytes())?;
+        }
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+    bail!("Couldn't find a clipboard tool, install xclip, xsel or wl-clipboard")
+}
@@ -0,0 +1,131 @@
+//! Commands that run without starting the TUI.
+use crate::{
+    audit::{audit as audit_branches, local_branches, report_lines},
+    config::{config_file_path, load_config, save_config, FieldMap},
+    jira::JiraClient,
+};
+use anyhow::Result;
+use crossterm::tty::IsTty;
+use std::io::stdout;
+
+/// Print suggested custom field mappings, saving them over any missing ones with `--save`.
+/// With `--dry-run` the merged mappings are printed instead of saved.
+pub async fn discover_fields(jira: &JiraClient, save: bool, dry_run: bool) -> Result<()> {
+    let suggested = jira.discover_field_map().await?;
+    let mut config = load_config();
+
+    println!("Suggested field mappings:");
+    print_field("story_points", &suggested.story_points, &config.field_map.story_points);
+    print_field("epic_link", &suggested.epic_link, &config.field_map.epic_link);
+    print_field("sprint", &suggested.sprint, &config.field_map.sprint);
+    print_field("team", &suggested.team, &config.field_map.team);
+    print_field("checklist", &suggested.checklist, &config.field_map.checklist);
+
+    if save {
+        config.field_map = merge_field_maps(config.field_map, suggested);
+        if dry_run {
+            println!(
+                "Would save field_map to {}: {}",
+                config_file_path()?.display(),
+                serde_json::to_string(&config.field_map)?
+            );
+        } else {
+            save_config(&config)?;
+            println!("Saved missing mappings to the config file.");
+        }
+    } else {
+        println!("Run again with --save to store any missing mappings.");
+    }
+
+    Ok(())
+}
+
+fn print_field(name: &str, suggested: &Option<String>, current: &Option<String>) {
+    let suggested = suggested.as_deref().unwrap_or("not found");
+    match current {
+        Some(current) => println!("  {}: {} (currently {})", name, suggested, current),
+        None => println!("  {}: {}", name, suggested),
+    }
+}
+
+/// Mappings that were already configured win over suggestions.
+fn merge_field_maps(current: FieldMap, suggested: FieldMap) -> FieldMap {
+    FieldMap {
+        story_points: current.story_points.or(suggested.story_points),
+        epic_link: current.epic_link.or(suggested.epic_link),
+        sprint: current.sprint.or(suggested.sprint),
+        team: current.team.or(suggested.team),
+        checklist: current.checklist.or(suggested.checklist),
+    }
+}
+
+/// Print an issue and its comments as plain text, for reading in a pager. Headings are bold when
+/// writing to a terminal, or with `--color` for pagers that pass colors through like `less -R`.
+pub async fn show_issue(jira: &JiraClient, key: String, color: bool) -> Result<()> {
+    let comments = jira.get_comments(&key).await?;
+    let issue = jira.get_issue(key).await?;
+    let bold = |text: &str| {
+        if color || stdout().is_tty() {
+            format!("\x1b[1m{}\x1b[0m", text)
+        } else {
+            text.to_string()
+        }
+    };
+
+    println!("{}", bold(&format!("{}: {}", issue.key, issue.summary)));
+    println!("Status:   {}", issue.status);
+    println!("Assignee: {}", issue.assignee_name);
+    if let Some(priority) = &issue.priority {
+        println!("Priority: {}", priority);
+    }
+    println!("Link:     {}", issue.permalink);
+
+    println!();
+    println!("{}", bold("Description"));
+    if issue.description.trim().is_empty() {
+        println!("  No description given");
+    } else {
+        print_indented(&issue.description);
+    }
+
+    println!();
+    println!("{}", bold(&format!("Comments ({})", comments.len())));
+    for comment in comments {
+        println!();
+        println!("{} on {}", comment.author, comment.created);
+        print_indented(&comment.body);
+    }
+    Ok(())
+}
+
+/// Print the local branches left behind by finished issues or named after issues Jira can't find,
+/// and your issues in progress without a branch.
+pub async fn audit(jira: &JiraClient) -> Result<()> {
+    let branches = local_branches()?;
+    let audit = audit_branches(jira, branches).await?;
+    for line in report_lines(&audit) {
+        println!("{}", line);
+    }
+    Ok(())
+}
+
+/// List the requests `--dry-run` held back, after whatever the command printed.
+pub fn print_unsent(jira: &JiraClient) {
+    let unsent = jira.take_unsent();
+    if !unsent.is_empty() {
+        println!("Not sent, as this is a dry run:");
+        for request in unsent {
+            println!("  {}", request);
+        }
+    }
+}
+
+fn print_indented(text: &str) {
+    for line in text.trim_end().lines() {
+        if line.is_empty() {
+            println!();
+        } else {
+            println!("  {}", line);
+        }
+    }
+}
@@ -1,53 +1,483 @@
-use crate::APP_INFO;
+use crate::{
+    locale::Locale,
+    platform::{strip_bom, with_line_endings},
+    tasks::DEFAULT_PARALLELISM,
+    APP_INFO,
+};
 use anyhow::Result;
 use app_dirs::*;
-use std::{fs::File, io::BufReader, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
 
 const CONFIG_FILE_NAME: &str = "config.json";
+/// How many recently used projects are offered in the project switcher.
+const RECENT_PROJECTS: usize = 5;
 
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Config {
+    /// Used when `JIRA_HOST` isn't set.
+    #[serde(default)]
+    pub jira_host: Option<String>,
+    /// Used when `JIRA_USER` isn't set, before falling back to the login in `~/.netrc`.
+    #[serde(default)]
+    pub jira_user: Option<String>,
+    /// A shell command that prints the API token, e.g. `pass show jira` or `op read ...`.
+    /// Used when `JIRA_PASS` isn't set, before falling back to the password in `~/.netrc`.
+    #[serde(default)]
+    pub credential_command: Option<String>,
+    /// A shell command that's given a replacement token on stdin after re-authenticating,
+    /// e.g. `pass insert -m jira`. Without it, new tokens only last for the session.
+    #[serde(default)]
+    pub credential_store_command: Option<String>,
+    /// Other Jira instances whose issues are listed alongside the main host's, e.g. a client's.
+    #[serde(default)]
+    pub extra_hosts: Vec<ExtraHost>,
     pub default_project_key: String,
     pub filter_in_progress: bool,
     pub filter_mine: bool,
+    /// Ignore `default_project_key` and search every project the user can see.
+    #[serde(default)]
+    pub search_all_projects: bool,
+    /// A JQL query typed in with `/`, the issue list searches for it instead of building a query
+    /// from the filters above. Kept until it's cleared, so it's offered to edit again next time.
+    #[serde(default)]
+    pub jql: Option<String>,
+    #[serde(default)]
+    pub field_map: FieldMap,
+    #[serde(default)]
+    pub sort_preset: SortPreset,
+    /// List issues under a header for their epic, issues without one coming last.
+    #[serde(default)]
+    pub group_by_epic: bool,
+    /// Named setups of the issue list to switch between, saved from the app with `v`.
+    #[serde(default)]
+    pub views: Vec<SavedView>,
+    /// Color issue keys by how severe their priority is.
+    #[serde(default)]
+    pub priority_colors: bool,
+    /// Issue keys whose changes are left out of the activity feed.
+    #[serde(default)]
+    pub muted_issues: Vec<String>,
+    /// Changed fields (e.g. `Comment` or `Rank`) left out of the activity feed.
+    #[serde(default)]
+    pub muted_event_types: Vec<String>,
+    /// How often the issue list refreshes in the background, 0 turns it off. This is stretched
+    /// out automatically when Jira reports the rate limit budget running low.
+    #[serde(default = "default_poll_interval")]
+    pub poll_interval_secs: u64,
+    /// How many requests to Jira run in the background at once. Any more wait their turn, and
+    /// how many are waiting is shown in the status bar.
+    #[serde(default = "default_parallel_requests")]
+    pub parallel_requests: usize,
+    /// Template for PR descriptions copied from an issue, see `template::pr_description`.
+    #[serde(default)]
+    pub pr_template: Option<String>,
+    /// Template for new branch names, e.g. `feature/{key}-{slug}`. Defaults to `{key}-{name}`
+    /// where `name` is what's typed in.
+    #[serde(default)]
+    pub branch_template: Option<String>,
+    /// Length of a pomodoro, the timer suggests a break once it's run this long.
+    #[serde(default)]
+    pub pomodoro_minutes: Option<u64>,
+    /// Draw with bold, underline and reverse video instead of colors, as `NO_COLOR` does.
+    #[serde(default)]
+    pub monochrome: bool,
+    /// Language of the UI's own text, `en`, `de` or `es`.
+    #[serde(default)]
+    pub locale: Locale,
+    /// Keys in the issue list that make a transition straight away, skipping the picker. Names
+    /// are looked up among the selected issue's transitions when the key is pressed.
+    #[serde(default = "default_transition_shortcuts")]
+    pub transition_shortcuts: BTreeMap<char, String>,
+    /// Issue list actions moved to other keys, by name, e.g. `"toggle_mine": "u"`. Keys are
+    /// single characters or names like `Enter` and `Tab`, see `action::parse_key`.
+    #[serde(default)]
+    pub keys: BTreeMap<String, String>,
+    /// Names of transitions the transition picker asks about before making, e.g. `Done` or
+    /// `Won't Do`, for those that are awkward to take back.
+    #[serde(default)]
+    pub confirm_transitions: Vec<String>,
+    /// IDs of boards starred in the boards view, they're listed first.
+    #[serde(default)]
+    pub favorite_boards: Vec<u64>,
+    /// The board the issue list is scoped to, chosen in the boards view.
+    #[serde(default)]
+    pub default_board: Option<SavedBoard>,
+    /// Project keys switched to, most recent first.
+    #[serde(default)]
+    pub recent_projects: Vec<String>,
+    /// Project keys mapped to what their branch names start with, e.g. `"PLAT": "platform/"`,
+    /// for repos with their own naming rules. See `template::branch_name`.
+    #[serde(default)]
+    pub branch_prefixes: BTreeMap<String, String>,
+    /// Project keys mapped to the directory their code lives in, relative to the root of a
+    /// monorepo. It's suggested as the place to work after checking out a branch.
+    #[serde(default)]
+    pub project_dirs: BTreeMap<String, String>,
+    /// Run the repo's post-checkout hook from the project's directory after checking out a
+    /// branch. Checking out from the app doesn't run hooks otherwise.
+    #[serde(default)]
+    pub run_post_checkout_hook: bool,
+    /// Project keys mapped to the repo their code lives in, for teams with a repo per project.
+    /// Branches for an issue are listed, created and checked out there instead of in the repo the
+    /// app was started from.
+    #[serde(default)]
+    pub project_repos: BTreeMap<String, String>,
+    /// Project keys mapped to the URL their repo is cloned from, e.g.
+    /// `"PLAT": "git@github.com:acme/platform.git"`. When the repo isn't there yet, it's offered
+    /// to be cloned before making a branch.
+    #[serde(default)]
+    pub project_remotes: BTreeMap<String, String>,
+    /// Where repos from `project_remotes` are cloned to, for projects without a path in
+    /// `project_repos`. Each goes in a directory named after the repo.
+    #[serde(default)]
+    pub workspace_dir: Option<String>,
+    /// Where attachments are downloaded to, `Downloads` in the home directory by default.
+    #[serde(default)]
+    pub download_dir: Option<String>,
+    /// Start new branches off with an empty `PROJ-123: start work` commit, so they can be pushed
+    /// and a draft PR opened straight away.
+    #[serde(default)]
+    pub initial_empty_commit: bool,
+    /// Where to listen for Jira webhooks, e.g. `127.0.0.1:8089`, to pick up changes as they
+    /// happen rather than at the next poll.
+    #[serde(default)]
+    pub webhook_address: Option<String>,
+    /// Webhooks are only acted on when they carry this, as `?secret=...` on the URL Jira is
+    /// given or in an `X-Webhook-Secret` header. Listening doesn't start without it.
+    #[serde(default)]
+    pub webhook_secret: Option<String>,
+    /// A shell command that prints a GitHub token for opening PRs, used when `GITHUB_TOKEN`
+    /// isn't set.
+    #[serde(default)]
+    pub github_token_command: Option<String>,
+    /// The API of a GitHub Enterprise server, `https://api.github.com` by default.
+    #[serde(default)]
+    pub github_api_url: Option<String>,
+    /// Open PRs as drafts. Either way it can be flipped with `d` before opening each one.
+    #[serde(default)]
+    pub draft_pull_requests: bool,
+    /// GitHub logins asked to review every PR opened from the app. Without any, the issue's
+    /// watchers are asked instead, those with a login in `github_logins`.
+    #[serde(default)]
+    pub default_reviewers: Vec<String>,
+    /// People's names in Jira and git mapped to their GitHub logins, e.g. `"Ada Lovelace": "ada"`,
+    /// so reviewers picked or watching the issue can be asked to review its PR.
+    #[serde(default)]
+    pub github_logins: BTreeMap<String, String>,
+}
+
+/// The project part of an issue key, e.g. `PROJ` for `PROJ-123`.
+pub fn project_of(key: &str) -> &str {
+    key.split('-').next().unwrap_or(key)
+}
+
+/// The name of the repo a clone URL points at, e.g. `platform` for
+/// `git@github.com:acme/platform.git`.
+fn repo_name(url: &str) -> &str {
+    let url = url.trim_end_matches('/');
+    let name = url.rsplit(['/', ':']).next().unwrap_or(url);
+    name.trim_end_matches(".git")
+}
+
+fn default_poll_interval() -> u64 {
+    120
+}
+
+fn default_parallel_requests() -> usize {
+    DEFAULT_PARALLELISM
+}
+
+fn default_transition_shortcuts() -> BTreeMap<char, String> {
+    [
+        ('1', "To Do"),
+        ('2', "In Progress"),
+        ('3', "In Review"),
+        ('4', "Done"),
+    ]
+    .iter()
+    .map(|&(key, name)| (key, name.to_string()))
+    .collect()
+}
+
+/// Another Jira instance to list issues from. Its issues are searched across all its projects
+/// with the same mine/in progress filters as the main host's.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ExtraHost {
+    pub host: String,
+    /// Shown next to the host's issues, defaults to its host name.
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Falls back to the login for the host in `~/.netrc`.
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Falls back to the password for the host in `~/.netrc`.
+    #[serde(default)]
+    pub credential_command: Option<String>,
+}
+
+/// A board remembered in the config, with its name for showing without fetching it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedBoard {
+    pub id: u64,
+    pub name: String,
+}
+
+/// The issue list's filters, sort order and layout saved under a name, e.g. a standup view of
+/// the board's issues grouped by epic or a triage view of prioritised issues.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedView {
+    pub name: String,
+    #[serde(default)]
+    pub default_project_key: String,
+    #[serde(default)]
+    pub search_all_projects: bool,
+    #[serde(default)]
+    pub default_board: Option<SavedBoard>,
+    #[serde(default)]
+    pub filter_mine: bool,
+    #[serde(default)]
+    pub filter_in_progress: bool,
+    #[serde(default)]
+    pub jql: Option<String>,
+    #[serde(default)]
+    pub sort_preset: SortPreset,
+    #[serde(default)]
+    pub group_by_epic: bool,
+    #[serde(default)]
+    pub priority_colors: bool,
+}
+
+/// How the issue list is ordered once it's fetched.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum SortPreset {
+    /// Whatever order Jira returned.
+    #[default]
+    Jira,
+    /// Most severe priority first, oldest first within a priority.
+    Triage,
+}
+
+/// IDs of the custom fields that hold commonly used values, these differ between Jira instances.
+/// Run `jira discover-fields` to find suggestions for the current instance.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq)]
+pub struct FieldMap {
+    pub story_points: Option<String>,
+    pub epic_link: Option<String>,
+    pub sprint: Option<String>,
+    pub team: Option<String>,
+    /// A text-based checklist field, where items are written as `* [x] Done`.
+    #[serde(default)]
+    pub checklist: Option<String>,
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
+            jira_host: None,
+            jira_user: None,
+            credential_command: None,
+            credential_store_command: None,
+            extra_hosts: vec![],
             default_project_key: "".to_string(),
             filter_in_progress: true,
             filter_mine: true,
+            search_all_projects: false,
+            jql: None,
+            field_map: FieldMap::default(),
+            sort_preset: SortPreset::default(),
+            group_by_epic: false,
+            views: vec![],
+            priority_colors: false,
+            muted_issues: vec![],
+            muted_event_types: vec![],
+            poll_interval_secs: default_poll_interval(),
+            parallel_requests: default_parallel_requests(),
+            pr_template: None,
+            branch_template: None,
+            pomodoro_minutes: None,
+            monochrome: false,
+            locale: Locale::default(),
+            transition_shortcuts: default_transition_shortcuts(),
+            keys: BTreeMap::new(),
+            confirm_transitions: vec![],
+            favorite_boards: vec![],
+            default_board: None,
+            recent_projects: vec![],
+            branch_prefixes: BTreeMap::new(),
+            project_dirs: BTreeMap::new(),
+            run_post_checkout_hook: false,
+            project_repos: BTreeMap::new(),
+            project_remotes: BTreeMap::new(),
+            workspace_dir: None,
+            download_dir: None,
+            initial_empty_commit: false,
+            webhook_address: None,
+            webhook_secret: None,
+            github_token_command: None,
+            github_api_url: None,
+            draft_pull_requests: false,
+            default_reviewers: vec![],
+            github_logins: BTreeMap::new(),
+        }
+    }
+}
+
+impl Config {
+    /// The project key searches should be scoped to, if any.
+    pub fn project_scope(&self) -> Option<&str> {
+        if self.search_all_projects || self.default_project_key.is_empty() {
+            None
+        } else {
+            Some(&self.default_project_key)
+        }
+    }
+
+    /// Make `key` the default project, remembering it as the most recently used.
+    pub fn switch_project(&mut self, key: String) {
+        self.recent_projects.retain(|recent| *recent != key);
+        self.recent_projects.insert(0, key.clone());
+        self.recent_projects.truncate(RECENT_PROJECTS);
+        self.default_project_key = key;
+    }
+
+    /// The issue list as it's set up now, to save under the given name.
+    pub fn current_view(&self, name: String) -> SavedView {
+        SavedView {
+            name,
+            default_project_key: self.default_project_key.clone(),
+            search_all_projects: self.search_all_projects,
+            default_board: self.default_board.clone(),
+            filter_mine: self.filter_mine,
+            filter_in_progress: self.filter_in_progress,
+            jql: self.jql.clone(),
+            sort_preset: self.sort_preset,
+            group_by_epic: self.group_by_epic,
+            priority_colors: self.priority_colors,
+        }
+    }
+
+    /// Set the issue list up as it was when the view was saved.
+    pub fn apply_view(&mut self, view: &SavedView) {
+        self.default_project_key = view.default_project_key.clone();
+        self.search_all_projects = view.search_all_projects;
+        self.default_board = view.default_board.clone();
+        self.filter_mine = view.filter_mine;
+        self.filter_in_progress = view.filter_in_progress;
+        self.jql = view.jql.clone();
+        self.sort_preset = view.sort_preset;
+        self.group_by_epic = view.group_by_epic;
+        self.priority_colors = view.priority_colors;
+    }
+
+    /// Save a view, replacing the one with the same name if there is one.
+    pub fn save_view(&mut self, view: SavedView) {
+        match self.views.iter_mut().find(|saved| saved.name == view.name) {
+            Some(saved) => *saved = view,
+            None => self.views.push(view),
+        }
+    }
+
+    /// Whether the issue list is set up the way the view would set it up.
+    pub fn is_current_view(&self, view: &SavedView) -> bool {
+        self.current_view(view.name.clone()) == *view
+    }
+
+    /// What branch names for the issue with the given key start with, from `branch_prefixes`.
+    pub fn branch_prefix(&self, key: &str) -> &str {
+        let prefix = self.branch_prefixes.get(project_of(key));
+        prefix.map_or("", String::as_str)
+    }
+
+    /// Where in the repo work on the issue with the given key happens, from `project_dirs`.
+    pub fn project_dir(&self, key: &str) -> Option<&str> {
+        self.project_dirs.get(project_of(key)).map(String::as_str)
+    }
+
+    /// The repo branches for the issue with the given key belong in, from `project_repos` or
+    /// else where its remote would be cloned to in the `workspace_dir`.
+    pub fn project_repo(&self, key: &str) -> Option<PathBuf> {
+        let project = project_of(key);
+        if let Some(path) = self.project_repos.get(project) {
+            return Some(PathBuf::from(path));
+        }
+        let remote = self.project_remotes.get(project)?;
+        let workspace = self.workspace_dir.as_ref()?;
+        Some(Path::new(workspace).join(repo_name(remote)))
+    }
+
+    /// Where to save downloaded attachments, falling back to the current directory when there's
+    /// no home directory to find `Downloads` in.
+    pub fn download_dir(&self) -> PathBuf {
+        if let Some(dir) = &self.download_dir {
+            return PathBuf::from(dir);
         }
+        match env::var("HOME").or_else(|_| env::var("USERPROFILE")) {
+            Ok(home) => Path::new(&home).join("Downloads"),
+            Err(_) => PathBuf::from("."),
+        }
+    }
+
+    /// The URL the repo for the issue with the given key is cloned from, from `project_remotes`.
+    pub fn project_remote(&self, key: &str) -> Option<&str> {
+        self.project_remotes
+            .get(project_of(key))
+            .map(String::as_str)
+    }
+
+    /// Whether an activity entry has been muted, by its issue or by the kind of change.
+    pub fn is_muted(&self, key: &str, event_type: &str) -> bool {
+        self.muted_issues.iter().any(|muted| muted == key)
+            || self
+                .muted_event_types
+                .iter()
+                .any(|muted| muted.eq_ignore_ascii_case(event_type))
     }
 }
 
-fn config_file_path() -> Result<PathBuf> {
-    let mut path = app_root(AppDataType::UserConfig, &APP_INFO)?;
-    path.push(CONFIG_FILE_NAME);
+/// The path of a file stored alongside the config.
+pub fn app_file_path(file_name: &str) -> Result<PathBuf> {
+    let mut path = app_dir()?;
+    path.push(file_name);
     Ok(path)
 }
 
+fn app_dir() -> Result<PathBuf> {
+    // Tests keep whatever they save out of the real config directory
+    if cfg!(test) {
+        let dir = std::env::temp_dir().join("jira-tui-tests");
+        fs::create_dir_all(&dir)?;
+        Ok(dir)
+    } else {
+        Ok(app_root(AppDataType::UserConfig, &APP_INFO)?)
+    }
+}
+
+pub fn config_file_path() -> Result<PathBuf> {
+    app_file_path(CONFIG_FILE_NAME)
+}
+
 pub fn load_config() -> Config {
+    let _span = tracing::info_span!("load_config").entered();
     let path = match config_file_path() {
         Ok(p) => p,
         Err(_) => return Default::default(),
     };
-    let file = match File::open(path) {
-        Ok(f) => f,
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
         Err(_) => return Default::default(),
     };
-    let reader = BufReader::new(file);
 
     // Read the JSON contents of the file as an instance of `Config`.
-    match serde_json::from_reader(reader) {
-        Ok(c) => c,
-        Err(_) => Default::default(),
-    }
+    serde_json::from_str(strip_bom(&contents)).unwrap_or_default()
 }
 
+/// Saved pretty printed since the file is meant to be edited by hand too.
 pub fn save_config(config: &Config) -> Result<()> {
-    let file = File::create(config_file_path()?)?;
-    serde_json::to_writer(file, &config)?;
+    let contents = serde_json::to_string_pretty(&config)?;
+    fs::write(config_file_path()?, with_line_endings(&contents))?;
     Ok(())
 }
@@ -0,0 +1,302 @@
+//! Finding the host, user and token to connect to Jira with. Each piece is looked up in turn from
+//! env vars, the config file, a configured `credential_command` and `~/.netrc`. The token for
+//! opening PRs on GitHub is found here too.
+use crate::{
+    config::{Config, ExtraHost},
+    platform::shell_command,
+};
+use anyhow::{anyhow, bail, Result};
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::Stdio,
+};
+
+pub struct Credentials {
+    pub host: String,
+    pub user: String,
+    pub pass: String,
+    pub source: CredentialSource,
+}
+
+/// Where the token was found, which is also where a replacement token gets saved.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CredentialSource {
+    Env,
+    Command,
+    Netrc,
+}
+
+pub fn load_credentials(config: &Config) -> Result<Credentials> {
+    let host = env::var("JIRA_HOST")
+        .ok()
+        .or_else(|| config.jira_host.clone())
+        .ok_or_else(|| anyhow!("Missing Jira host, set JIRA_HOST or jira_host in the config"))?;
+    let netrc = netrc_entry(&host);
+
+    let user = env::var("JIRA_USER")
+        .ok()
+        .or_else(|| config.jira_user.clone())
+        .or_else(|| netrc.as_ref().and_then(|entry| entry.login.clone()));
+    let pass = match env::var("JIRA_PASS") {
+        Ok(pass) => Some((pass, CredentialSource::Env)),
+        Err(_) => match &config.credential_command {
+            Some(command) => Some((run_credential_command(command)?, CredentialSource::Command)),
+            None => netrc
+                .and_then(|entry| entry.password)
+                .map(|pass| (pass, CredentialSource::Netrc)),
+        },
+    };
+
+    match (user, pass) {
+        (Some(user), Some((pass, source))) => Ok(Credentials {
+            host,
+            user,
+            pass,
+            source,
+        }),
+        _ => Err(anyhow!(
+            "Missing Jira Credentials, set JIRA_USER and JIRA_PASS, configure a credential_command \
+             or add {} to ~/.netrc",
+            hostname(&host)
+        )),
+    }
+}
+
+/// Credentials for one of `Config::extra_hosts`. Env vars are left to the main host, so these
+/// come from the host's own config or `~/.netrc`.
+pub fn load_extra_credentials(extra: &ExtraHost) -> Result<Credentials> {
+    let netrc = netrc_entry(&extra.host);
+    let user = extra
+        .user
+        .clone()
+        .or_else(|| netrc.as_ref().and_then(|entry| entry.login.clone()));
+    let pass = match &extra.credential_command {
+        Some(command) => Some((run_credential_command(command)?, CredentialSource::Command)),
+        None => netrc
+            .and_then(|entry| entry.password)
+            .map(|pass| (pass, CredentialSource::Netrc)),
+    };
+
+    match (user, pass) {
+        (Some(user), Some((pass, source))) => Ok(Credentials {
+            host: extra.host.clone(),
+            user,
+            pass,
+            source,
+        }),
+        _ => Err(anyhow!(
+            "Missing Jira Credentials for {0}, set its user and credential_command in the config \
+             or add {0} to ~/.netrc",
+            hostname(&extra.host)
+        )),
+    }
+}
+
+/// Whether there's a GitHub token to open PRs with, without running the command to get it.
+pub fn has_github_token(config: &Config) -> bool {
+    env::var_os("GITHUB_TOKEN").is_some() || config.github_token_command.is_some()
+}
+
+/// The token to open PRs on GitHub with, from `GITHUB_TOKEN` or the `github_token_command`.
+pub fn load_github_token(config: &Config) -> Result<String> {
+    match env::var("GITHUB_TOKEN") {
+        Ok(token) => Ok(token),
+        Err(_) => match &config.github_token_command {
+            Some(command) => run_credential_command(command),
+            None => bail!("Set GITHUB_TOKEN or github_token_command in the config to open PRs"),
+        },
+    }
+}
+
+/// Save a replacement token back to where the old one came from. Returns false when there's
+/// nowhere to save it, e.g. env vars or a `credential_command` without a `credential_store_command`.
+pub fn store_token(
+    config: &Config,
+    source: CredentialSource,
+    host: &str,
+    user: &str,
+    token: &str,
+) -> Result<bool> {
+    match source {
+        CredentialSource::Env => Ok(false),
+        CredentialSource::Command => match &config.credential_store_command {
+            Some(command) => {
+                let mut child = shell_command(command)
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                if let Some(stdin) = child.stdin.as_mut() {
+                    writeln!(stdin, "{}", token)?;
+                }
+                if !child.wait()?.success() {
+                    bail!("credential_store_command failed");
+                }
+                Ok(true)
+            }
+            None => Ok(false),
+        },
+        CredentialSource::Netrc => {
+            let path = netrc_path().ok_or_else(|| anyhow!("Couldn't find ~/.netrc"))?;
+            let contents = fs::read_to_string(&path).unwrap_or_default();
+            fs::write(&path, replace_netrc_password(&contents, hostname(host), user, token))?;
+            Ok(true)
+        }
+    }
+}
+
+/// Run the configured command through the shell, its trimmed stdout is the token.
+fn run_credential_command(command: &str) -> Result<String> {
+    let output = shell_command(command).output()?;
+    if !output.status.success() {
+        bail!(
+            "credential_command failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    let token = String::from_utf8(output.stdout)?.trim().to_string();
+    if token.is_empty() {
+        bail!("credential_command printed nothing");
+    }
+    Ok(token)
+}
+
+/// The host name of a URL such as `https://yourorg.atlassian.net/`, without scheme or port.
+pub fn hostname(host: &str) -> &str {
+    let host = host.split("://").last().unwrap_or(host);
+    let host = host.split('/').next().unwrap_or(host);
+    host.split(':').next().unwrap_or(host)
+}
+
+#[derive(Default)]
+struct NetrcEntry {
+    login: Option<String>,
+    password: Option<String>,
+}
+
+fn netrc_path() -> Option<PathBuf> {
+    if let Ok(path) = env::var("NETRC") {
+        return Some(PathBuf::from(path));
+    }
+    let home = PathBuf::from(env::var("HOME").or_else(|_| env::var("USERPROFILE")).ok()?);
+    let path = home.join(".netrc");
+    // Windows tools tend to use `_netrc`, since dotfiles used to be awkward to create there
+    let windows_path = home.join("_netrc");
+    if cfg!(windows) && !path.exists() && windows_path.exists() {
+        return Some(windows_path);
+    }
+    Some(path)
+}
+
+fn netrc_entry(host: &str) -> Option<NetrcEntry> {
+    let contents = fs::read_to_string(netrc_path()?).ok()?;
+    parse_netrc(&contents, hostname(host))
+}
+
+/// Find the entry for `machine`, falling back to the `default` entry.
+fn parse_netrc(contents: &str, machine: &str) -> Option<NetrcEntry> {
+    let mut tokens = contents.split_whitespace();
+    let mut found: Option<NetrcEntry> = None;
+    let mut default: Option<NetrcEntry> = None;
+    // Whether the login/password tokens being read belong to `found` (true), `default` (false)
+    // or an entry we don't care about (None)
+    let mut current = None;
+
+    while let Some(token) = tokens.next() {
+        match token {
+            "machine" => {
+                let name = tokens.next()?;
+                current = if name == machine && found.is_none() {
+                    found = Some(NetrcEntry::default());
+                    Some(true)
+                } else {
+                    None
+                };
+            }
+            "default" => {
+                default = Some(NetrcEntry::default());
+                current = Some(false);
+            }
+            "login" | "password" | "account" => {
+                let value = tokens.next()?.to_string();
+                let entry = match current {
+                    Some(true) => found.as_mut(),
+                    Some(false) => default.as_mut(),
+                    None => None,
+                };
+                if let Some(entry) = entry {
+                    match token {
+                        "login" => entry.login = Some(value),
+                        "password" => entry.password = Some(value),
+                        _ => {}
+                    }
+                }
+            }
+            // Macros run until a blank line, which whitespace splitting loses, so stop here
+            "macdef" => break,
+            _ => {}
+        }
+    }
+
+    found.or(default)
+}
+
+/// Swap the password of `machine`'s entry for `token`, leaving the rest of the file as it was.
+/// An entry without a password has one added, and a new entry is added at the end if there's no
+/// entry at all.
+fn replace_netrc_password(contents: &str, machine: &str, login: &str, token: &str) -> String {
+    // Words paired with where they start, so the password can be replaced in place
+    let words: Vec<(usize, &str)> = contents
+        .split_whitespace()
+        .map(|word| (word.as_ptr() as usize - contents.as_ptr() as usize, word))
+        .collect();
+
+    let mut in_machine = false;
+    // Where the entry's last word ends, as that's where a missing password goes. Only the first
+    // entry for the machine is read, so later ones are left alone.
+    let mut entry_end = None;
+    for (i, (start, word)) in words.iter().enumerate() {
+        match *word {
+            "machine" | "default" | "macdef" if entry_end.is_some() => break,
+            "machine" => in_machine = words.get(i + 1).map(|(_, name)| *name) == Some(machine),
+            "default" | "macdef" => in_machine = false,
+            "password" if in_machine => {
+                if let Some((start, old)) = words.get(i + 1) {
+                    return format!(
+                        "{}{}{}",
+                        &contents[..*start],
+                        token,
+                        &contents[start + old.len()..]
+                    );
+                }
+            }
+            _ => {}
+        }
+        if in_machine {
+            entry_end = Some(start + word.len());
+        }
+    }
+    if let Some(end) = entry_end {
+        return format!(
+            "{} password {}{}",
+            &contents[..end],
+            token,
+            &contents[end..]
+        );
+    }
+
+    // Keep to the file's line endings, so a netrc edited on Windows stays CRLF
+    let newline = if contents.contains("\r\n") { "\r\n" } else { "\n" };
+    let separator = if contents.is_empty() || contents.ends_with('\n') {
+        ""
+    } else {
+        newline
+    };
+    format!(
+        "{}{}machine {} login {} password {}{}",
+        contents, separator, machine, login, token, newline
+    )
+}
+
+#[cfg(test)]
+mod tests;
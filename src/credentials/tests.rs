@@ -0,0 +1,99 @@
+//! Reading and updating `.netrc` files, whose entries are whitespace separated words that can be
+//! laid out over as many lines as people like.
+use super::*;
+
+fn login_and_password(entry: Option<NetrcEntry>) -> (Option<String>, Option<String>) {
+    let entry = entry.expect("no entry found");
+    (entry.login, entry.password)
+}
+
+fn some(login: &str, password: &str) -> (Option<String>, Option<String>) {
+    (Some(login.to_string()), Some(password.to_string()))
+}
+
+#[test]
+fn netrc_entries_are_found_by_machine() {
+    let contents = "machine other.example.com login other password nope\n\
+                    machine jira.example.com\n  login me\n  password secret\n";
+    let entry = parse_netrc(contents, "jira.example.com");
+    assert_eq!(login_and_password(entry), some("me", "secret"));
+    assert!(parse_netrc(contents, "missing.example.com").is_none());
+}
+
+#[test]
+fn netrc_default_is_used_when_the_machine_is_missing() {
+    let contents = "default login anyone password fallback\n\
+                    machine other.example.com login other password nope\n";
+    let entry = parse_netrc(contents, "jira.example.com");
+    assert_eq!(login_and_password(entry), some("anyone", "fallback"));
+
+    // A machine of its own wins over the default, wherever the default is
+    let contents = format!(
+        "{}machine jira.example.com login me password secret\n",
+        contents
+    );
+    let entry = parse_netrc(&contents, "jira.example.com");
+    assert_eq!(login_and_password(entry), some("me", "secret"));
+}
+
+#[test]
+fn netrc_macros_are_not_read_as_entries() {
+    let contents = "machine jira.example.com login me password secret\n\
+                    macdef init\n\
+                    echo machine jira.example.com password leaked\n\
+                    \n";
+    let entry = parse_netrc(contents, "jira.example.com");
+    assert_eq!(login_and_password(entry), some("me", "secret"));
+
+    let replaced = replace_netrc_password(contents, "jira.example.com", "me", "new");
+    assert_eq!(
+        replaced,
+        contents.replacen("password secret", "password new", 1)
+    );
+}
+
+#[test]
+fn netrc_machines_without_a_password_get_one() {
+    let contents = "machine jira.example.com login me\nmachine other.example.com login other\n";
+    let entry = parse_netrc(contents, "jira.example.com");
+    assert_eq!(login_and_password(entry), (Some("me".to_string()), None));
+
+    let replaced = replace_netrc_password(contents, "jira.example.com", "me", "new");
+    assert_eq!(
+        replaced,
+        "machine jira.example.com login me password new\nmachine other.example.com login other\n"
+    );
+    let entry = parse_netrc(&replaced, "jira.example.com");
+    assert_eq!(login_and_password(entry), some("me", "new"));
+}
+
+#[test]
+fn netrc_passwords_are_replaced_in_place() {
+    let contents = "machine jira.example.com\r\n  login me\r\n  password old\r\n";
+    assert_eq!(
+        replace_netrc_password(contents, "jira.example.com", "me", "new"),
+        "machine jira.example.com\r\n  login me\r\n  password new\r\n"
+    );
+}
+
+#[test]
+fn netrc_entries_are_added_with_the_files_line_endings() {
+    let crlf = "machine other.example.com login other password nope\r\n";
+    assert_eq!(
+        replace_netrc_password(crlf, "jira.example.com", "me", "new"),
+        "machine other.example.com login other password nope\r\n\
+         machine jira.example.com login me password new\r\n"
+    );
+
+    // The last line is ended before the entry goes after it
+    let unterminated = "machine other.example.com login other password nope";
+    assert_eq!(
+        replace_netrc_password(unterminated, "jira.example.com", "me", "new"),
+        "machine other.example.com login other password nope\n\
+         machine jira.example.com login me password new\n"
+    );
+    assert_eq!(
+        replace_netrc_password("", "jira.example.com", "me", "new"),
+        "machine jira.example.com login me password new\n"
+    );
+}
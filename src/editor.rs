@@ -0,0 +1,150 @@
+//! A small multi-line text editor for editing longer issue fields inside the TUI.
+
+#[derive(Clone, Debug, Default)]
+pub struct TextEditor {
+    lines: Vec<String>,
+    row: usize,
+    /// Cursor position within the current line, counted in characters.
+    col: usize,
+}
+
+impl TextEditor {
+    pub fn new(text: &str) -> TextEditor {
+        let mut lines: Vec<String> = text.lines().map(|line| line.to_string()).collect();
+        if lines.is_empty() {
+            lines.push(String::new());
+        }
+        TextEditor {
+            lines,
+            row: 0,
+            col: 0,
+        }
+    }
+
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The cursor as (column, row).
+    pub fn cursor(&self) -> (usize, usize) {
+        (self.col, self.row)
+    }
+
+    fn byte_index(&self) -> usize {
+        self.lines[self.row]
+            .char_indices()
+            .nth(self.col)
+            .map_or(self.lines[self.row].len(), |(i, _)| i)
+    }
+
+    fn line_len(&self) -> usize {
+        self.lines[self.row].chars().count()
+    }
+
+    pub fn insert(&mut self, c: char) {
+        let index = self.byte_index();
+        self.lines[self.row].insert(index, c);
+        self.col += 1;
+    }
+
+    pub fn newline(&mut self) {
+        let index = self.byte_index();
+        let rest = self.lines[self.row].split_off(index);
+        self.row += 1;
+        self.col = 0;
+        self.lines.insert(self.row, rest);
+    }
+
+    pub fn backspace(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+            let index = self.byte_index();
+            self.lines[self.row].remove(index);
+        } else if self.row > 0 {
+            let line = self.lines.remove(self.row);
+            self.row -= 1;
+            self.col = self.line_len();
+            self.lines[self.row].push_str(&line);
+        }
+    }
+
+    pub fn left(&mut self) {
+        if self.col > 0 {
+            self.col -= 1;
+        } else if self.row > 0 {
+            self.row -= 1;
+            self.col = self.line_len();
+        }
+    }
+
+    pub fn right(&mut self) {
+        if self.col < self.line_len() {
+            self.col += 1;
+        } else if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = 0;
+        }
+    }
+
+    pub fn up(&mut self) {
+        if self.row > 0 {
+            self.row -= 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+
+    pub fn down(&mut self) {
+        if self.row + 1 < self.lines.len() {
+            self.row += 1;
+            self.col = self.col.min(self.line_len());
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DiffLine {
+    Same(String),
+    Added(String),
+    Removed(String),
+}
+
+/// A line based diff of two texts, using the longest common subsequence of their lines.
+pub fn diff_lines(before: &str, after: &str) -> Vec<DiffLine> {
+    let before: Vec<&str> = before.lines().collect();
+    let after: Vec<&str> = after.lines().collect();
+
+    // lengths[i][j] is the LCS length of before[i..] and after[j..]
+    let mut lengths = vec![vec![0; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lengths[i][j] = if before[i] == after[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut diff = vec![];
+    while i < before.len() && j < after.len() {
+        if before[i] == after[j] {
+            diff.push(DiffLine::Same(before[i].to_string()));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            diff.push(DiffLine::Removed(before[i].to_string()));
+            i += 1;
+        } else {
+            diff.push(DiffLine::Added(after[j].to_string()));
+            j += 1;
+        }
+    }
+    diff.extend(before[i..].iter().map(|line| DiffLine::Removed(line.to_string())));
+    diff.extend(after[j..].iter().map(|line| DiffLine::Added(line.to_string())));
+    diff
+}
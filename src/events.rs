@@ -1,17 +1,149 @@
 use crate::{
+    audit::Audit,
     git::BranchSummary,
-    jira::{BoardSummary, IssueSummary, TransitionSummary},
+    jira::{
+        AccountInfo, ActivityEntry, AttachmentSummary, BoardSummary, BulkEdit, CommentPage,
+        IssueLinkSummary, IssuePermissions, IssueSummary, JiraError, LinkRelation, SprintSummary,
+        TimeTracking, TransitionSummary, UserSummary,
+    },
+    state::{IssueAction, ItemStatus, Picker, Retry, SearchProgress},
+};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crossterm_events::event::{
+    Event as CrosstermEvent, EventStream, KeyCode as InputKeyCode, KeyEvent as InputKeyEvent,
+    KeyEventKind,
 };
-use crossterm::event::{Event as CrosstermEvent, EventStream, KeyCode};
 use futures::{future::FutureExt, StreamExt};
+use std::{collections::BTreeSet, path::PathBuf};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::mpsc;
 pub enum Event {
-    KeyEvent(KeyCode),
-    TransitionsFetched(Vec<TransitionSummary>),
+    /// A key press along with the modifiers held, so chords like Ctrl-R can be bound.
+    KeyEvent(KeyEvent),
+    /// The transitions of issues in the given project and status.
+    TransitionsFetched((String, String), Vec<TransitionSummary>),
     TransitionExecuted,
+    /// The issue with the given key was moved along the named transition.
+    IssueTransitioned(String, String),
+    /// The issue with the given key has no transition with the name a shortcut asked for.
+    TransitionUnavailable(String, String),
+    LinksFetched(String, Vec<IssueLinkSummary>),
     IssuesUpdated(Vec<IssueSummary>),
+    /// Some of a search spanning several pages has been fetched.
+    IssuesLoading(SearchProgress),
+    IssueJumped(Box<IssueSummary>),
+    SiblingsFetched(Vec<IssueSummary>),
+    /// The sub-tasks of the issue with the given key.
+    SubtasksFetched(String, Vec<IssueSummary>),
+    /// The files attached to the issue with the given key.
+    AttachmentsFetched(String, Vec<AttachmentSummary>),
+    /// An attachment was saved to the given path.
+    AttachmentDownloaded(PathBuf),
+    /// Saving an attachment failed, with the reason.
+    DownloadFailed(String),
+    /// A file was attached to the issue with the given key, under the given name.
+    AttachmentUploaded(String, String),
+    /// Reading a file to attach failed, with the reason.
+    UploadFailed(String),
+    /// The time tracked on the issue with the given key.
+    TimeTrackingFetched(String, TimeTracking),
+    /// A page of comments on the issue with the given key.
+    CommentsFetched(String, CommentPage),
+    DescriptionFetched(String, String, String),
+    DescriptionSaved(String, String, String),
+    SummarySaved(String, String),
+    SummarySaveFailed(String, String, JiraError),
+    /// The issue with the given key is now watched, or not.
+    WatchingSet(String, bool),
+    /// Watching or unwatching the issue with the given key failed, with what it was meant to be.
+    WatchFailed(String, bool, JiraError),
+    ActivityFetched(Vec<ActivityEntry>),
     BoardsUpdated(Vec<BoardSummary>),
-    BranchesUpdated(Vec<BranchSummary>),
+    /// The active sprint of the board with the given ID and the issues in it, `None` when no
+    /// sprint is running.
+    SprintFetched(u64, Option<(SprintSummary, Vec<IssueSummary>)>),
+    /// The backlog of the board with the given id, in rank order.
+    BacklogFetched(u64, Vec<IssueSummary>),
+    /// The running and planned sprints of the board with the given id.
+    SprintsFetched(u64, Vec<SprintSummary>),
+    /// The issue with the given key was moved into the named sprint.
+    MovedToSprint(String, String),
+    /// Keys of listed issues with a local branch, and of those with an open PR as well.
+    StartedWorkFound(BTreeSet<String>, BTreeSet<String>),
+    /// Branches for the issue with the given key, and the branch checked out in their repo.
+    BranchesUpdated(String, Vec<BranchSummary>, Option<String>),
+    /// An issue in a bulk edit was updated, failed or skipped.
+    BulkEditStep(String, ItemStatus),
+    JiraFailed(JiraError),
+    /// What the user may do to the issue with the given key, checked on opening the picker.
+    PermissionsChecked(String, Picker, IssuePermissions),
+    /// Jira doesn't let the user do that to the issue with the given key, and said why.
+    ActionRefused(String, IssueAction, JiraError),
+    /// Time for a background refresh.
+    Poll,
+    /// A webhook said the issue with the given key was created, changed or deleted.
+    IssueChanged(String),
+    /// A webhook said a comment on the issue with the given key was added, edited or deleted.
+    CommentsChanged(String),
+    /// A sub-task was created under the issue with the given key, `None` on a dry run.
+    SubtaskCreated(String, Option<String>),
+    /// A PR was linked to the issue with the given key.
+    PullRequestLinked(String),
+    /// A PR was opened on GitHub for the issue with the given key and linked to it, `None` on a
+    /// dry run. Along with why nobody was asked to review it, when the watchers couldn't be read.
+    PullRequestOpened(String, Option<String>, Option<String>),
+    /// Opening a PR failed, with the reason.
+    PullRequestFailed(String),
+    /// A comment was posted on the issue with the given key.
+    CommentAdded(String),
+    AccountFetched(AccountInfo),
+    AuditFinished(Audit),
+    /// The branches couldn't be listed for the audit, with the reason.
+    AuditFailed(String),
+    /// People matching what was typed into the assignee picker.
+    UsersFound(String, Vec<UserSummary>),
+    /// The issue with the given key was assigned to someone, by their name.
+    IssueAssigned(String, String),
+    /// The names of the priorities the selected issue can be given.
+    PrioritiesFetched(Vec<String>),
+    /// The issue with the given key was given the named priority.
+    PrioritySet(String, String),
+    /// Both ends of the kinds of links issues can have.
+    LinkRelationsFetched(Vec<LinkRelation>),
+    /// The issue with the first key was linked to the one with the last, as described between
+    /// them.
+    IssueLinked(String, String, String),
+    /// The labels of the issue with the given key.
+    LabelsFetched(String, Vec<String>),
+    /// A label was added to or removed from the issue with the given key.
+    LabelsEdited(String, BulkEdit),
+    /// People who could review a PR for the issue with the given key, possibly no one.
+    ReviewersSuggested(String, Vec<String>),
+    /// The repo for the issue with the given key was cloned into the given directory.
+    RepoCloned(String, PathBuf),
+    /// Cloning a project's repo failed, with the reason.
+    CloneFailed(String),
+    /// A branch was created, deleted or packed, possibly from another terminal.
+    RefsChanged,
+    /// The credentials were rejected, ask for a new token and then send the request again.
+    AuthExpired(Retry),
+    /// A new token was entered (true) or re-authenticating was given up on (false).
+    ReauthFinished(bool),
+    /// The terminal gained focus (true) or lost it (false).
+    FocusChanged(bool),
+    /// Time passed, for redrawing anything showing it.
+    Tick,
+    /// The next frame of the spinners shown while something's running.
+    Animate,
+    /// Time was logged against the issue with the given key.
+    WorklogAdded(String, u64),
+    /// A signal asked the app to stop, exit with this code once the terminal is restored.
+    Terminated(i32),
+    /// SIGTSTP asked the app to stop until it's continued, as Ctrl-Z does.
+    Suspend,
+    /// SIGCONT continued the app after it was stopped.
+    Resumed,
 }
 pub type EventsTx = mpsc::UnboundedSender<Event>;
 pub type EventsRx = mpsc::UnboundedReceiver<Event>;
@@ -26,9 +158,16 @@ pub fn subscribe_to_key_events(tx: EventsTx) {
                 maybe_event = event => {
                     match maybe_event {
                         Some(Ok(event)) => {
-                            if let CrosstermEvent::Key(input) = event {
-                                let _ = tx.send(Event::KeyEvent(input.code));
-
+                            let event = match event {
+                                // Only presses, Windows reports releases too and keys would be
+                                // handled twice
+                                CrosstermEvent::Key(input) => key_event(input).map(Event::KeyEvent),
+                                CrosstermEvent::FocusGained => Some(Event::FocusChanged(true)),
+                                CrosstermEvent::FocusLost => Some(Event::FocusChanged(false)),
+                                _ => None,
+                            };
+                            if let Some(event) = event {
+                                let _ = tx.send(event);
                             }
                         },
                         _ => break,
@@ -38,3 +177,80 @@ pub fn subscribe_to_key_events(tx: EventsTx) {
         }
     });
 }
+
+/// Turn a key press read by the input crossterm into the drawing one's, which the rest of the
+/// app uses. Keys that version doesn't know, like media keys, are dropped, as are releases.
+/// Held keys repeat like pressing them again, so holding Down keeps moving.
+fn key_event(input: InputKeyEvent) -> Option<KeyEvent> {
+    if input.kind == KeyEventKind::Release {
+        return None;
+    }
+    let code = match input.code {
+        InputKeyCode::Backspace => KeyCode::Backspace,
+        InputKeyCode::Enter => KeyCode::Enter,
+        InputKeyCode::Left => KeyCode::Left,
+        InputKeyCode::Right => KeyCode::Right,
+        InputKeyCode::Up => KeyCode::Up,
+        InputKeyCode::Down => KeyCode::Down,
+        InputKeyCode::Home => KeyCode::Home,
+        InputKeyCode::End => KeyCode::End,
+        InputKeyCode::PageUp => KeyCode::PageUp,
+        InputKeyCode::PageDown => KeyCode::PageDown,
+        InputKeyCode::Tab => KeyCode::Tab,
+        InputKeyCode::BackTab => KeyCode::BackTab,
+        InputKeyCode::Delete => KeyCode::Delete,
+        InputKeyCode::Insert => KeyCode::Insert,
+        InputKeyCode::F(n) => KeyCode::F(n),
+        InputKeyCode::Char(c) => KeyCode::Char(c),
+        InputKeyCode::Null => KeyCode::Null,
+        InputKeyCode::Esc => KeyCode::Esc,
+        _ => return None,
+    };
+    // The modifiers share their bits across versions
+    let modifiers = KeyModifiers::from_bits_truncate(input.modifiers.bits());
+    Some(KeyEvent::new(code, modifiers))
+}
+
+/// Turn signals asking the app to stop into events, so it can restore the terminal before
+/// exiting. Exit codes follow the shell convention of 128 plus the signal number. Job control
+/// signals become events too, for giving the terminal back while stopped and taking it over
+/// again once continued.
+#[cfg(unix)]
+pub fn subscribe_to_signals(tx: EventsTx) {
+    tokio::spawn(async move {
+        let (mut interrupt, mut terminate, mut hangup, mut stop, mut resume) = match (
+            signal(SignalKind::interrupt()),
+            signal(SignalKind::terminate()),
+            signal(SignalKind::hangup()),
+            signal(SignalKind::from_raw(libc::SIGTSTP)),
+            signal(SignalKind::from_raw(libc::SIGCONT)),
+        ) {
+            (Ok(interrupt), Ok(terminate), Ok(hangup), Ok(stop), Ok(resume)) => {
+                (interrupt, terminate, hangup, stop, resume)
+            }
+            _ => return,
+        };
+        loop {
+            let event = tokio::select! {
+                _ = interrupt.recv() => Event::Terminated(130),
+                _ = terminate.recv() => Event::Terminated(143),
+                _ = hangup.recv() => Event::Terminated(129),
+                _ = stop.recv() => Event::Suspend,
+                _ = resume.recv() => Event::Resumed,
+            };
+            let terminated = matches!(event, Event::Terminated(_));
+            if tx.send(event).is_err() || terminated {
+                break;
+            }
+        }
+    });
+}
+
+#[cfg(not(unix))]
+pub fn subscribe_to_signals(tx: EventsTx) {
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            let _ = tx.send(Event::Terminated(130));
+        }
+    });
+}
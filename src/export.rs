@@ -0,0 +1,60 @@
+//! Static HTML snapshots of the issue list, for sharing where a screenshot would otherwise go.
+use crate::jira::IssueSummary;
+use std::fmt::Write;
+
+/// A standalone page listing the issues grouped by status, in the order statuses first appear.
+pub fn issues_html(title: &str, issues: &[IssueSummary]) -> String {
+    let mut statuses: Vec<&str> = vec![];
+    for issue in issues {
+        if !statuses.contains(&issue.status.as_str()) {
+            statuses.push(&issue.status);
+        }
+    }
+
+    let mut html = String::new();
+    let _ = writeln!(
+        html,
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n\
+         <style>{}</style>\n</head>\n<body>\n<h1>{}</h1>",
+        escape(title),
+        STYLE,
+        escape(title)
+    );
+    for status in statuses {
+        let _ = writeln!(
+            html,
+            "<h2>{}</h2>\n<table>\n<tr><th>Key</th><th>Summary</th><th>Assignee</th>\
+             <th>Priority</th><th>Sprint</th><th>Points</th></tr>",
+            escape(status)
+        );
+        for issue in issues.iter().filter(|issue| issue.status == status) {
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td>\
+                 <td>{}</td><td>{}</td></tr>",
+                escape(&issue.permalink),
+                escape(&issue.key),
+                escape(&issue.summary),
+                escape(&issue.assignee_name),
+                escape(issue.priority.as_deref().unwrap_or("")),
+                escape(issue.sprint.as_deref().unwrap_or("")),
+                escape(issue.story_points.as_deref().unwrap_or(""))
+            );
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; } \
+table { border-collapse: collapse; width: 100%; margin-bottom: 2em; } \
+th, td { border: 1px solid #ccc; padding: 0.3em 0.6em; text-align: left; } \
+th { background: #f4f5f7; }";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,126 @@
+//! Opening PRs on GitHub for an issue's branch, as drafts when asked, with reviewers requested.
+use anyhow::{bail, Result};
+use reqwest::{
+    header::{ACCEPT, USER_AGENT},
+    Client, Method, RequestBuilder,
+};
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// Where the GitHub API is when `github_api_url` isn't set.
+pub const GITHUB_API: &str = "https://api.github.com";
+
+/// A PR to open from `head` into the repo's default branch.
+pub struct PullRequest {
+    pub owner: String,
+    pub repo: String,
+    pub head: String,
+    pub title: String,
+    pub body: String,
+    pub draft: bool,
+    /// GitHub logins asked to review it. Whoever opens it is left out, GitHub refuses them.
+    pub reviewers: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct Repo {
+    default_branch: String,
+}
+
+#[derive(Deserialize)]
+struct Login {
+    login: String,
+}
+
+#[derive(Deserialize)]
+struct Created {
+    number: u64,
+    html_url: String,
+}
+
+pub struct GitHub {
+    client: Client,
+    api: String,
+    token: String,
+}
+
+impl GitHub {
+    pub fn new(api: &str, token: String) -> GitHub {
+        GitHub {
+            client: Client::new(),
+            api: api.trim_end_matches('/').to_string(),
+            token,
+        }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, &format!("{}{}", self.api, path))
+            .bearer_auth(&self.token)
+            .header(ACCEPT, "application/vnd.github+json")
+            // GitHub turns away requests without one
+            .header(USER_AGENT, "jira-ticket-fetch")
+    }
+
+    /// Open the PR and ask for its reviews, returning where it can be seen.
+    pub async fn open_pull_request(&self, pr: &PullRequest) -> Result<String> {
+        let repo_path = format!("/repos/{}/{}", pr.owner, pr.repo);
+        let repo: Repo = send(self.request(Method::GET, &repo_path)).await?;
+        let body = json!({
+            "title": pr.title,
+            "head": pr.head,
+            "base": repo.default_branch,
+            "body": pr.body,
+            "draft": pr.draft,
+        });
+        let pulls = format!("{}/pulls", repo_path);
+        let created: Created = send(self.request(Method::POST, &pulls).json(&body)).await?;
+
+        let me: Login = send(self.request(Method::GET, "/user")).await?;
+        let reviewers: Vec<&String> = pr
+            .reviewers
+            .iter()
+            .filter(|login| !login.eq_ignore_ascii_case(&me.login))
+            .collect();
+        if !reviewers.is_empty() {
+            let path = format!("{}/{}/requested_reviewers", pulls, created.number);
+            let body = json!({ "reviewers": reviewers });
+            let _: Value = send(self.request(Method::POST, &path).json(&body)).await?;
+        }
+        Ok(created.html_url)
+    }
+}
+
+/// Send a request, turning GitHub's error responses into what they say went wrong.
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    let response = request.send().await?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response.json().await?);
+    }
+    let body: Value = response.json().await.unwrap_or_default();
+    // A head branch GitHub doesn't know is the usual reason for a PR to be refused
+    if body["errors"][0]["field"] == "head" {
+        bail!("GitHub doesn't have the branch yet, push it before opening a PR");
+    }
+    let message = body["errors"][0]["message"]
+        .as_str()
+        .or_else(|| body["message"].as_str())
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("request failed"));
+    bail!("GitHub said {}: {}", status.as_u16(), message)
+}
+
+/// The owner and name of a GitHub repo from its remote URL, e.g. `acme` and `platform` for
+/// `git@github.com:acme/platform.git` or `https://github.com/acme/platform`.
+pub fn github_repo(url: &str) -> Option<(String, String)> {
+    let url = url.trim_end_matches('/').trim_end_matches(".git");
+    let mut parts = url.rsplit(['/', ':']);
+    let repo = parts.next().filter(|repo| !repo.is_empty())?;
+    let owner = parts.next().filter(|owner| !owner.is_empty())?;
+    // There has to be a host in front of them
+    parts.next()?;
+    Some((owner.to_string(), repo.to_string()))
+}
+
+#[cfg(test)]
+mod tests;
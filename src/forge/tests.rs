@@ -0,0 +1,125 @@
+//! PRs opened against a stand-in for the GitHub API, which answers each request in turn and notes
+//! down what was asked of it.
+use super::*;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+/// Serve the responses in order, one per connection, returning where to reach the server and the
+/// requests it got, written like `POST /repos/acme/platform/pulls {...}`.
+fn fake_github(responses: Vec<(u16, Value)>) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let log = requests.clone();
+    thread::spawn(move || {
+        for (status, body) in responses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).unwrap();
+            let mut length = 0;
+            loop {
+                let mut header = String::new();
+                reader.read_line(&mut header).unwrap();
+                if header.trim().is_empty() {
+                    break;
+                }
+                let header = header.to_lowercase();
+                if let Some(value) = header.strip_prefix("content-length:") {
+                    length = value.trim().parse().unwrap();
+                }
+            }
+            let mut request_body = vec![0; length];
+            reader.read_exact(&mut request_body).unwrap();
+            let mut parts = request_line.split_whitespace();
+            let request = format!(
+                "{} {} {}",
+                parts.next().unwrap(),
+                parts.next().unwrap(),
+                String::from_utf8_lossy(&request_body)
+            );
+            log.lock().unwrap().push(request.trim().to_string());
+
+            let body = body.to_string();
+            let mut stream = reader.into_inner();
+            write!(
+                stream,
+                "HTTP/1.1 {} OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\
+                 Connection: close\r\n\r\n{}",
+                status,
+                body.len(),
+                body
+            )
+            .unwrap();
+        }
+    });
+    (address, requests)
+}
+
+fn pull_request(reviewers: &[&str]) -> PullRequest {
+    PullRequest {
+        owner: "acme".to_string(),
+        repo: "platform".to_string(),
+        head: "PROJ-1-fix-crash".to_string(),
+        title: "PROJ-1: Crash when quitting".to_string(),
+        body: "Fixes the crash".to_string(),
+        draft: true,
+        reviewers: reviewers.iter().map(|login| login.to_string()).collect(),
+    }
+}
+
+#[test]
+fn repos_are_read_from_ssh_and_https_remotes() {
+    let expected = Some(("acme".to_string(), "platform".to_string()));
+    assert_eq!(github_repo("git@github.com:acme/platform.git"), expected);
+    assert_eq!(github_repo("https://github.com/acme/platform"), expected);
+    assert_eq!(github_repo("https://github.com/acme/platform.git/"), expected);
+    assert_eq!(github_repo("platform"), None);
+}
+
+#[tokio::test]
+async fn drafts_are_opened_into_the_default_branch_with_reviewers_asked() {
+    let (api, requests) = fake_github(vec![
+        (200, json!({ "default_branch": "main" })),
+        (201, json!({ "number": 12, "html_url": "https://github.com/acme/platform/pull/12" })),
+        (200, json!({ "login": "me" })),
+        (201, json!({})),
+    ]);
+    let github = GitHub::new(&api, "token".to_string());
+
+    let url = github.open_pull_request(&pull_request(&["ada", "me"])).await.unwrap();
+    assert_eq!(url, "https://github.com/acme/platform/pull/12");
+    let requests = requests.lock().unwrap();
+    assert_eq!(requests[0], "GET /repos/acme/platform");
+    let created = requests[1].trim_start_matches("POST /repos/acme/platform/pulls ");
+    let created: Value = serde_json::from_str(created).unwrap();
+    assert_eq!(created["base"], "main");
+    assert_eq!(created["draft"], true);
+    // Whoever opens the PR can't be asked to review it
+    assert_eq!(
+        requests[3],
+        r#"POST /repos/acme/platform/pulls/12/requested_reviewers {"reviewers":["ada"]}"#
+    );
+}
+
+#[tokio::test]
+async fn branches_github_lacks_are_asked_to_be_pushed() {
+    let (api, _) = fake_github(vec![
+        (200, json!({ "default_branch": "main" })),
+        (
+            422,
+            json!({
+                "message": "Validation Failed",
+                "errors": [{ "resource": "PullRequest", "field": "head", "code": "invalid" }],
+            }),
+        ),
+    ]);
+    let github = GitHub::new(&api, "token".to_string());
+
+    let err = github.open_pull_request(&pull_request(&[])).await.unwrap_err();
+    assert!(err.to_string().contains("push it"));
+}
@@ -1,6 +1,19 @@
-use anyhow::{Context, Result};
-use git2::{BranchType, Cred, CredentialType, Direction, RemoteCallbacks, Repository};
-use std::env;
+use anyhow::{anyhow, bail, Context, Result};
+use git2::{
+    build::RepoBuilder, BranchType, Cred, CredentialType, Direction, FetchOptions, Oid,
+    RemoteCallbacks, Repository, Tree,
+};
+use std::{
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+use tempfile::NamedTempFile;
 
 #[derive(Clone)]
 pub struct BranchSummary {
@@ -11,23 +24,182 @@ pub struct BranchSummary {
 pub fn get_current_repo() -> Result<Repository> {
     let path = env::current_dir().context("Couldn't get the current directory")?;
     // println!("{:?}", path);
-    Ok(Repository::discover(path).context("Couldn't find a git repo at the current directory")?)
+    Repository::discover(path).context("Couldn't find a git repo at the current directory")
 }
 
-/// Done for Git side effects
-pub fn create_and_use_branch(repo: &Repository, branch_name: String) -> Result<()> {
-    let default_branch = get_default_branch(repo);
-    let main_branch = repo.refname_to_id(&default_branch)?;
-    let main_commit = repo.find_commit(main_branch)?;
-    if repo.find_branch(&branch_name, BranchType::Local).is_err() {
-        let _ = repo.branch(&branch_name, &main_commit, false)?;
+/// Get the Git repo at `path`, or the current one when there's no path.
+pub fn open_repo(path: Option<&Path>) -> Result<Repository> {
+    match path {
+        Some(path) => Repository::discover(path)
+            .with_context(|| format!("Couldn't find a git repo at {}", path.display())),
+        None => get_current_repo(),
+    }
+}
+
+/// Clone the repo at `url` into `path`, authenticating the same way as fetching does.
+pub fn clone_repo(url: &str, path: &Path) -> Result<Repository> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Couldn't create {}", parent.display()))?;
+    }
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(git_credentials_callback);
+    let mut fetch = FetchOptions::new();
+    fetch.remote_callbacks(callbacks);
+    RepoBuilder::new()
+        .fetch_options(fetch)
+        .clone(url, path)
+        .with_context(|| format!("Couldn't clone {}", url))
+}
+
+/// Done for Git side effects, returns whether the branch was created rather than already there.
+/// The branch starts at the default branch's head, see `commit_empty` for starting it off with
+/// a commit.
+pub fn create_and_use_branch(repo: &Repository, branch_name: String) -> Result<bool> {
+    let created = !branch_exists(repo, &branch_name);
+    if created {
+        start_branch(repo, &branch_name, false)?;
     }
     let refname = format!("refs/heads/{}", branch_name);
     repo.set_head(&refname)?;
 
+    Ok(created)
+}
+
+/// Start an existing branch over from the default branch's head and check it out, dropping
+/// whatever was committed on it. Fails for the branch that's checked out.
+pub fn overwrite_and_use_branch(repo: &Repository, branch_name: String) -> Result<()> {
+    start_branch(repo, &branch_name, true)?;
+    let refname = format!("refs/heads/{}", branch_name);
+    repo.set_head(&refname)?;
+
     Ok(())
 }
 
+fn start_branch(repo: &Repository, branch_name: &str, force: bool) -> Result<()> {
+    let default_branch = get_default_branch(repo);
+    let main_branch = repo.refname_to_id(&default_branch)?;
+    let main_commit = repo.find_commit(main_branch)?;
+    let _ = repo.branch(branch_name, &main_commit, force)?;
+    Ok(())
+}
+
+pub fn branch_exists(repo: &Repository, branch_name: &str) -> bool {
+    repo.find_branch(branch_name, BranchType::Local).is_ok()
+}
+
+/// `branch_name` with the first of `-2`, `-3` and so on that no local branch has yet.
+pub fn unused_branch_name(repo: &Repository, branch_name: &str) -> String {
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{}-{}", branch_name, suffix);
+        if !branch_exists(repo, &candidate) {
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// Commit on the checked out branch without changing any files, signed when the repo's
+/// `commit.gpgsign` asks for it. libgit2 doesn't sign by itself, so this signs the way git
+/// would, with the program `gpg.format` picks.
+pub fn commit_empty(repo: &Repository, message: &str) -> Result<Oid> {
+    let parent = repo.head()?.peel_to_commit()?;
+    let tree = parent.tree()?;
+    let author = repo
+        .signature()
+        .context("Set user.name and user.email to make commits")?;
+    let config = repo.config()?;
+    if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+        return Ok(repo.commit(Some("HEAD"), &author, &author, message, &tree, &[&parent])?);
+    }
+
+    let content = repo.commit_create_buffer(&author, &author, message, &tree, &[&parent])?;
+    let content = content
+        .as_str()
+        .context("The commit isn't valid UTF-8 to sign")?;
+    let signature = sign(&config, content)?;
+    let commit = repo.commit_signed(content, &signature, None)?;
+    // Signed commits are written without moving the branch along
+    repo.head()?
+        .set_target(commit, &format!("commit: {}", message))?;
+    Ok(commit)
+}
+
+/// An armored signature for `content`, from gpg, gpgsm or ssh-keygen depending on `gpg.format`,
+/// using `user.signingkey` as git does.
+fn sign(config: &git2::Config, content: &str) -> Result<String> {
+    let format = config.get_string("gpg.format");
+    let key = config.get_string("user.signingkey").ok();
+    let program = |name: &str, default: &str| {
+        let program = config.get_string(&format!("gpg.{}.program", name));
+        program.unwrap_or_else(|_| default.to_string())
+    };
+    // A literal SSH key is handed over in a file only this user can read, removed once signed
+    let mut key_file = None;
+    let mut command = match format.as_deref() {
+        Ok("ssh") => {
+            let key = key.context("Signing with SSH needs user.signingkey to be set")?;
+            let mut command = Command::new(program("ssh", "ssh-keygen"));
+            command.args(["-Y", "sign", "-n", "git"]);
+            // The key can be given as the public key itself, signed with by the agent
+            let literal = match key.strip_prefix("key::") {
+                Some(literal) => Some(literal),
+                None if key.starts_with("ssh-") || key.starts_with("ecdsa-") => Some(key.as_str()),
+                None => None,
+            };
+            match literal {
+                Some(literal) => {
+                    let mut file = NamedTempFile::new()?;
+                    file.write_all(literal.as_bytes())?;
+                    command.arg("-U").arg("-f").arg(file.path());
+                    key_file = Some(file);
+                }
+                None => {
+                    command.arg("-f").arg(config.get_path("user.signingkey")?);
+                }
+            }
+            command
+        }
+        Ok("x509") => {
+            let mut command = Command::new(program("x509", "gpgsm"));
+            command.args(["--status-fd=2", "-bsa"]);
+            if let Some(key) = &key {
+                command.args(["-u", key]);
+            }
+            command
+        }
+        _ => {
+            let default = config.get_string("gpg.program");
+            let mut command = Command::new(program("openpgp", default.as_deref().unwrap_or("gpg")));
+            command.args(["--status-fd=2", "-bsa"]);
+            if let Some(key) = &key {
+                command.args(["-u", key]);
+            }
+            command
+        }
+    };
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("Couldn't start the program to sign the commit with")?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(content.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    drop(key_file);
+    if !output.status.success() {
+        bail!(
+            "Signing the commit failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
 /// Try to find a default branch based on the origin, if no origin remote exists or anything else
 /// happens, assume `main`.
 fn get_default_branch(repo: &Repository) -> String {
@@ -48,6 +220,119 @@ fn get_default_branch(repo: &Repository) -> String {
     }
 }
 
+/// The default branch's commit, found without going to the network: where `origin/HEAD` points as
+/// recorded when cloning, or else the first of `origin/main`, `origin/master` and the local
+/// branches of those names.
+fn default_branch_tip(repo: &Repository) -> Result<Oid> {
+    let candidates = [
+        "refs/remotes/origin/HEAD",
+        "refs/remotes/origin/main",
+        "refs/remotes/origin/master",
+        "refs/heads/main",
+        "refs/heads/master",
+    ];
+    candidates
+        .iter()
+        .find_map(|name| repo.refname_to_id(name).ok())
+        .ok_or_else(|| anyhow!("Couldn't find the default branch to compare with"))
+}
+
+/// How far back the default branch's history is looked through for reviewers.
+const REVIEWER_HISTORY: usize = 500;
+/// Reviewers suggested at most.
+const MAX_REVIEWERS: usize = 5;
+
+/// People who recently committed to the files changed on the checked out branch, as suggested
+/// reviewers, most commits first. Whoever the repo commits as is left out.
+pub fn recent_committers(repo: &Repository) -> Result<Vec<String>> {
+    let main = default_branch_tip(repo)?;
+    let head = repo.head()?.peel_to_commit()?;
+    let base = repo.find_commit(repo.merge_base(main, head.id())?)?;
+    let changed = changed_paths(repo, &base.tree()?, &head.tree()?)?;
+    if changed.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let me = repo.signature().ok();
+    let me = me.as_ref().and_then(|me| me.email());
+    let mut commits: BTreeMap<String, usize> = BTreeMap::new();
+    let mut history = repo.revwalk()?;
+    history.push(base.id())?;
+    for id in history.take(REVIEWER_HISTORY) {
+        let commit = repo.find_commit(id?)?;
+        // Merges repeat changes already counted on their own
+        if commit.parent_count() != 1 {
+            continue;
+        }
+        let touched = changed_paths(repo, &commit.parent(0)?.tree()?, &commit.tree()?)?;
+        let author = commit.author();
+        if touched.is_disjoint(&changed) || author.email().is_some_and(|email| Some(email) == me) {
+            continue;
+        }
+        if let Some(name) = author.name() {
+            *commits.entry(name.to_string()).or_default() += 1;
+        }
+    }
+
+    let mut reviewers: Vec<(String, usize)> = commits.into_iter().collect();
+    reviewers.sort_by_key(|&(_, count)| Reverse(count));
+    Ok(reviewers
+        .into_iter()
+        .take(MAX_REVIEWERS)
+        .map(|(name, _)| name)
+        .collect())
+}
+
+fn changed_paths(repo: &Repository, old: &Tree, new: &Tree) -> Result<BTreeSet<PathBuf>> {
+    let diff = repo.diff_tree_to_tree(Some(old), Some(new), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// The commit checked out, if any.
+pub fn head_commit(repo: &Repository) -> Option<Oid> {
+    repo.head().ok()?.target()
+}
+
+/// The short name of the branch checked out, none when HEAD is detached or the repo is empty.
+pub fn current_branch(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    head.shorthand().map(str::to_string)
+}
+
+/// Run the post-checkout hook from `dir`, the way git does after switching to the current branch
+/// from `previous`. libgit2 doesn't run hooks itself. The hook's output goes to stderr, keeping
+/// stdout for what was checked out.
+pub fn run_post_checkout_hook(repo: &Repository, dir: &Path, previous: Option<Oid>) -> Result<()> {
+    let root = repo.workdir().unwrap_or_else(|| repo.path());
+    let hooks = match repo.config()?.get_path("core.hooksPath") {
+        Ok(path) => root.join(path),
+        Err(_) => repo.path().join("hooks"),
+    };
+    let hook = hooks.join("post-checkout");
+    if !hook.exists() {
+        return Ok(());
+    }
+    let head = head_commit(repo).unwrap_or_else(Oid::zero);
+    let previous = previous.unwrap_or_else(Oid::zero);
+    let status = Command::new(&hook)
+        .args(&[previous.to_string(), head.to_string(), "1".to_string()])
+        .current_dir(dir)
+        .stdout(Stdio::from(io::stderr()))
+        .status()
+        .with_context(|| format!("Couldn't run {}", hook.display()))?;
+    if !status.success() {
+        bail!("The post-checkout hook failed ({})", status);
+    }
+    Ok(())
+}
+
 /// Check out a branch given by a short-name. Done for Git side effects.
 pub fn checkout_branch(repo: &Repository, branch_name: String) -> Result<()> {
     let refname = format!("refs/heads/{}", branch_name);
@@ -56,28 +341,94 @@ pub fn checkout_branch(repo: &Repository, branch_name: String) -> Result<()> {
     Ok(())
 }
 
-pub fn matching_branches(repo: &Repository, branch_name: String) -> Result<Vec<BranchSummary>> {
+/// Local branch names, listed once and then reused until a branch is created, deleted or packed.
+/// Listing is slow on repos with thousands of branches, so this is shared between clones and
+/// meant to be used from a blocking task.
+#[derive(Clone, Default)]
+pub struct BranchIndex {
+    cache: Arc<Mutex<Option<CachedBranches>>>,
+}
+
+struct CachedBranches {
+    git_dir: PathBuf,
+    stamp: Option<SystemTime>,
+    /// Sorted, so the branches starting with a key sit next to each other.
+    names: Vec<String>,
+}
+
+impl BranchIndex {
+    pub fn matching(&self, repo: &Repository, prefix: &str) -> Result<Vec<BranchSummary>> {
+        let git_dir = repo.path().to_path_buf();
+        let stamp = refs_stamp(&git_dir);
+        let mut cache = self
+            .cache
+            .lock()
+            .map_err(|_| anyhow!("The branch cache was poisoned"))?;
+        let fresh = match cache.as_ref() {
+            Some(cached) => cached.git_dir == git_dir && stamp.is_some() && cached.stamp == stamp,
+            None => false,
+        };
+        if !fresh {
+            let mut names = branch_names(repo)?;
+            names.sort();
+            *cache = Some(CachedBranches {
+                git_dir,
+                stamp,
+                names,
+            });
+        }
+
+        let names = match cache.as_ref() {
+            Some(cached) => &cached.names,
+            None => return Ok(vec![]),
+        };
+        let start = names.partition_point(|name| name.as_str() < prefix);
+        Ok(names[start..]
+            .iter()
+            .take_while(|name| name.starts_with(prefix))
+            .map(|name| BranchSummary { name: name.clone() })
+            .collect())
+    }
+
+    /// Forget the listed branches, so they're listed again next time.
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.cache.lock() {
+            *cache = None;
+        }
+    }
+}
+
+/// The names of the repo's local branches.
+pub fn branch_names(repo: &Repository) -> Result<Vec<String>> {
     let branches = repo.branches(Some(BranchType::Local))?;
     Ok(branches
         .filter_map(|branch| {
-            if let Ok((branch, _branch_type)) = branch {
-                let name = branch
-                    .name()
-                    .unwrap_or(None)
-                    .unwrap_or("Invalid Branch")
-                    .to_string();
-                if name.starts_with(&branch_name) {
-                    Some(BranchSummary { name })
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
+            let (branch, _branch_type) = branch.ok()?;
+            let name = branch.name().ok()??;
+            Some(name.to_string())
         })
         .collect())
 }
 
+/// The latest change to packed-refs or the directories holding local branches. Creating or
+/// deleting a branch touches one of these, while commits to existing branches don't.
+pub fn refs_stamp(git_dir: &Path) -> Option<SystemTime> {
+    let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+    let mut latest = modified(&git_dir.join("packed-refs"));
+    let mut dirs = vec![git_dir.join("refs").join("heads")];
+    while let Some(dir) = dirs.pop() {
+        latest = latest.max(modified(&dir));
+        if let Ok(entries) = fs::read_dir(&dir) {
+            for entry in entries.flatten() {
+                if entry.file_type().is_ok_and(|kind| kind.is_dir()) {
+                    dirs.push(entry.path());
+                }
+            }
+        }
+    }
+    latest
+}
+
 pub fn git_credentials_callback(
     _user: &str,
     _user_from_url: Option<&str>,
@@ -0,0 +1,165 @@
+//! Recorded Jira responses that can be played back, so the client can be tested against real
+//! payloads without a Jira instance.
+//!
+//! Run the app with `JIRA_RECORD=path/to/cassette.json` to record every response it gets. The
+//! host and people's details are scrubbed out before anything is written, but look over a
+//! cassette before committing it. Tests play cassettes back with `JiraClient::replay`.
+use anyhow::Result;
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue},
+    StatusCode,
+};
+use serde_json::Value;
+use std::{collections::BTreeMap, fs, path::PathBuf};
+
+/// The host recorded responses refer to in place of the real one.
+pub const FIXTURE_HOST: &str = "https://example.atlassian.net";
+
+/// Response headers worth keeping, since the client behaves differently based on them.
+const RECORDED_HEADERS: [&str; 5] = [
+    "retry-after",
+    "x-authentication-denied-reason",
+    "x-ratelimit-remaining",
+    "x-ratelimit-limit",
+    "x-ratelimit-reset",
+];
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Cassette {
+    interactions: Vec<Interaction>,
+    /// Where to write new interactions to, `None` when playing back.
+    #[serde(skip)]
+    recording: Option<PathBuf>,
+    /// Which interactions have been played back, so a repeated request gets the next response.
+    #[serde(skip)]
+    played: Vec<bool>,
+}
+
+/// A request, written the same way as in the offline request log, and the response it got.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Interaction {
+    request: String,
+    status: u16,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    /// JSON bodies are stored as JSON, anything else (e.g. an HTML error page) as a string.
+    #[serde(default)]
+    body: Value,
+}
+
+pub type Response = (StatusCode, HeaderMap, String);
+
+impl Cassette {
+    /// Record to `path`, adding to what's already there.
+    pub fn record(path: PathBuf) -> Result<Cassette> {
+        let mut cassette = match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)?,
+            Err(_) => Cassette::default(),
+        };
+        cassette.recording = Some(path);
+        Ok(cassette)
+    }
+
+    #[cfg(test)]
+    pub fn play(path: PathBuf) -> Result<Cassette> {
+        let cassette: Cassette = serde_json::from_str(&fs::read_to_string(path)?)?;
+        Ok(Cassette {
+            played: vec![false; cassette.interactions.len()],
+            ..cassette
+        })
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.recording.is_none()
+    }
+
+    /// The response recorded for a request. Repeats of a request get the responses recorded for
+    /// it in turn, sticking with the last one once they run out.
+    pub fn replay(&mut self, request: &str) -> Option<Response> {
+        let matching: Vec<usize> = (0..self.interactions.len())
+            .filter(|&i| self.interactions[i].request == request)
+            .collect();
+        let index = matching
+            .iter()
+            .copied()
+            .find(|&i| !self.played[i])
+            .or_else(|| matching.last().copied())?;
+        self.played[index] = true;
+
+        let interaction = &self.interactions[index];
+        let status = StatusCode::from_u16(interaction.status).ok()?;
+        let mut headers = HeaderMap::new();
+        for (name, value) in &interaction.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                headers.insert(name, value);
+            }
+        }
+        let body = match &interaction.body {
+            Value::Null => String::new(),
+            Value::String(text) => text.clone(),
+            body => body.to_string(),
+        };
+        Some((status, headers, body))
+    }
+
+    /// Add a scrubbed copy of a response to the cassette and write it out.
+    pub fn add(
+        &mut self,
+        request: &str,
+        (status, headers, body): &Response,
+        host: &str,
+    ) -> Result<()> {
+        let path = match &self.recording {
+            Some(path) => path.clone(),
+            None => return Ok(()),
+        };
+        let headers = RECORDED_HEADERS
+            .iter()
+            .filter_map(|name| {
+                let value = headers.get(*name)?.to_str().ok()?;
+                Some((name.to_string(), value.to_string()))
+            })
+            .collect();
+        let mut body = if body.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.clone()))
+        };
+        scrub(&mut body, host);
+
+        self.interactions.push(Interaction {
+            request: request.replace(host, FIXTURE_HOST),
+            status: status.as_u16(),
+            headers,
+            body,
+        });
+        fs::write(path, serde_json::to_string_pretty(&self)?)?;
+        Ok(())
+    }
+}
+
+/// Swap out the host and anything identifying people for placeholders.
+fn scrub(value: &mut Value, host: &str) {
+    match value {
+        Value::String(text) => *text = text.replace(host, FIXTURE_HOST),
+        Value::Array(values) => values.iter_mut().for_each(|value| scrub(value, host)),
+        Value::Object(object) => {
+            object.remove("avatarUrls");
+            // `name` is the username on Server, but also the name of statuses, priorities and so
+            // on, so it's only scrubbed in objects that are clearly people
+            let is_user = object.contains_key("accountId") || object.contains_key("emailAddress");
+            for (key, value) in object.iter_mut() {
+                match key.as_str() {
+                    "emailAddress" => *value = Value::from("user@example.com"),
+                    "displayName" => *value = Value::from("Jira User"),
+                    "accountId" | "key" | "name" if is_user => *value = Value::from("fixture-user"),
+                    _ => scrub(value, host),
+                }
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,109 @@
+use std::{error::Error, fmt};
+
+pub type JiraResult<T> = Result<T, JiraError>;
+
+/// The ways talking to Jira can fail, split up so each can be explained to the user with
+/// something they can act on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JiraError {
+    Unauthorized,
+    Forbidden,
+    NotFound,
+    RateLimited { retry_after: Option<u64> },
+    Validation { messages: Vec<String> },
+    /// The issue changed in Jira since it was loaded, so saving would overwrite someone's edit.
+    Conflict,
+    Server { status: u16 },
+    Network(String),
+    Deserialization(String),
+}
+
+impl fmt::Display for JiraError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JiraError::Unauthorized => write!(
+                f,
+                "Jira rejected the credentials, the API token may have expired - check JIRA_USER and JIRA_PASS"
+            ),
+            JiraError::Forbidden => write!(f, "You don't have permission to do that in Jira"),
+            JiraError::NotFound => write!(f, "Jira couldn't find that, it may have been moved or deleted"),
+            JiraError::RateLimited {
+                retry_after: Some(seconds),
+            } => write!(f, "Jira is rate limiting requests, try again in {}s", seconds),
+            JiraError::RateLimited { retry_after: None } => {
+                write!(f, "Jira is rate limiting requests, try again shortly")
+            }
+            JiraError::Validation { messages } => {
+                write!(f, "Jira refused the request: {}", messages.join(", "))
+            }
+            JiraError::Conflict => write!(
+                f,
+                "The issue was changed in Jira since it was loaded, reload it before saving"
+            ),
+            JiraError::Server { status } => {
+                write!(f, "Jira had a problem handling the request ({})", status)
+            }
+            JiraError::Network(err) => write!(f, "Couldn't reach Jira, check JIRA_HOST and your connection: {}", err),
+            JiraError::Deserialization(err) => {
+                write!(f, "Couldn't understand Jira's response: {}", err)
+            }
+        }
+    }
+}
+
+impl JiraError {
+    /// Whether the credentials were rejected, usually because the API token expired or was revoked.
+    pub fn is_auth_failure(&self) -> bool {
+        *self == JiraError::Unauthorized
+    }
+
+    /// Whether Jira refused because the user isn't allowed to do that to the issue, either with a
+    /// 403 or by explaining as much in a validation error, e.g. for a field that can't be set.
+    pub fn is_permission_denied(&self) -> bool {
+        match self {
+            JiraError::Forbidden => true,
+            JiraError::Validation { messages } => messages.iter().any(|message| {
+                let message = message.to_lowercase();
+                message.contains("permission") || message.contains("cannot be set")
+            }),
+            _ => false,
+        }
+    }
+}
+
+impl Error for JiraError {}
+
+impl From<reqwest::Error> for JiraError {
+    fn from(err: reqwest::Error) -> Self {
+        JiraError::Network(err.to_string())
+    }
+}
+
+impl From<serde_json::Error> for JiraError {
+    fn from(err: serde_json::Error) -> Self {
+        JiraError::Deserialization(err.to_string())
+    }
+}
+
+#[derive(Deserialize, Default)]
+struct ErrorCollection {
+    #[serde(rename = "errorMessages", default)]
+    error_messages: Vec<String>,
+    #[serde(default)]
+    errors: std::collections::BTreeMap<String, String>,
+}
+
+/// Jira reports validation problems as a list of general messages plus messages per field.
+pub fn validation_messages(body: &str) -> Vec<String> {
+    let collection: ErrorCollection = serde_json::from_str(body).unwrap_or_default();
+    collection
+        .error_messages
+        .into_iter()
+        .chain(
+            collection
+                .errors
+                .into_iter()
+                .map(|(field, message)| format!("{}: {}", field, message)),
+        )
+        .collect()
+}
@@ -0,0 +1,74 @@
+//! Discovery and reading of the custom fields configured in `FieldMap`.
+use super::models::Field;
+use crate::config::FieldMap;
+use serde_json::Value;
+
+const SPRINT_TYPE: &str = "com.pyxis.greenhopper.jira:gh-sprint";
+const EPIC_LINK_TYPE: &str = "com.pyxis.greenhopper.jira:gh-epic-link";
+const TEAM_TYPE: &str = "com.atlassian.teams:rm-teams-custom-field-team";
+
+/// Guess which custom fields hold story points, epic links, sprints and teams.
+pub fn suggest_field_map(fields: &[Field]) -> FieldMap {
+    FieldMap {
+        story_points: find_field(fields, None, &["story points", "story point estimate"]),
+        epic_link: find_field(fields, Some(EPIC_LINK_TYPE), &["epic link"]),
+        sprint: find_field(fields, Some(SPRINT_TYPE), &["sprint"]),
+        team: find_field(fields, Some(TEAM_TYPE), &["team"]),
+        checklist: find_field(fields, None, &["checklist text", "checklist"]),
+    }
+}
+
+/// Prefer a field with the expected plugin type, otherwise fall back to a custom field with a
+/// well known name.
+fn find_field(fields: &[Field], custom_type: Option<&str>, names: &[&str]) -> Option<String> {
+    let custom_fields = || fields.iter().filter(|field| field.id.starts_with("customfield_"));
+    let by_type = custom_type.and_then(|custom_type| {
+        custom_fields().find(|field| {
+            field
+                .schema
+                .as_ref()
+                .and_then(|schema| schema.custom.as_deref())
+                == Some(custom_type)
+        })
+    });
+
+    by_type
+        .or_else(|| {
+            custom_fields().find(|field| names.contains(&field.name.to_lowercase().as_str()))
+        })
+        .map(|field| field.id.clone())
+}
+
+/// Turn a custom field value into something displayable. Multi-valued fields, like the sprints
+/// an issue has been in, show the most recent value. Whole numbers, like story points sent as
+/// `5.0`, show without a fraction.
+pub fn field_text(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => None,
+        Value::String(text) => Some(greenhopper_name(text).unwrap_or(text).to_string()),
+        Value::Number(number) => Some(match number.as_f64() {
+            Some(f) if f.fract() == 0.0 && !number.is_i64() && !number.is_u64() => {
+                format!("{}", f as i64)
+            }
+            _ => number.to_string(),
+        }),
+        Value::Bool(flag) => Some(flag.to_string()),
+        Value::Array(values) => values.iter().rev().find_map(field_text),
+        Value::Object(object) => ["name", "title", "value", "key"]
+            .iter()
+            .find_map(|key| object.get(*key).and_then(Value::as_str))
+            .map(|text| text.to_string()),
+    }
+}
+
+/// Server/DC returns sprints as serialized Java objects, e.g.
+/// `com.atlassian.greenhopper.service.sprint.Sprint@1f[id=1,name=Sprint 1,...]`.
+fn greenhopper_name(text: &str) -> Option<&str> {
+    if !text.starts_with("com.atlassian.greenhopper") {
+        return None;
+    }
+    let start = text.find("name=")? + "name=".len();
+    let rest = &text[start..];
+    let end = rest.find(',').unwrap_or_else(|| rest.trim_end_matches(']').len());
+    Some(&rest[..end])
+}
@@ -0,0 +1,1760 @@
+mod cassette;
+mod error;
+mod fields;
+mod models;
+mod rest;
+
+pub use self::{
+    error::{JiraError, JiraResult},
+    rest::{RateLimit, RequestLog},
+};
+use self::{
+    fields::{field_text, suggest_field_map},
+    models::{
+        ApproximateCount, ApproximateCountRequest, Attachment, BoardResults, Comment,
+        CommentRequest, CommentResults, CreatedIssue, EditMeta, Field, FieldOperation, Issue,
+        IssueUpdate, LinkTypeResults, MyPermissions, Myself, Priority, Project, RemoteLink,
+        RemoteLinkIcon, RemoteLinkObject, RemoteLinkRequest, RemoteLinkStatus, SearchResults,
+        ServerInfo, Sprint, SprintResults, TimeTrackingField, TransitionId, TransitionRequest,
+        TransitionResults, User, Watchers, WorklogRequest,
+    },
+    rest::{Api, Rest},
+};
+use crate::{
+    config::{load_config, Config, FieldMap},
+    credentials::{
+        hostname, load_credentials, load_extra_credentials, store_token, CredentialSource,
+        Credentials,
+    },
+    tasks::TaskPool,
+};
+use anyhow::{bail, Result};
+use futures::future::join_all;
+use serde_json::{json, Value};
+use tracing::Instrument;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    fs::{self, File},
+    io::Write,
+    iter,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// The maximum number of results requested from paginated endpoints.
+const MAX_RESULTS: &str = "100";
+/// The standard fields shown for an issue, custom fields from the field map are asked for too.
+const SUMMARY_FIELDS: [&str; 9] = [
+    "summary",
+    "status",
+    "assignee",
+    "description",
+    "parent",
+    "priority",
+    "created",
+    "updated",
+    "watches",
+];
+/// Comments are loaded into the detail view this many at a time, newest first.
+const COMMENT_PAGE_SIZE: usize = 20;
+/// Cross-project searches can match a lot, stop paging through the issue list after this many.
+const MAX_ISSUES: usize = 5000;
+/// How many boards are fetched at most, big orgs can have thousands.
+const MAX_BOARDS: usize = 2000;
+/// How many people a user search lists, it's narrowed down by typing more.
+const MAX_USERS: &str = "20";
+
+#[derive(Clone)]
+pub struct JiraClient {
+    rest: Rest,
+    deployment: Deployment,
+    field_map: FieldMap,
+    credential_source: CredentialSource,
+    /// What the host's issues are badged with, `None` for the main host.
+    label: Option<String>,
+    /// Clients for `Config::extra_hosts`, searched along with this one.
+    others: Vec<JiraClient>,
+    /// Project keys mapped to the index in `others` of the host their issues came from, so
+    /// requests about them go back there.
+    routes: Arc<RwLock<BTreeMap<String, usize>>>,
+    /// Where background requests wait their turn, shared with the clients in `others`.
+    tasks: TaskPool,
+}
+
+impl JiraClient {
+    pub async fn new() -> Result<JiraClient> {
+        let config = load_config();
+        let Credentials {
+            host,
+            user,
+            pass,
+            source,
+        } = tracing::info_span!("credentials").in_scope(|| load_credentials(&config))?;
+        let mut rest = Rest::new(host, user, pass);
+        if let Ok(path) = env::var("JIRA_RECORD") {
+            rest.record_to(path.into())?;
+        }
+        let detecting = tracing::info_span!("detect_deployment");
+        let deployment = Deployment::detect(&rest).instrument(detecting).await;
+        let tasks = TaskPool::new(config.parallel_requests);
+
+        let mut others = vec![];
+        for extra in &config.extra_hosts {
+            let credentials = load_extra_credentials(extra)?;
+            let rest = Rest::new(credentials.host, credentials.user, credentials.pass);
+            let label = extra
+                .label
+                .clone()
+                .unwrap_or_else(|| hostname(&extra.host).to_string());
+            others.push(JiraClient {
+                deployment: Deployment::detect(&rest).await,
+                rest,
+                field_map: config.field_map.clone(),
+                credential_source: credentials.source,
+                label: Some(label),
+                others: vec![],
+                routes: Arc::default(),
+                tasks: tasks.clone(),
+            });
+        }
+        Ok(JiraClient {
+            rest,
+            deployment,
+            field_map: config.field_map,
+            credential_source: source,
+            label: None,
+            others,
+            routes: Arc::default(),
+            tasks,
+        })
+    }
+
+    /// A client that notes requests down instead of sending them, for tests.
+    #[cfg(test)]
+    pub fn offline() -> (JiraClient, RequestLog) {
+        let (rest, log) = Rest::offline("https://example.atlassian.net");
+        let client = JiraClient {
+            rest,
+            deployment: Deployment::Cloud,
+            field_map: FieldMap::default(),
+            credential_source: CredentialSource::Env,
+            label: None,
+            others: vec![],
+            routes: Arc::default(),
+            tasks: TaskPool::default(),
+        };
+        (client, log)
+    }
+
+    /// A client that answers requests from a cassette in `src/jira/fixtures`, for tests.
+    #[cfg(test)]
+    pub fn replay(cassette: &str, field_map: FieldMap) -> Result<JiraClient> {
+        let path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/jira/fixtures")
+            .join(format!("{}.json", cassette));
+        Ok(JiraClient {
+            rest: Rest::replay(path)?,
+            deployment: Deployment::Cloud,
+            field_map,
+            credential_source: CredentialSource::Env,
+            label: None,
+            others: vec![],
+            routes: Arc::default(),
+            tasks: TaskPool::default(),
+        })
+    }
+
+    /// The request budget left, if Jira reports one.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rest.rate_limit()
+    }
+
+    /// Where requests made in the background wait for a free slot.
+    pub fn tasks(&self) -> TaskPool {
+        self.tasks.clone()
+    }
+
+    /// Hold back requests that would change something in Jira, on every host, keeping them for
+    /// `take_unsent` instead.
+    pub fn set_dry_run(&mut self, dry_run: bool) {
+        let unsent = if dry_run {
+            Some(RequestLog::default())
+        } else {
+            None
+        };
+        self.rest.set_dry_run(unsent.clone());
+        for other in &mut self.others {
+            other.rest.set_dry_run(unsent.clone());
+        }
+    }
+
+    /// Hold back a request to somewhere other than Jira, e.g. GitHub, in a dry run, returning
+    /// whether it was. Held back requests are listed along with Jira's.
+    pub fn hold_back(&self, request: String) -> bool {
+        self.rest.hold_back(request)
+    }
+
+    /// The requests a dry run held back since this was last called, oldest first.
+    pub fn take_unsent(&self) -> Vec<String> {
+        self.rest.take_unsent()
+    }
+
+    /// The client for the host the issue with the given key was found on. Issues from projects
+    /// on the main host, or not seen in a search yet, go to the main host.
+    pub fn at(&self, key: &str) -> &JiraClient {
+        let project = key.rsplit_once('-').map_or(key, |(project, _)| project);
+        let index = self
+            .routes
+            .read()
+            .ok()
+            .and_then(|routes| routes.get(project).copied());
+        index.and_then(|i| self.others.get(i)).unwrap_or(self)
+    }
+
+    /// Swap in a new API token after the old one was rejected, saving it where the old one came
+    /// from when possible. Returns whether it was saved rather than only kept for this session.
+    pub fn reauthenticate(&self, token: String) -> Result<bool> {
+        self.rest.set_pass(token.clone());
+        store_token(
+            &load_config(),
+            self.credential_source,
+            self.rest.host(),
+            self.rest.user(),
+            &token,
+        )
+    }
+
+    /// The issue list without progress, for tests.
+    #[cfg(test)]
+    pub async fn current_issues(&self, config: &Config) -> JiraResult<Vec<IssueSummary>> {
+        self.current_issues_paged(config, |_, _| true).await
+    }
+
+    /// Like `current_issues_paged`, searching `Config::extra_hosts` at the same time and merging
+    /// what's found into one list. Extra hosts are searched across all their projects, since the
+    /// project and board are the main host's. Progress is summed across the hosts.
+    pub async fn current_issues_everywhere(
+        &self,
+        config: &Config,
+        on_page: impl Fn(usize, usize) -> bool,
+    ) -> JiraResult<Vec<IssueSummary>> {
+        if self.others.is_empty() {
+            return self.current_issues_paged(config, on_page).await;
+        }
+        let others_config = Config {
+            search_all_projects: true,
+            default_board: None,
+            ..config.clone()
+        };
+        let progress = Mutex::new(vec![(0, 0); self.others.len() + 1]);
+        let on_host_page = |host: usize, loaded: usize, total: usize| {
+            let (loaded, total) = match progress.lock() {
+                Ok(mut progress) => {
+                    progress[host] = (loaded, total);
+                    progress
+                        .iter()
+                        .fold((0, 0), |(l, t), (loaded, total)| (l + loaded, t + total))
+                }
+                Err(_) => (loaded, total),
+            };
+            on_page(loaded, total)
+        };
+        let searches = iter::once(self)
+            .chain(&self.others)
+            .enumerate()
+            .map(|(host, jira)| {
+                let config = if host == 0 { config } else { &others_config };
+                jira.current_issues_paged(config, move |loaded, total| {
+                    on_host_page(host, loaded, total)
+                })
+            });
+        let found = join_all(searches).await;
+
+        let mut routes = BTreeMap::new();
+        let mut issues = vec![];
+        for (host, found) in found.into_iter().enumerate() {
+            let found = match found {
+                // Asking for a new token only updates the main host's, so don't ask
+                Err(JiraError::Unauthorized) if host > 0 => {
+                    let label = self.others[host - 1].label.clone().unwrap_or_default();
+                    return Err(JiraError::Validation {
+                        messages: vec![format!("{} rejected the credentials", label)],
+                    });
+                }
+                found => found?,
+            };
+            for issue in &found {
+                let project = issue.key.rsplit_once('-').map(|(project, _)| project);
+                if let (Some(project), Some(other)) = (project, host.checked_sub(1)) {
+                    routes.entry(project.to_string()).or_insert(other);
+                }
+            }
+            issues.extend(found);
+        }
+        // A project on the main host stays there, even if an extra host has one with that key
+        for issue in issues.iter().filter(|issue| issue.host.is_none()) {
+            if let Some((project, _)) = issue.key.rsplit_once('-') {
+                routes.remove(project);
+            }
+        }
+        if let Ok(mut current) = self.routes.write() {
+            *current = routes;
+        }
+        Ok(issues)
+    }
+
+    /// Like `current_issues`, calling `on_page` with how many of how many issues have been
+    /// fetched after each page, for showing progress through big searches. Returning false from
+    /// it stops with the issues fetched so far.
+    pub async fn current_issues_paged(
+        &self,
+        config: &Config,
+        on_page: impl Fn(usize, usize) -> bool,
+    ) -> JiraResult<Vec<IssueSummary>> {
+        let query = issue_list_query(config);
+        // A board's issues are searched the same way, within the board's own filter
+        let issues = match &config.default_board {
+            Some(board) => {
+                let endpoint = format!("/board/{}/issue", board.id);
+                self.search_by_offset(Api::Agile, &endpoint, &query, &on_page)
+                    .await?
+            }
+            None => self.search(&query, &on_page).await?,
+        };
+
+        Ok(issues
+            .iter()
+            .map(|issue| self.summarize_issue(issue))
+            .collect())
+    }
+
+    /// Issues assigned to you with a status in the In Progress category.
+    pub async fn my_issues_in_progress(&self) -> JiraResult<Vec<IssueSummary>> {
+        let query = "assignee = currentUser() AND statusCategory = \"In Progress\" ORDER BY key";
+        let issues = self.search(query, &|_, _| true).await?;
+        Ok(issues
+            .iter()
+            .map(|issue| self.summarize_issue(issue))
+            .collect())
+    }
+
+    /// Search outside of a board. Cloud's newer search pages by token and is much quicker on big
+    /// instances, older sites answer it with a 404 and get the classic search instead.
+    async fn search(
+        &self,
+        query: &str,
+        on_page: &impl Fn(usize, usize) -> bool,
+    ) -> JiraResult<Vec<Issue>> {
+        if self.deployment == Deployment::Cloud {
+            match self.search_by_token(query, on_page).await {
+                Err(JiraError::NotFound) => {}
+                issues => return issues,
+            }
+        }
+        self.search_by_offset(Api::Core, "/search", query, on_page)
+            .await
+    }
+
+    /// Page through a search by `startAt`, as Server/Data Center and the agile API do.
+    async fn search_by_offset(
+        &self,
+        api: Api,
+        endpoint: &str,
+        query: &str,
+        on_page: &impl Fn(usize, usize) -> bool,
+    ) -> JiraResult<Vec<Issue>> {
+        let mut issues = vec![];
+        loop {
+            let results: SearchResults = self
+                .rest
+                .get(
+                    api,
+                    endpoint,
+                    &[
+                        ("jql", query.to_string()),
+                        ("maxResults", MAX_RESULTS.to_string()),
+                        ("startAt", issues.len().to_string()),
+                        ("fields", self.summary_fields()),
+                    ],
+                )
+                .await?;
+            let done = results.issues.is_empty();
+            issues.extend(results.issues);
+            let keep_going = on_page(issues.len(), results.total.min(MAX_ISSUES));
+            if done || !keep_going || issues.len() >= results.total || issues.len() >= MAX_ISSUES {
+                return Ok(issues);
+            }
+        }
+    }
+
+    /// Page through a search with Cloud's `/search/jql`, which hands back a token for the next
+    /// page instead of a total. The total for showing progress is only asked for once a search
+    /// turns out to need more than one page.
+    async fn search_by_token(
+        &self,
+        query: &str,
+        on_page: &impl Fn(usize, usize) -> bool,
+    ) -> JiraResult<Vec<Issue>> {
+        let mut issues = vec![];
+        let mut total = None;
+        let mut next_page_token: Option<String> = None;
+        loop {
+            let mut params = vec![
+                ("jql", query.to_string()),
+                ("maxResults", MAX_RESULTS.to_string()),
+                ("fields", self.summary_fields()),
+            ];
+            if let Some(token) = next_page_token {
+                params.push(("nextPageToken", token));
+            }
+            let results: SearchResults = self.rest.get(Api::Core, "/search/jql", &params).await?;
+            issues.extend(results.issues);
+            next_page_token = results.next_page_token;
+            if next_page_token.is_some() && total.is_none() {
+                total = Some(self.approximate_count(query).await.unwrap_or_default());
+            }
+            let total = total.unwrap_or_default().max(issues.len());
+            let keep_going = on_page(issues.len(), total.min(MAX_ISSUES));
+            if next_page_token.is_none() || !keep_going || issues.len() >= MAX_ISSUES {
+                return Ok(issues);
+            }
+        }
+    }
+
+    async fn approximate_count(&self, query: &str) -> JiraResult<usize> {
+        let request = ApproximateCountRequest {
+            jql: query.to_string(),
+        };
+        let count: ApproximateCount = self
+            .rest
+            .post_query(Api::Core, "/search/approximate-count", &request)
+            .await?;
+        Ok(count.count)
+    }
+
+    pub async fn get_issue(&self, key: String) -> JiraResult<IssueSummary> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", self.summary_fields())],
+            )
+            .await?;
+
+        Ok(self.summarize_issue(&issue))
+    }
+
+    /// The fields `summarize_issue` reads. Asking for only these keeps responses for big searches
+    /// small, rather than every field (and every custom field) of every issue.
+    fn summary_fields(&self) -> String {
+        let mapped = [
+            &self.field_map.story_points,
+            &self.field_map.epic_link,
+            &self.field_map.sprint,
+            &self.field_map.team,
+            &self.field_map.checklist,
+        ];
+        SUMMARY_FIELDS
+            .iter()
+            .map(|field| field.to_string())
+            .chain(mapped.iter().filter_map(|id| id.as_ref().cloned()))
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    fn summarize_issue(&self, issue: &Issue) -> IssueSummary {
+        let summary = issue
+            .summary()
+            .unwrap_or_else(|| "No summary given".to_string());
+        let assignee_name = match issue.assignee() {
+            Some(u) => u.display_name,
+            None => "Unassigned".to_string(),
+        };
+        let description = issue
+            .fields
+            .get("description")
+            .map(|value| self.deployment.render_text(value))
+            .unwrap_or_default();
+        // Prefer a dedicated checklist field, falling back to checkboxes in the description
+        let checklist = self
+            .field_map
+            .checklist
+            .as_ref()
+            .and_then(|id| {
+                let text = issue.field::<String>(id)?;
+                Some(TextField {
+                    field: id.clone(),
+                    text,
+                })
+            })
+            .unwrap_or_else(|| TextField {
+                field: "description".to_string(),
+                text: description.clone(),
+            });
+        let permalink = format!("{}/browse/{}", self.rest.host(), issue.key);
+        IssueSummary {
+            key: issue.key.clone(),
+            summary,
+            description,
+            permalink,
+            status: status_name(issue),
+            assignee_name,
+            story_points: mapped_field(issue, &self.field_map.story_points),
+            epic: mapped_field(issue, &self.field_map.epic_link),
+            sprint: mapped_field(issue, &self.field_map.sprint),
+            team: mapped_field(issue, &self.field_map.team),
+            parent: self.parent_key(issue),
+            updated: updated_at(issue),
+            created: issue.field::<String>("created").unwrap_or_default(),
+            priority: issue.priority().map(|priority| priority.name),
+            watching: issue.watches().is_some_and(|watches| watches.is_watching),
+            checklist,
+            host: self.label.clone(),
+        }
+    }
+
+    /// Sub-tasks (and epics' children on newer projects) have a parent field, older epics are
+    /// linked to through the epic link custom field.
+    fn parent_key(&self, issue: &Issue) -> Option<String> {
+        issue.field::<Issue>("parent").map(|parent| parent.key).or_else(|| {
+            self.field_map
+                .epic_link
+                .as_ref()
+                .and_then(|id| issue.field::<String>(id))
+        })
+    }
+
+    /// The sub-tasks of an issue, in the order Jira lists them.
+    pub async fn subtasks(&self, key: &str) -> JiraResult<Vec<IssueSummary>> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "subtasks".to_string())],
+            )
+            .await?;
+
+        let subtasks: Vec<Issue> = issue.field("subtasks").unwrap_or_default();
+        Ok(subtasks
+            .iter()
+            .map(|subtask| self.summarize_issue(subtask))
+            .collect())
+    }
+
+    /// An issue's status, and whether it's in the Done category.
+    pub async fn issue_status(&self, key: &str) -> JiraResult<(String, bool)> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "status".to_string())],
+            )
+            .await?;
+        Ok((status_name(&issue), is_resolved(&issue)))
+    }
+
+    /// The time estimated, spent and remaining on an issue.
+    pub async fn time_tracking(&self, key: &str) -> JiraResult<TimeTracking> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "timetracking".to_string())],
+            )
+            .await?;
+
+        let field: TimeTrackingField = issue.field("timetracking").unwrap_or_default();
+        let over_budget = match (field.time_spent_seconds, field.original_estimate_seconds) {
+            (Some(spent), Some(estimate)) => spent > estimate,
+            _ => false,
+        };
+        Ok(TimeTracking {
+            estimate: field.original_estimate,
+            spent: field.time_spent,
+            remaining: field.remaining_estimate,
+            over_budget,
+        })
+    }
+
+    /// The files attached to an issue, oldest first.
+    pub async fn attachments(&self, key: &str) -> JiraResult<Vec<AttachmentSummary>> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "attachment".to_string())],
+            )
+            .await?;
+
+        let attachments: Vec<Attachment> = issue.field("attachment").unwrap_or_default();
+        Ok(attachments
+            .into_iter()
+            .map(|attachment| AttachmentSummary {
+                filename: attachment.filename,
+                size: attachment.size,
+                url: attachment.content,
+                author: attachment
+                    .author
+                    .map(|author| author.display_name)
+                    .unwrap_or_default(),
+                created: attachment.created,
+            })
+            .collect())
+    }
+
+    /// Download an attachment into a directory, a chunk at a time so large files aren't held in
+    /// memory. A file already there by the same name is kept, the download is numbered instead.
+    pub async fn download_attachment(
+        &self,
+        attachment: &AttachmentSummary,
+        dir: &Path,
+    ) -> Result<PathBuf> {
+        let mut response = self.rest.download(&attachment.url).await?;
+        fs::create_dir_all(dir)?;
+        let path = unused_path(dir, &attachment.filename);
+        let mut file = File::create(&path)?;
+        while let Some(chunk) = response.chunk().await.map_err(JiraError::from)? {
+            file.write_all(&chunk)?;
+        }
+        Ok(path)
+    }
+
+    /// Attach a local file to an issue, returning the name it was attached under. The file is
+    /// read whole, Jira caps attachments at a size that fits in memory anyway.
+    pub async fn upload_attachment(&self, key: &str, path: &Path) -> Result<String> {
+        let filename = match path.file_name() {
+            Some(name) => name.to_string_lossy().to_string(),
+            None => bail!("{} isn't a file", path.display()),
+        };
+        let contents = fs::read(path)?;
+        // The response describes the new attachments, the list is fetched again to show them
+        let endpoint = format!("/issue/{}/attachments", key);
+        let _: Value = self
+            .rest
+            .upload(Api::Core, &endpoint, &filename, contents)
+            .await?;
+        Ok(filename)
+    }
+
+    /// Issues whose parent, or epic, is the given issue.
+    pub async fn child_issues(&self, parent_key: String) -> JiraResult<Vec<IssueSummary>> {
+        let mut clauses = vec![format!("parent = \"{}\"", parent_key)];
+        if let Some(id) = &self.field_map.epic_link {
+            let number = id.trim_start_matches("customfield_");
+            clauses.push(format!("cf[{}] = \"{}\"", number, parent_key));
+        }
+        let query = format!("{} ORDER BY key ASC", clauses.join(" OR "));
+
+        let results: SearchResults = self
+            .rest
+            .get(
+                Api::Core,
+                "/search",
+                &[
+                    ("jql", query),
+                    ("maxResults", MAX_RESULTS.to_string()),
+                    ("fields", self.summary_fields()),
+                ],
+            )
+            .await?;
+
+        Ok(results
+            .issues
+            .iter()
+            .map(|issue| self.summarize_issue(issue))
+            .collect())
+    }
+
+    /// Recent changes to issues I'm involved in, newest first.
+    pub async fn recent_activity(&self) -> JiraResult<Vec<ActivityEntry>> {
+        let query = "(assignee = currentUser() OR reporter = currentUser() OR watcher = currentUser()) \
+                     AND updated >= -7d ORDER BY updated DESC"
+            .to_string();
+        let results: SearchResults = self
+            .rest
+            .get(
+                Api::Core,
+                "/search",
+                &[
+                    ("jql", query),
+                    ("maxResults", MAX_RESULTS.to_string()),
+                    ("fields", "summary".to_string()),
+                    ("expand", "changelog".to_string()),
+                ],
+            )
+            .await?;
+
+        let mut entries: Vec<ActivityEntry> = results
+            .issues
+            .iter()
+            .flat_map(|issue| {
+                let summary = issue.summary().unwrap_or_default();
+                let histories = issue
+                    .changelog
+                    .as_ref()
+                    .map(|changelog| changelog.histories.clone())
+                    .unwrap_or_default();
+                let key = issue.key.clone();
+                histories.into_iter().flat_map(move |history| {
+                    let author = history
+                        .author
+                        .map_or_else(|| "Someone".to_string(), |user| user.display_name);
+                    let (key, summary, created) =
+                        (key.clone(), summary.clone(), history.created.clone());
+                    history.items.into_iter().map(move |item| ActivityEntry {
+                        key: key.clone(),
+                        summary: summary.clone(),
+                        author: author.clone(),
+                        field: item.field,
+                        from: item.from.unwrap_or_default(),
+                        to: item.to.unwrap_or_default(),
+                        created: created.clone(),
+                    })
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| b.created.cmp(&a.created));
+
+        Ok(entries)
+    }
+
+    /// Look through the instance's fields for the custom fields `FieldMap` cares about.
+    pub async fn discover_field_map(&self) -> JiraResult<FieldMap> {
+        let fields: Vec<Field> = self.rest.get(Api::Core, "/field", &[]).await?;
+
+        Ok(suggest_field_map(&fields))
+    }
+
+    /// Boards in the current project, only those with `name` in their names unless it's empty.
+    pub async fn current_boards(
+        &self,
+        config: &Config,
+        name: &str,
+    ) -> JiraResult<Vec<BoardSummary>> {
+        let mut boards = vec![];
+        loop {
+            let mut query = vec![
+                ("maxResults", MAX_RESULTS.to_string()),
+                ("startAt", boards.len().to_string()),
+            ];
+            if let Some(project_key) = config.project_scope() {
+                query.push(("projectKeyOrId", project_key.to_string()));
+            }
+            if !name.is_empty() {
+                query.push(("name", name.to_string()));
+            }
+            let results: BoardResults = self.rest.get(Api::Agile, "/board", &query).await?;
+            let done = results.values.is_empty() || results.is_last;
+            boards.extend(results.values);
+            if done || boards.len() >= MAX_BOARDS {
+                break;
+            }
+        }
+
+        Ok(boards
+            .iter()
+            .map(|board| BoardSummary {
+                key: board.id,
+                name: board.name.clone(),
+                project: board
+                    .location
+                    .as_ref()
+                    .and_then(|location| location.project_key.clone()),
+                permalink: format!(
+                    "{}/secure/RapidBoard.jspa?rapidView={}",
+                    self.rest.host(),
+                    board.id
+                ),
+            })
+            .collect())
+    }
+
+    /// The board's active sprint and the issues in it, in rank order, or `None` when no sprint
+    /// is running. Boards without sprints, like kanban boards, fail with a validation error.
+    pub async fn active_sprint_issues(
+        &self,
+        board_id: u64,
+    ) -> JiraResult<Option<(SprintSummary, Vec<IssueSummary>)>> {
+        let sprints: SprintResults = self
+            .rest
+            .get(
+                Api::Agile,
+                &format!("/board/{}/sprint", board_id),
+                &[("state", "active".to_string())],
+            )
+            .await?;
+        // Parallel sprints can be running, only the first is shown
+        let sprint = match sprints.values.into_iter().next() {
+            Some(sprint) => sprint,
+            None => return Ok(None),
+        };
+
+        let endpoint = format!("/sprint/{}/issue", sprint.id);
+        let issues = self
+            .search_by_offset(Api::Agile, &endpoint, "ORDER BY Rank ASC", &|_, _| true)
+            .await?;
+        let issues = issues.iter().map(|issue| self.summarize_issue(issue));
+        Ok(Some((summarize_sprint(sprint), issues.collect())))
+    }
+
+    /// The board's running sprint followed by the ones planned after it, for moving issues into.
+    pub async fn open_sprints(&self, board_id: u64) -> JiraResult<Vec<SprintSummary>> {
+        let sprints: SprintResults = self
+            .rest
+            .get(
+                Api::Agile,
+                &format!("/board/{}/sprint", board_id),
+                &[("state", "active,future".to_string())],
+            )
+            .await?;
+        Ok(sprints.values.into_iter().map(summarize_sprint).collect())
+    }
+
+    /// Move an issue into a sprint, out of the backlog or whichever sprint it was in.
+    pub async fn move_to_sprint(&self, sprint_id: u64, key: &str) -> JiraResult<()> {
+        let body = json!({ "issues": [key] });
+        self.rest
+            .post(Api::Agile, &format!("/sprint/{}/issue", sprint_id), &body)
+            .await
+    }
+
+    /// The board's backlog in rank order: the issues not in any sprint that aren't done yet. On
+    /// kanban boards without a backlog column this fails with a validation error.
+    pub async fn backlog_issues(&self, board_id: u64) -> JiraResult<Vec<IssueSummary>> {
+        let endpoint = format!("/board/{}/backlog", board_id);
+        let issues = self
+            .search_by_offset(Api::Agile, &endpoint, "ORDER BY Rank ASC", &|_, _| true)
+            .await?;
+        let issues = issues.iter().map(|issue| self.summarize_issue(issue));
+        Ok(issues.collect())
+    }
+
+    pub async fn get_transitions(&self, id: String) -> JiraResult<Vec<TransitionSummary>> {
+        let results: TransitionResults = self
+            .rest
+            .get(Api::Core, &format!("/issue/{}/transitions", id), &[])
+            .await?;
+
+        Ok(results
+            .transitions
+            .iter()
+            .map(|transition| TransitionSummary {
+                key: transition.id.clone(),
+                name: transition.name.clone(),
+            })
+            .collect())
+    }
+
+    /// The names of the priorities issues can be given, in the order Jira ranks them.
+    pub async fn get_priorities(&self) -> JiraResult<Vec<String>> {
+        let priorities: Vec<Priority> = self.rest.get(Api::Core, "/priority", &[]).await?;
+        Ok(priorities
+            .into_iter()
+            .map(|priority| priority.name)
+            .collect())
+    }
+
+    /// Give an issue the priority with the given name.
+    pub async fn set_priority(&self, key: &str, name: &str) -> JiraResult<()> {
+        let mut update = IssueUpdate::default();
+        update
+            .fields
+            .insert("priority".to_string(), json!({ "name": name }));
+
+        self.update_issue(key, &update).await
+    }
+
+    pub async fn get_issue_links(&self, key: String) -> JiraResult<Vec<IssueLinkSummary>> {
+        let issue: Issue = self
+            .rest
+            .get(Api::Core, &format!("/issue/{}", key), &[])
+            .await?;
+
+        Ok(issue
+            .links()
+            .iter()
+            .filter_map(|link| {
+                // A link only carries the issue on the other end, the direction tells us which
+                // side of the link type's description applies to the current issue.
+                let (relation, other, inward) = match (&link.inward_issue, &link.outward_issue) {
+                    (Some(other), _) => (link.link_type.inward.clone(), other, true),
+                    (None, Some(other)) => (link.link_type.outward.clone(), other, false),
+                    (None, None) => return None,
+                };
+                Some(IssueLinkSummary {
+                    relation,
+                    key: other.key.clone(),
+                    summary: other
+                        .summary()
+                        .unwrap_or_else(|| "No summary given".to_string()),
+                    status: status_name(other),
+                    resolved: is_resolved(other),
+                    blocker: inward && link.link_type.name == "Blocks",
+                })
+            })
+            .collect())
+    }
+
+    /// Both ends of every kind of link issues can have, e.g. "blocks" and "is blocked by". Kinds
+    /// that read the same either way, like "relates to", are only given once.
+    pub async fn link_relations(&self) -> JiraResult<Vec<LinkRelation>> {
+        let results: LinkTypeResults = self.rest.get(Api::Core, "/issueLinkType", &[]).await?;
+        let mut relations = vec![];
+        for link_type in results.issue_link_types {
+            relations.push(LinkRelation {
+                link_type: link_type.name.clone(),
+                description: link_type.outward.clone(),
+                outward: true,
+            });
+            if link_type.inward != link_type.outward {
+                relations.push(LinkRelation {
+                    link_type: link_type.name,
+                    description: link_type.inward,
+                    outward: false,
+                });
+            }
+        }
+        Ok(relations)
+    }
+
+    /// Link the issue to the target, so that it reads as `key relation target`, e.g.
+    /// `PROJ-1 is blocked by PROJ-2`.
+    pub async fn create_link(
+        &self,
+        key: &str,
+        relation: &LinkRelation,
+        target: &str,
+    ) -> JiraResult<()> {
+        // The issue given as inward is the one the outward description reads from, so
+        // `PROJ-1 blocks PROJ-2` has PROJ-1 as the inward issue
+        let (inward, outward) = if relation.outward {
+            (key, target)
+        } else {
+            (target, key)
+        };
+        let body = json!({
+            "type": { "name": relation.link_type },
+            "inwardIssue": { "key": inward },
+            "outwardIssue": { "key": outward },
+        });
+        self.rest.post(Api::Core, "/issueLink", &body).await
+    }
+
+    /// The issue's comments, oldest first.
+    pub async fn get_comments(&self, key: &str) -> JiraResult<Vec<CommentSummary>> {
+        let results: CommentResults = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}/comment", key),
+                &[
+                    ("maxResults", MAX_RESULTS.to_string()),
+                    ("orderBy", "created".to_string()),
+                ],
+            )
+            .await?;
+
+        Ok(results
+            .comments
+            .iter()
+            .map(|comment| self.summarize_comment(comment))
+            .collect())
+    }
+
+    /// A page of the issue's comments, skipping the `loaded` most recent ones. Pages go back in
+    /// time so threads with hundreds of comments open on the latest.
+    pub async fn comment_page(&self, key: &str, loaded: usize) -> JiraResult<CommentPage> {
+        let results: CommentResults = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}/comment", key),
+                &[
+                    ("startAt", loaded.to_string()),
+                    ("maxResults", COMMENT_PAGE_SIZE.to_string()),
+                    ("orderBy", "-created".to_string()),
+                ],
+            )
+            .await?;
+
+        let mut comments: Vec<CommentSummary> = results
+            .comments
+            .iter()
+            .map(|comment| self.summarize_comment(comment))
+            .collect();
+        comments.reverse();
+        Ok(CommentPage {
+            older: results.total.saturating_sub(loaded + comments.len()),
+            loaded,
+            comments,
+        })
+    }
+
+    fn summarize_comment(&self, comment: &Comment) -> CommentSummary {
+        CommentSummary {
+            author: comment
+                .author
+                .as_ref()
+                .map_or_else(|| "Someone".to_string(), |user| user.display_name.clone()),
+            body: self.deployment.render_text(&comment.body),
+            created: comment.created.clone(),
+        }
+    }
+
+    pub async fn do_transition(&self, issue_id: String, transition_id: String) -> JiraResult<()> {
+        let body = TransitionRequest {
+            transition: TransitionId { id: transition_id },
+        };
+        self.rest
+            .post(Api::Core, &format!("/issue/{}/transitions", issue_id), &body)
+            .await
+    }
+
+    /// Make the issue's transition with the given name, ignoring case. Which transitions are
+    /// available depends on the issue's workflow and status, so this returns the matching
+    /// transition's name if it was made, or None if the issue has no such transition.
+    pub async fn transition_to(&self, key: &str, name: &str) -> JiraResult<Option<String>> {
+        let transitions = self.get_transitions(key.to_string()).await?;
+        let transition = transitions
+            .into_iter()
+            .find(|transition| transition.name.eq_ignore_ascii_case(name));
+        match transition {
+            Some(transition) => {
+                self.do_transition(key.to_string(), transition.key).await?;
+                Ok(Some(transition.name))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// People matching what's been typed of their name or email address.
+    pub async fn search_users(&self, query: &str) -> JiraResult<Vec<UserSummary>> {
+        // Server predates the query parameter, its username one matches names and emails too
+        let by = match self.deployment {
+            Deployment::Cloud => "query",
+            Deployment::Server => "username",
+        };
+        let users: Vec<User> = self
+            .rest
+            .get(
+                Api::Core,
+                "/user/search",
+                &[
+                    (by, query.to_string()),
+                    ("maxResults", MAX_USERS.to_string()),
+                ],
+            )
+            .await?;
+        Ok(users
+            .iter()
+            .filter_map(|user| self.summarize_user(user))
+            .collect())
+    }
+
+    /// The user the client is logged in as.
+    pub async fn myself(&self) -> JiraResult<UserSummary> {
+        let user: User = self.rest.get(Api::Core, "/myself", &[]).await?;
+        self.summarize_user(&user)
+            .ok_or_else(|| JiraError::Deserialization("No id for the current user".to_string()))
+    }
+
+    /// Who the client is logged in as and what it's talking to, for working out why requests
+    /// are made as the wrong account.
+    pub async fn account_info(&self) -> JiraResult<AccountInfo> {
+        let myself: Myself = self
+            .rest
+            .get(Api::Core, "/myself", &[("expand", "groups".to_string())])
+            .await?;
+        let server: Option<ServerInfo> = self.rest.get(Api::Core, "/serverInfo", &[]).await.ok();
+        let server = server.as_ref();
+        let groups = myself.groups.items.into_iter().map(|group| group.name);
+        Ok(AccountInfo {
+            display_name: myself.user.display_name.clone(),
+            email: myself.email_address,
+            id: self.summarize_user(&myself.user).map(|user| user.id),
+            groups: groups.collect(),
+            login: self.rest.user().to_string(),
+            credential_source: self.credential_source,
+            deployment: self.deployment,
+            host: self.rest.host().to_string(),
+            base_url: server.and_then(|server| server.base_url.clone()),
+            version: server.and_then(|server| server.version.clone()),
+        })
+    }
+
+    /// Which of the given permissions the user holds on an issue, and the fields they can edit.
+    pub async fn issue_permissions(
+        &self,
+        key: &str,
+        permissions: &[&str],
+    ) -> JiraResult<IssuePermissions> {
+        let query = [
+            ("issueKey", key.to_string()),
+            ("permissions", permissions.join(",")),
+        ];
+        let held: MyPermissions = self.rest.get(Api::Core, "/mypermissions", &query).await?;
+        let endpoint = format!("/issue/{}/editmeta", key);
+        let meta: EditMeta = self.rest.get(Api::Core, &endpoint, &[]).await?;
+        let held = held.permissions.into_iter();
+        Ok(IssuePermissions {
+            held: held
+                .filter(|(_, permission)| permission.have_permission)
+                .map(|(name, _)| name)
+                .collect(),
+            editable_fields: meta.fields.into_keys().collect(),
+        })
+    }
+
+    /// Assign an issue to the user with the given id, or to no one.
+    pub async fn assign_issue(&self, key: &str, user_id: Option<&str>) -> JiraResult<()> {
+        let body = match self.deployment {
+            Deployment::Cloud => json!({ "accountId": user_id }),
+            Deployment::Server => json!({ "name": user_id }),
+        };
+        self.rest
+            .put(Api::Core, &format!("/issue/{}/assignee", key), &body)
+            .await
+    }
+
+    /// The names of the people watching an issue.
+    pub async fn watchers(&self, key: &str) -> JiraResult<Vec<String>> {
+        let endpoint = format!("/issue/{}/watchers", key);
+        let watchers: Watchers = self.rest.get(Api::Core, &endpoint, &[]).await?;
+        Ok(watchers
+            .watchers
+            .into_iter()
+            .map(|user| user.display_name)
+            .collect())
+    }
+
+    /// Start or stop watching an issue as the logged in user.
+    pub async fn set_watching(&self, key: &str, watching: bool) -> JiraResult<()> {
+        let me = self.myself().await?;
+        let endpoint = format!("/issue/{}/watchers", key);
+        if watching {
+            // The body is just the user's id as a JSON string
+            return self.rest.post(Api::Core, &endpoint, &me.id).await;
+        }
+        let param = match self.deployment {
+            Deployment::Cloud => "accountId",
+            Deployment::Server => "username",
+        };
+        self.rest
+            .delete(Api::Core, &endpoint, &[(param, me.id)])
+            .await
+    }
+
+    fn summarize_user(&self, user: &User) -> Option<UserSummary> {
+        let id = match self.deployment {
+            Deployment::Cloud => user.account_id.clone(),
+            Deployment::Server => user.name.clone(),
+        };
+        Some(UserSummary {
+            id: id?,
+            display_name: user.display_name.clone(),
+        })
+    }
+
+    /// Comment on an issue. The text is sent as wiki markup, which this version of the API takes
+    /// on Cloud as well as Server.
+    pub async fn add_comment(&self, key: &str, text: &str) -> JiraResult<()> {
+        let body = CommentRequest {
+            body: text.to_string(),
+        };
+        // The response is the new comment, the thread is fetched again to show it
+        let _: Value = self
+            .rest
+            .post(Api::Core, &format!("/issue/{}/comment", key), &body)
+            .await?;
+        Ok(())
+    }
+
+    /// Log time spent on an issue, Jira records it as starting now. The comment is wiki markup,
+    /// like an issue comment's.
+    pub async fn add_worklog(
+        &self,
+        key: &str,
+        seconds: u64,
+        comment: Option<String>,
+    ) -> JiraResult<()> {
+        let body = WorklogRequest {
+            time_spent_seconds: seconds,
+            comment,
+        };
+        // The response describes the new worklog, which isn't needed
+        let _: Value = self
+            .rest
+            .post(Api::Core, &format!("/issue/{}/worklog", key), &body)
+            .await?;
+        Ok(())
+    }
+
+    /// Link a pull request to an issue, so it's listed in Jira with the forge's icon rather than
+    /// only mentioned in a comment.
+    pub async fn link_pull_request(&self, key: &str, url: &str) -> JiraResult<()> {
+        let body = pull_request_link(url);
+        let _: Value = self
+            .rest
+            .post(Api::Core, &format!("/issue/{}/remotelink", key), &body)
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the issue has a PR linked that isn't merged or closed yet, going by the resolved
+    /// flag forges set on the remote links they add.
+    pub async fn has_open_pull_request(&self, key: &str) -> JiraResult<bool> {
+        let links: Vec<RemoteLink> = self
+            .rest
+            .get(Api::Core, &format!("/issue/{}/remotelink", key), &[])
+            .await?;
+        Ok(links.iter().any(|link| {
+            let pull_request = link.relationship.as_deref() == Some("pull request")
+                || is_pull_request_url(&link.object.url);
+            let status = link.object.status.as_ref();
+            pull_request && !status.is_some_and(|status| status.resolved)
+        }))
+    }
+
+    /// Create a sub-task under an issue, as the first sub-task type its project has. Returns the
+    /// new issue's key, or `None` on a dry run where nothing was created.
+    pub async fn create_subtask(
+        &self,
+        parent_key: &str,
+        summary: &str,
+    ) -> JiraResult<Option<String>> {
+        let project_key = match parent_key.rsplit_once('-') {
+            Some((project_key, _)) => project_key,
+            None => parent_key,
+        };
+        let project: Project = self
+            .rest
+            .get(Api::Core, &format!("/project/{}", project_key), &[])
+            .await?;
+        let issue_type = project
+            .issue_types
+            .into_iter()
+            .find(|issue_type| issue_type.subtask)
+            .ok_or_else(|| JiraError::Validation {
+                messages: vec![format!("{} has no sub-task issue type", project_key)],
+            })?;
+
+        let body = json!({
+            "fields": {
+                "project": { "key": project_key },
+                "parent": { "key": parent_key },
+                "summary": summary,
+                "issuetype": { "id": issue_type.id },
+            }
+        });
+        let created: Option<CreatedIssue> = self.rest.post(Api::Core, "/issue", &body).await?;
+        Ok(created.map(|created| created.key))
+    }
+
+    async fn update_issue(&self, key: &str, update: &IssueUpdate) -> JiraResult<()> {
+        self.rest
+            .put(Api::Core, &format!("/issue/{}", key), update)
+            .await
+    }
+
+    pub async fn get_labels(&self, key: &str) -> JiraResult<Vec<String>> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "labels".to_string())],
+            )
+            .await?;
+
+        Ok(issue.labels())
+    }
+
+    /// Read a text field along with when the issue was last updated.
+    pub async fn get_text_field(&self, key: &str, field: &str) -> JiraResult<(String, String)> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", format!("{},updated", field))],
+            )
+            .await?;
+
+        Ok((
+            issue.field::<String>(field).unwrap_or_default(),
+            updated_at(&issue),
+        ))
+    }
+
+    /// Fail with a conflict if the issue changed since `updated`, the last time we saw it. Jira
+    /// has no conditional updates so this is checked just before writing.
+    async fn ensure_unchanged(&self, key: &str, updated: &str) -> JiraResult<()> {
+        let issue: Issue = self
+            .rest
+            .get(
+                Api::Core,
+                &format!("/issue/{}", key),
+                &[("fields", "updated".to_string())],
+            )
+            .await?;
+
+        if updated_at(&issue) == updated {
+            Ok(())
+        } else {
+            Err(JiraError::Conflict)
+        }
+    }
+
+    /// Save a text field unless the issue changed since `updated`, returning the new timestamp.
+    pub async fn save_text_field(
+        &self,
+        key: &str,
+        updated: &str,
+        field: &TextField,
+    ) -> JiraResult<String> {
+        self.ensure_unchanged(key, updated).await?;
+        self.update_text_field(key, field).await?;
+
+        let (_, updated) = self.get_text_field(key, &field.field).await?;
+        Ok(updated)
+    }
+
+    /// Replace the contents of a plain text field, like the description.
+    pub async fn update_text_field(&self, key: &str, field: &TextField) -> JiraResult<()> {
+        let mut update = IssueUpdate::default();
+        update
+            .fields
+            .insert(field.field.clone(), Value::String(field.text.clone()));
+
+        self.update_issue(key, &update).await
+    }
+
+    /// Apply an edit to an issue, unless it changed since `updated`.
+    pub async fn apply_bulk_edit(
+        &self,
+        key: &str,
+        updated: &str,
+        edit: &BulkEdit,
+    ) -> JiraResult<()> {
+        self.ensure_unchanged(key, updated).await?;
+        self.edit_issue(key, edit).await
+    }
+
+    /// Apply an edit to an issue as it is now.
+    pub async fn edit_issue(&self, key: &str, edit: &BulkEdit) -> JiraResult<()> {
+        let (field, operation) = match edit {
+            BulkEdit::AddLabel(label) => ("labels", FieldOperation::Add(json!(label))),
+            BulkEdit::RemoveLabel(label) => ("labels", FieldOperation::Remove(json!(label))),
+            BulkEdit::SetFixVersion(version) => (
+                "fixVersions",
+                FieldOperation::Set(json!([{ "name": version }])),
+            ),
+        };
+        let mut update = IssueUpdate::default();
+        update.update.insert(field.to_string(), vec![operation]);
+
+        self.update_issue(key, &update).await
+    }
+}
+
+/// A change to an issue, which can be applied to many at once.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BulkEdit {
+    AddLabel(String),
+    RemoveLabel(String),
+    SetFixVersion(String),
+}
+
+/// Jira Cloud and self-hosted Server/Data Center instances differ in a few endpoints and in
+/// how rich text is represented, so requests are routed based on which one we're talking to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Deployment {
+    Cloud,
+    Server,
+}
+
+impl Deployment {
+    /// Ask the instance what it is, falling back to guessing from the host name if the
+    /// server info can't be read.
+    async fn detect(rest: &Rest) -> Deployment {
+        let info: Option<ServerInfo> = rest.get(Api::Core, "/serverInfo", &[]).await.ok();
+
+        match info.and_then(|info| info.deployment_type) {
+            Some(deployment_type) if deployment_type == "Cloud" => Deployment::Cloud,
+            Some(_) => Deployment::Server,
+            None if rest.host().contains(".atlassian.net") => Deployment::Cloud,
+            None => Deployment::Server,
+        }
+    }
+
+    /// Cloud may hand back rich text as Atlassian Document Format, Server only ever uses
+    /// wiki markup strings.
+    pub fn render_text(self, value: &Value) -> String {
+        match (self, value) {
+            (_, Value::String(text)) => text.clone(),
+            (Deployment::Cloud, Value::Object(_)) => adf_to_text(value).trim_end().to_string(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// The JQL the issue list is searched with: the query typed in, or one built from the filters.
+fn issue_list_query(config: &Config) -> String {
+    if let Some(jql) = &config.jql {
+        return jql.clone();
+    }
+
+    // status=3 is "In Progress"
+    let mut query_parts: Vec<String> = vec![];
+
+    if config.filter_mine {
+        query_parts.push("assignee=currentuser()".to_string());
+    }
+
+    if config.filter_in_progress {
+        query_parts.push("status=3".to_string());
+    } else {
+        query_parts.push("status=\"Prioritised\"".to_string());
+    }
+
+    if let Some(project_key) = config.project_scope() {
+        query_parts.push(format!("project = \"{}\"", project_key));
+    }
+
+    query_parts.join(" AND ")
+}
+
+fn adf_to_text(node: &Value) -> String {
+    let mut text = node
+        .get("text")
+        .and_then(Value::as_str)
+        .unwrap_or("")
+        .to_string();
+    if let Some(children) = node.get("content").and_then(Value::as_array) {
+        for child in children {
+            text.push_str(&adf_to_text(child));
+        }
+    }
+    match node.get("type").and_then(Value::as_str) {
+        Some("paragraph") | Some("heading") | Some("listItem") | Some("codeBlock")
+        | Some("hardBreak") => text.push('\n'),
+        _ => {}
+    }
+    text
+}
+
+#[derive(Clone, Default)]
+pub struct IssueSummary {
+    pub key: String,
+    pub summary: String,
+    pub description: String,
+    pub permalink: String,
+    pub status: String,
+    pub assignee_name: String,
+    pub story_points: Option<String>,
+    pub epic: Option<String>,
+    pub sprint: Option<String>,
+    pub team: Option<String>,
+    /// The key of the parent issue or epic.
+    pub parent: Option<String>,
+    /// When the issue was last changed, used to avoid overwriting other people's edits.
+    pub updated: String,
+    pub created: String,
+    pub priority: Option<String>,
+    /// Whether the logged in user is watching the issue.
+    pub watching: bool,
+    /// Where checklist items for the issue are read from and written back to.
+    pub checklist: TextField,
+    /// The label of the extra host the issue is from, `None` for the main host.
+    pub host: Option<String>,
+}
+
+impl IssueSummary {
+    /// How urgent the issue's priority is, 0 being the most urgent. Covers both the current
+    /// default priority scheme and the older Blocker..Trivial one.
+    pub fn severity(&self) -> u8 {
+        match self.priority.as_deref().map(str::to_lowercase).as_deref() {
+            Some("highest") | Some("blocker") => 0,
+            Some("high") | Some("critical") => 1,
+            Some("low") | Some("minor") => 3,
+            Some("lowest") | Some("trivial") => 4,
+            _ => 2,
+        }
+    }
+
+    /// The epic the issue belongs to. Newer projects link issues to their epic as their parent
+    /// rather than through the epic link field, so sub-tasks come out under their parent.
+    pub fn epic_key(&self) -> Option<&str> {
+        self.epic.as_deref().or(self.parent.as_deref())
+    }
+}
+
+/// A change to a single field of an issue.
+#[derive(Clone)]
+pub struct ActivityEntry {
+    pub key: String,
+    pub summary: String,
+    pub author: String,
+    /// The field that changed, this is also the type of event for muting purposes.
+    pub field: String,
+    pub from: String,
+    pub to: String,
+    pub created: String,
+}
+
+#[derive(Clone)]
+pub struct CommentSummary {
+    pub author: String,
+    pub body: String,
+    pub created: String,
+}
+
+/// Comments in the order they were written, from a page fetched after `loaded` newer ones.
+#[derive(Clone)]
+pub struct CommentPage {
+    pub comments: Vec<CommentSummary>,
+    pub loaded: usize,
+    /// How many comments are older than this page.
+    pub older: usize,
+}
+
+/// The raw text of an issue field along with the field's ID.
+#[derive(Clone, Default)]
+pub struct TextField {
+    pub field: String,
+    pub text: String,
+}
+
+/// One end of a kind of link, as picked when linking an issue to another.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LinkRelation {
+    /// The name of the link type, e.g. `Blocks`.
+    pub link_type: String,
+    /// How the issue being linked relates to the other one, e.g. `is blocked by`.
+    pub description: String,
+    /// Whether that's the link type's outward description.
+    pub outward: bool,
+}
+
+#[derive(Clone)]
+pub struct IssueLinkSummary {
+    pub relation: String,
+    pub key: String,
+    pub summary: String,
+    pub status: String,
+    pub resolved: bool,
+    /// The linked issue blocks the one the links were fetched for.
+    pub blocker: bool,
+}
+
+/// The path segment before the number in PR URLs on GitHub, Bitbucket and GitLab.
+const PULL_REQUEST_SEGMENTS: [&str; 3] = ["pull", "pull-requests", "merge_requests"];
+
+fn is_pull_request_url(url: &str) -> bool {
+    url.split('/')
+        .any(|segment| PULL_REQUEST_SEGMENTS.contains(&segment))
+}
+
+/// A remote link for a PR on GitHub, GitLab or Bitbucket, titled like `platform #12` from its
+/// URL. The icon is the forge's favicon.
+fn pull_request_link(url: &str) -> RemoteLinkRequest {
+    let url = url.trim().trim_end_matches('/');
+    let segments: Vec<&str> = url.split('/').collect();
+    let marker = segments
+        .iter()
+        .position(|segment| PULL_REQUEST_SEGMENTS.contains(segment));
+    let title = match marker {
+        Some(i) if i + 1 < segments.len() => {
+            // GitLab puts a `-` between the project and the merge request
+            let repo = segments[..i].iter().rev().find(|segment| **segment != "-");
+            format!("{} #{}", repo.unwrap_or(&""), segments[i + 1])
+        }
+        _ => "Pull request".to_string(),
+    };
+    let site = segments.get(..3).map(|site| site.join("/")).unwrap_or_default();
+    RemoteLinkRequest {
+        global_id: url.to_string(),
+        relationship: "pull request".to_string(),
+        object: RemoteLinkObject {
+            url: url.to_string(),
+            title,
+            icon: RemoteLinkIcon {
+                url: format!("{}/favicon.ico", site),
+                title: "Pull request".to_string(),
+            },
+            status: RemoteLinkStatus { resolved: false },
+        },
+    }
+}
+
+fn mapped_field(issue: &Issue, field_id: &Option<String>) -> Option<String> {
+    field_id
+        .as_ref()
+        .and_then(|id| issue.fields.get(id))
+        .and_then(field_text)
+}
+
+fn updated_at(issue: &Issue) -> String {
+    issue.field::<String>("updated").unwrap_or_default()
+}
+
+/// Where to save a file in a directory without overwriting anything, e.g. `log (2).txt` when
+/// `log.txt` is taken. Only the file name is used from what Jira calls the file.
+fn unused_path(dir: &Path, filename: &str) -> PathBuf {
+    let name = Path::new(filename)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("attachment");
+    let (stem, extension) = match name.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+        _ => (name, String::new()),
+    };
+    let mut path = dir.join(name);
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{} ({}){}", stem, n, extension));
+        n += 1;
+    }
+    path
+}
+
+fn status_name(issue: &Issue) -> String {
+    issue
+        .status()
+        .map(|status| status.name)
+        .unwrap_or_else(|| "Unknown".to_string())
+}
+
+/// Linked issues only come with a handful of fields, so rely on the status category rather
+/// than the resolution to decide whether they're finished.
+fn is_resolved(issue: &Issue) -> bool {
+    match issue.status().and_then(|status| status.category) {
+        Some(category) => category.key == "done",
+        None => false,
+    }
+}
+
+fn summarize_sprint(sprint: Sprint) -> SprintSummary {
+    SprintSummary {
+        id: sprint.id,
+        name: sprint.name,
+        active: sprint.state == "active",
+        goal: sprint.goal.unwrap_or_default(),
+        end_date: sprint.end_date,
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct BoardSummary {
+    pub key: u64,
+    pub name: String,
+    pub project: Option<String>,
+    pub permalink: String,
+}
+
+/// A sprint on a board, running or planned.
+#[derive(Clone, Debug, Default)]
+pub struct SprintSummary {
+    pub id: u64,
+    pub name: String,
+    /// Running now rather than planned for later.
+    pub active: bool,
+    pub goal: String,
+    /// When the sprint is due to end, as Jira wrote it.
+    pub end_date: Option<String>,
+}
+
+/// A file attached to an issue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AttachmentSummary {
+    pub filename: String,
+    /// In bytes.
+    pub size: u64,
+    /// Where the file is downloaded from.
+    pub url: String,
+    pub author: String,
+    pub created: String,
+}
+
+/// Time tracked on an issue, written the way Jira writes it, e.g. `1d 4h`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TimeTracking {
+    pub estimate: Option<String>,
+    pub spent: Option<String>,
+    pub remaining: Option<String>,
+    /// More time has been spent than was first estimated.
+    pub over_budget: bool,
+}
+
+impl TimeTracking {
+    /// A line like `Time: 1d estimated, 1d 2h spent, 0m remaining (over the estimate)`, or
+    /// `None` for an issue that's neither estimated nor had work logged.
+    pub fn describe(&self) -> Option<String> {
+        let parts = [
+            ("estimated", &self.estimate),
+            ("spent", &self.spent),
+            ("remaining", &self.remaining),
+        ];
+        let parts: Vec<String> = parts
+            .iter()
+            .filter_map(|(name, value)| Some(format!("{} {}", value.as_ref()?, name)))
+            .collect();
+        if parts.is_empty() {
+            return None;
+        }
+        let mut line = format!("Time: {}", parts.join(", "));
+        if self.over_budget {
+            line.push_str(" (over the estimate)");
+        }
+        Some(line)
+    }
+}
+
+#[derive(Clone)]
+pub struct TransitionSummary {
+    pub key: String,
+    pub name: String,
+}
+
+/// What the user is allowed to do to an issue.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IssuePermissions {
+    /// Keys of the permissions held, out of those asked about, e.g. `ASSIGN_ISSUES`.
+    pub held: BTreeSet<String>,
+    /// Ids of the fields on the issue's edit screen.
+    pub editable_fields: BTreeSet<String>,
+}
+
+/// The account requests are made as, and the instance they're made to.
+#[derive(Clone, Debug)]
+pub struct AccountInfo {
+    pub display_name: String,
+    pub email: Option<String>,
+    /// The account id on Cloud, the user name on Server.
+    pub id: Option<String>,
+    pub groups: Vec<String>,
+    /// The user the client logs in with, which isn't always the account it ends up as.
+    pub login: String,
+    pub credential_source: CredentialSource,
+    pub deployment: Deployment,
+    pub host: String,
+    pub base_url: Option<String>,
+    /// Jira's own version.
+    pub version: Option<String>,
+}
+
+/// Someone issues can be assigned to. `id` is an account id on Cloud and a user name on Server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UserSummary {
+    pub id: String,
+    pub display_name: String,
+}
+
+#[cfg(test)]
+mod tests;
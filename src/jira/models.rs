@@ -0,0 +1,435 @@
+//! Typed representations of the parts of Jira's REST responses that this app reads.
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Issue {
+    pub key: String,
+    /// Fields are left untyped since which ones come back, and the shape of custom fields,
+    /// depends on the request and the instance.
+    #[serde(default)]
+    pub fields: BTreeMap<String, Value>,
+    /// Only present when requested with `expand=changelog`.
+    pub changelog: Option<Changelog>,
+}
+
+impl Issue {
+    /// Read a field straight out of the JSON without copying it first.
+    pub fn field<F: DeserializeOwned>(&self, name: &str) -> Option<F> {
+        self.fields
+            .get(name)
+            .and_then(|value| F::deserialize(value).ok())
+    }
+
+    pub fn summary(&self) -> Option<String> {
+        self.field("summary")
+    }
+
+    pub fn status(&self) -> Option<Status> {
+        self.field("status")
+    }
+
+    pub fn priority(&self) -> Option<Priority> {
+        self.field("priority")
+    }
+
+    pub fn assignee(&self) -> Option<User> {
+        self.field("assignee")
+    }
+
+    pub fn labels(&self) -> Vec<String> {
+        self.field("labels").unwrap_or_default()
+    }
+
+    pub fn links(&self) -> Vec<IssueLink> {
+        self.field("issuelinks").unwrap_or_default()
+    }
+
+    pub fn watches(&self) -> Option<Watches> {
+        self.field("watches")
+    }
+}
+
+/// The `watches` field, as seen by the user making the request.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Watches {
+    #[serde(rename = "isWatching")]
+    pub is_watching: bool,
+}
+
+/// Everyone watching an issue.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Watchers {
+    pub watchers: Vec<User>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Status {
+    pub name: String,
+    #[serde(rename = "statusCategory")]
+    pub category: Option<StatusCategory>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct StatusCategory {
+    pub key: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Priority {
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct User {
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    /// How Cloud identifies users.
+    #[serde(default, rename = "accountId")]
+    pub account_id: Option<String>,
+    /// How Server identifies users.
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct IssueLink {
+    #[serde(rename = "inwardIssue")]
+    pub inward_issue: Option<Issue>,
+    #[serde(rename = "outwardIssue")]
+    pub outward_issue: Option<Issue>,
+    #[serde(rename = "type")]
+    pub link_type: LinkType,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LinkType {
+    pub name: String,
+    pub inward: String,
+    pub outward: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct LinkTypeResults {
+    #[serde(rename = "issueLinkTypes")]
+    pub issue_link_types: Vec<LinkType>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct Changelog {
+    #[serde(default)]
+    pub histories: Vec<History>,
+}
+
+/// A single change to an issue, which may touch several fields at once.
+#[derive(Deserialize, Debug, Clone)]
+pub struct History {
+    pub author: Option<User>,
+    pub created: String,
+    #[serde(default)]
+    pub items: Vec<HistoryItem>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct HistoryItem {
+    pub field: String,
+    #[serde(rename = "fromString")]
+    pub from: Option<String>,
+    #[serde(rename = "toString")]
+    pub to: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchResults {
+    pub issues: Vec<Issue>,
+    /// How many issues match in total, across every page.
+    #[serde(default)]
+    pub total: usize,
+    /// Where the next page starts, from Cloud's newer search which pages by token.
+    #[serde(default, rename = "nextPageToken")]
+    pub next_page_token: Option<String>,
+}
+
+/// The body asking Cloud roughly how many issues a search matches, since its newer search
+/// doesn't report a total.
+#[derive(Serialize, Debug)]
+pub struct ApproximateCountRequest {
+    pub jql: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ApproximateCount {
+    pub count: usize,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Comment {
+    pub author: Option<User>,
+    /// Wiki markup on Server, Atlassian Document Format on Cloud.
+    pub body: Value,
+    pub created: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CommentResults {
+    pub comments: Vec<Comment>,
+    #[serde(default)]
+    pub total: usize,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Board {
+    pub id: u64,
+    pub name: String,
+    /// The project a board belongs to, missing for boards spanning several.
+    #[serde(default)]
+    pub location: Option<BoardLocation>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BoardLocation {
+    #[serde(rename = "projectKey")]
+    pub project_key: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BoardResults {
+    pub values: Vec<Board>,
+    /// Whether this is the last page of boards.
+    #[serde(default, rename = "isLast")]
+    pub is_last: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Sprint {
+    pub id: u64,
+    pub name: String,
+    /// `active`, `future` or `closed`.
+    #[serde(default)]
+    pub state: String,
+    #[serde(default)]
+    pub goal: Option<String>,
+    #[serde(default, rename = "endDate")]
+    pub end_date: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SprintResults {
+    pub values: Vec<Sprint>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Transition {
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct TransitionResults {
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Field {
+    pub id: String,
+    pub name: String,
+    pub schema: Option<FieldSchema>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct FieldSchema {
+    /// The plugin type of a custom field, e.g. `com.pyxis.greenhopper.jira:gh-sprint`.
+    pub custom: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct ServerInfo {
+    #[serde(rename = "deploymentType")]
+    pub deployment_type: Option<String>,
+    /// Where Jira thinks it's hosted, which may not be the host requests are sent to.
+    #[serde(default, rename = "baseUrl")]
+    pub base_url: Option<String>,
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// The logged in user, along with the groups they're in.
+#[derive(Deserialize, Debug)]
+pub struct Myself {
+    #[serde(flatten)]
+    pub user: User,
+    #[serde(default, rename = "emailAddress")]
+    pub email_address: Option<String>,
+    #[serde(default)]
+    pub groups: Groups,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct Groups {
+    #[serde(default)]
+    pub items: Vec<Group>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Group {
+    pub name: String,
+}
+
+/// The permissions asked about, by key, whether or not they're held.
+#[derive(Deserialize, Debug)]
+pub struct MyPermissions {
+    pub permissions: BTreeMap<String, Permission>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct Permission {
+    #[serde(rename = "havePermission")]
+    pub have_permission: bool,
+}
+
+/// The fields an issue's edit screen lets the user change, by field id.
+#[derive(Deserialize, Debug)]
+pub struct EditMeta {
+    #[serde(default)]
+    pub fields: BTreeMap<String, Value>,
+}
+
+/// Only the issue types are read, to find the one sub-tasks are created as.
+#[derive(Deserialize, Debug)]
+pub struct Project {
+    #[serde(default, rename = "issueTypes")]
+    pub issue_types: Vec<IssueType>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct IssueType {
+    pub id: String,
+    #[serde(default)]
+    pub subtask: bool,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct CreatedIssue {
+    pub key: String,
+}
+
+#[derive(Serialize)]
+pub struct TransitionRequest {
+    pub transition: TransitionId,
+}
+
+#[derive(Serialize)]
+pub struct TransitionId {
+    pub id: String,
+}
+
+#[derive(Serialize)]
+pub struct CommentRequest {
+    pub body: String,
+}
+
+#[derive(Serialize)]
+pub struct WorklogRequest {
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+/// A link from an issue to something outside Jira, listed with the issue's links in the web UI.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RemoteLinkRequest {
+    /// Linking with the same ID again updates the link rather than adding another.
+    #[serde(rename = "globalId")]
+    pub global_id: String,
+    pub relationship: String,
+    pub object: RemoteLinkObject,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RemoteLinkObject {
+    pub url: String,
+    pub title: String,
+    pub icon: RemoteLinkIcon,
+    pub status: RemoteLinkStatus,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RemoteLinkIcon {
+    #[serde(rename = "url16x16")]
+    pub url: String,
+    pub title: String,
+}
+
+/// Resolved links are struck through.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct RemoteLinkStatus {
+    pub resolved: bool,
+}
+
+/// A file attached to an issue, from the `attachment` field.
+#[derive(Deserialize, Debug, Clone)]
+pub struct Attachment {
+    pub filename: String,
+    #[serde(default)]
+    pub size: u64,
+    /// Where the file is served from, behind the same login as the API.
+    pub content: String,
+    pub author: Option<User>,
+    #[serde(default)]
+    pub created: String,
+}
+
+/// The `timetracking` field. Each part is missing until the issue is estimated or work is
+/// logged on it, and is written both like `1d 4h` and in seconds.
+#[derive(Deserialize, Debug, Clone, Default)]
+pub struct TimeTrackingField {
+    #[serde(rename = "originalEstimate")]
+    pub original_estimate: Option<String>,
+    #[serde(rename = "remainingEstimate")]
+    pub remaining_estimate: Option<String>,
+    #[serde(rename = "timeSpent")]
+    pub time_spent: Option<String>,
+    #[serde(rename = "originalEstimateSeconds")]
+    pub original_estimate_seconds: Option<u64>,
+    #[serde(rename = "timeSpentSeconds")]
+    pub time_spent_seconds: Option<u64>,
+}
+
+/// A remote link as it's listed on an issue.
+#[derive(Deserialize, Debug, Clone)]
+pub struct RemoteLink {
+    pub relationship: Option<String>,
+    pub object: LinkedObject,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LinkedObject {
+    pub url: String,
+    pub status: Option<LinkedObjectStatus>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct LinkedObjectStatus {
+    #[serde(default)]
+    pub resolved: bool,
+}
+
+/// The body of an issue edit. `fields` replaces values outright while `update` applies
+/// operations such as adding a single label.
+#[derive(Serialize, Default)]
+pub struct IssueUpdate {
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub fields: BTreeMap<String, Value>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub update: BTreeMap<String, Vec<FieldOperation>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldOperation {
+    Add(Value),
+    Remove(Value),
+    Set(Value),
+}
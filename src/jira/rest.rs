@@ -0,0 +1,451 @@
+//! A thin wrapper over reqwest that knows how to authenticate with and talk to Jira's REST APIs.
+use super::{
+    cassette::{self, Cassette},
+    error::{validation_messages, JiraError, JiraResult},
+};
+use reqwest::{
+    header::{HeaderMap, RETRY_AFTER},
+    multipart::{Form, Part},
+    Method, RequestBuilder, Response, StatusCode,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::PathBuf,
+    sync::{Arc, Mutex, RwLock},
+};
+
+/// Set by Jira Server when it refuses a login, e.g. once a CAPTCHA is required.
+const AUTHENTICATION_DENIED: &str = "X-Authentication-Denied-Reason";
+const RATE_LIMIT_REMAINING: &str = "X-RateLimit-Remaining";
+const RATE_LIMIT_LIMIT: &str = "X-RateLimit-Limit";
+const RATE_LIMIT_RESET: &str = "X-RateLimit-Reset";
+/// Jira turns away uploads without this header, as they could come from a forged form.
+const NO_CSRF_CHECK: &str = "X-Atlassian-Token";
+
+/// Requests noted down by an offline client, e.g. `GET /search jql=... maxResults=100`, or held
+/// back by a dry run.
+pub type RequestLog = Arc<Mutex<Vec<String>>>;
+
+/// The request budget Jira reported on its last response, only sent by some instances.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RateLimit {
+    pub remaining: u64,
+    pub limit: Option<u64>,
+    /// When the budget refills, as an ISO 8601 timestamp.
+    pub reset: Option<String>,
+}
+
+impl RateLimit {
+    fn from_headers(headers: &HeaderMap) -> Option<RateLimit> {
+        let header = |name| headers.get(name).and_then(|value| value.to_str().ok());
+        Some(RateLimit {
+            remaining: header(RATE_LIMIT_REMAINING)?.parse().ok()?,
+            limit: header(RATE_LIMIT_LIMIT).and_then(|limit| limit.parse().ok()),
+            reset: header(RATE_LIMIT_RESET).map(String::from),
+        })
+    }
+
+    /// How many times longer to wait between background refreshes, so polling doesn't use up
+    /// what's left of the budget.
+    pub fn poll_multiplier(&self) -> u32 {
+        let limit = match self.limit {
+            Some(limit) if limit > 0 => limit,
+            _ => return 1,
+        };
+        match self.remaining * 100 / limit {
+            0..=9 => 8,
+            10..=24 => 4,
+            25..=49 => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// The REST APIs that Jira exposes under different prefixes.
+#[derive(Clone, Copy, Debug)]
+pub enum Api {
+    Core,
+    Agile,
+}
+
+impl Api {
+    fn prefix(self) -> &'static str {
+        match self {
+            Api::Core => "/rest/api/2",
+            Api::Agile => "/rest/agile/1.0",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Rest {
+    client: reqwest::Client,
+    host: String,
+    user: String,
+    /// Shared between clones so a replacement token applies to every request made afterwards.
+    pass: Arc<RwLock<String>>,
+    /// In a dry run, requests that would change something in Jira are noted here, with their
+    /// full URL and payload, rather than sent. Reads still go out.
+    dry_run: Option<RequestLog>,
+    rate_limit: Arc<RwLock<Option<RateLimit>>>,
+    /// When set, requests are noted here and fail rather than being sent, so the rest of the
+    /// app can be exercised without a Jira instance.
+    offline_log: Option<RequestLog>,
+    /// Responses to record to, or when playing back, to answer requests from.
+    cassette: Option<Arc<Mutex<Cassette>>>,
+}
+
+impl Rest {
+    pub fn new(host: String, user: String, pass: String) -> Rest {
+        Rest {
+            client: reqwest::Client::new(),
+            host: host.trim_end_matches('/').to_string(),
+            user,
+            pass: Arc::new(RwLock::new(pass)),
+            dry_run: None,
+            rate_limit: Arc::new(RwLock::new(None)),
+            offline_log: None,
+            cassette: None,
+        }
+    }
+
+    /// Save a scrubbed copy of every response to a cassette file.
+    pub fn record_to(&mut self, path: PathBuf) -> anyhow::Result<()> {
+        self.cassette = Some(Arc::new(Mutex::new(Cassette::record(path)?)));
+        Ok(())
+    }
+
+    /// Answer requests from a recorded cassette instead of sending them.
+    #[cfg(test)]
+    pub fn replay(path: PathBuf) -> anyhow::Result<Rest> {
+        let mut rest = Rest::new(
+            cassette::FIXTURE_HOST.to_string(),
+            "user".to_string(),
+            "token".to_string(),
+        );
+        rest.cassette = Some(Arc::new(Mutex::new(Cassette::play(path)?)));
+        Ok(rest)
+    }
+
+    #[cfg(test)]
+    pub fn offline(host: &str) -> (Rest, RequestLog) {
+        let log = RequestLog::default();
+        let mut rest = Rest::new(host.to_string(), "user".to_string(), "token".to_string());
+        rest.offline_log = Some(log.clone());
+        (rest, log)
+    }
+
+    /// Note a request down instead of sending it in a dry run, returning whether it was.
+    pub fn hold_back(&self, request: String) -> bool {
+        match &self.dry_run {
+            Some(unsent) => {
+                if let Ok(mut unsent) = unsent.lock() {
+                    unsent.push(request);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Note a request down if offline, returning the error it fails with.
+    fn log_offline(&self, request: String) -> Option<JiraError> {
+        let log = self.offline_log.as_ref()?;
+        if let Ok(mut log) = log.lock() {
+            log.push(request);
+        }
+        Some(JiraError::Network("offline".to_string()))
+    }
+
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limit.read().ok().and_then(|limit| limit.clone())
+    }
+
+    pub fn set_dry_run(&mut self, unsent: Option<RequestLog>) {
+        self.dry_run = unsent;
+    }
+
+    /// The requests a dry run held back since this was last called.
+    pub fn take_unsent(&self) -> Vec<String> {
+        let unsent = self.dry_run.as_ref().and_then(|unsent| unsent.lock().ok());
+        unsent.map_or_else(Vec::new, |mut unsent| unsent.drain(..).collect())
+    }
+
+    pub fn set_pass(&self, pass: String) {
+        if let Ok(mut current) = self.pass.write() {
+            *current = pass;
+        }
+    }
+
+    fn url(&self, api: Api, endpoint: &str) -> String {
+        format!("{}{}{}", self.host, api.prefix(), endpoint)
+    }
+
+    fn request(&self, method: Method, api: Api, endpoint: &str) -> RequestBuilder {
+        let url = self.url(api, endpoint);
+        self.request_url(method, &url)
+    }
+
+    fn request_url(&self, method: Method, url: &str) -> RequestBuilder {
+        let pass = self
+            .pass
+            .read()
+            .map(|pass| pass.clone())
+            .unwrap_or_default();
+        self.client
+            .request(method, url)
+            .basic_auth(&self.user, Some(pass))
+    }
+
+    /// Start fetching a file Jira links to by its full URL, such as an attachment's content,
+    /// leaving the body to be read a chunk at a time. Downloads skip cassettes, which only hold
+    /// JSON.
+    pub async fn download(&self, url: &str) -> JiraResult<Response> {
+        let path = url.strip_prefix(self.host.as_str()).unwrap_or(url);
+        if let Some(err) = self.log_offline(format!("GET {}", path)) {
+            return Err(err);
+        }
+        let response = self.request_url(Method::GET, url).send().await?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        let headers = response.headers().clone();
+        let body = response.text().await?;
+        Err(status_error(status, &headers, &body))
+    }
+
+    pub async fn get<D: DeserializeOwned>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        query: &[(&str, String)],
+    ) -> JiraResult<D> {
+        let query_text = query_summary(query);
+        let described = format!("GET {} {}", endpoint, query_text);
+        if let Some(err) = self.log_offline(described.clone()) {
+            return Err(err);
+        }
+        let request = self.request(Method::GET, api, endpoint).query(query);
+        self.send(request, described).await
+    }
+
+    /// Writes, here and below, answer with `D`'s default in a dry run, as nothing was sent.
+    pub async fn delete<D: DeserializeOwned + Default>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        query: &[(&str, String)],
+    ) -> JiraResult<D> {
+        let query_text = query_summary(query);
+        if self.hold_back(format!("DELETE {} {}", self.url(api, endpoint), query_text)) {
+            return Ok(D::default());
+        }
+        let described = format!("DELETE {} {}", endpoint, query_text);
+        if let Some(err) = self.log_offline(described.clone()) {
+            return Err(err);
+        }
+        let request = self.request(Method::DELETE, api, endpoint).query(query);
+        self.send(request, described).await
+    }
+
+    pub async fn post<D: DeserializeOwned + Default, S: Serialize>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        body: &S,
+    ) -> JiraResult<D> {
+        self.write(Method::POST, api, endpoint, body).await
+    }
+
+    /// A POST that only reads, like a search with a JQL too long for a URL, so it's sent even in
+    /// a dry run.
+    pub async fn post_query<D: DeserializeOwned, S: Serialize>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        body: &S,
+    ) -> JiraResult<D> {
+        let described = format!("POST {} {}", endpoint, payload_summary(body));
+        if let Some(err) = self.log_offline(described.clone()) {
+            return Err(err);
+        }
+        let request = self.request(Method::POST, api, endpoint).json(body);
+        self.send(request, described).await
+    }
+
+    pub async fn put<D: DeserializeOwned + Default, S: Serialize>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        body: &S,
+    ) -> JiraResult<D> {
+        self.write(Method::PUT, api, endpoint, body).await
+    }
+
+    /// Send a file as a multipart form under the `file` field, the way Jira takes attachments.
+    pub async fn upload<D: DeserializeOwned + Default>(
+        &self,
+        api: Api,
+        endpoint: &str,
+        filename: &str,
+        contents: Vec<u8>,
+    ) -> JiraResult<D> {
+        let url = self.url(api, endpoint);
+        let size = contents.len();
+        if self.hold_back(format!("POST {} file={} ({} bytes)", url, filename, size)) {
+            return Ok(D::default());
+        }
+        let described = format!("POST {} file={}", endpoint, filename);
+        if let Some(err) = self.log_offline(described.clone()) {
+            return Err(err);
+        }
+        let part = Part::bytes(contents).file_name(filename.to_string());
+        let request = self
+            .request(Method::POST, api, endpoint)
+            .header(NO_CSRF_CHECK, "no-check")
+            .multipart(Form::new().part("file", part));
+        self.send(request, described).await
+    }
+
+    async fn write<D: DeserializeOwned + Default, S: Serialize>(
+        &self,
+        method: Method,
+        api: Api,
+        endpoint: &str,
+        body: &S,
+    ) -> JiraResult<D> {
+        let payload = payload_summary(body);
+        if self.hold_back(format!("{} {} {}", method, self.url(api, endpoint), payload)) {
+            return Ok(D::default());
+        }
+        let described = format!("{} {} {}", method, endpoint, payload);
+        if let Some(err) = self.log_offline(described.clone()) {
+            return Err(err);
+        }
+        let request = self.request(method, api, endpoint).json(body);
+        self.send(request, described).await
+    }
+
+    /// Send a request, noting the rate limit budget the response reports. `described` is the
+    /// request as written in the request log, and is what cassettes match requests on.
+    async fn send<D: DeserializeOwned>(
+        &self,
+        request: RequestBuilder,
+        described: String,
+    ) -> JiraResult<D> {
+        let response = match self.replayed(&described) {
+            Some(response) => response?,
+            None => {
+                let response = request.send().await?;
+                let status = response.status();
+                let headers = response.headers().clone();
+                let response = (status, headers, response.text().await?);
+                self.record(&described, &response);
+                response
+            }
+        };
+        let (status, headers, body) = response;
+        if let Some(limit) = RateLimit::from_headers(&headers) {
+            if let Ok(mut current) = self.rate_limit.write() {
+                *current = Some(limit);
+            }
+        }
+        read_response(status, &headers, &body)
+    }
+
+    /// The recorded response to a request when playing a cassette back, `None` otherwise.
+    fn replayed(&self, described: &str) -> Option<JiraResult<cassette::Response>> {
+        let mut cassette = self.cassette.as_ref()?.lock().ok()?;
+        if !cassette.is_playing() {
+            return None;
+        }
+        Some(
+            cassette
+                .replay(described)
+                .ok_or_else(|| JiraError::Network(format!("Nothing recorded for {}", described))),
+        )
+    }
+
+    fn record(&self, described: &str, response: &cassette::Response) {
+        let cassette = match &self.cassette {
+            Some(cassette) => cassette,
+            None => return,
+        };
+        // Recording is best effort, failing to write shouldn't fail the request
+        if let Ok(mut cassette) = cassette.lock() {
+            let _ = cassette.add(described, response, &self.host);
+        }
+    }
+}
+
+/// Query parameters as written in the request log, e.g. `jql=... maxResults=100`.
+fn query_summary(query: &[(&str, String)]) -> String {
+    let params: Vec<String> = query
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, value))
+        .collect();
+    params.join(" ")
+}
+
+/// The JSON payload cut down to a line or so.
+fn payload_summary<S: Serialize>(body: &S) -> String {
+    const MAX_CHARS: usize = 200;
+    let payload = serde_json::to_string(body).unwrap_or_default();
+    if payload.chars().count() > MAX_CHARS {
+        format!("{}...", payload.chars().take(MAX_CHARS).collect::<String>())
+    } else {
+        payload
+    }
+}
+
+/// Deserialize a response. Empty responses (e.g. 204s) are treated as `null`, so `()` can be
+/// used when nothing is returned.
+fn read_response<D: DeserializeOwned>(
+    status: StatusCode,
+    headers: &HeaderMap,
+    body: &str,
+) -> JiraResult<D> {
+    if !status.is_success() {
+        return Err(status_error(status, headers, body));
+    }
+
+    let body = if body.trim().is_empty() { "null" } else { body };
+    Ok(serde_json::from_str(body)?)
+}
+
+/// The error for an unsuccessful response.
+fn status_error(status: StatusCode, headers: &HeaderMap, body: &str) -> JiraError {
+    let retry_after = headers
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+    let login_denied = headers.contains_key(AUTHENTICATION_DENIED);
+    if status == StatusCode::FORBIDDEN && login_denied {
+        return JiraError::Unauthorized;
+    }
+    error_for_status(status, retry_after, body)
+}
+
+fn error_for_status(status: StatusCode, retry_after: Option<u64>, body: &str) -> JiraError {
+    match status {
+        StatusCode::UNAUTHORIZED => JiraError::Unauthorized,
+        StatusCode::FORBIDDEN => JiraError::Forbidden,
+        StatusCode::NOT_FOUND => JiraError::NotFound,
+        StatusCode::TOO_MANY_REQUESTS => JiraError::RateLimited { retry_after },
+        status if status.is_client_error() => JiraError::Validation {
+            messages: validation_messages(body),
+        },
+        status => JiraError::Server {
+            status: status.as_u16(),
+        },
+    }
+}
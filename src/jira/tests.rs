@@ -0,0 +1,458 @@
+//! Tests against recorded responses in `src/jira/fixtures`, covering how the client pages through
+//! results, maps failures to `JiraError` and reads fields. Record new fixtures by running the app
+//! with `JIRA_RECORD=src/jira/fixtures/<name>.json`.
+use super::*;
+use crate::audit::{audit, branch_issue_key, report_lines};
+
+fn config() -> Config {
+    Config {
+        default_project_key: "PROJ".to_string(),
+        filter_mine: false,
+        ..Default::default()
+    }
+}
+
+fn custom_field_map() -> FieldMap {
+    FieldMap {
+        story_points: Some("customfield_10016".to_string()),
+        epic_link: Some("customfield_10014".to_string()),
+        sprint: Some("customfield_10020".to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn searches_page_until_the_total_is_reached() {
+    let mut jira = JiraClient::replay("search_pages", FieldMap::default()).unwrap();
+    jira.deployment = Deployment::Server;
+    let issues = jira.current_issues(&config()).await.unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-1", "PROJ-2", "PROJ-3"]);
+    assert_eq!(issues[0].summary, "Crash when quitting");
+    assert_eq!(issues[0].priority.as_deref(), Some("Highest"));
+    assert_eq!(issues[0].assignee_name, "Jira User");
+    assert_eq!(
+        issues[0].permalink,
+        "https://example.atlassian.net/browse/PROJ-1"
+    );
+}
+
+#[tokio::test]
+async fn cloud_searches_page_by_token() {
+    let jira = JiraClient::replay("search_jql_pages", FieldMap::default()).unwrap();
+    let progress = std::sync::Mutex::new(vec![]);
+    let issues = jira
+        .current_issues_paged(&config(), |loaded, total| {
+            progress.lock().unwrap().push((loaded, total));
+            true
+        })
+        .await
+        .unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-1", "PROJ-2", "PROJ-3"]);
+    assert_eq!(*progress.lock().unwrap(), [(2, 3), (3, 3)]);
+}
+
+#[tokio::test]
+async fn cloud_searches_fall_back_to_the_classic_search() {
+    let jira = JiraClient::replay("search_fallback", FieldMap::default()).unwrap();
+    let issues = jira.current_issues(&config()).await.unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-1"]);
+}
+
+#[tokio::test]
+async fn extra_hosts_are_merged_and_routed_to() {
+    let mut jira = JiraClient::replay("search_pages", FieldMap::default()).unwrap();
+    jira.deployment = Deployment::Server;
+    let mut other = JiraClient::replay("search_other_host", FieldMap::default()).unwrap();
+    other.label = Some("client".to_string());
+    jira.others.push(other);
+
+    let issues = jira
+        .current_issues_everywhere(&config(), |_, _| true)
+        .await
+        .unwrap();
+    let keys: Vec<(&str, Option<&str>)> = issues
+        .iter()
+        .map(|issue| (issue.key.as_str(), issue.host.as_deref()))
+        .collect();
+    assert_eq!(
+        keys,
+        [
+            ("PROJ-1", None),
+            ("PROJ-2", None),
+            ("PROJ-3", None),
+            ("CLI-7", Some("client"))
+        ]
+    );
+    assert_eq!(jira.at("CLI-7").label.as_deref(), Some("client"));
+    assert_eq!(jira.at("PROJ-1").label, None);
+}
+
+#[tokio::test]
+async fn account_info_shows_who_requests_are_made_as() {
+    let jira = JiraClient::replay("account", FieldMap::default()).unwrap();
+    let account = jira.account_info().await.unwrap();
+
+    assert_eq!(account.display_name, "Jira User");
+    assert_eq!(account.email.as_deref(), Some("user@example.com"));
+    assert_eq!(account.id.as_deref(), Some("fixture-user"));
+    assert_eq!(account.groups, ["jira-software-users", "site-admins"]);
+    assert_eq!(
+        account.base_url.as_deref(),
+        Some("https://jira.example.com")
+    );
+    assert_eq!(account.host, "https://example.atlassian.net");
+}
+
+#[tokio::test]
+async fn issue_permissions_list_what_is_held_and_editable() {
+    let jira = JiraClient::replay("permissions", FieldMap::default()).unwrap();
+    let permissions = jira
+        .issue_permissions("PROJ-1", &["EDIT_ISSUES", "ASSIGN_ISSUES"])
+        .await
+        .unwrap();
+
+    let held: Vec<&str> = permissions.held.iter().map(String::as_str).collect();
+    assert_eq!(held, ["EDIT_ISSUES"]);
+    let editable = permissions.editable_fields.iter();
+    let editable: Vec<&str> = editable.map(String::as_str).collect();
+    assert_eq!(editable, ["labels", "summary"]);
+}
+
+#[tokio::test]
+async fn subtasks_are_listed_and_created_as_the_projects_subtask_type() {
+    let jira = JiraClient::replay("subtasks", FieldMap::default()).unwrap();
+    let subtasks = jira.subtasks("PROJ-1").await.unwrap();
+
+    let keys: Vec<&str> = subtasks.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-7", "PROJ-8"]);
+    assert_eq!(subtasks[0].status, "Done");
+    assert_eq!(subtasks[1].summary, "Backfill old rows");
+
+    let created = jira.create_subtask("PROJ-1", "Update the docs").await;
+    assert_eq!(created.unwrap().as_deref(), Some("PROJ-9"));
+}
+
+#[tokio::test]
+async fn boards_page_until_the_last_page() {
+    let jira = JiraClient::replay("boards_pages", FieldMap::default()).unwrap();
+    let boards = jira.current_boards(&config(), "").await.unwrap();
+
+    let names: Vec<&str> = boards.iter().map(|board| board.name.as_str()).collect();
+    assert_eq!(names, ["PROJ board", "PROJ kanban", "PROJ releases"]);
+    assert_eq!(boards[0].project.as_deref(), Some("PROJ"));
+    assert_eq!(boards[2].project, None);
+    assert_eq!(
+        boards[2].permalink,
+        "https://example.atlassian.net/secure/RapidBoard.jspa?rapidView=3"
+    );
+}
+
+#[tokio::test]
+async fn active_sprint_lists_its_issues_in_rank_order() {
+    let jira = JiraClient::replay("sprint", FieldMap::default()).unwrap();
+    let (sprint, issues) = jira.active_sprint_issues(12).await.unwrap().unwrap();
+
+    assert_eq!(sprint.name, "PROJ Sprint 7");
+    assert_eq!(sprint.goal, "Ship dark mode");
+    assert_eq!(sprint.end_date.as_deref(), Some("2021-03-15T17:00:00.000Z"));
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-2", "PROJ-5"]);
+    assert_eq!(issues[1].assignee_name, "Unassigned");
+
+    // A board with nothing running
+    assert!(jira.active_sprint_issues(13).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn issues_move_into_a_running_or_planned_sprint() {
+    let jira = JiraClient::replay("sprints", FieldMap::default()).unwrap();
+    let sprints = jira.open_sprints(12).await.unwrap();
+
+    let names: Vec<&str> = sprints.iter().map(|sprint| sprint.name.as_str()).collect();
+    assert_eq!(names, ["PROJ Sprint 7", "PROJ Sprint 8"]);
+    assert!(sprints[0].active);
+    assert!(!sprints[1].active);
+    jira.move_to_sprint(sprints[1].id, "PROJ-4").await.unwrap();
+}
+
+#[tokio::test]
+async fn backlog_lists_the_boards_unplanned_issues_in_rank_order() {
+    let jira = JiraClient::replay("backlog", FieldMap::default()).unwrap();
+    let issues = jira.backlog_issues(12).await.unwrap();
+
+    let keys: Vec<&str> = issues.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-9", "PROJ-4", "PROJ-6"]);
+    assert_eq!(issues[0].priority.as_deref(), Some("High"));
+}
+
+#[tokio::test]
+async fn attachments_are_listed_with_who_added_them() {
+    let jira = JiraClient::replay("attachments", FieldMap::default()).unwrap();
+    let attachments = jira.attachments("PROJ-1").await.unwrap();
+
+    let names: Vec<&str> = attachments.iter().map(|a| a.filename.as_str()).collect();
+    assert_eq!(names, ["crash.log", "screenshot.png"]);
+    assert_eq!(attachments[0].size, 2048);
+    assert_eq!(attachments[0].author, "Alice");
+    assert_eq!(
+        attachments[0].url,
+        "https://example.atlassian.net/rest/api/2/attachment/content/10100"
+    );
+}
+
+#[test]
+fn downloads_are_numbered_rather_than_overwriting() {
+    let dir = env::temp_dir().join("jira-tui-tests").join("downloads");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    assert_eq!(unused_path(&dir, "crash.log"), dir.join("crash.log"));
+
+    fs::write(dir.join("crash.log"), "").unwrap();
+    fs::write(dir.join("crash (2).log"), "").unwrap();
+    assert_eq!(unused_path(&dir, "crash.log"), dir.join("crash (3).log"));
+    // Only the name is kept from paths
+    assert_eq!(unused_path(&dir, "../../.bashrc"), dir.join(".bashrc"));
+}
+
+#[tokio::test]
+async fn time_tracking_flags_issues_over_their_estimate() {
+    let jira = JiraClient::replay("time_tracking", FieldMap::default()).unwrap();
+
+    let tracking = jira.time_tracking("PROJ-1").await.unwrap();
+    assert_eq!(tracking.estimate.as_deref(), Some("1d"));
+    assert_eq!(tracking.spent.as_deref(), Some("1d 2h"));
+    assert_eq!(tracking.remaining.as_deref(), Some("0m"));
+    assert!(tracking.over_budget);
+    let line = "Time: 1d estimated, 1d 2h spent, 0m remaining (over the estimate)";
+    assert_eq!(tracking.describe().as_deref(), Some(line));
+
+    // Never estimated nor logged on
+    let untracked = jira.time_tracking("PROJ-2").await.unwrap();
+    assert_eq!(untracked, TimeTracking::default());
+    assert!(untracked.describe().is_none());
+}
+
+#[tokio::test]
+async fn links_are_made_from_either_end_of_a_link_type() {
+    let jira = JiraClient::replay("issue_links", FieldMap::default()).unwrap();
+
+    let relations = jira.link_relations().await.unwrap();
+    let descriptions: Vec<&str> = relations.iter().map(|r| r.description.as_str()).collect();
+    // Relates reads the same both ways, so it's only offered once
+    assert_eq!(descriptions, ["blocks", "is blocked by", "relates to"]);
+    // PROJ-1 is blocked by PROJ-2, so PROJ-2 goes in as the inward issue
+    jira.create_link("PROJ-1", &relations[1], "PROJ-2")
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn issues_can_be_watched_and_unwatched() {
+    let jira = JiraClient::replay("watchers", FieldMap::default()).unwrap();
+
+    let issue = jira.get_issue("PROJ-1".to_string()).await.unwrap();
+    assert!(issue.watching);
+    // Both go through the logged in user's id
+    jira.set_watching("PROJ-1", true).await.unwrap();
+    jira.set_watching("PROJ-1", false).await.unwrap();
+    assert_eq!(
+        jira.watchers("PROJ-1").await.unwrap(),
+        ["Jira User", "Other User"]
+    );
+}
+
+#[tokio::test]
+async fn only_unresolved_pull_request_links_count_as_open() {
+    let jira = JiraClient::replay("remote_links", FieldMap::default()).unwrap();
+
+    assert!(jira.has_open_pull_request("PROJ-1").await.unwrap());
+    // Merged, and a link to a wiki page
+    assert!(!jira.has_open_pull_request("PROJ-2").await.unwrap());
+}
+
+#[test]
+fn branches_are_matched_to_issues_by_the_key_they_start_with() {
+    let key = |branch| branch_issue_key(branch).unwrap_or_default();
+    assert_eq!(key("PROJ-12-fix-crash"), "PROJ-12");
+    assert_eq!(key("platform/PROJ-12-fix-crash"), "PROJ-12");
+    assert_eq!(key("PROJ-12"), "PROJ-12");
+    assert_eq!(key("main"), "");
+    assert_eq!(key("fix-crash"), "");
+    assert_eq!(key("proj-12-fix-crash"), "");
+}
+
+#[tokio::test]
+async fn audit_finds_finished_and_missing_issues_and_unstarted_work() {
+    let jira = JiraClient::replay("audit", FieldMap::default()).unwrap();
+    let branches = [
+        "PROJ-1-fix-crash",
+        "platform/PROJ-2-dark-mode",
+        "PROJ-404-gone",
+        "PROJ-7-flaky-test",
+        "main",
+    ];
+    let branches = branches.iter().map(|branch| branch.to_string()).collect();
+
+    let audit = audit(&jira, branches).await.unwrap();
+    let finished = ("PROJ-1-fix-crash".to_string(), "Done".to_string());
+    assert_eq!(audit.finished, vec![finished]);
+    assert_eq!(audit.untracked, vec!["PROJ-404-gone".to_string()]);
+    // One lookup failing doesn't stop the rest
+    let failed = (
+        "PROJ-7-flaky-test".to_string(),
+        "Jira had a problem handling the request (500)".to_string(),
+    );
+    assert_eq!(audit.failed, vec![failed]);
+    let unstarted: Vec<&str> = audit.unstarted.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(unstarted, vec!["PROJ-5"]);
+
+    let report = report_lines(&audit);
+    assert_eq!(report[0], "Branches for finished issues:");
+    assert_eq!(report[1], "  PROJ-1-fix-crash (Done)");
+    assert_eq!(
+        report[5],
+        "  PROJ-7-flaky-test (Jira had a problem handling the request (500))"
+    );
+    assert_eq!(report[7], "  PROJ-5 Export to CSV");
+}
+
+#[tokio::test]
+async fn rate_limit_comes_from_the_latest_response() {
+    let mut jira = JiraClient::replay("search_pages", FieldMap::default()).unwrap();
+    jira.deployment = Deployment::Server;
+    jira.current_issues(&config()).await.unwrap();
+
+    assert_eq!(
+        jira.rate_limit(),
+        Some(RateLimit {
+            remaining: 41,
+            limit: Some(100),
+            reset: Some("2021-03-01T14:05:00Z".to_string()),
+        })
+    );
+}
+
+#[tokio::test]
+async fn error_responses_map_to_jira_errors() {
+    let jira = JiraClient::replay("errors", FieldMap::default()).unwrap();
+    let error = |key: &str| {
+        let jira = jira.clone();
+        let key = key.to_string();
+        async move { jira.get_issue(key).await.err() }
+    };
+
+    assert_eq!(error("PROJ-401").await, Some(JiraError::Unauthorized));
+    assert_eq!(error("PROJ-403").await, Some(JiraError::Forbidden));
+    // Server refuses logins with a 403 once a CAPTCHA is needed
+    assert_eq!(error("PROJ-captcha").await, Some(JiraError::Unauthorized));
+    assert_eq!(error("PROJ-404").await, Some(JiraError::NotFound));
+    assert_eq!(
+        error("PROJ-429").await,
+        Some(JiraError::RateLimited {
+            retry_after: Some(30)
+        })
+    );
+    assert_eq!(
+        error("PROJ-400").await,
+        Some(JiraError::Validation {
+            messages: vec![
+                "The value 'PROJ-400' does not exist for the field 'key'.".to_string(),
+                "summary: You must specify a summary of the issue.".to_string(),
+            ]
+        })
+    );
+    assert_eq!(
+        error("PROJ-503").await,
+        Some(JiraError::Server { status: 503 })
+    );
+}
+
+#[test]
+fn permission_errors_are_told_apart_from_other_refusals() {
+    let refused = |message: &str| JiraError::Validation {
+        messages: vec![message.to_string()],
+    };
+
+    assert!(JiraError::Forbidden.is_permission_denied());
+    assert!(
+        refused("You do not have the permission to comment on this issue.").is_permission_denied()
+    );
+    assert!(refused(
+        "summary: Field 'summary' cannot be set. It is not on the appropriate screen, or unknown."
+    )
+    .is_permission_denied());
+    assert!(!refused("summary: You must specify a summary of the issue.").is_permission_denied());
+    assert!(!JiraError::Unauthorized.is_permission_denied());
+}
+
+#[tokio::test]
+async fn unrecorded_requests_fail() {
+    let jira = JiraClient::replay("errors", FieldMap::default()).unwrap();
+    assert!(matches!(
+        jira.get_issue("PROJ-1".to_string()).await,
+        Err(JiraError::Network(_))
+    ));
+}
+
+#[tokio::test]
+async fn dry_runs_hold_back_writes_but_still_read() {
+    let (mut jira, requests) = JiraClient::offline();
+    jira.set_dry_run(true);
+
+    jira.assign_issue("PROJ-1", Some("alice-id")).await.unwrap();
+    let url = "https://example.atlassian.net/rest/api/2/issue/PROJ-1/assignee";
+    let assigned = format!("PUT {} {{\"accountId\":\"alice-id\"}}", url);
+    assert_eq!(jira.take_unsent(), [assigned]);
+    assert!(jira.take_unsent().is_empty());
+
+    assert!(jira.get_comments("PROJ-1").await.is_err());
+    let sent = requests.lock().unwrap().clone();
+    assert_eq!(sent.len(), 1);
+    assert!(sent[0].starts_with("GET /issue/PROJ-1/comment "));
+}
+
+#[tokio::test]
+async fn custom_fields_are_read_through_the_field_map() {
+    let jira = JiraClient::replay("custom_fields", custom_field_map()).unwrap();
+    let issue = jira.get_issue("PROJ-7".to_string()).await.unwrap();
+
+    assert_eq!(issue.status, "In Review");
+    assert_eq!(issue.story_points.as_deref(), Some("5"));
+    // Server style sprints, the latest one wins
+    assert_eq!(issue.sprint.as_deref(), Some("Sprint 2"));
+    // No parent field, so the epic link is used
+    assert_eq!(issue.epic.as_deref(), Some("PROJ-5"));
+    assert_eq!(issue.parent.as_deref(), Some("PROJ-5"));
+    assert_eq!(issue.description, "Steps to reproduce:\nOpen the app");
+}
+
+#[tokio::test]
+async fn parent_field_and_cloud_sprints_are_read() {
+    let jira = JiraClient::replay("custom_fields", custom_field_map()).unwrap();
+    let issue = jira.get_issue("PROJ-8".to_string()).await.unwrap();
+
+    assert_eq!(issue.parent.as_deref(), Some("PROJ-5"));
+    assert_eq!(issue.epic, None);
+    assert_eq!(issue.story_points, None);
+    assert_eq!(issue.sprint.as_deref(), Some("Sprint 2"));
+    assert_eq!(issue.description, "");
+}
+
+#[test]
+fn pull_requests_are_linked_with_the_forges_icon() {
+    let link = pull_request_link("https://github.com/acme/platform/pull/12/");
+    assert_eq!(link.global_id, "https://github.com/acme/platform/pull/12");
+    assert_eq!(link.object.title, "platform #12");
+    assert_eq!(link.object.icon.url, "https://github.com/favicon.ico");
+    assert!(!link.object.status.resolved);
+
+    let link = pull_request_link("https://gitlab.com/acme/platform/-/merge_requests/7");
+    assert_eq!(link.object.title, "platform #7");
+    assert_eq!(link.object.icon.url, "https://gitlab.com/favicon.ico");
+}
@@ -0,0 +1,452 @@
+//! UI text in each supported language, picked with `locale` in the config file. Jira content,
+//! and messages that come with details filled in (errors, notices), are shown as they are.
+//!
+//! Help text keeps the `keys: what they do` entries separated by ` - `, with the keys themselves
+//! left untranslated.
+use crate::state::InputMode;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Locale {
+    #[default]
+    En,
+    De,
+    Es,
+}
+
+/// Fixed text shown outside of the per-mode help.
+#[derive(Clone, Copy, Debug)]
+pub enum Text {
+    /// Help while a new API token is typed in.
+    ReauthHelp,
+    /// Help while asking what to do with a stopped timer.
+    StoppedTimerHelp,
+    CloneOfferHelp,
+    /// Put in front of the issue list's help, `{key}` is the key found on the clipboard.
+    JumpFromClipboard,
+    Sort,
+    SnoozeFor,
+    SnoozedIssues,
+    RecentProjects,
+    SuggestedReviewers,
+    /// Added to the reviewer picker's title while the PR is to be opened as a draft.
+    DraftPullRequest,
+    AssignTo,
+    Account,
+    BranchAudit,
+    AttachFile,
+    /// Title of the list of ways to link an issue to another.
+    LinkTypes,
+    /// The assignee picker's choice of yourself.
+    Me,
+    Unassigned,
+    Comments,
+    /// Put in front of the key of the issue a comment is being written on.
+    NewCommentOn,
+    ExistingBranches,
+    InRepo,
+    /// The row at the end of the branches pane for making a new branch.
+    CreateNewBranch,
+    /// The branch checked out, shown in the status bar.
+    OnBranch,
+    /// How many background requests are waiting their turn, shown in the status bar.
+    RequestsQueued,
+    /// The latest change a dry run didn't send to Jira, shown in the status bar.
+    NotSent,
+    Transitions,
+    Priorities,
+    /// Heading for the issues without an epic, when grouping by epic.
+    NoEpic,
+    SavedViews,
+    ViewName,
+    JqlQuery,
+    /// In place of the sprint's name when the board has none running.
+    NoActiveSprint,
+    Backlog,
+    MoveToSprint,
+    Labels,
+    AddLabel,
+    Checklist,
+    IssueLinks,
+    BulkEdit,
+    NewBranchName,
+    WorkIn,
+    TimerStopped,
+    CloneRepo,
+    BranchExists,
+    ExistingBranchHelp,
+    BranchCollisionHelp,
+    TokenRejected,
+    DefaultProjectKey,
+    Issues,
+    Loading,
+    LoadingIssue,
+    LoadingComments,
+    /// The smart commit form's choice of not moving the issue.
+    LeaveAsIs,
+}
+
+pub fn text(locale: Locale, text: Text) -> &'static str {
+    let [en, de, es] = match text {
+        Text::ReauthHelp => [
+            "Paste a new API token - Enter: Save it and retry - Esc: Give up",
+            "Neues API-Token einfügen - Enter: Speichern und erneut versuchen - Esc: Aufgeben",
+            "Pega un token de API nuevo - Enter: Guardar y reintentar - Esc: Rendirse",
+        ],
+        Text::StoppedTimerHelp => [
+            "y/Enter: Log the time - n: Discard it - Esc: Keep the timer running",
+            "y/Enter: Zeit erfassen - n: Verwerfen - Esc: Timer weiterlaufen lassen",
+            "y/Enter: Registrar el tiempo - n: Descartarlo - Esc: Seguir con el temporizador",
+        ],
+        Text::CloneOfferHelp => [
+            "y/Enter: Clone it - n/Esc: Not now",
+            "y/Enter: Klonen - n/Esc: Jetzt nicht",
+            "y/Enter: Clonarlo - n/Esc: Ahora no",
+        ],
+        Text::JumpFromClipboard => [
+            "j: Jump to {key} from clipboard",
+            "j: Zu {key} aus der Zwischenablage",
+            "j: Ir a {key} del portapapeles",
+        ],
+        Text::Sort => ["Sort", "Sortieren", "Ordenar"],
+        Text::SnoozeFor => ["Snooze for", "Zurückstellen für", "Posponer durante"],
+        Text::SnoozedIssues => [
+            "Snoozed Issues",
+            "Zurückgestellte Tickets",
+            "Incidencias pospuestas",
+        ],
+        Text::ExistingBranches => [
+            "Existing Branches",
+            "Vorhandene Branches",
+            "Ramas existentes",
+        ],
+        Text::Transitions => ["Transitions", "Übergänge", "Transiciones"],
+        Text::Priorities => ["Priorities", "Prioritäten", "Prioridades"],
+        Text::NoEpic => ["No epic", "Kein Epic", "Sin épica"],
+        Text::SavedViews => ["Saved views", "Gespeicherte Ansichten", "Vistas guardadas"],
+        Text::NoActiveSprint => [
+            "No sprint is running",
+            "Kein Sprint läuft",
+            "No hay ningún sprint en curso",
+        ],
+        Text::Backlog => ["Backlog", "Backlog", "Backlog"],
+        Text::MoveToSprint => ["Move to sprint", "In Sprint verschieben", "Mover al sprint"],
+        Text::ViewName => [
+            "Name for this view",
+            "Name für diese Ansicht",
+            "Nombre para esta vista",
+        ],
+        Text::JqlQuery => [
+            "JQL query, empty to use the filters",
+            "JQL-Abfrage, leer für die Filter",
+            "Consulta JQL, vacía para usar los filtros",
+        ],
+        Text::Labels => ["Labels", "Labels", "Etiquetas"],
+        Text::AddLabel => ["Add label", "Label hinzufügen", "Añadir etiqueta"],
+        Text::Checklist => ["Checklist", "Checkliste", "Checklist"],
+        Text::IssueLinks => ["Issue Links", "Verknüpfungen", "Enlaces"],
+        Text::BulkEdit => ["Bulk edit", "Sammelbearbeitung", "Edición en bloque"],
+        Text::NewBranchName => [
+            "Enter new branch name",
+            "Neuen Branch-Namen eingeben",
+            "Nombre de la rama nueva",
+        ],
+        Text::TimerStopped => ["Timer stopped", "Timer gestoppt", "Temporizador parado"],
+        Text::CloneRepo => ["Clone repo", "Repo klonen", "Clonar repositorio"],
+        Text::BranchExists => [
+            "Branch already exists",
+            "Branch existiert bereits",
+            "La rama ya existe",
+        ],
+        Text::ExistingBranchHelp => [
+            "c/Enter: Check it out - n: Create another one - Esc: Cancel",
+            "c/Enter: Auschecken - n: Trotzdem einen neuen anlegen - Esc: Abbrechen",
+            "c/Enter: Cambiar a ella - n: Crear otra - Esc: Cancelar",
+        ],
+        Text::BranchCollisionHelp => [
+            "c/Enter: Check it out - f: Start it over, dropping its commits - s: Add a number to the name - Esc: Change the name",
+            "c/Enter: Auschecken - f: Neu anfangen, seine Commits gehen verloren - s: Nummer an den Namen anhängen - Esc: Namen ändern",
+            "c/Enter: Cambiar a ella - f: Empezarla de nuevo, perdiendo sus commits - s: Añadir un número al nombre - Esc: Cambiar el nombre",
+        ],
+        Text::TokenRejected => [
+            "Jira rejected the API token, enter a new one",
+            "Jira hat das API-Token abgelehnt, bitte ein neues eingeben",
+            "Jira rechazó el token de API, introduce uno nuevo",
+        ],
+        Text::DefaultProjectKey => [
+            "Enter default project key",
+            "Standard-Projektschlüssel eingeben",
+            "Clave de proyecto por defecto",
+        ],
+        Text::RecentProjects => ["Recent projects", "Letzte Projekte", "Proyectos recientes"],
+        Text::Comments => ["Comments", "Kommentare", "Comentarios"],
+        Text::AssignTo => ["Assign to", "Zuweisen an", "Asignar a"],
+        Text::Account => ["Account", "Konto", "Cuenta"],
+        Text::BranchAudit => ["Branch audit", "Branch-Abgleich", "Auditoría de ramas"],
+        Text::AttachFile => ["Attach a file", "Datei anhängen", "Adjuntar un archivo"],
+        Text::LinkTypes => ["Link as", "Verknüpfen als", "Enlazar como"],
+        Text::Me => ["Me", "Mir", "A mí"],
+        Text::Unassigned => ["Unassigned", "Nicht zugewiesen", "Sin asignar"],
+        Text::NewCommentOn => [
+            "New comment on",
+            "Neuer Kommentar zu",
+            "Nuevo comentario en",
+        ],
+        Text::SuggestedReviewers => [
+            "Suggested reviewers",
+            "Vorgeschlagene Reviewer",
+            "Revisores sugeridos",
+        ],
+        Text::DraftPullRequest => ["draft PR", "PR-Entwurf", "PR en borrador"],
+        Text::WorkIn => ["work in", "arbeiten in", "trabajar en"],
+        Text::InRepo => ["in", "in", "en"],
+        Text::CreateNewBranch => [
+            "+ Create new branch…",
+            "+ Neuen Branch anlegen…",
+            "+ Crear rama nueva…",
+        ],
+        Text::OnBranch => ["On {branch}", "Auf {branch}", "En {branch}"],
+        Text::RequestsQueued => [
+            "{count} queued",
+            "{count} in der Warteschlange",
+            "{count} en cola",
+        ],
+        Text::NotSent => [
+            "Dry run, not sent: {request}",
+            "Probelauf, nicht gesendet: {request}",
+            "Simulación, no enviado: {request}",
+        ],
+        Text::Issues => ["Issues", "Tickets", "Incidencias"],
+        Text::Loading => ["Loading...", "Wird geladen...", "Cargando..."],
+        Text::LoadingIssue => [
+            "Loading issue...",
+            "Ticket wird geladen...",
+            "Cargando incidencia...",
+        ],
+        Text::LoadingComments => [
+            "Loading comments...",
+            "Kommentare werden geladen...",
+            "Cargando comentarios...",
+        ],
+        Text::LeaveAsIs => ["Leave as is", "Unverändert lassen", "Dejar como está"],
+    };
+    pick(locale, en, de, es)
+}
+
+/// The keys available in a mode.
+pub fn mode_help(locale: Locale, mode: &InputMode) -> &'static str {
+    let [en, de, es] = match mode {
+        InputMode::IssuesList => [
+            "Up/Down: Navigate issues - Enter/Right: Create new branch - b: Go to list of Jira Boards - n: Active sprint of the board - B: Backlog of the board - M: Move to a sprint - Space: Mark issue - E: Bulk edit marked issues - 1-4: Move to To Do, In Progress, In Review or Done - s: Pick a transition - P: Copy PR description - Y: Build a smart commit message - T: Start/stop a timer - X: Export list to HTML - @: Assign issue - !: Set priority - l: Edit labels - e: Rename issue - Tab/Shift-Tab: Move between filters - x: Remove the filter moved to - S: Sort, grouping and colors - f: Fold/unfold epic - z: Snooze issue - Z: Snoozed issues - A: Activity feed - O: Watch/unwatch issue - I: Account and connection info - W: Compare branches with Jira - K: Link to another issue - d: Show issue details - C: Show comments - g: Show issue links - m: Filter to issues owned by me - p: Change project key - w: Switch to a recent project - v: Saved views - /: Search with JQL - i: Filter in/not in progress - a: Toggle searching all projects (slower) - Ctrl-R: Hard refresh - Esc: Stop loading a long search - q/Ctrl-C: Quit this application",
+            "Up/Down: Tickets durchgehen - Enter/Right: Neuen Branch anlegen - b: Zur Liste der Jira-Boards - n: Aktiver Sprint des Boards - B: Backlog des Boards - M: In einen Sprint verschieben - Space: Ticket markieren - E: Markierte Tickets gesammelt bearbeiten - 1-4: Nach To Do, In Progress, In Review oder Done verschieben - s: Übergang auswählen - P: PR-Beschreibung kopieren - Y: Smart-Commit-Nachricht erstellen - T: Timer starten/stoppen - X: Liste als HTML exportieren - @: Ticket zuweisen - !: Priorität setzen - l: Labels bearbeiten - e: Ticket umbenennen - Tab/Shift-Tab: Zwischen Filtern wechseln - x: Gewählten Filter entfernen - S: Sortierung, Gruppierung und Farben - f: Epic ein-/ausklappen - z: Ticket zurückstellen - Z: Zurückgestellte Tickets - A: Aktivitäten - O: Ticket beobachten/nicht mehr beobachten - I: Konto und Verbindung - W: Branches mit Jira abgleichen - K: Mit anderem Ticket verknüpfen - d: Ticketdetails anzeigen - C: Kommentare anzeigen - g: Verknüpfungen anzeigen - m: Nur meine Tickets - p: Projektschlüssel ändern - w: Zu letztem Projekt wechseln - v: Gespeicherte Ansichten - /: Mit JQL suchen - i: In Arbeit/nicht in Arbeit filtern - a: Alle Projekte durchsuchen (langsamer) - Ctrl-R: Komplett neu laden - Esc: Lange Suche abbrechen - q/Ctrl-C: Beenden",
+            "Up/Down: Recorrer incidencias - Enter/Right: Crear rama nueva - b: Ir a la lista de tableros de Jira - n: Sprint activo del tablero - B: Backlog del tablero - M: Mover a un sprint - Space: Marcar incidencia - E: Editar en bloque las marcadas - 1-4: Pasar a To Do, In Progress, In Review o Done - s: Elegir transición - P: Copiar descripción de PR - Y: Crear mensaje de smart commit - T: Iniciar/parar temporizador - X: Exportar lista a HTML - @: Asignar incidencia - !: Fijar prioridad - l: Editar etiquetas - e: Renombrar incidencia - Tab/Shift-Tab: Moverse entre filtros - x: Quitar el filtro elegido - S: Orden, agrupación y colores - f: Plegar/desplegar épica - z: Posponer incidencia - Z: Incidencias pospuestas - A: Actividad - O: Seguir/dejar de seguir incidencia - I: Cuenta y conexión - W: Comparar ramas con Jira - K: Enlazar con otra incidencia - d: Ver detalles - C: Ver comentarios - g: Ver enlaces - m: Solo mis incidencias - p: Cambiar clave de proyecto - w: Cambiar a un proyecto reciente - v: Vistas guardadas - /: Buscar con JQL - i: Filtrar en curso/no en curso - a: Buscar en todos los proyectos (más lento) - Ctrl-R: Recargar todo - Esc: Detener una búsqueda larga - q/Ctrl-C: Salir",
+        ],
+        InputMode::BoardsList => [
+            "Up/Down: Navigate boards - Enter: Scope issues to board, again to undo - f: Star board - n: Active sprint - B: Backlog - /: Filter - o: Open in browser - Esc: Back",
+            "Up/Down: Boards durchgehen - Enter: Tickets auf Board beschränken, erneut zum Aufheben - f: Board markieren - n: Aktiver Sprint - B: Backlog - /: Filtern - o: Im Browser öffnen - Esc: Zurück",
+            "Up/Down: Recorrer tableros - Enter: Limitar incidencias al tablero, otra vez para deshacer - f: Marcar tablero - n: Sprint activo - B: Backlog - /: Filtrar - o: Abrir en el navegador - Esc: Volver",
+        ],
+        InputMode::UpdateIssueStatus => [
+            "Update Issue Status - r: Refresh transitions - Esc: Back",
+            "Status ändern - r: Übergänge neu laden - Esc: Zurück",
+            "Cambiar estado - r: Recargar transiciones - Esc: Volver",
+        ],
+        InputMode::IssueDetail => [
+            "Up/Down: Navigate branches - Enter: Check out or create branch - Tab/Shift-Tab: Switch to checklist - Space: Toggle checklist item - PgUp/PgDn: Scroll, PgUp at the top loads older comments - /: Search - n/N: Next/previous match - c: Comment - D: Edit description - P: Copy PR description - L: Link a PR - a: Add a sub-task - A: Attachments - Y: Build a smart commit message - T: Start/stop a timer - w: Log work - g: Linked issues - K: Link to another issue - s: Pick a transition - u: Go to parent - [/]: Previous/next sibling - o: Open in browser - Esc: Back",
+            "Up/Down: Branches durchgehen - Enter: Branch auschecken oder anlegen - Tab/Shift-Tab: Zur Checkliste wechseln - Space: Checklistenpunkt umschalten - PgUp/PgDn: Blättern, PgUp ganz oben lädt ältere Kommentare - /: Suchen - n/N: Nächster/vorheriger Treffer - c: Kommentieren - D: Beschreibung bearbeiten - P: PR-Beschreibung kopieren - L: PR verknüpfen - a: Unteraufgabe anlegen - A: Anhänge - Y: Smart-Commit-Nachricht erstellen - T: Timer starten/stoppen - w: Arbeitszeit erfassen - g: Verknüpfte Tickets - K: Mit anderem Ticket verknüpfen - s: Übergang auswählen - u: Zum übergeordneten Ticket - [/]: Vorheriges/nächstes Geschwisterticket - o: Im Browser öffnen - Esc: Zurück",
+            "Up/Down: Recorrer ramas - Enter: Cambiar a la rama o crearla - Tab/Shift-Tab: Ir a la checklist - Space: Marcar/desmarcar punto - PgUp/PgDn: Desplazar, PgUp arriba del todo carga comentarios anteriores - /: Buscar - n/N: Coincidencia siguiente/anterior - c: Comentar - D: Editar descripción - P: Copiar descripción de PR - L: Enlazar un PR - a: Añadir una subtarea - A: Adjuntos - Y: Crear mensaje de smart commit - T: Iniciar/parar temporizador - w: Registrar trabajo - g: Incidencias enlazadas - K: Enlazar con otra incidencia - s: Elegir transición - u: Ir a la incidencia padre - [/]: Incidencia hermana anterior/siguiente - o: Abrir en el navegador - Esc: Volver",
+        ],
+        InputMode::EditingSummary => [
+            "Enter: Save summary - Esc: Cancel",
+            "Enter: Titel speichern - Esc: Abbrechen",
+            "Enter: Guardar título - Esc: Cancelar",
+        ],
+        InputMode::SortMenu => [
+            "Up/Down: Choose - Enter: Apply - Esc: Cancel",
+            "Up/Down: Auswählen - Enter: Anwenden - Esc: Abbrechen",
+            "Up/Down: Elegir - Enter: Aplicar - Esc: Cancelar",
+        ],
+        InputMode::SnoozeMenu => [
+            "Up/Down: Choose how long - Enter: Snooze - Esc: Cancel",
+            "Up/Down: Dauer wählen - Enter: Zurückstellen - Esc: Abbrechen",
+            "Up/Down: Elegir cuánto tiempo - Enter: Posponer - Esc: Cancelar",
+        ],
+        InputMode::SnoozedList => [
+            "Up/Down: Navigate - Enter/u: Unsnooze - Esc: Back to issues",
+            "Up/Down: Durchgehen - Enter/u: Wieder anzeigen - Esc: Zurück zu den Tickets",
+            "Up/Down: Recorrer - Enter/u: Dejar de posponer - Esc: Volver a las incidencias",
+        ],
+        InputMode::ActivityFeed => [
+            "Up/Down: Navigate - Enter: Go to issue - x: Mute this issue - t: Mute this kind of change - c: Clear mutes - r: Refresh - Esc: Back to issues",
+            "Up/Down: Durchgehen - Enter: Zum Ticket - x: Dieses Ticket stummschalten - t: Diese Art Änderung stummschalten - c: Stummschaltungen aufheben - r: Neu laden - Esc: Zurück zu den Tickets",
+            "Up/Down: Recorrer - Enter: Ir a la incidencia - x: Silenciar esta incidencia - t: Silenciar este tipo de cambio - c: Quitar silencios - r: Recargar - Esc: Volver a las incidencias",
+        ],
+        InputMode::EditingDescription => [
+            "Arrows: Move cursor - Esc: Finish editing",
+            "Arrows: Cursor bewegen - Esc: Bearbeitung beenden",
+            "Arrows: Mover el cursor - Esc: Terminar de editar",
+        ],
+        InputMode::ConfirmDescription => [
+            "y: Save changes - e: Keep editing - r: Reload the latest version and compare again - n: Discard changes",
+            "y: Änderungen speichern - e: Weiter bearbeiten - r: Neueste Version laden und erneut vergleichen - n: Änderungen verwerfen",
+            "y: Guardar cambios - e: Seguir editando - r: Cargar la última versión y volver a comparar - n: Descartar cambios",
+        ],
+        InputMode::BulkEditAction => [
+            "Up/Down: Choose an edit - Enter: Select - Esc: Cancel",
+            "Up/Down: Änderung wählen - Enter: Auswählen - Esc: Abbrechen",
+            "Up/Down: Elegir un cambio - Enter: Seleccionar - Esc: Cancelar",
+        ],
+        InputMode::BulkEditValue => [
+            "Enter: Apply to marked issues - Esc: Back",
+            "Enter: Auf markierte Tickets anwenden - Esc: Zurück",
+            "Enter: Aplicar a las incidencias marcadas - Esc: Volver",
+        ],
+        InputMode::BulkEditProgress => [
+            "Esc: Cancel the remaining issues, or close once finished",
+            "Esc: Restliche Tickets abbrechen oder schließen, sobald fertig",
+            "Esc: Cancelar las incidencias restantes, o cerrar al terminar",
+        ],
+        InputMode::IssueLinks => [
+            "Enter: Go to linked issue - Esc: Back - Red entries are unresolved blockers",
+            "Enter: Zum verknüpften Ticket - Esc: Zurück - Rote Einträge sind offene Blocker",
+            "Enter: Ir a la incidencia enlazada - Esc: Volver - Las entradas en rojo son bloqueos sin resolver",
+        ],
+        InputMode::Editing | InputMode::EditingDefaultProject => {
+            ["Editing", "Bearbeiten", "Editando"]
+        }
+        InputMode::FilteringBoards => [
+            "Type to filter by name or project - Up/Down: Navigate boards - Enter: Also search Jira - Esc: Clear",
+            "Nach Name oder Projekt filtern - Up/Down: Boards durchgehen - Enter: Auch in Jira suchen - Esc: Zurücksetzen",
+            "Escribe para filtrar por nombre o proyecto - Up/Down: Recorrer tableros - Enter: Buscar también en Jira - Esc: Borrar",
+        ],
+        InputMode::SearchingDetail => [
+            "Type to search the issue - Enter: Find - Esc: Cancel",
+            "Suchbegriff eingeben - Enter: Suchen - Esc: Abbrechen",
+            "Escribe para buscar en la incidencia - Enter: Buscar - Esc: Cancelar",
+        ],
+        InputMode::SwitchingProject => [
+            "Up/Down: Navigate projects - 1-5: Switch to project - Enter: Switch to selected - Esc: Cancel",
+            "Up/Down: Projekte durchgehen - 1-5: Zu Projekt wechseln - Enter: Zum ausgewählten wechseln - Esc: Abbrechen",
+            "Up/Down: Recorrer proyectos - 1-5: Cambiar al proyecto - Enter: Cambiar al seleccionado - Esc: Cancelar",
+        ],
+        InputMode::SprintView => [
+            "Up/Down: Navigate issues - o: Open in browser - B: Switch between sprint and backlog - M: Move to another sprint - r: Reload - Esc: Back",
+            "Up/Down: Tickets durchgehen - o: Im Browser öffnen - B: Zwischen Sprint und Backlog wechseln - M: In einen anderen Sprint verschieben - r: Neu laden - Esc: Zurück",
+            "Up/Down: Recorrer incidencias - o: Abrir en el navegador - B: Cambiar entre sprint y backlog - M: Mover a otro sprint - r: Recargar - Esc: Volver",
+        ],
+        InputMode::PickingSprint => [
+            "Up/Down: Navigate sprints - Enter: Move the issue into the sprint - Esc: Cancel",
+            "Up/Down: Sprints durchgehen - Enter: Ticket in den Sprint verschieben - Esc: Abbrechen",
+            "Up/Down: Recorrer sprints - Enter: Mover la incidencia al sprint - Esc: Cancelar",
+        ],
+        InputMode::PickingView => [
+            "Up/Down: Navigate views - 1-9: Switch to view - Enter: Switch to selected - s: Save the current setup as a view - Delete: Remove view - Esc: Cancel",
+            "Up/Down: Ansichten durchgehen - 1-9: Zu Ansicht wechseln - Enter: Zur ausgewählten wechseln - s: Aktuelle Einstellungen als Ansicht speichern - Delete: Ansicht entfernen - Esc: Abbrechen",
+            "Up/Down: Recorrer vistas - 1-9: Cambiar a la vista - Enter: Cambiar a la seleccionada - s: Guardar la configuración actual como vista - Delete: Quitar vista - Esc: Cancelar",
+        ],
+        InputMode::NamingView => [
+            "Type a name, an existing one is overwritten - Enter: Save - Esc: Cancel",
+            "Namen eingeben, ein vorhandener wird überschrieben - Enter: Speichern - Esc: Abbrechen",
+            "Escribe un nombre, uno existente se sobrescribe - Enter: Guardar - Esc: Cancelar",
+        ],
+        InputMode::ConfirmTransition => [
+            "y/Enter: Make the transition - n/Esc: Back to the transitions",
+            "y/Enter: Übergang ausführen - n/Esc: Zurück zu den Übergängen",
+            "y/Enter: Hacer la transición - n/Esc: Volver a las transiciones",
+        ],
+        InputMode::EditingJql => [
+            "Type a JQL query, it replaces the filters until cleared - Enter: Search - Esc: Cancel",
+            "JQL-Abfrage eingeben, sie ersetzt die Filter bis sie geleert wird - Enter: Suchen - Esc: Abbrechen",
+            "Escribe una consulta JQL, sustituye a los filtros hasta vaciarla - Enter: Buscar - Esc: Cancelar",
+        ],
+        InputMode::AttachmentsView => [
+            "Up/Down: Navigate attachments - Enter/s: Download - u: Upload a file - Esc: Back",
+            "Up/Down: Anhänge durchgehen - Enter/s: Herunterladen - u: Datei hochladen - Esc: Zurück",
+            "Up/Down: Recorrer adjuntos - Enter/s: Descargar - u: Subir un archivo - Esc: Volver",
+        ],
+        InputMode::UploadingAttachment => [
+            "Type a path - Up/Down: Pick a file from the current directory - Enter: Upload - Esc: Cancel",
+            "Pfad eingeben - Up/Down: Datei aus dem aktuellen Verzeichnis wählen - Enter: Hochladen - Esc: Abbrechen",
+            "Escribe una ruta - Up/Down: Elegir un archivo del directorio actual - Enter: Subir - Esc: Cancelar",
+        ],
+        InputMode::PickingLinkType => [
+            "Up/Down: Choose how to link the issue - Enter: Pick the issue to link to - Esc: Cancel",
+            "Up/Down: Art der Verknüpfung wählen - Enter: Zu verknüpfendes Ticket wählen - Esc: Abbrechen",
+            "Up/Down: Elegir cómo enlazar la incidencia - Enter: Elegir la incidencia a enlazar - Esc: Cancelar",
+        ],
+        InputMode::LinkingIssue => [
+            "Type an issue key or part of a summary - Up/Down: Pick an issue from the list - Enter: Link - Esc: Back",
+            "Ticketschlüssel oder Teil eines Titels eingeben - Up/Down: Ticket aus der Liste wählen - Enter: Verknüpfen - Esc: Zurück",
+            "Escribe una clave o parte de un título - Up/Down: Elegir una incidencia de la lista - Enter: Enlazar - Esc: Volver",
+        ],
+        InputMode::CommentsView => [
+            "Up/Down: Scroll - PgUp/PgDn: Scroll a page, PgUp at the top loads older comments - c: Comment - Esc: Back to issues",
+            "Up/Down: Blättern - PgUp/PgDn: Seitenweise blättern, PgUp ganz oben lädt ältere Kommentare - c: Kommentieren - Esc: Zurück zu den Tickets",
+            "Up/Down: Desplazar - PgUp/PgDn: Desplazar una página, PgUp arriba del todo carga comentarios anteriores - c: Comentar - Esc: Volver a las incidencias",
+        ],
+        InputMode::AccountInfo => [
+            "Esc: Back to issues",
+            "Esc: Zurück zu den Tickets",
+            "Esc: Volver a las incidencias",
+        ],
+        InputMode::AuditView => [
+            "r: Run again - Esc: Back to issues",
+            "r: Erneut ausführen - Esc: Zurück zu den Tickets",
+            "r: Volver a ejecutar - Esc: Volver a las incidencias",
+        ],
+        InputMode::SettingPriority => [
+            "Up/Down: Choose - Enter: Set priority - Esc: Cancel",
+            "Up/Down: Auswählen - Enter: Priorität setzen - Esc: Abbrechen",
+            "Up/Down: Elegir - Enter: Fijar prioridad - Esc: Cancelar",
+        ],
+        InputMode::EditingLabels => [
+            "Type a label - Enter: Add it - Up/Down: Choose a label - Delete: Remove it - Esc: Done",
+            "Label tippen - Enter: Hinzufügen - Up/Down: Label auswählen - Delete: Entfernen - Esc: Fertig",
+            "Escribe una etiqueta - Enter: Añadirla - Up/Down: Elegir una etiqueta - Delete: Quitarla - Esc: Listo",
+        ],
+        InputMode::AssigningIssue => [
+            "Type to search for people - Up/Down: Choose - Enter: Assign - Esc: Cancel",
+            "Tippen, um Personen zu suchen - Up/Down: Auswählen - Enter: Zuweisen - Esc: Abbrechen",
+            "Escribe para buscar personas - Up/Down: Elegir - Enter: Asignar - Esc: Cancelar",
+        ],
+        InputMode::AddingComment => [
+            "Arrows: Move cursor - Enter: New line - Ctrl-S: Post comment - Esc: Discard",
+            "Arrows: Cursor bewegen - Enter: Neue Zeile - Ctrl-S: Kommentar senden - Esc: Verwerfen",
+            "Arrows: Mover el cursor - Enter: Nueva línea - Ctrl-S: Publicar comentario - Esc: Descartar",
+        ],
+        InputMode::CreatingSubtask => [
+            "Type the sub-task's summary - Enter: Create it under the issue - Esc: Cancel",
+            "Titel der Unteraufgabe eingeben - Enter: Unter dem Ticket anlegen - Esc: Abbrechen",
+            "Escribe el título de la subtarea - Enter: Crearla bajo la incidencia - Esc: Cancelar",
+        ],
+        InputMode::LinkingPullRequest => [
+            "Type or paste the PR's URL - Enter: Link it to the issue - Esc: Cancel",
+            "URL des PR eingeben oder einfügen - Enter: Mit dem Ticket verknüpfen - Esc: Abbrechen",
+            "Escribe o pega la URL del PR - Enter: Enlazarlo a la incidencia - Esc: Cancelar",
+        ],
+        InputMode::PickingReviewers => [
+            "Up/Down: Navigate reviewers - Space: Pick reviewer - Enter: Copy PR description - o: Open the PR on GitHub - d: Toggle draft - Esc: Cancel",
+            "Up/Down: Reviewer durchgehen - Space: Reviewer wählen - Enter: PR-Beschreibung kopieren - o: PR auf GitHub öffnen - d: Entwurf umschalten - Esc: Abbrechen",
+            "Up/Down: Recorrer revisores - Space: Elegir revisor - Enter: Copiar descripción del PR - o: Abrir el PR en GitHub - d: Alternar borrador - Esc: Cancelar",
+        ],
+        InputMode::LoggingWork => [
+            "Type the time spent (e.g. 1h 30m) and an optional comment - Tab/Up/Down: Next field - Enter: Log it - Esc: Cancel",
+            "Aufgewendete Zeit (z. B. 1h 30m) und optional einen Kommentar eingeben - Tab/Up/Down: Nächstes Feld - Enter: Erfassen - Esc: Abbrechen",
+            "Escribe el tiempo dedicado (p. ej. 1h 30m) y un comentario opcional - Tab/Up/Down: Siguiente campo - Enter: Registrarlo - Esc: Cancelar",
+        ],
+        InputMode::SmartCommit => [
+            "Type a time (e.g. 1h 30m) and a comment - Tab/Up/Down: Next field - Left/Right: Choose a transition - Enter: Copy - Esc: Cancel",
+            "Zeit (z. B. 1h 30m) und Kommentar eingeben - Tab/Up/Down: Nächstes Feld - Left/Right: Übergang wählen - Enter: Kopieren - Esc: Abbrechen",
+            "Escribe un tiempo (p. ej. 1h 30m) y un comentario - Tab/Up/Down: Siguiente campo - Left/Right: Elegir una transición - Enter: Copiar - Esc: Cancelar",
+        ],
+    };
+    pick(locale, en, de, es)
+}
+
+fn pick(locale: Locale, en: &'static str, de: &'static str, es: &'static str) -> &'static str {
+    match locale {
+        Locale::En => en,
+        Locale::De => de,
+        Locale::Es => es,
+    }
+}
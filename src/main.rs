@@ -1,21 +1,42 @@
 extern crate app_dirs;
 extern crate git2;
-extern crate goji;
 #[macro_use]
 extern crate serde;
 extern crate tokio;
 
+mod audit;
+mod checklist;
+mod clipboard;
+mod commands;
 mod config;
+mod credentials;
+mod editor;
 mod events;
+mod export;
+mod forge;
 mod git;
 mod jira;
+mod locale;
+mod platform;
+mod profile;
+mod snooze;
 mod state;
+mod tasks;
+mod template;
+mod timer;
 mod ui;
 mod utils;
+mod webhook;
 
-use crate::{jira::JiraClient, state::State};
-use anyhow::Result;
+use crate::{
+    jira::JiraClient,
+    profile::StartupProfile,
+    state::{InputMode, State},
+    utils::extract_issue_key,
+};
+use anyhow::{bail, Result};
 use app_dirs::AppInfo;
+use std::{env, path::Path, process};
 use tokio::sync::mpsc;
 
 pub const APP_INFO: AppInfo = AppInfo {
@@ -25,17 +46,90 @@ pub const APP_INFO: AppInfo = AppInfo {
 
 #[tokio::main]
 async fn main() -> Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    // Commands and the TUI alike can show what they'd change in Jira without changing it
+    let dry_run = args.iter().any(|arg| arg == "--dry-run");
+    // Set up first, so it sees every step of starting up
+    let profile = args.iter().any(|arg| arg == "--profile-startup");
+    let profile = if profile {
+        Some(StartupProfile::enable())
+    } else {
+        None
+    };
+
     // Create a Jira client
-    let jira = JiraClient::new()?;
+    let mut jira = JiraClient::new().await?;
+    jira.set_dry_run(dry_run);
+
+    if args.first().map(String::as_str) == Some("discover-fields") {
+        let save = args.iter().any(|arg| arg == "--save");
+        commands::discover_fields(&jira, save, dry_run).await?;
+        commands::print_unsent(&jira);
+        return Ok(());
+    }
+    if args.first().map(String::as_str) == Some("audit") {
+        commands::audit(&jira).await?;
+        commands::print_unsent(&jira);
+        return Ok(());
+    }
+    if args.first().map(String::as_str) == Some("show") {
+        let key = match args.get(1).and_then(|arg| extract_issue_key(arg)) {
+            Some(key) => key,
+            None => bail!("Usage: jira show PROJ-123"),
+        };
+        let color = args.iter().any(|arg| arg == "--color");
+        commands::show_issue(&jira, key, color).await?;
+        commands::print_unsent(&jira);
+        return Ok(());
+    }
 
     let (event_tx, event_rx) = mpsc::unbounded_channel();
     events::subscribe_to_key_events(event_tx.clone());
+    events::subscribe_to_signals(event_tx.clone());
+
+    // An issue key or link can be given to open straight onto that issue
+    let initial_issue = args.first().and_then(|arg| extract_issue_key(arg));
 
-    let state = State::new();
-    let state_rx = state::updater(event_tx, event_rx, jira, state).await;
+    let mut state = State::new();
+    state.clipboard_issue = clipboard::issue_key_from_clipboard();
+    if initial_issue.is_some() {
+        state.input_mode = InputMode::IssueDetail;
+    }
+    let state_rx = state::updater(event_tx, event_rx, jira, state, initial_issue).await;
+
+    // Shell wrappers can capture what was checked out, the UI goes to stderr so it isn't captured too
+    let print_key = args.iter().any(|arg| arg == "--print-key");
+    let print_branch = args.iter().any(|arg| arg == "--print-branch");
+    // Screen readers get a line per change instead of a redrawn screen
+    let plain = args.iter().any(|arg| arg == "--plain");
+    let last_state = ui::init_ui(state_rx, print_key || print_branch, plain).await?;
+    if let Some(profile) = profile {
+        profile.print();
+    }
 
-    if let Err(e) = ui::init_ui(state_rx).await {
-        return Err(e);
+    if let Some(state) = last_state {
+        if let Some(checked_out) = &state.checked_out {
+            if print_key {
+                println!("{}", checked_out.key.as_deref().unwrap_or(""));
+            }
+            if print_branch {
+                println!("{}", checked_out.branch);
+            }
+            if !print_key && !print_branch {
+                println!("{}", checked_out.describe());
+            }
+            // Run once the terminal is back to normal, so the hook's output can be read
+            if state.config.run_post_checkout_hook {
+                let repo = git::open_repo(checked_out.repo.as_deref())?;
+                let root = repo.workdir().map(Path::to_path_buf);
+                if let Some(dir) = checked_out.dir.clone().or(root) {
+                    git::run_post_checkout_hook(&repo, &dir, checked_out.previous)?;
+                }
+            }
+        }
+        if let Some(code) = state.exit_code {
+            process::exit(code);
+        }
     }
 
     Ok(())
@@ -0,0 +1,65 @@
+//! The few things done differently per OS, so the rest of the app doesn't need to care.
+use std::process::Command;
+
+/// Line endings for files people may open in an editor, Notepad only recently learned `\n`.
+const LINE_ENDING: &str = if cfg!(windows) { "\r\n" } else { "\n" };
+
+/// Open a link in the default browser.
+pub fn open_link(link: &str) {
+    let _ = open_command(link).output();
+}
+
+fn open_command(link: &str) -> Command {
+    if cfg!(windows) {
+        // `start` is built into cmd, whose parsing would split the link at any `&`. The empty
+        // argument is the window title, otherwise a quoted link would be taken as one.
+        let mut command = Command::new("cmd");
+        command.args(["/C", "start", "", &link.replace('&', "^&")]);
+        command
+    } else if cfg!(target_os = "macos") {
+        let mut command = Command::new("open");
+        command.arg(link);
+        command
+    } else {
+        let mut command = Command::new("xdg-open");
+        command.arg(link);
+        command
+    }
+}
+
+/// Stop the process, the way Ctrl-Z does outside of raw mode, until the shell continues it. The
+/// app handles SIGTSTP to get the terminal back in order first, so it stops with SIGSTOP, which
+/// can't be handled. There's no stopping a process like this elsewhere.
+pub fn stop_process() {
+    #[cfg(unix)]
+    unsafe {
+        libc::raise(libc::SIGSTOP);
+    }
+}
+
+/// A command that runs `script` through the platform's shell, for user configured commands.
+pub fn shell_command(script: &str) -> Command {
+    let mut command = if cfg!(windows) {
+        let mut command = Command::new("cmd");
+        command.arg("/C");
+        command
+    } else {
+        let mut command = Command::new("sh");
+        command.arg("-c");
+        command
+    };
+    command.arg(script);
+    command
+}
+
+/// Text with the platform's line endings and a trailing newline, for writing to disk.
+pub fn with_line_endings(text: &str) -> String {
+    let mut lines: Vec<&str> = text.lines().collect();
+    lines.push("");
+    lines.join(LINE_ENDING)
+}
+
+/// File contents without the byte order mark some Windows editors add, which JSON parsers reject.
+pub fn strip_bom(contents: &str) -> &str {
+    contents.trim_start_matches('\u{feff}')
+}
@@ -0,0 +1,103 @@
+//! Startup timings for `--profile-startup`. The steps of starting up, like `load_config` and
+//! `search`, run in `tracing` spans, which cost nothing until a subscriber is set. While
+//! profiling, `StartupProfile` is that subscriber and notes down the first run of each span.
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{span, Subscriber};
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    prelude::*,
+    registry::LookupSpan,
+};
+
+/// How long the first run of each span took, in the order they finished.
+#[derive(Clone)]
+pub struct StartupProfile {
+    started: Instant,
+    steps: Arc<Mutex<Vec<Step>>>,
+}
+
+struct Step {
+    name: &'static str,
+    took: Duration,
+    /// How long after starting up it finished.
+    done_at: Duration,
+}
+
+/// When a span was created, kept with the span until it closes. Async steps are timed from
+/// creation rather than entry so the time spent waiting on Jira counts.
+struct Created(Instant);
+
+impl StartupProfile {
+    pub fn new() -> StartupProfile {
+        StartupProfile {
+            started: Instant::now(),
+            steps: Arc::default(),
+        }
+    }
+
+    /// Start noting down spans for the rest of the run.
+    pub fn enable() -> StartupProfile {
+        let profile = StartupProfile::new();
+        let subscriber = tracing_subscriber::registry().with(profile.clone());
+        // Only fails when a subscriber's already set, and then that one gets the spans
+        let _ = tracing::subscriber::set_global_default(subscriber);
+        profile
+    }
+
+    pub fn lines(&self) -> Vec<String> {
+        let steps = self.steps.lock().unwrap();
+        let mut lines = vec!["Startup steps, the first time each ran:".to_string()];
+        for step in steps.iter() {
+            lines.push(format!(
+                "  {:<20} {:>10.1?}  done {:.1?} in",
+                step.name, step.took, step.done_at
+            ));
+        }
+        lines
+    }
+
+    /// Print the steps, once the terminal's back to normal.
+    pub fn print(&self) {
+        for line in self.lines() {
+            println!("{}", line);
+        }
+    }
+}
+
+impl<S> Layer<S> for StartupProfile
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn new_span(&self, _attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            span.extensions_mut().insert(Created(Instant::now()));
+        }
+    }
+
+    fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(&id) {
+            Some(span) => span,
+            None => return,
+        };
+        let created = match span.extensions().get::<Created>() {
+            Some(created) => created.0,
+            None => return,
+        };
+        let mut steps = self.steps.lock().unwrap();
+        // Drawing and searching happen again and again, only the first time is part of startup
+        if steps.iter().any(|step| step.name == span.name()) {
+            return;
+        }
+        steps.push(Step {
+            name: span.name(),
+            took: created.elapsed(),
+            done_at: self.started.elapsed(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests;
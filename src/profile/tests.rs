@@ -0,0 +1,19 @@
+use super::*;
+use tracing::info_span;
+
+#[test]
+fn only_the_first_run_of_each_step_is_noted_down() {
+    let profile = StartupProfile::new();
+    let subscriber = tracing_subscriber::registry().with(profile.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        info_span!("load_config").in_scope(|| {});
+        for _ in 0..3 {
+            info_span!("draw").in_scope(|| {});
+        }
+    });
+
+    let lines = profile.lines();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].trim_start().starts_with("load_config "));
+    assert!(lines[2].trim_start().starts_with("draw "));
+}
@@ -0,0 +1,62 @@
+//! Issues hidden from the list until a later time. Only stored locally, nothing changes in Jira.
+use crate::config::app_file_path;
+use anyhow::Result;
+use std::{
+    fs::File,
+    io::BufReader,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const SNOOZE_FILE_NAME: &str = "snoozed.json";
+
+/// How long an issue can be snoozed for, as (label, seconds).
+pub const SNOOZE_DURATIONS: [(&str, u64); 4] = [
+    ("1 hour", 60 * 60),
+    ("4 hours", 4 * 60 * 60),
+    ("1 day", 24 * 60 * 60),
+    ("1 week", 7 * 24 * 60 * 60),
+];
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SnoozedIssue {
+    pub key: String,
+    pub summary: String,
+    /// Unix timestamp after which the issue shows up again.
+    pub until: u64,
+}
+
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0)
+}
+
+/// Load the issues that are still snoozed, dropping any that have woken up.
+pub fn load_snoozed() -> Vec<SnoozedIssue> {
+    let file = match app_file_path(SNOOZE_FILE_NAME).and_then(|path| Ok(File::open(path)?)) {
+        Ok(f) => f,
+        Err(_) => return vec![],
+    };
+    let snoozed: Vec<SnoozedIssue> =
+        serde_json::from_reader(BufReader::new(file)).unwrap_or_default();
+    let now = now();
+    snoozed.into_iter().filter(|issue| issue.until > now).collect()
+}
+
+pub fn save_snoozed(snoozed: &[SnoozedIssue]) -> Result<()> {
+    let file = File::create(app_file_path(SNOOZE_FILE_NAME)?)?;
+    serde_json::to_writer(file, snoozed)?;
+    Ok(())
+}
+
+/// A rough description of how long until a snoozed issue wakes up, e.g. "3h".
+pub fn describe_remaining(until: u64) -> String {
+    let remaining = until.saturating_sub(now());
+    match remaining {
+        0..=59 => "<1m".to_string(),
+        60..=3599 => format!("{}m", remaining / 60),
+        3600..=86399 => format!("{}h", remaining / 3600),
+        _ => format!("{}d", remaining / 86400),
+    }
+}
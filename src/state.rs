@@ -1,77 +1,650 @@
 use crate::{
-    config::{load_config, save_config, Config},
+    audit::{audit, local_branches, Audit},
+    checklist::{parse_checklist, toggle_item, ChecklistItem},
+    clipboard::write_clipboard,
+    config::{load_config, project_of, save_config, Config, SavedBoard, SavedView, SortPreset},
+    credentials::{has_github_token, load_github_token},
+    editor::TextEditor,
     events::{Event, EventsRx, EventsTx},
+    export::issues_html,
+    forge::{github_repo, GitHub, PullRequest, GITHUB_API},
     git::{
         checkout_branch,
+        clone_repo,
+        commit_empty,
+        current_branch,
         get_current_repo,
         create_and_use_branch,
-        matching_branches,
+        branch_exists,
+        overwrite_and_use_branch,
+        unused_branch_name,
+        head_commit,
+        open_repo,
+        recent_committers,
+        refs_stamp,
+        BranchIndex,
         BranchSummary,
     },
-    jira::{BoardSummary, IssueSummary, TransitionSummary, JiraClient},
-    utils::StatefulList,
+    jira::{
+        AccountInfo, ActivityEntry, AttachmentSummary, BoardSummary, BulkEdit, CommentPage, CommentSummary, IssueLinkSummary, IssuePermissions, IssueSummary, JiraClient,
+        JiraError, JiraResult, LinkRelation, RateLimit, SprintSummary, TextField, TimeTracking,
+        TransitionSummary, UserSummary,
+    },
+    platform::open_link,
+    snooze::{load_snoozed, now, save_snoozed, SnoozedIssue, SNOOZE_DURATIONS},
+    template::{branch_name, pr_description, smart_commit},
+    timer::{describe_duration, load_timer, parse_duration, save_timer, WorkTimer},
+    utils::{is_issue_key, StatefulList},
+    webhook,
+};
+use anyhow::{bail, Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use git2::{Oid, Repository};
+use futures::future::join;
+use tokio::{
+    sync::mpsc,
+    task::spawn_blocking,
+    time::sleep,
+};
+use tracing::Instrument;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
-use anyhow::{bail, Result};
-use crossterm::event::KeyCode;
-use tokio::sync::mpsc;
-use std::process::Command;
+use action::{chord_action, key_action, keymap_problems};
+use reducer::apply_action;
 
 pub type StateRx = mpsc::Receiver<State>;
 
+/// Where the issue list is exported to, in the temp directory.
+const EXPORT_FILE_NAME: &str = "jira-issues.html";
+/// Lines above the description in the detail view, give or take the optional fields.
+const DETAIL_HEADER_LINES: usize = 12;
+/// How far PageUp and PageDown scroll the detail view.
+const DETAIL_PAGE_LINES: u16 = 10;
+/// How often running timers are redrawn.
+const TICK_INTERVAL_SECS: u64 = 30;
+/// How often spinners move on while something's running.
+const ANIMATION_INTERVAL_MILLIS: u64 = 120;
+const REFS_CHECK_INTERVAL_SECS: u64 = 2;
+/// How long transitions fetched for a project and status are reused for.
+const TRANSITIONS_TTL_SECS: u64 = 10 * 60;
+
+pub const BULK_EDIT_ACTIONS: [&str; 3] = ["Add label", "Remove label", "Set fix version"];
+
 pub async fn updater(
     event_tx: EventsTx,
     mut event_rx: EventsRx,
     jira: JiraClient,
     mut state: State,
+    initial_issue: Option<String>,
 ) -> StateRx {
     let (tx, rx) = mpsc::channel(20);
 
     // Prime the receiver with the initial state
     let _ = tx.send(state.clone()).await;
 
-    fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await;
+    // When opened on a specific issue the list isn't fetched until it's asked for
+    match initial_issue {
+        Some(key) => fetch_issue(event_tx.clone(), jira.clone(), key).await,
+        None => fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await,
+    }
+
+    spawn_poller(event_tx.clone(), jira.clone(), state.config.poll_interval_secs);
+    watch_refs(event_tx.clone());
+    spawn_ticker(event_tx.clone());
+    if let Some(address) = &state.config.webhook_address {
+        let secret = state.config.webhook_secret.clone();
+        if let Err(err) = webhook::listen(address, secret, event_tx.clone()) {
+            state.error = Some(format!("{:#}", err));
+        }
+    }
 
     tokio::spawn(async move {
         let tx = tx.clone();
+        // Requests waiting on a new token before they're sent again
+        let mut retries: Vec<Retry> = vec![];
         loop {
             if let Some(event_type) = event_rx.recv().await {
+                state.rate_limit = jira.rate_limit();
+                state.queued_requests = jira.tasks().queued();
+                // Held back requests are listed until the next key press, like errors
+                let unsent = jira.take_unsent();
+                if matches!(event_type, Event::KeyEvent(_)) {
+                    state.unsent_requests.clear();
+                }
+                state.unsent_requests.extend(unsent);
                 match event_type {
-                    Event::KeyEvent(code) => {
+                    Event::KeyEvent(key) => {
+                        // Errors are shown until the next key press
+                        state.error = None;
+                        state.notice = None;
                         // TODO this is kind of weird, we use an error to handle quitting, refactor
                         // with channels
-                        if handle_input(&mut state, code, event_tx.clone(), jira.clone()).await.is_err() {
+                        if handle_input(&mut state, key, event_tx.clone(), jira.clone()).await.is_err() {
+                            // The UI hands the last state back, e.g. to print what was checked out
+                            let _ = tx.send(state.clone()).await;
                             break;
                         }
                         let _ = tx.send(state.clone()).await;
+                        state.suspend = false;
                     }
-                    Event::TransitionsFetched(transitions) => {
+                    Event::TransitionsFetched(workflow, transitions) => {
+                        let cached = CachedTransitions {
+                            transitions: transitions.clone(),
+                            fetched: Instant::now(),
+                        };
+                        state.transition_cache.insert(workflow, cached);
                         state.transitions = StatefulList::with_items(transitions);
                         state.transitions.next();
                         let _ = tx.send(state.clone()).await;
                     }
                     Event::TransitionExecuted => {
                         state.transitions = StatefulList::new();
-                        state.input_mode = InputMode::IssuesList;
+                        state.close_transition_picker();
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::IssueTransitioned(key, name) => {
+                        state.notice = Some(format!("Moved {} to {}", key, name));
+                        // It may no longer match the filters
+                        fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::TransitionUnavailable(key, name) => {
+                        state.error = Some(format!("{} can't be moved to {} from here", key, name));
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::LinksFetched(key, links) => {
+                        if let Some(view) = state.links_view.as_mut().filter(|v| v.key == key) {
+                            view.list(links.clone());
+                        }
+                        state.links.insert(key, links);
                         let _ = tx.send(state.clone()).await;
                     }
                     Event::IssuesUpdated(issues) => {
+                        // Stay on the same issue across refreshes when it's still there
+                        let selected = state.selected_issue_key();
+                        state.issues_fetched = true;
+                        if let Some(search) = state.issues_loading.take() {
+                            if search.cancelled() {
+                                state.notice = Some(format!(
+                                    "Loaded {} of {} issues, skipped the rest",
+                                    issues.len(),
+                                    search.total
+                                ));
+                            }
+                        }
                         state.issues = StatefulList::with_items(issues);
-                        state.issues.next();
+                        state.hide_snoozed();
+                        state.sort_issues();
+                        let index = selected
+                            .and_then(|key| state.issues.items.iter().position(|i| i.key == key));
+                        match index {
+                            Some(index) => state.issues.state.select(Some(index)),
+                            None => state.issues.next(),
+                        }
+                        find_relevant_branches(event_tx.clone(), state.clone()).await;
+                        find_started_work(event_tx.clone(), jira.clone(), state.clone()).await;
+
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::IssueJumped(issue) => {
+                        state.jump_to_issue(*issue);
+                        state.load_checklist();
                         find_relevant_branches(event_tx.clone(), state.clone()).await;
+                        fetch_issue_details(event_tx.clone(), jira.clone(), &state).await;
 
                         let _ = tx.send(state.clone()).await;
                     }
+                    Event::DescriptionFetched(key, description, updated) => {
+                        if let InputMode::ConfirmDescription = state.input_mode {
+                            // Reloaded after a conflict, diff the edit against the latest version
+                            if let Some(edit) = state.description_edit.as_mut() {
+                                if edit.key == key {
+                                    edit.original = description;
+                                    edit.updated = updated;
+                                }
+                            }
+                        } else if let InputMode::IssueDetail = state.input_mode {
+                            let selected = state.selected_issue_key().as_ref() == Some(&key);
+                            // The permissions may have ruled out editing since it was asked for
+                            let denied = state.denial(&key, Picker::Description).is_some();
+                            if selected && !denied {
+                                state.description_edit = Some(DescriptionEdit {
+                                    key,
+                                    editor: TextEditor::new(&description),
+                                    original: description,
+                                    updated,
+                                });
+                                state.set_mode(InputMode::EditingDescription);
+                            }
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::DescriptionSaved(key, description, updated) => {
+                        state.set_description(&key, description, updated);
+                        state.description_edit = None;
+                        if let InputMode::ConfirmDescription = state.input_mode {
+                            state.set_mode(InputMode::IssueDetail);
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SummarySaved(key, updated) => {
+                        if let Some(issue) = state.issue_mut(&key) {
+                            issue.updated = updated;
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SummarySaveFailed(key, previous, err) => {
+                        // Undo the optimistic rename
+                        if let Some(issue) = state.issue_mut(&key) {
+                            issue.summary = previous;
+                        }
+                        if err.is_permission_denied() {
+                            state.restrict(&key, IssueAction::Edit, err.to_string());
+                        }
+                        state.error = Some(err.to_string());
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::WatchingSet(key, watching) => {
+                        let notice = if watching {
+                            format!("Watching {}", key)
+                        } else {
+                            format!("Stopped watching {}", key)
+                        };
+                        state.notice = Some(notice);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::WatchFailed(key, watching, err) => {
+                        // Undo the optimistic toggle
+                        if let Some(issue) = state.issue_mut(&key) {
+                            issue.watching = !watching;
+                        }
+                        state.error = Some(err.to_string());
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SiblingsFetched(siblings) => {
+                        state.siblings = siblings;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SubtasksFetched(key, subtasks) => {
+                        state.subtasks.insert(key, subtasks);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AttachmentsFetched(key, attachments) => {
+                        if state.selected_issue_key() == Some(key) {
+                            state.attachments = StatefulList::with_items(attachments);
+                            state.attachments.next();
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AttachmentDownloaded(path) => {
+                        state.notice = Some(format!("Saved to {}", path.display()));
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::DownloadFailed(message) => {
+                        state.error = Some(message);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AttachmentUploaded(key, filename) => {
+                        state.notice = Some(format!("Attached {} to {}", filename, key));
+                        if state.selected_issue_key() == Some(key) {
+                            fetch_attachments(event_tx.clone(), jira.clone(), state.clone()).await;
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::UploadFailed(message) => {
+                        state.error = Some(message);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::LinkRelationsFetched(relations) => {
+                        state.link_relations = relations.clone();
+                        if let Some(form) = state.link_form.as_mut() {
+                            form.relations = StatefulList::with_items(relations);
+                            form.relations.next();
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::IssueLinked(key, relation, target) => {
+                        state.notice = Some(format!("{} {} {}", key, relation, target));
+                        // The other end's links are fetched again when it's next shown
+                        state.links.remove(&target);
+                        if state.selected_issue_key() == Some(key) {
+                            fetch_links(event_tx.clone(), jira.clone(), state.clone()).await;
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::TimeTrackingFetched(key, tracking) => {
+                        state.time_tracking.insert(key, tracking);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::CommentsFetched(key, page) => {
+                        state.add_comments(&key, page);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::ActivityFetched(entries) => {
+                        state.activity = StatefulList::with_items(entries);
+                        state.hide_muted_activity();
+                        state.activity.next();
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SprintFetched(board_id, sprint) => {
+                        // Drop a sprint for a board since moved away from, or switched to the
+                        // backlog of
+                        let view = state.sprint.as_mut();
+                        let view = view.filter(|view| view.board.id == board_id && !view.backlog);
+                        if let Some(view) = view {
+                            view.show(sprint);
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::SprintsFetched(board_id, sprints) => {
+                        let picker = state.sprint_picker.as_mut();
+                        if let Some(picker) = picker.filter(|picker| picker.board_id == board_id) {
+                            picker.sprints = StatefulList::with_items(sprints);
+                            picker.sprints.next();
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::MovedToSprint(key, name) => {
+                        state.notice = Some(format!("Moved {} to {}", key, name));
+                        // The issue has left the backlog or sprint being looked at
+                        fetch_sprint_view(event_tx.clone(), jira.clone(), &state).await;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::BacklogFetched(board_id, issues) => {
+                        let view = state.sprint.as_mut();
+                        let view = view.filter(|view| view.board.id == board_id && view.backlog);
+                        if let Some(view) = view {
+                            view.list(issues);
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
                     Event::BoardsUpdated(boards) => {
-                        state.boards.items = boards;
-                        state.boards.next();
+                        state.all_boards = boards;
+                        state.filter_boards();
 
                         let _ = tx.send(state.clone()).await;
                     }
-                    Event::BranchesUpdated(branches) => {
-                        state.branches.items = branches;
-                        state.branches.items.push(BranchSummary {
-                            name: "Create New".to_string(),
+                    Event::IssuesLoading(search) => {
+                        // The last page isn't shown, the issues arrive straight after
+                        state.issues_loading = Some(search).filter(|s| s.loaded < s.total);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::BulkEditStep(key, status) => {
+                        if let Some(progress) = state.bulk_progress.as_mut() {
+                            progress.record(&key, status);
+                            if progress.finished() && progress.cancelled() {
+                                state.notice = Some(format!("Cancelled, {}", progress.summary()));
+                            }
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::JiraFailed(err) => {
+                        // A failed search doesn't get as far as the last page
+                        state.issues_loading = None;
+                        state.error = Some(err.to_string());
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PermissionsChecked(key, picker, permissions) => {
+                        state.apply_permissions(&key, permissions);
+                        state.close_denied_picker(&key, picker);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::ActionRefused(key, action, err) => {
+                        state.restrict(&key, action, err.to_string());
+                        state.error = state.refusal(&key, action);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::Poll => {
+                        if state.refreshes_in_background() {
+                            fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await;
+                        }
+                    }
+                    Event::FocusChanged(focused) => {
+                        state.terminal_focused = focused;
+                        // Catch up on the polls skipped while the terminal was in the background
+                        if state.refreshes_in_background() {
+                            fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await;
+                        }
+                    }
+                    Event::IssueChanged(key) => {
+                        // Refreshed the same way as a poll, just sooner
+                        if state.refreshes_in_background() {
+                            fetch_tickets(event_tx.clone(), jira.clone(), state.clone()).await;
+                        } else {
+                            state.notice = Some(format!("{} changed in Jira", key));
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::CommentsChanged(key) => {
+                        let reading = matches!(
+                            state.input_mode,
+                            InputMode::IssueDetail | InputMode::CommentsView
+                        );
+                        if reading && state.selected_issue_key().as_ref() == Some(&key) {
+                            // Fetch the thread again from the latest comment, staying put
+                            let scroll = (state.detail_scroll, state.comments_scroll);
+                            state.clear_comments();
+                            state.detail_scroll = scroll.0;
+                            state.comments_scroll = scroll.1;
+                            fetch_comments(event_tx.clone(), jira.clone(), state.clone()).await;
+                        }
+                    }
+                    Event::RefsChanged => {
+                        find_relevant_branches(event_tx.clone(), state.clone()).await;
+                    }
+                    Event::AuthExpired(retry) => {
+                        state.issues_loading = None;
+                        if state.reauth_input.is_none() {
+                            state.reauth_input = Some(String::new());
+                        }
+                        if !retries.contains(&retry) {
+                            retries.push(retry);
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::ReauthFinished(resume) => {
+                        for retry in retries.drain(..) {
+                            if resume {
+                                send_again(retry, event_tx.clone(), jira.clone(), state.clone())
+                                    .await;
+                            }
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::Tick => {
+                        if state.timer.is_some() {
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::Animate => {
+                        let running = state.bulk_progress.as_ref().is_some_and(|p| !p.finished());
+                        // Spinners stand still while no one's looking, saving the redraws
+                        let showing = running || state.issues_loading.is_some();
+                        if showing && state.terminal_focused {
+                            state.animation_frame = state.animation_frame.wrapping_add(1);
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::WorklogAdded(key, seconds) => {
+                        state.notice =
+                            Some(format!("Logged {} on {}", describe_duration(seconds), key));
+                        // The detail view's time spent and remaining are out of date now
+                        if state.selected_issue_key() == Some(key) {
+                            let refetch =
+                                fetch_time_tracking(event_tx.clone(), jira.clone(), state.clone());
+                            refetch.await;
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PullRequestLinked(key) => {
+                        state.notice = Some(format!("Linked the PR to {}", key));
+                        state.pull_requests.insert(key);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PullRequestOpened(key, url, unreviewed) => {
+                        let mut notes = vec![];
+                        if let Some(url) = url {
+                            notes.push(format!("Opened {} and linked it to {}", url, key));
+                            state.pull_requests.insert(key);
+                        }
+                        if let Some(reason) = unreviewed {
+                            notes.push(format!("No reviewers were asked, {}", reason));
+                        }
+                        if !notes.is_empty() {
+                            state.notice = Some(notes.join(". "));
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PullRequestFailed(message) => {
+                        state.error = Some(message);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::SubtaskCreated(parent, created) => {
+                        if let Some(created) = created {
+                            state.notice = Some(format!("Created {} under {}", created, parent));
+                        }
+                        fetch_subtasks(event_tx.clone(), jira.clone(), state.clone()).await;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AccountFetched(account) => {
+                        state.account = Some(account);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AuditFinished(audit) => {
+                        state.audit = Some(audit);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::AuditFailed(message) => {
+                        state.error = Some(message);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::UsersFound(query, users) => {
+                        // Drop results for something since typed over
+                        let picking = matches!(state.input_mode, InputMode::AssigningIssue);
+                        if picking && state.input == query {
+                            state.show_assignees(users);
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::IssueAssigned(key, name) => {
+                        if let Some(issue) = state.issue_mut(&key) {
+                            issue.assignee_name = name.clone();
+                        }
+                        state.notice = Some(format!("Assigned {} to {}", key, name));
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PrioritiesFetched(priorities) => {
+                        state.show_priorities(priorities);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::PrioritySet(key, name) => {
+                        if let Some(issue) = state.issue_mut(&key) {
+                            issue.priority = Some(name.clone());
+                        }
+                        state.notice = Some(format!("Set the priority of {} to {}", key, name));
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::LabelsFetched(key, labels) => {
+                        let editing = matches!(state.input_mode, InputMode::EditingLabels);
+                        if editing && state.selected_issue_key().as_ref() == Some(&key) {
+                            state.show_labels(labels);
+                            let _ = tx.send(state.clone()).await;
+                        }
+                    }
+                    Event::LabelsEdited(key, edit) => {
+                        state.notice = Some(match &edit {
+                            BulkEdit::AddLabel(label) => format!("Added {} to {}", label, key),
+                            BulkEdit::RemoveLabel(label) => {
+                                format!("Removed {} from {}", label, key)
+                            }
+                            BulkEdit::SetFixVersion(version) => {
+                                format!("Set the fix version of {} to {}", key, version)
+                            }
                         });
+                        fetch_labels(event_tx.clone(), jira.clone(), key).await;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::CommentAdded(key) => {
+                        state.notice = Some(format!("Commented on {}", key));
+                        // Show the new comment the same way as one added in Jira
+                        send_event(&event_tx, Event::CommentsChanged(key));
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::ReviewersSuggested(key, _)
+                        if state.selected_issue_key().as_ref() != Some(&key) => {}
+                    Event::ReviewersSuggested(_, reviewers) => {
+                        if reviewers.is_empty() && !has_github_token(&state.config) {
+                            state.copy_pr_description();
+                        } else {
+                            state.reviewer_picker.suggested = StatefulList::with_items(reviewers);
+                            state.reviewer_picker.suggested.next();
+                            state.open_mode(InputMode::PickingReviewers);
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::RepoCloned(key, path) => {
+                        state.notice = Some(format!("Cloned into {}", path.display()));
+                        // Carry on to naming the branch the clone was made for
+                        if state.selected_issue_key().as_ref() == Some(&key) {
+                            state.open_mode(InputMode::Editing);
+                            find_relevant_branches(event_tx.clone(), state.clone()).await;
+                        }
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::CloneFailed(message) => {
+                        state.error = Some(message);
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::Terminated(code) => {
+                        // Config and snoozes are saved as soon as they change, so there's
+                        // nothing left to write before stopping
+                        state.exit_code = Some(code);
+                        let _ = tx.send(state.clone()).await;
+                        break;
+                    }
+                    Event::Suspend => {
+                        state.suspend = true;
+                        let _ = tx.send(state.clone()).await;
+                        state.suspend = false;
+                    }
+                    Event::Resumed => {
+                        state.resumed = true;
+                        let _ = tx.send(state.clone()).await;
+                        state.resumed = false;
+                    }
+                    // Scans can finish out of order when moving through issues quickly
+                    Event::StartedWorkFound(branched, pull_requests) => {
+                        state.branched = branched;
+                        state.pull_requests = pull_requests;
+                        let _ = tx.send(state.clone()).await;
+                    }
+                    Event::BranchesUpdated(key, _, _)
+                        if state.selected_issue_key().as_ref() != Some(&key) => {}
+                    Event::BranchesUpdated(key, branches, head) => {
+                        // Keeps the list's marker right after a branch is made or deleted
+                        if branches.is_empty() {
+                            state.branched.remove(&key);
+                        } else {
+                            state.branched.insert(key);
+                        }
+                        let mut entries: Vec<BranchEntry> =
+                            branches.into_iter().map(BranchEntry::Existing).collect();
+                        entries.push(BranchEntry::CreateNew);
+                        state.branches.set_items(entries);
+                        state.head_branch = head;
+                        if let InputMode::IssueDetail = state.input_mode {
+                            if state.branches.state.selected().is_none() {
+                                state.branches.next();
+                            }
+                        }
 
                         let _ = tx.send(state.clone()).await;
                     }
@@ -83,307 +656,3169 @@ pub async fn updater(
     rx
 }
 
-async fn fetch_tickets(event_tx: EventsTx, jira: JiraClient, state: State) {
+/// Ask for a background refresh every `interval` seconds, backing off as the rate limit budget
+/// runs low. Polls are skipped while the terminal is in the background.
+fn spawn_poller(event_tx: EventsTx, jira: JiraClient, interval: u64) {
+    if interval == 0 {
+        return;
+    }
     tokio::spawn(async move {
-        if let Ok(issues) = jira.current_issues(&state.config).await {
-            assert!(event_tx.send(Event::IssuesUpdated(issues)).is_ok())
+        loop {
+            let multiplier = jira.rate_limit().map_or(1, |limit| limit.poll_multiplier());
+            sleep(Duration::from_secs(interval * u64::from(multiplier))).await;
+            if event_tx.send(Event::Poll).is_err() {
+                break;
+            }
         }
     });
 }
 
-async fn fetch_boards(event_tx: EventsTx, jira: JiraClient, state: State) {
+/// Redraw every so often so running timers stay current.
+fn spawn_ticker(event_tx: EventsTx) {
     tokio::spawn(async move {
-        if let Ok(boards) = jira.current_boards(&state.config).await {
-            assert!(event_tx.send(Event::BoardsUpdated(boards)).is_ok())
+        loop {
+            sleep(Duration::from_secs(TICK_INTERVAL_SECS)).await;
+            if event_tx.send(Event::Tick).is_err() {
+                break;
+            }
         }
     });
 }
 
-async fn fetch_transitions(event_tx: EventsTx, jira: JiraClient, state: State) {
+/// Move spinners on until `running` is cleared, once whatever they're showing is done.
+fn spawn_animator(event_tx: EventsTx, running: Arc<AtomicBool>) {
     tokio::spawn(async move {
-        if let Some(key) = state.selected_issue_key() {
-            if let Ok(transitions) = jira.get_transitions(key).await {
-                // let mut path = app_root(AppDataType::UserConfig, &APP_INFO).unwrap();
-                // path.push(TEMP_BUFFER_NAME);
-                // let file = File::create(path).unwrap();
-                // serde_json::to_writer_pretty(file, &editmeta).unwrap();
-                assert!(event_tx.send(Event::TransitionsFetched(transitions)).is_ok())
+        while running.load(Ordering::Relaxed) {
+            sleep(Duration::from_millis(ANIMATION_INTERVAL_MILLIS)).await;
+            if event_tx.send(Event::Animate).is_err() {
+                break;
             }
         }
     });
 }
 
-async fn find_relevant_branches(event_tx: EventsTx, state: State) {
-    if let Some(key) = state.selected_issue_key() {
-        tokio::spawn(async move {
-            if let Ok(repo) = get_current_repo() {
-                if let Ok(branches) = matching_branches(&repo, key) {
-                    assert!(event_tx.send(Event::BranchesUpdated(branches)).is_ok())
-                }
-            }
-        });
+/// Keep an eye on the repo's refs so the branches pane picks up branches made elsewhere. This
+/// checks modification times rather than using OS file events, which is cheap enough at this rate.
+fn watch_refs(event_tx: EventsTx) {
+    let git_dir = match get_current_repo() {
+        Ok(repo) => repo.path().to_path_buf(),
+        Err(_) => return,
     };
-}
-
-async fn do_selected_transition(event_tx: EventsTx, jira: JiraClient, state: State) {
     tokio::spawn(async move {
-        if let Some(i) = state.transitions.state.selected() {
-            let transition_id = state.transitions.items[i].key.clone();
-            if let Ok(_) = jira.do_transition(state.selected_issue_key().unwrap(), transition_id).await {
-                assert!(event_tx.send(Event::TransitionExecuted).is_ok())
+        let mut last = refs_stamp(&git_dir);
+        loop {
+            sleep(Duration::from_secs(REFS_CHECK_INTERVAL_SECS)).await;
+            let git_dir = git_dir.clone();
+            let stamp = spawn_blocking(move || refs_stamp(&git_dir)).await.unwrap_or(last);
+            if stamp != last {
+                last = stamp;
+                if event_tx.send(Event::RefsChanged).is_err() {
+                    break;
+                }
             }
         }
     });
 }
 
-#[derive(Clone)]
-pub enum InputMode {
-    IssuesList,
-    BoardsList,
-    Editing,
-    UpdateIssueStatus,
-    EditingDefaultProject,
-}
-
-#[derive(Clone)]
-pub struct State {
-    pub issues: StatefulList<IssueSummary>,
-    pub boards: StatefulList<BoardSummary>,
-    pub branches: StatefulList<BranchSummary>,
-    pub transitions: StatefulList<TransitionSummary>,
-    pub config: Config,
-    pub input_mode: InputMode,
-    issues_focused: bool,
-    input: String,
+/// A request that failed because the credentials were rejected, to be sent again once a new
+/// token is entered. They're sent based on the state at that point.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Retry {
+    Tickets,
+    Issue(String),
+    Siblings,
+    Subtasks,
+    TimeTracking,
+    Comments,
+    Boards,
+    Sprint(u64),
+    Backlog(u64),
+    Sprints(u64),
+    Activity,
+    Transitions,
+    Links,
+    Transition,
+    /// Moving an issue to a transition by name, from a shortcut.
+    TransitionTo(String, String),
+    Description(String),
+    SaveDescription,
+    Worklog(String, u64, Option<String>),
+    LinkPullRequest(String, String),
+    AddComment(String, String),
+    Users(String, String),
+    Account,
+    Assign(String, Assignee),
+    Priorities(String),
+    SetPriority(String, String),
+    Labels(String),
+    EditLabels(String, BulkEdit),
+    CreateSubtask(String, String),
+    /// Moving an issue into a sprint, given by id and name.
+    MoveToSprint(String, u64, String),
+    Attachments,
+    /// Downloading an attachment of the issue with the given key into the given directory.
+    Download(String, AttachmentSummary, PathBuf),
+    /// Attaching the file at the given path to the issue with the given key.
+    Upload(String, PathBuf),
+    Audit,
+    LinkRelations,
+    /// Linking the issue with the first key to the one with the second.
+    CreateLink(String, LinkRelation, String),
 }
 
-impl State {
-    pub fn new() -> State {
-        let config = load_config();
-        State {
-            issues: StatefulList::new(),
-            boards: StatefulList::new(),
-            branches: StatefulList::new(),
-            transitions: StatefulList::new(),
-            issues_focused: true,
-            input_mode: InputMode::IssuesList,
-            input: String::new(),
-            config,
-        }
-    }
-
-    fn selected_issue_key(&self) -> Option<String> {
-        if let Some(i) = self.issues.state.selected() {
-            if let Some(issue) = self.issues.items.get(i) {
-                return Some(issue.key.clone());
+async fn send_again(retry: Retry, event_tx: EventsTx, jira: JiraClient, state: State) {
+    match retry {
+        Retry::Tickets => fetch_tickets(event_tx, jira, state).await,
+        Retry::Issue(key) => fetch_issue(event_tx, jira, key).await,
+        Retry::Siblings => fetch_siblings(event_tx, jira, state).await,
+        Retry::Subtasks => fetch_subtasks(event_tx, jira, state).await,
+        Retry::TimeTracking => fetch_time_tracking(event_tx, jira, state).await,
+        Retry::Comments => fetch_comments(event_tx, jira, state).await,
+        Retry::Boards => fetch_boards(event_tx, jira, state).await,
+        Retry::Sprint(board_id) => fetch_sprint(event_tx, jira, board_id).await,
+        Retry::Backlog(board_id) => fetch_backlog(event_tx, jira, board_id).await,
+        Retry::Sprints(board_id) => fetch_open_sprints(event_tx, jira, board_id).await,
+        Retry::Activity => fetch_activity(event_tx, jira).await,
+        Retry::Transitions => fetch_transitions(event_tx, jira, state).await,
+        Retry::Links => fetch_links(event_tx, jira, state).await,
+        Retry::Transition => do_selected_transition(event_tx, jira, state).await,
+        Retry::TransitionTo(key, name) => transition_to(event_tx, jira, key, name).await,
+        Retry::Description(key) => fetch_description(event_tx, jira, key).await,
+        Retry::SaveDescription => {
+            if let Some(edit) = state.description_edit {
+                save_description(event_tx, jira, edit).await;
             }
         }
-        None
+        Retry::Worklog(key, seconds, comment) => {
+            add_worklog(event_tx, jira, key, seconds, comment).await
+        }
+        Retry::LinkPullRequest(key, url) => link_pull_request(event_tx, jira, key, url).await,
+        Retry::AddComment(key, text) => add_comment(event_tx, jira, key, text).await,
+        Retry::Users(key, query) => search_users(event_tx, jira, key, query).await,
+        Retry::Account => fetch_account(event_tx, jira).await,
+        Retry::Assign(key, assignee) => assign_issue(event_tx, jira, key, assignee).await,
+        Retry::Priorities(key) => fetch_priorities(event_tx, jira, key).await,
+        Retry::SetPriority(key, name) => set_priority(event_tx, jira, key, name).await,
+        Retry::Labels(key) => fetch_labels(event_tx, jira, key).await,
+        Retry::EditLabels(key, edit) => edit_labels(event_tx, jira, key, edit).await,
+        Retry::CreateSubtask(parent, summary) => {
+            create_subtask(event_tx, jira, parent, summary).await
+        }
+        Retry::MoveToSprint(key, sprint_id, name) => {
+            move_to_sprint(event_tx, jira, key, sprint_id, name).await
+        }
+        Retry::Attachments => fetch_attachments(event_tx, jira, state).await,
+        Retry::Download(key, attachment, dir) => {
+            download_attachment(event_tx, jira, key, attachment, dir).await
+        }
+        Retry::Upload(key, path) => upload_attachment(event_tx, jira, key, path).await,
+        Retry::Audit => run_audit(event_tx, jira).await,
+        Retry::LinkRelations => fetch_link_relations(event_tx, jira).await,
+        Retry::CreateLink(key, relation, target) => {
+            create_link(event_tx, jira, key, relation, target).await
+        }
     }
+}
 
-    fn selected_issue_permalink(&self) -> Option<String> {
-        match self.issues.state.selected() {
-            Some(i) => Some(self.issues.items[i].permalink.clone()),
-            None => None,
+/// Report a failed request, asking for a new token when it looks like the old one expired.
+fn report_failure(event_tx: &EventsTx, err: JiraError, retry: Retry) {
+    let event = if err.is_auth_failure() {
+        Event::AuthExpired(retry)
+    } else {
+        Event::JiraFailed(err)
+    };
+    send_event(event_tx, event);
+}
+
+/// Hand an event to the updater. It only stops listening while the app shuts down, when there's
+/// no one left to tell, so a closed channel is fine to ignore. Tasks in the pool are stopped then
+/// too, see `TaskPool::spawn`.
+fn send_event(event_tx: &EventsTx, event: Event) {
+    let _ = event_tx.send(event);
+}
+
+/// Check what the user may do to an issue before they try, leaving it to the action itself to
+/// report any failure.
+async fn check_permissions(event_tx: EventsTx, jira: JiraClient, key: String, picker: Picker) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let permissions: Vec<&str> = IssueAction::ALL
+            .iter()
+            .map(|action| action.permission())
+            .collect();
+        let jira = jira.at(&key);
+        if let Ok(held) = jira.issue_permissions(&key, &permissions).await {
+            send_event(&event_tx, Event::PermissionsChecked(key, picker, held));
         }
+    });
+}
+
+/// Report a failed action on an issue, remembering not to offer it again when it failed because
+/// the user isn't allowed to do it.
+fn report_refusal(
+    event_tx: &EventsTx,
+    err: JiraError,
+    key: String,
+    action: IssueAction,
+    retry: Retry,
+) {
+    if err.is_permission_denied() {
+        send_event(event_tx, Event::ActionRefused(key, action, err));
+    } else {
+        report_failure(event_tx, err, retry);
     }
+}
 
-    fn open_selected_board(&self) {
-        if let Some(i) = self.boards.state.selected() {
-            let link = self.boards.items[i].permalink.clone();
-            let _ = Command::new("open").arg(link).output();
+async fn fetch_tickets(event_tx: EventsTx, jira: JiraClient, state: State) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let running = Arc::new(AtomicBool::new(true));
+        spawn_animator(event_tx.clone(), running.clone());
+        let cancel = Arc::new(AtomicBool::new(false));
+        let on_page = |loaded, total| {
+            let search = SearchProgress {
+                loaded,
+                total,
+                cancel: cancel.clone(),
+            };
+            let _ = event_tx.send(Event::IssuesLoading(search));
+            !cancel.load(Ordering::Relaxed)
+        };
+        let search = jira.current_issues_everywhere(&state.config, on_page);
+        let result = search.instrument(tracing::info_span!("search")).await;
+        running.store(false, Ordering::Relaxed);
+        match result {
+            Ok(issues) => send_event(&event_tx, Event::IssuesUpdated(issues)),
+            Err(err) => report_failure(&event_tx, err, Retry::Tickets),
         }
-    }
+    });
+}
 
-    fn selected_branch_name(&self) -> Option<String> {
-        match self.branches.state.selected() {
-            Some(i) => Some(self.branches.items[i].name.clone()),
-            None => None,
+async fn fetch_issue(event_tx: EventsTx, jira: JiraClient, key: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).get_issue(key.clone()).await {
+            Ok(issue) => send_event(&event_tx, Event::IssueJumped(Box::new(issue))),
+            Err(err) => report_failure(&event_tx, err, Retry::Issue(key)),
         }
-    }
+    });
+}
 
-    pub fn raw_input_clone(&self) -> String {
-        self.input.clone()
-    }
+/// Fetch everything the detail view shows about the selected issue besides the issue itself.
+async fn fetch_issue_details(event_tx: EventsTx, jira: JiraClient, state: &State) {
+    fetch_siblings(event_tx.clone(), jira.clone(), state.clone()).await;
+    fetch_subtasks(event_tx.clone(), jira.clone(), state.clone()).await;
+    fetch_time_tracking(event_tx.clone(), jira.clone(), state.clone()).await;
+    fetch_links(event_tx.clone(), jira.clone(), state.clone()).await;
+    fetch_comments(event_tx, jira, state.clone()).await;
+}
 
-    pub fn new_branch_name(&self) -> String {
-        match self.selected_issue_key() {
-            Some(key) => format!("{}-{}", key, self.input),
-            None => "unhandled-error".to_string(),
+/// Fetch the issues sharing a parent with the selected one, for cycling through in the detail view.
+async fn fetch_siblings(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let parent = match state.selected_issue().and_then(|issue| issue.parent.clone()) {
+        Some(parent) => parent,
+        None => {
+            send_event(&event_tx, Event::SiblingsFetched(vec![]));
+            return;
         }
-    }
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&parent).child_issues(parent.clone()).await {
+            Ok(siblings) => send_event(&event_tx, Event::SiblingsFetched(siblings)),
+            Err(err) => report_failure(&event_tx, err, Retry::Siblings),
+        }
+    });
 }
 
-async fn handle_input(
-    state: &mut State,
-    input: KeyCode,
+/// Fetch the sub-tasks of the selected issue, to list in the detail view.
+async fn fetch_subtasks(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let key = match state.selected_issue_key() {
+        Some(key) => key,
+        None => return,
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).subtasks(&key).await {
+            Ok(subtasks) => send_event(&event_tx, Event::SubtasksFetched(key, subtasks)),
+            Err(err) => report_failure(&event_tx, err, Retry::Subtasks),
+        }
+    });
+}
+
+/// Fetch the sub-tasks and time tracking of the issues either side of the selected one, so the
+/// detail view has them straight away when moved onto. Each issue is only fetched ahead once.
+async fn prefetch_adjacent(event_tx: EventsTx, jira: JiraClient, state: &mut State) {
+    for key in state.adjacent_issue_keys() {
+        if !state.prefetched.insert(key.clone()) {
+            continue;
+        }
+        let jira = jira.clone();
+        jira.tasks().spawn(event_tx.clone(), move |event_tx| async move {
+            let jira = jira.at(&key);
+            let (subtasks, tracking) = join(jira.subtasks(&key), jira.time_tracking(&key)).await;
+            // Failures can wait, opening the issue fetches all of this again
+            if let Ok(subtasks) = subtasks {
+                let event = Event::SubtasksFetched(key.clone(), subtasks);
+                send_event(&event_tx, event)
+            }
+            if let Ok(tracking) = tracking {
+                let event = Event::TimeTrackingFetched(key, tracking);
+                send_event(&event_tx, event)
+            }
+        });
+    }
+}
+
+/// Fetch the time tracked on the selected issue, to show in the detail view.
+async fn fetch_time_tracking(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let key = match state.selected_issue_key() {
+        Some(key) => key,
+        None => return,
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).time_tracking(&key).await {
+            Ok(tracking) => {
+                let event = Event::TimeTrackingFetched(key, tracking);
+                send_event(&event_tx, event)
+            }
+            Err(err) => report_failure(&event_tx, err, Retry::TimeTracking),
+        }
+    });
+}
+
+/// Fetch the files attached to the selected issue.
+async fn fetch_attachments(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let key = match state.selected_issue_key() {
+        Some(key) => key,
+        None => return,
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).attachments(&key).await {
+            Ok(attachments) => {
+                let event = Event::AttachmentsFetched(key, attachments);
+                send_event(&event_tx, event)
+            }
+            Err(err) => report_failure(&event_tx, err, Retry::Attachments),
+        }
+    });
+}
+
+async fn download_attachment(
     event_tx: EventsTx,
     jira: JiraClient,
-) -> Result<()> {
-    match state.input_mode {
-        InputMode::IssuesList => match input {
-            KeyCode::Char('b') => {
-                state.input_mode = InputMode::BoardsList;
-                fetch_boards(event_tx, jira.clone(), state.clone()).await;
-            }
-            KeyCode::Char('c') => {
-                state.input = state.config.default_project_key.clone();
-                state.input_mode = InputMode::EditingDefaultProject;
-            }
-            KeyCode::Char('i') => {
-                state.config.filter_in_progress = !state.config.filter_in_progress;
-                let _ = save_config(&state.config);
-                // TODO fix cloning
-                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
-            }
-            KeyCode::Char('m') => {
-                state.config.filter_mine = !state.config.filter_mine;
-                let _ = save_config(&state.config);
-                // TODO fix cloning
-                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
-            }
-            KeyCode::Char('o') => {
-                if let Some(link) = state.selected_issue_permalink() {
-                    let _ = Command::new("open").arg(link).output();
+    key: String,
+    attachment: AttachmentSummary,
+    dir: PathBuf,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).download_attachment(&attachment, &dir).await {
+            Ok(path) => send_event(&event_tx, Event::AttachmentDownloaded(path)),
+            // Jira's errors can be retried after logging in again, saving the file can't
+            Err(err) => match err.downcast::<JiraError>() {
+                Ok(err) => {
+                    let retry = Retry::Download(key, attachment, dir);
+                    report_failure(&event_tx, err, retry)
                 }
-            }
-            KeyCode::Char('q') => bail!("Just exiting early"),
-            KeyCode::Char('r') => {
-                // TODO fix cloning
-                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
-            }
-            KeyCode::Char('s') => {
-                // TODO fix cloning
-                fetch_transitions(event_tx, jira.clone(), state.clone()).await;
-                state.input_mode = InputMode::UpdateIssueStatus;
-            }
-            KeyCode::Enter => {
-                if state.issues_focused {
-                    // Focus on first branch
-                    state.branches.next();
-                    state.issues_focused = false;
-                } else if let Some(name) = state.selected_branch_name() {
-                    if name == *"Create New" {
-                        state.input_mode = InputMode::Editing;
-                    } else {
-                        let repo = get_current_repo().unwrap();
-                        match checkout_branch(&repo, name) {
-                            Ok(_) => bail!("Done!"),
-                            Err(e) => println!("Error setting branch: {:?}", e),
-                        }
-                    }
+                Err(err) => {
+                    let message = format!("Couldn't save {}: {:#}", attachment.filename, err);
+                    send_event(&event_tx, Event::DownloadFailed(message))
                 }
+            },
+        }
+    });
+}
+
+async fn upload_attachment(event_tx: EventsTx, jira: JiraClient, key: String, path: PathBuf) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).upload_attachment(&key, &path).await {
+            Ok(filename) => {
+                let event = Event::AttachmentUploaded(key, filename);
+                send_event(&event_tx, event)
             }
-            KeyCode::Right => {
-                if state.issues_focused && state.selected_issue_key().is_some() {
-                    // Focus on first branch
-                    state.branches.next();
-                    state.issues_focused = false;
+            // As with downloads, only Jira's errors are worth retrying
+            Err(err) => match err.downcast::<JiraError>() {
+                Ok(err) => report_failure(&event_tx, err, Retry::Upload(key, path)),
+                Err(err) => {
+                    let message = format!("Couldn't attach {}: {:#}", path.display(), err);
+                    send_event(&event_tx, Event::UploadFailed(message))
                 }
+            },
+        }
+    });
+}
+
+/// The names of the files in the current directory starting with `prefix`, in order.
+fn files_starting_with(prefix: &str) -> Vec<String> {
+    let entries = match fs::read_dir(".") {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+    names.sort();
+    names
+}
+
+/// Fetch the next page of older comments on the selected issue, or the latest page if none have
+/// been loaded yet.
+async fn fetch_comments(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let key = match state.selected_issue_key() {
+        Some(key) => key,
+        None => return,
+    };
+    let loaded = state.comments.len();
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).comment_page(&key, loaded).await {
+            Ok(page) => send_event(&event_tx, Event::CommentsFetched(key, page)),
+            Err(err) => report_failure(&event_tx, err, Retry::Comments),
+        }
+    });
+}
+
+async fn fetch_boards(event_tx: EventsTx, jira: JiraClient, state: State) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.current_boards(&state.config, &state.board_filter).await {
+            Ok(boards) => send_event(&event_tx, Event::BoardsUpdated(boards)),
+            Err(err) => report_failure(&event_tx, err, Retry::Boards),
+        }
+    });
+}
+
+async fn fetch_sprint(event_tx: EventsTx, jira: JiraClient, board_id: u64) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.active_sprint_issues(board_id).await {
+            Ok(sprint) => send_event(&event_tx, Event::SprintFetched(board_id, sprint)),
+            Err(err) => report_failure(&event_tx, err, Retry::Sprint(board_id)),
+        }
+    });
+}
+
+async fn fetch_backlog(event_tx: EventsTx, jira: JiraClient, board_id: u64) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.backlog_issues(board_id).await {
+            Ok(issues) => send_event(&event_tx, Event::BacklogFetched(board_id, issues)),
+            Err(err) => report_failure(&event_tx, err, Retry::Backlog(board_id)),
+        }
+    });
+}
+
+async fn fetch_open_sprints(event_tx: EventsTx, jira: JiraClient, board_id: u64) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.open_sprints(board_id).await {
+            Ok(sprints) => send_event(&event_tx, Event::SprintsFetched(board_id, sprints)),
+            Err(err) => report_failure(&event_tx, err, Retry::Sprints(board_id)),
+        }
+    });
+}
+
+async fn move_to_sprint(
+    event_tx: EventsTx,
+    jira: JiraClient,
+    key: String,
+    sprint_id: u64,
+    name: String,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).move_to_sprint(sprint_id, &key).await {
+            Ok(()) => send_event(&event_tx, Event::MovedToSprint(key, name)),
+            Err(err) => {
+                let retry = Retry::MoveToSprint(key.clone(), sprint_id, name);
+                report_refusal(&event_tx, err, key, IssueAction::Edit, retry)
             }
-            KeyCode::Left => {
-                if state.issues_focused {
-                    state.issues.unselect();
-                    state.branches.items.clear();
-                } else {
-                    state.branches.unselect();
-                    state.issues_focused = true;
-                }
+        }
+    });
+}
+
+/// Fetch whichever of the sprint or backlog the sprint view is showing.
+async fn fetch_sprint_view(event_tx: EventsTx, jira: JiraClient, state: &State) {
+    match &state.sprint {
+        Some(view) if view.backlog => fetch_backlog(event_tx, jira, view.board.id).await,
+        Some(view) => fetch_sprint(event_tx, jira, view.board.id).await,
+        None => {}
+    }
+}
+
+async fn fetch_activity(event_tx: EventsTx, jira: JiraClient) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.recent_activity().await {
+            Ok(entries) => send_event(&event_tx, Event::ActivityFetched(entries)),
+            Err(err) => report_failure(&event_tx, err, Retry::Activity),
+        }
+    });
+}
+
+async fn fetch_transitions(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let (key, workflow) = match (state.selected_issue_key(), state.workflow()) {
+        (Some(key), Some(workflow)) => (key, workflow),
+        _ => return,
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).get_transitions(key.clone()).await {
+            Ok(transitions) => {
+                let event = Event::TransitionsFetched(workflow, transitions);
+                send_event(&event_tx, event)
             }
-            KeyCode::Down => {
-                if state.issues_focused {
-                    state.issues.next();
-                    let _ = find_relevant_branches(event_tx.clone(), state.clone()).await;
-                } else {
-                    state.branches.next();
-                }
+            Err(err) => report_failure(&event_tx, err, Retry::Transitions),
+        }
+    });
+}
+
+async fn fetch_links(event_tx: EventsTx, jira: JiraClient, state: State) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        if let Some(key) = state.selected_issue_key() {
+            match jira.at(&key).get_issue_links(key.clone()).await {
+                Ok(links) => send_event(&event_tx, Event::LinksFetched(key, links)),
+                Err(err) => report_failure(&event_tx, err, Retry::Links),
             }
-            KeyCode::Up => {
-                if state.issues_focused {
-                    state.issues.previous();
-                    let _ = find_relevant_branches(event_tx.clone(), state.clone()).await;
-                } else {
-                    state.branches.previous();
-                }
+        }
+    });
+}
+
+async fn fetch_link_relations(event_tx: EventsTx, jira: JiraClient) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.link_relations().await {
+            Ok(relations) => {
+                let event = Event::LinkRelationsFetched(relations);
+                send_event(&event_tx, event)
             }
-            _ => {}
-        },
-        InputMode::BoardsList => match input {
-            KeyCode::Esc => {
-                state.input_mode = InputMode::IssuesList;
+            Err(err) => report_failure(&event_tx, err, Retry::LinkRelations),
+        }
+    });
+}
+
+async fn create_link(
+    event_tx: EventsTx,
+    jira: JiraClient,
+    key: String,
+    relation: LinkRelation,
+    target: String,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).create_link(&key, &relation, &target).await {
+            Ok(()) => {
+                let event = Event::IssueLinked(key, relation.description, target);
+                send_event(&event_tx, event)
             }
-            KeyCode::Enter => {}
-            KeyCode::Down => {
-                state.boards.next();
+            Err(err) => report_failure(&event_tx, err, Retry::CreateLink(key, relation, target)),
+        }
+    });
+}
+
+/// The branches for an issue. Branches made before the project had a prefix are listed too.
+fn issue_branches(
+    index: &BranchIndex,
+    repo: &Repository,
+    config: &Config,
+    key: &str,
+) -> Result<Vec<BranchSummary>> {
+    let prefixed = format!("{}{}", config.branch_prefix(key), key);
+    let mut prefixes = vec![key, prefixed.as_str()];
+    prefixes.dedup();
+    let branches: Result<Vec<Vec<BranchSummary>>> = prefixes
+        .iter()
+        .map(|prefix| index.matching(repo, prefix))
+        .collect();
+    branches.map(|branches| branches.concat())
+}
+
+async fn find_relevant_branches(event_tx: EventsTx, state: State) {
+    if let Some(key) = state.selected_issue_key() {
+        let index = state.branch_index.clone();
+        let repo_path = state.project_repo();
+        let config = state.config;
+        // Listing branches is blocking git work, keep it off the async workers
+        spawn_blocking(move || {
+            let mut head = None;
+            let branches = match open_repo(repo_path.as_deref()) {
+                Ok(repo) => {
+                    head = current_branch(&repo);
+                    issue_branches(&index, &repo, &config, &key)
+                }
+                // A project's repo that isn't cloned yet has no branches, only the row for a new one
+                Err(_) if repo_path.as_ref().is_some_and(|path| !path.exists()) => Ok(vec![]),
+                Err(err) => Err(err),
+            };
+            if let Ok(branches) = branches {
+                let event = Event::BranchesUpdated(key, branches, head);
+                send_event(&event_tx, event)
             }
-            KeyCode::Up => {
-                state.boards.previous();
+        });
+    };
+}
+
+/// Which listed issues have work started on them: a local branch, and an open PR linked in Jira.
+/// Only issues with a branch are asked about PRs, so a long list doesn't cost a request per row.
+async fn find_started_work(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let keys: Vec<String> = state.issues.items.iter().map(|i| i.key.clone()).collect();
+    let index = state.branch_index.clone();
+    let repo_path = state.project_repo();
+    let config = state.config;
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let branched = spawn_blocking(move || {
+            let repo = open_repo(repo_path.as_deref()).ok()?;
+            let started = |key: &String| {
+                let branches = issue_branches(&index, &repo, &config, key);
+                branches.is_ok_and(|branches| !branches.is_empty())
+            };
+            let branched: BTreeSet<String> = keys.into_iter().filter(started).collect();
+            Some(branched)
+        });
+        let branched = branched.await.ok().flatten().unwrap_or_default();
+
+        let mut pull_requests = BTreeSet::new();
+        for key in &branched {
+            // A failed lookup only leaves the marker off
+            if let Ok(true) = jira.at(key).has_open_pull_request(key).await {
+                pull_requests.insert(key.clone());
             }
-            KeyCode::Char('o') => {
-                let _ = state.open_selected_board();
+        }
+        let event = Event::StartedWorkFound(branched, pull_requests);
+        send_event(&event_tx, event);
+    });
+}
+
+async fn do_selected_transition(event_tx: EventsTx, jira: JiraClient, state: State) {
+    // The list can be refreshed from under the picker, leaving nothing to move
+    let selected = state.transitions.state.selected();
+    let transition = selected.and_then(|i| state.transitions.items.get(i));
+    let (transition_id, key) = match (transition, state.selected_issue_key()) {
+        (Some(transition), Some(key)) => (transition.key.clone(), key),
+        _ => return,
+    };
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let jira = jira.at(&key);
+        match jira.do_transition(key.clone(), transition_id).await {
+            Ok(_) => send_event(&event_tx, Event::TransitionExecuted),
+            Err(err) => {
+                let retry = Retry::Transition;
+                report_refusal(&event_tx, err, key, IssueAction::Transition, retry)
             }
-            _ => {}
-        },
-        InputMode::UpdateIssueStatus => match input {
-            KeyCode::Esc => {
-                state.input_mode = InputMode::IssuesList;
+        }
+    });
+}
+
+/// Make the transition with the given name, looked up among the issue's transitions as it is now.
+async fn transition_to(event_tx: EventsTx, jira: JiraClient, key: String, name: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).transition_to(&key, &name).await {
+            Ok(Some(made)) => send_event(&event_tx, Event::IssueTransitioned(key, made)),
+            Ok(None) => send_event(&event_tx, Event::TransitionUnavailable(key, name)),
+            Err(err) => {
+                let retry = Retry::TransitionTo(key.clone(), name);
+                report_refusal(&event_tx, err, key, IssueAction::Transition, retry)
             }
-            KeyCode::Down => {
-                state.transitions.next();
+        }
+    });
+}
+
+async fn fetch_description(event_tx: EventsTx, jira: JiraClient, key: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).get_text_field(&key, "description").await {
+            Ok((description, updated)) => {
+                let event = Event::DescriptionFetched(key, description, updated);
+                send_event(&event_tx, event)
             }
-            KeyCode::Up => {
-                state.transitions.previous();
+            Err(err) => report_failure(&event_tx, err, Retry::Description(key)),
+        }
+    });
+}
+
+async fn save_description(event_tx: EventsTx, jira: JiraClient, edit: DescriptionEdit) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let field = TextField {
+            field: "description".to_string(),
+            text: edit.editor.text(),
+        };
+        let jira = jira.at(&edit.key);
+        match jira.save_text_field(&edit.key, &edit.updated, &field).await {
+            Ok(updated) => {
+                let event = Event::DescriptionSaved(edit.key, field.text, updated);
+                send_event(&event_tx, event)
             }
-            KeyCode::Enter => {
-                do_selected_transition(event_tx, jira.clone(), state.clone()).await;
+            Err(err) => {
+                let retry = Retry::SaveDescription;
+                report_refusal(&event_tx, err, edit.key, IssueAction::Edit, retry)
             }
-            _ => {}
         }
-        InputMode::Editing => match input {
-            KeyCode::Enter =>  {
-                if let Ok(repo) = get_current_repo() {
-                    match create_and_use_branch(&repo, state.new_branch_name()) {
-                        Ok(_) => bail!("Done!"),
-                        Err(e) => println!("Error setting branch: {:?}", e),
-                    }
-                }
-            },
-            KeyCode::Char(c) => {
-                state.input.push(c);
-            }
-            KeyCode::Backspace => {
-                state.input.pop();
+    });
+}
+
+async fn save_summary(
+    event_tx: EventsTx,
+    jira: JiraClient,
+    key: String,
+    updated: String,
+    previous: String,
+    summary: String,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let field = TextField {
+            field: "summary".to_string(),
+            text: summary,
+        };
+        match jira.at(&key).save_text_field(&key, &updated, &field).await {
+            Ok(updated) => send_event(&event_tx, Event::SummarySaved(key, updated)),
+            Err(err) => send_event(&event_tx, Event::SummarySaveFailed(key, previous, err)),
+        }
+    });
+}
+
+async fn set_watching(event_tx: EventsTx, jira: JiraClient, key: String, watching: bool) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let event = match jira.at(&key).set_watching(&key, watching).await {
+            Ok(()) => Event::WatchingSet(key, watching),
+            Err(err) => Event::WatchFailed(key, watching, err),
+        };
+        send_event(&event_tx, event)
+    });
+}
+
+async fn link_pull_request(event_tx: EventsTx, jira: JiraClient, key: String, url: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).link_pull_request(&key, &url).await {
+            Ok(()) => send_event(&event_tx, Event::PullRequestLinked(key)),
+            Err(err) => report_failure(&event_tx, err, Retry::LinkPullRequest(key, url)),
+        }
+    });
+}
+
+async fn create_subtask(event_tx: EventsTx, jira: JiraClient, parent: String, summary: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&parent).create_subtask(&parent, &summary).await {
+            Ok(created) => send_event(&event_tx, Event::SubtaskCreated(parent, created)),
+            Err(err) => report_failure(&event_tx, err, Retry::CreateSubtask(parent, summary)),
+        }
+    });
+}
+
+async fn add_comment(event_tx: EventsTx, jira: JiraClient, key: String, text: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).add_comment(&key, &text).await {
+            Ok(()) => send_event(&event_tx, Event::CommentAdded(key)),
+            Err(err) => {
+                let retry = Retry::AddComment(key.clone(), text);
+                report_refusal(&event_tx, err, key, IssueAction::Comment, retry)
             }
-            KeyCode::Esc => {
-                state.input_mode = InputMode::IssuesList;
+        }
+    });
+}
+
+async fn fetch_account(event_tx: EventsTx, jira: JiraClient) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.account_info().await {
+            Ok(account) => send_event(&event_tx, Event::AccountFetched(account)),
+            Err(err) => report_failure(&event_tx, err, Retry::Account),
+        }
+    });
+}
+
+/// Compare the local branches with Jira for the audit screen.
+async fn run_audit(event_tx: EventsTx, jira: JiraClient) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let branches = match local_branches() {
+            Ok(branches) => branches,
+            Err(err) => {
+                let message = format!("Couldn't list branches: {:#}", err);
+                send_event(&event_tx, Event::AuditFailed(message));
+                return;
             }
-            _ => {}
-        },
-        InputMode::EditingDefaultProject => match input {
-            KeyCode::Enter => {
-                state.config.default_project_key = state.input.to_string();
-                match save_config(&state.config) {
-                    Ok(_) => {
-                        state.input_mode = InputMode::IssuesList;
-                        fetch_tickets(event_tx, jira.clone(), state.clone()).await;
-                    }
-                    Err(e) => {
-                        state.input = e.to_string();
-                    }
-                }
+        };
+        match audit(&jira, branches).await {
+            Ok(audit) => send_event(&event_tx, Event::AuditFinished(audit)),
+            Err(err) => report_failure(&event_tx, err, Retry::Audit),
+        }
+    });
+}
+
+/// Search for people on the host of the issue with the given key.
+async fn search_users(event_tx: EventsTx, jira: JiraClient, key: String, query: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).search_users(&query).await {
+            Ok(users) => send_event(&event_tx, Event::UsersFound(query, users)),
+            Err(err) => report_failure(&event_tx, err, Retry::Users(key, query)),
+        }
+    });
+}
+
+async fn assign_issue(event_tx: EventsTx, jira: JiraClient, key: String, assignee: Assignee) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let jira = jira.at(&key);
+        let assigned: JiraResult<Option<UserSummary>> = async {
+            let user = match &assignee {
+                Assignee::Me => Some(jira.myself().await?),
+                Assignee::Unassigned => None,
+                Assignee::User(user) => Some(user.clone()),
+            };
+            let id = user.as_ref().map(|user| user.id.as_str());
+            jira.assign_issue(&key, id).await?;
+            Ok(user)
+        }
+        .await;
+        match assigned {
+            Ok(user) => {
+                let name = user.map_or_else(|| "Unassigned".to_string(), |user| user.display_name);
+                send_event(&event_tx, Event::IssueAssigned(key, name));
             }
-            KeyCode::Char(c) => {
-                state.input.push(c);
+            Err(err) => {
+                let retry = Retry::Assign(key.clone(), assignee);
+                report_refusal(&event_tx, err, key, IssueAction::Assign, retry)
             }
-            KeyCode::Backspace => {
-                state.input.pop();
+        }
+    });
+}
+
+/// Fetch the priorities the issue with the given key can be given, from its host.
+async fn fetch_priorities(event_tx: EventsTx, jira: JiraClient, key: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).get_priorities().await {
+            Ok(priorities) => send_event(&event_tx, Event::PrioritiesFetched(priorities)),
+            Err(err) => report_failure(&event_tx, err, Retry::Priorities(key)),
+        }
+    });
+}
+
+async fn set_priority(event_tx: EventsTx, jira: JiraClient, key: String, name: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).set_priority(&key, &name).await {
+            Ok(()) => send_event(&event_tx, Event::PrioritySet(key, name)),
+            Err(err) => {
+                let retry = Retry::SetPriority(key.clone(), name);
+                report_refusal(&event_tx, err, key, IssueAction::Edit, retry)
             }
-            KeyCode::Esc => {
-                state.input_mode = InputMode::IssuesList;
+        }
+    });
+}
+
+async fn fetch_labels(event_tx: EventsTx, jira: JiraClient, key: String) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).get_labels(&key).await {
+            Ok(labels) => send_event(&event_tx, Event::LabelsFetched(key, labels)),
+            Err(err) => report_failure(&event_tx, err, Retry::Labels(key)),
+        }
+    });
+}
+
+/// Add or remove one of an issue's labels.
+async fn edit_labels(event_tx: EventsTx, jira: JiraClient, key: String, edit: BulkEdit) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        match jira.at(&key).edit_issue(&key, &edit).await {
+            Ok(()) => send_event(&event_tx, Event::LabelsEdited(key, edit)),
+            Err(err) => {
+                let retry = Retry::EditLabels(key.clone(), edit);
+                report_refusal(&event_tx, err, key, IssueAction::Edit, retry)
             }
-            _ => {}
-        },
+        }
+    });
+}
+
+/// Look through the history of the issue's repo for people to review its branch. Without a repo
+/// or anything changed on the branch, there's no one to suggest.
+async fn suggest_reviewers(event_tx: EventsTx, key: String, repo_path: Option<PathBuf>) {
+    spawn_blocking(move || {
+        let repo = open_repo(repo_path.as_deref());
+        let reviewers = repo.and_then(|repo| recent_committers(&repo));
+        let reviewers = reviewers.unwrap_or_default();
+        send_event(&event_tx, Event::ReviewersSuggested(key, reviewers));
+    });
+}
+
+/// Open a PR on GitHub from the branch checked out in the selected issue's repo, described the
+/// same way as a copied description, then link it to the issue.
+async fn open_pull_request(event_tx: EventsTx, jira: JiraClient, state: State) {
+    let issue = match state.selected_issue() {
+        Some(issue) => issue.clone(),
+        None => return,
+    };
+    let picked = state.reviewer_picker.reviewers();
+    let body = pr_description(&issue, &picked, state.config.pr_template.as_deref());
+    let draft = state.reviewer_picker.draft;
+    let repo_path = state.project_repo();
+    let config = state.config;
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let opened = pull_request_for(&jira, &config, &issue, body, &picked, draft, repo_path);
+        let event = match opened.await {
+            Ok((url, unreviewed)) => Event::PullRequestOpened(issue.key, url, unreviewed),
+            Err(err) => Event::PullRequestFailed(format!("{:#}", err)),
+        };
+        send_event(&event_tx, event);
+    });
+}
+
+/// Work out where the PR for an issue goes and who reviews it, then open it. Returns its URL,
+/// `None` when a dry run held it back, and why nobody was asked to review it if that's the case.
+async fn pull_request_for(
+    jira: &JiraClient,
+    config: &Config,
+    issue: &IssueSummary,
+    body: String,
+    picked: &[String],
+    draft: bool,
+    repo_path: Option<PathBuf>,
+) -> Result<(Option<String>, Option<String>)> {
+    let key = &issue.key;
+    let (owner, repo, head) = spawn_blocking(move || -> Result<(String, String, String)> {
+        let repo = open_repo(repo_path.as_deref())?;
+        let origin = repo.find_remote("origin")?;
+        let (owner, name) = match origin.url().and_then(github_repo) {
+            Some(found) => found,
+            None => bail!("The repo's origin isn't on GitHub"),
+        };
+        match current_branch(&repo) {
+            Some(head) => Ok((owner, name, head)),
+            None => bail!("No branch is checked out to open a PR from"),
+        }
+    })
+    .await??;
+    if !head.to_lowercase().contains(&key.to_lowercase()) {
+        bail!("Check out a branch for {} before opening its PR", key);
     }
 
-    Ok(())
+    let (reviewers, unreviewed) = pull_request_reviewers(jira, config, key, picked).await;
+    let api = config.github_api_url.as_deref().unwrap_or(GITHUB_API);
+    if jira.hold_back(format!("POST {}/repos/{}/{}/pulls", api, owner, repo)) {
+        return Ok((None, unreviewed));
+    }
+    let pr = PullRequest {
+        owner,
+        repo,
+        head,
+        title: format!("{}: {}", key, issue.summary),
+        body,
+        draft,
+        reviewers,
+    };
+    let url = GitHub::new(api, load_github_token(config)?)
+        .open_pull_request(&pr)
+        .await?;
+    jira.at(key)
+        .link_pull_request(key, &url)
+        .await
+        .with_context(|| format!("Opened {} but couldn't link it to {}", url, key))?;
+    Ok((Some(url), unreviewed))
+}
+
+/// The GitHub logins to ask for a review: whoever was picked along with the default reviewers,
+/// or the issue's watchers when that's nobody. The PR still opens when the watchers can't be
+/// read, with the reason nobody was asked.
+async fn pull_request_reviewers(
+    jira: &JiraClient,
+    config: &Config,
+    key: &str,
+    picked: &[String],
+) -> (Vec<String>, Option<String>) {
+    let reviewers = github_reviewers(config, picked);
+    if !reviewers.is_empty() {
+        return (reviewers, None);
+    }
+    match jira.at(key).watchers(key).await {
+        Ok(watchers) => (github_reviewers(config, &watchers), None),
+        Err(err) => {
+            let reason = format!("couldn't find who watches {}: {}", key, err);
+            (reviewers, Some(reason))
+        }
+    }
+}
+
+/// The GitHub logins of the people named, along with the `default_reviewers`. People without a
+/// login in `github_logins` are left out.
+fn github_reviewers(config: &Config, names: &[String]) -> Vec<String> {
+    let mut logins: Vec<String> = names
+        .iter()
+        .filter_map(|name| config.github_logins.get(name))
+        .chain(&config.default_reviewers)
+        .cloned()
+        .collect();
+    logins.sort();
+    logins.dedup();
+    logins
+}
+
+/// Clone the repo for an issue's project, which is blocking git work like listing branches.
+async fn clone_project_repo(event_tx: EventsTx, offer: CloneOffer) {
+    spawn_blocking(move || {
+        let event = match clone_repo(&offer.url, &offer.path) {
+            Ok(_) => Event::RepoCloned(offer.key, offer.path),
+            Err(err) => Event::CloneFailed(format!("{:#}", err)),
+        };
+        send_event(&event_tx, event);
+    });
+}
+
+async fn add_worklog(
+    event_tx: EventsTx,
+    jira: JiraClient,
+    key: String,
+    seconds: u64,
+    comment: Option<String>,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let jira = jira.at(&key);
+        match jira.add_worklog(&key, seconds, comment.clone()).await {
+            Ok(()) => send_event(&event_tx, Event::WorklogAdded(key, seconds)),
+            Err(err) => {
+                let retry = Retry::Worklog(key.clone(), seconds, comment);
+                report_refusal(&event_tx, err, key, IssueAction::LogWork, retry)
+            }
+        }
+    });
+}
+
+async fn save_text_field(event_tx: EventsTx, jira: JiraClient, key: String, field: TextField) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        if let Err(err) = jira.at(&key).update_text_field(&key, &field).await {
+            send_event(&event_tx, Event::JiraFailed(err))
+        }
+    });
+}
+
+/// Apply an edit to each (key, last updated) pair in turn, reporting progress as it goes. Once
+/// `cancel` is set the issues not yet started are skipped.
+async fn run_bulk_edit(
+    event_tx: EventsTx,
+    jira: JiraClient,
+    issues: Vec<(String, String)>,
+    edit: BulkEdit,
+    cancel: Arc<AtomicBool>,
+) {
+    jira.tasks().spawn(event_tx, move |event_tx| async move {
+        let running = Arc::new(AtomicBool::new(true));
+        spawn_animator(event_tx.clone(), running.clone());
+        for (key, updated) in issues {
+            let status = if cancel.load(Ordering::Relaxed) {
+                ItemStatus::Skipped
+            } else {
+                match jira.at(&key).apply_bulk_edit(&key, &updated, &edit).await {
+                    Ok(()) => ItemStatus::Done,
+                    Err(err) => ItemStatus::Failed(err.to_string()),
+                }
+            };
+            send_event(&event_tx, Event::BulkEditStep(key, status))
+        }
+        running.store(false, Ordering::Relaxed);
+    });
+}
+
+#[derive(Clone)]
+pub enum InputMode {
+    IssuesList,
+    BoardsList,
+    Editing,
+    UpdateIssueStatus,
+    IssueLinks,
+    IssueDetail,
+    BulkEditAction,
+    BulkEditValue,
+    BulkEditProgress,
+    EditingDescription,
+    ConfirmDescription,
+    EditingSummary,
+    SortMenu,
+    SnoozeMenu,
+    SnoozedList,
+    ActivityFeed,
+    EditingDefaultProject,
+    /// Picking one of the recently used projects.
+    SwitchingProject,
+    SmartCommit,
+    /// Typing the time spent on the selected issue and a comment for it.
+    LoggingWork,
+    SearchingDetail,
+    /// Typing to narrow down the boards list.
+    FilteringBoards,
+    /// Choosing who to name as reviewers in a PR description.
+    PickingReviewers,
+    /// Typing the URL of a PR to link to the selected issue.
+    LinkingPullRequest,
+    /// The selected issue's comments, on their own.
+    CommentsView,
+    /// Writing a comment on the selected issue.
+    AddingComment,
+    /// Picking who to assign the selected issue to, typing to search for people.
+    AssigningIssue,
+    /// Who's logged in to which Jira, for sorting out credentials.
+    AccountInfo,
+    /// Picking a priority for the selected issue.
+    SettingPriority,
+    /// Adding and removing the selected issue's labels.
+    EditingLabels,
+    /// Typing the summary of a new sub-task under the selected issue.
+    CreatingSubtask,
+    /// The issues in a board's active sprint or its backlog.
+    SprintView,
+    /// Picking a sprint to move an issue into.
+    PickingSprint,
+    /// Picking one of the saved views of the issue list.
+    PickingView,
+    /// Typing a name to save the issue list's current setup under.
+    NamingView,
+    /// The files attached to the selected issue, to download.
+    AttachmentsView,
+    /// Typing the path of a file to attach to the selected issue, or picking one from the
+    /// current directory.
+    UploadingAttachment,
+    /// Local branches compared with Jira: those for finished or missing issues, and issues in
+    /// progress without one.
+    AuditView,
+    /// Picking how the selected issue is to be linked to another, e.g. "is blocked by".
+    PickingLinkType,
+    /// Typing the key of the issue to link to, or picking one from the list.
+    LinkingIssue,
+    /// Typing a JQL query to search the issue list with instead of the filters.
+    EditingJql,
+    /// Asking before making a transition listed in `Config::confirm_transitions`, over the
+    /// transition picker.
+    ConfirmTransition,
+}
+
+/// Transitions fetched for an issue, reused for others in the same project and status until
+/// they're `TRANSITIONS_TTL_SECS` old.
+#[derive(Clone)]
+struct CachedTransitions {
+    transitions: Vec<TransitionSummary>,
+    fetched: Instant,
+}
+
+/// Text being looked for in the detail view. Matches are found as the view is drawn, `current`
+/// counts n/N presses and wraps around however many there turn out to be.
+#[derive(Clone, Default)]
+pub struct DetailSearch {
+    pub query: String,
+    pub current: isize,
+    /// Scroll to the current match, until the view is scrolled by hand.
+    pub jump: bool,
+}
+
+/// A smart commit message being put together for the selected issue.
+#[derive(Clone)]
+pub struct SmartCommitForm {
+    /// The row being edited, see `SMART_COMMIT_ROWS`.
+    pub row: usize,
+    pub time: String,
+    pub comment: String,
+    /// Index into `State::transitions`, `None` leaves the status alone.
+    pub transition: Option<usize>,
+}
+
+pub const SMART_COMMIT_ROWS: [&str; 3] = ["Time spent", "Comment", "Transition"];
+
+impl SmartCommitForm {
+    fn new() -> SmartCommitForm {
+        SmartCommitForm {
+            row: 0,
+            time: String::new(),
+            comment: String::new(),
+            transition: None,
+        }
+    }
 }
+
+/// Time being logged on an issue by hand.
+#[derive(Clone)]
+pub struct WorklogForm {
+    pub key: String,
+    /// The row being edited, see `WORKLOG_ROWS`.
+    pub row: usize,
+    /// Time spent as typed, e.g. "1h 30m".
+    pub time: String,
+    pub comment: String,
+}
+
+pub const WORKLOG_ROWS: [&str; 2] = ["Time spent", "Comment"];
+
+impl WorklogForm {
+    fn new(key: String) -> WorklogForm {
+        WorklogForm {
+            key,
+            row: 0,
+            time: String::new(),
+            comment: String::new(),
+        }
+    }
+
+    /// The row being typed into.
+    fn field(&mut self) -> &mut String {
+        match self.row {
+            0 => &mut self.time,
+            _ => &mut self.comment,
+        }
+    }
+}
+
+/// A board's active sprint or its backlog, listed on its own.
+#[derive(Clone)]
+pub struct SprintView {
+    pub board: SavedBoard,
+    /// Listing the backlog, the issues planned into no sprint yet, rather than the active sprint.
+    pub backlog: bool,
+    /// `None` while it's fetched as well as when no sprint is running, `loaded` tells them apart.
+    pub sprint: Option<SprintSummary>,
+    pub loaded: bool,
+    pub issues: StatefulList<IssueSummary>,
+}
+
+impl SprintView {
+    fn new(board: SavedBoard, backlog: bool) -> SprintView {
+        SprintView {
+            board,
+            backlog,
+            sprint: None,
+            loaded: false,
+            issues: StatefulList::new(),
+        }
+    }
+
+    fn show(&mut self, sprint: Option<(SprintSummary, Vec<IssueSummary>)>) {
+        let (sprint, issues) = match sprint {
+            Some((sprint, issues)) => (Some(sprint), issues),
+            None => (None, vec![]),
+        };
+        self.sprint = sprint;
+        self.list(issues);
+    }
+
+    fn list(&mut self, issues: Vec<IssueSummary>) {
+        self.loaded = true;
+        self.issues = StatefulList::with_items(issues);
+        self.issues.next();
+    }
+
+    /// Switch between the sprint and the backlog, empty until the other one is fetched.
+    fn toggle_backlog(&mut self) {
+        self.backlog = !self.backlog;
+        self.loaded = false;
+        self.sprint = None;
+        self.issues = StatefulList::new();
+    }
+
+    fn selected_issue(&self) -> Option<&IssueSummary> {
+        let selected = self.issues.state.selected()?;
+        self.issues.items.get(selected)
+    }
+}
+
+/// An issue's links, one of which is picked to go to the issue on its other end.
+#[derive(Clone)]
+pub struct LinksView {
+    /// The issue the links belong to.
+    pub key: String,
+    pub links: StatefulList<IssueLinkSummary>,
+}
+
+impl LinksView {
+    fn new(key: String, links: Vec<IssueLinkSummary>) -> LinksView {
+        let mut view = LinksView {
+            key,
+            links: StatefulList::new(),
+        };
+        view.list(links);
+        view
+    }
+
+    /// Show freshly fetched links, staying on the same row while there still is one.
+    fn list(&mut self, links: Vec<IssueLinkSummary>) {
+        let selected = self.links.state.selected().filter(|&i| i < links.len());
+        self.links.set_items(links);
+        self.links.state.select(selected);
+        if selected.is_none() {
+            self.links.next();
+        }
+    }
+
+    fn selected_link(&self) -> Option<&IssueLinkSummary> {
+        let selected = self.links.state.selected()?;
+        self.links.items.get(selected)
+    }
+}
+
+/// A link being made from one issue to another, picking how they're linked and then the issue
+/// on the other end.
+#[derive(Clone)]
+pub struct LinkForm {
+    /// The issue the link is made from.
+    pub key: String,
+    pub relations: StatefulList<LinkRelation>,
+    /// Issues in the list matching what's been typed, to pick the other end from.
+    pub targets: StatefulList<IssueSummary>,
+}
+
+impl LinkForm {
+    fn selected_relation(&self) -> Option<&LinkRelation> {
+        let selected = self.relations.state.selected()?;
+        self.relations.items.get(selected)
+    }
+
+    fn selected_target(&self) -> Option<&IssueSummary> {
+        let selected = self.targets.state.selected()?;
+        self.targets.items.get(selected)
+    }
+}
+
+/// A board's running and planned sprints, one of which an issue is being moved into.
+#[derive(Clone)]
+pub struct SprintPicker {
+    /// The issue being moved.
+    pub key: String,
+    pub board_id: u64,
+    pub sprints: StatefulList<SprintSummary>,
+}
+
+/// Reviewers suggested for the selected issue's PR, picked before its description is copied or
+/// the PR opened.
+#[derive(Clone)]
+pub struct ReviewerPicker {
+    pub suggested: StatefulList<String>,
+    pub picked: BTreeSet<String>,
+    /// Open the PR as a draft.
+    pub draft: bool,
+}
+
+impl ReviewerPicker {
+    fn new(draft: bool) -> ReviewerPicker {
+        ReviewerPicker {
+            suggested: StatefulList::new(),
+            picked: BTreeSet::new(),
+            draft,
+        }
+    }
+
+    /// Pick the selected reviewer, or unpick them if they already were.
+    fn toggle(&mut self) {
+        let suggested = &self.suggested;
+        let selected = suggested.state.selected();
+        if let Some(name) = selected.and_then(|i| suggested.items.get(i)) {
+            if !self.picked.remove(name) {
+                self.picked.insert(name.clone());
+            }
+        }
+    }
+
+    /// The reviewers picked, in the order they were suggested.
+    fn reviewers(&self) -> Vec<String> {
+        let suggested = self.suggested.items.iter();
+        suggested.filter(|name| self.picked.contains(*name)).cloned().collect()
+    }
+}
+
+/// A description being edited, along with what it was when editing started.
+#[derive(Clone)]
+pub struct DescriptionEdit {
+    pub key: String,
+    pub original: String,
+    /// When the issue was last updated as of fetching `original`.
+    pub updated: String,
+    pub editor: TextEditor,
+}
+
+/// Things done to an issue that Jira may not let the user do.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueAction {
+    Transition,
+    Edit,
+    Comment,
+    Assign,
+    LogWork,
+}
+
+impl IssueAction {
+    const ALL: [IssueAction; 5] = [
+        IssueAction::Transition,
+        IssueAction::Edit,
+        IssueAction::Comment,
+        IssueAction::Assign,
+        IssueAction::LogWork,
+    ];
+
+    pub fn describe(self) -> &'static str {
+        match self {
+            IssueAction::Transition => "move",
+            IssueAction::Edit => "edit",
+            IssueAction::Comment => "comment on",
+            IssueAction::Assign => "assign",
+            IssueAction::LogWork => "log work on",
+        }
+    }
+
+    /// The project permission Jira asks for before letting the action through.
+    fn permission(self) -> &'static str {
+        match self {
+            IssueAction::Transition => "TRANSITION_ISSUES",
+            IssueAction::Edit => "EDIT_ISSUES",
+            IssueAction::Comment => "ADD_COMMENTS",
+            IssueAction::Assign => "ASSIGN_ISSUES",
+            IssueAction::LogWork => "WORK_ON_ISSUES",
+        }
+    }
+}
+
+/// Pickers and editors opened on the selected issue, checked against what Jira lets the user do
+/// to it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Picker {
+    Transitions,
+    Assignee,
+    Summary,
+    Description,
+    Priority,
+    Labels,
+}
+
+impl Picker {
+    fn action(self) -> IssueAction {
+        match self {
+            Picker::Transitions => IssueAction::Transition,
+            Picker::Assignee => IssueAction::Assign,
+            Picker::Summary | Picker::Description | Picker::Priority | Picker::Labels => {
+                IssueAction::Edit
+            }
+        }
+    }
+
+    /// The field the picker changes, which has to be on the issue's edit screen.
+    fn field(self) -> Option<&'static str> {
+        match self {
+            Picker::Summary => Some("summary"),
+            Picker::Description => Some("description"),
+            Picker::Priority => Some("priority"),
+            Picker::Labels => Some("labels"),
+            Picker::Transitions | Picker::Assignee => None,
+        }
+    }
+
+    fn is_open(self, mode: &InputMode) -> bool {
+        matches!(
+            (self, mode),
+            (Picker::Transitions, InputMode::UpdateIssueStatus)
+                | (Picker::Transitions, InputMode::ConfirmTransition)
+                | (Picker::Assignee, InputMode::AssigningIssue)
+                | (Picker::Summary, InputMode::EditingSummary)
+                | (Picker::Description, InputMode::EditingDescription)
+                | (Picker::Priority, InputMode::SettingPriority)
+                | (Picker::Labels, InputMode::EditingLabels)
+        )
+    }
+}
+
+/// Who to assign an issue to, in the order the assignee picker lists them.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Assignee {
+    Me,
+    Unassigned,
+    /// Someone found by searching.
+    User(UserSummary),
+}
+
+/// A comment being written on an issue.
+#[derive(Clone)]
+pub struct CommentDraft {
+    pub key: String,
+    pub editor: TextEditor,
+}
+
+/// How far an issue in a bulk edit has got.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ItemStatus {
+    Pending,
+    Done,
+    /// Why it couldn't be updated.
+    Failed(String),
+    /// The edit was cancelled before getting to it.
+    Skipped,
+}
+
+#[derive(Clone, Default)]
+pub struct BulkProgress {
+    /// Issue keys in the order they're updated, with how each went.
+    pub items: Vec<(String, ItemStatus)>,
+    /// Set by pressing Esc, to stop once the issue being updated is done.
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl BulkProgress {
+    pub fn new(keys: impl IntoIterator<Item = String>) -> BulkProgress {
+        BulkProgress {
+            items: keys.into_iter().map(|key| (key, ItemStatus::Pending)).collect(),
+            ..Default::default()
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn done(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|(_, status)| *status != ItemStatus::Pending)
+            .count()
+    }
+
+    pub fn finished(&self) -> bool {
+        self.done() >= self.total()
+    }
+
+    pub fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// The issue being updated, issues are done one at a time in order.
+    pub fn current(&self) -> Option<&str> {
+        self.items
+            .iter()
+            .find(|(_, status)| *status == ItemStatus::Pending)
+            .map(|(key, _)| key.as_str())
+    }
+
+    /// How many issues were updated, failed or skipped, e.g. "3 updated, 1 failed, 2 skipped".
+    pub fn summary(&self) -> String {
+        let count = |wanted: fn(&ItemStatus) -> bool| {
+            self.items.iter().filter(|(_, status)| wanted(status)).count()
+        };
+        format!(
+            "{} updated, {} failed, {} skipped",
+            count(|status| *status == ItemStatus::Done),
+            count(|status| matches!(status, ItemStatus::Failed(_))),
+            count(|status| *status == ItemStatus::Skipped)
+        )
+    }
+
+    fn record(&mut self, key: &str, status: ItemStatus) {
+        if let Some(item) = self.items.iter_mut().find(|(k, _)| k == key) {
+            item.1 = status;
+        }
+    }
+}
+
+/// A filter the issue list is searched with, shown as a chip above it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FilterChip {
+    Board,
+    Project,
+    Mine,
+    /// There unless searching with JQL, the search is either for issues in progress or
+    /// prioritised ones.
+    Status,
+    /// A JQL query typed in, in place of the project, mine and status filters.
+    Jql,
+}
+
+impl FilterChip {
+    /// The chips for the filters in use, in the order they're shown.
+    pub fn active(config: &Config) -> Vec<FilterChip> {
+        let mut chips = vec![];
+        if config.default_board.is_some() {
+            chips.push(FilterChip::Board);
+        }
+        if config.jql.is_some() {
+            chips.push(FilterChip::Jql);
+            return chips;
+        }
+        if config.project_scope().is_some() {
+            chips.push(FilterChip::Project);
+        }
+        if config.filter_mine {
+            chips.push(FilterChip::Mine);
+        }
+        chips.push(FilterChip::Status);
+        chips
+    }
+
+    pub fn describe(self, config: &Config) -> String {
+        match self {
+            FilterChip::Board => {
+                let board = config.default_board.as_ref();
+                format!("Board: {}", board.map_or("", |board| board.name.as_str()))
+            }
+            FilterChip::Project => format!("Project: {}", config.default_project_key),
+            FilterChip::Mine => "Owned by me".to_string(),
+            FilterChip::Status if config.filter_in_progress => "In Progress".to_string(),
+            FilterChip::Status => "Prioritised".to_string(),
+            FilterChip::Jql => format!("JQL: {}", config.jql.as_deref().unwrap_or("")),
+        }
+    }
+
+    /// Stop filtering by the chip. The status can't be dropped, so it's switched over instead.
+    fn remove(self, config: &mut Config) {
+        match self {
+            FilterChip::Board => config.default_board = None,
+            FilterChip::Project => config.search_all_projects = true,
+            FilterChip::Mine => config.filter_mine = false,
+            FilterChip::Status => config.filter_in_progress = !config.filter_in_progress,
+            FilterChip::Jql => config.jql = None,
+        }
+    }
+}
+
+/// A row of the issue list: an issue, by its index, or the header above an epic's issues when
+/// the list is grouped by epic.
+#[derive(Clone, Debug, PartialEq)]
+pub enum IssueRow {
+    Epic {
+        /// `None` for the issues without an epic.
+        key: Option<String>,
+        count: usize,
+        folded: bool,
+    },
+    Issue(usize),
+}
+
+/// How far a search spanning several pages has got.
+#[derive(Clone)]
+pub struct SearchProgress {
+    pub loaded: usize,
+    pub total: usize,
+    /// Set by pressing Esc, to stop after the page being fetched and keep what's been loaded.
+    pub cancel: Arc<AtomicBool>,
+}
+
+impl SearchProgress {
+    pub fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+}
+
+/// A row of the branches pane: one of the issue's branches, or the action for making a new one.
+#[derive(Clone)]
+pub enum BranchEntry {
+    Existing(BranchSummary),
+    CreateNew,
+}
+
+impl BranchEntry {
+    /// The branch, unless this is the row for making a new one.
+    pub fn existing(&self) -> Option<&BranchSummary> {
+        match self {
+            BranchEntry::Existing(branch) => Some(branch),
+            BranchEntry::CreateNew => None,
+        }
+    }
+}
+
+/// A project's repo that isn't there yet, offered to be cloned before making a branch.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CloneOffer {
+    /// The issue the branch is for.
+    pub key: String,
+    pub url: String,
+    pub path: PathBuf,
+}
+
+/// A branch checked out on the way out of the app.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckedOut {
+    pub key: Option<String>,
+    pub branch: String,
+    /// Whether the branch was created rather than already existing.
+    pub created: bool,
+    /// The repo the branch is in, when the issue's project has one in `project_repos`.
+    pub repo: Option<PathBuf>,
+    /// Where to work on the issue, when its project has a directory in `project_dirs`.
+    pub dir: Option<PathBuf>,
+    /// The commit checked out before, for the post-checkout hook.
+    pub previous: Option<Oid>,
+}
+
+impl CheckedOut {
+    /// A line of `name=value` pairs for scripts to read, e.g.
+    /// `key=PROJ-1 branch=PROJ-1-fix-crash created=true`, with `repo=...` and `dir=...` at the end
+    /// when the project has its own repo or a directory to work in.
+    pub fn describe(&self) -> String {
+        let mut line = format!(
+            "key={} branch={} created={}",
+            self.key.as_deref().unwrap_or(""),
+            self.branch,
+            self.created
+        );
+        if let Some(repo) = &self.repo {
+            line.push_str(&format!(" repo={}", repo.display()));
+        }
+        if let Some(dir) = &self.dir {
+            line.push_str(&format!(" dir={}", dir.display()));
+        }
+        line
+    }
+}
+
+#[derive(Clone)]
+pub struct State {
+    pub issues: StatefulList<IssueSummary>,
+    /// The first row of the issue list on screen. Kept by the UI, which knows how many rows fit,
+    /// and only moved when the selection would go off screen.
+    pub issues_offset: usize,
+    /// Boards matching `board_filter`, out of `all_boards`.
+    pub boards: StatefulList<BoardSummary>,
+    all_boards: Vec<BoardSummary>,
+    /// Matched against board names and project keys, ignoring case.
+    pub board_filter: String,
+    /// Whether Jira was asked for only the boards matching the filter, so clearing it means
+    /// fetching them all again.
+    boards_filtered_by_jira: bool,
+    pub branches: StatefulList<BranchEntry>,
+    /// The branch checked out in the repo the branches were last listed in.
+    pub head_branch: Option<String>,
+    branch_index: BranchIndex,
+    /// Keys of listed issues with a local branch, and of those with an open PR linked.
+    pub branched: BTreeSet<String>,
+    pub pull_requests: BTreeSet<String>,
+    pub transitions: StatefulList<TransitionSummary>,
+    /// Links of issues shown in the detail view or the links view, by their key.
+    pub links: BTreeMap<String, Vec<IssueLinkSummary>>,
+    /// The links of an issue listed to jump to one, while the links view is open.
+    pub links_view: Option<LinksView>,
+    /// The kinds of links issues can have, fetched the first time an issue is linked.
+    pub link_relations: Vec<LinkRelation>,
+    /// A link being made, while picking how and what to link the issue to.
+    pub link_form: Option<LinkForm>,
+    /// Keys of issues marked for bulk editing.
+    pub marked: BTreeSet<String>,
+    /// Issues whose details were fetched ahead of being moved onto.
+    prefetched: BTreeSet<String>,
+    /// Epics folded away in the list when it's grouped by epic, `None` standing for the issues
+    /// without one.
+    pub folded_epics: BTreeSet<Option<String>>,
+    pub bulk_actions: StatefulList<String>,
+    pub bulk_progress: Option<BulkProgress>,
+    /// How far a search spanning several pages has got, while it's being fetched.
+    pub issues_loading: Option<SearchProgress>,
+    /// Moved on while something's running, for drawing spinners.
+    pub animation_frame: usize,
+    /// Checklist items of the issue shown in the detail view.
+    pub checklist: StatefulList<ChecklistItem>,
+    pub checklist_focused: bool,
+    /// Issues previously shown in the detail view, most recent last.
+    pub breadcrumbs: Vec<String>,
+    /// Issues sharing a parent with the one shown in the detail view.
+    pub siblings: Vec<IssueSummary>,
+    /// Sub-tasks of issues shown in the detail view, by their parent's key.
+    pub subtasks: BTreeMap<String, Vec<IssueSummary>>,
+    /// Files attached to the selected issue, once the attachments view has fetched them.
+    pub attachments: StatefulList<AttachmentSummary>,
+    /// Files in the current directory starting with the path typed, to pick one to attach.
+    pub local_files: StatefulList<String>,
+    /// Time tracked on issues shown in the detail view, by their key.
+    pub time_tracking: BTreeMap<String, TimeTracking>,
+    /// Comments on the issue shown in the detail view, oldest first. Only the latest pages are
+    /// loaded, `older_comments` is how many came before them, or `None` before any arrive.
+    pub comments: Vec<CommentSummary>,
+    pub older_comments: Option<usize>,
+    /// How far down the comments view is scrolled.
+    pub comments_scroll: u16,
+    /// How many lines the detail view is scrolled down by.
+    pub detail_scroll: u16,
+    pub detail_search: Option<DetailSearch>,
+    pub description_edit: Option<DescriptionEdit>,
+    pub comment_draft: Option<CommentDraft>,
+    pub sort_menu: StatefulList<String>,
+    pub snooze_menu: StatefulList<String>,
+    /// Recently used project keys, offered by the project switcher.
+    pub project_menu: StatefulList<String>,
+    /// The saved views, offered by the view picker.
+    pub view_menu: StatefulList<SavedView>,
+    /// The sprint view, while it's open.
+    pub sprint: Option<SprintView>,
+    /// The sprints to move an issue into, while they're being picked from.
+    pub sprint_picker: Option<SprintPicker>,
+    /// Issues hidden from the list until a later time.
+    pub snoozed: StatefulList<SnoozedIssue>,
+    /// Recent changes to issues I'm involved in, minus anything muted.
+    pub activity: StatefulList<ActivityEntry>,
+    pub smart_commit: SmartCommitForm,
+    /// Time being logged on the selected issue, while it's typed in.
+    pub worklog: Option<WorklogForm>,
+    pub reviewer_picker: ReviewerPicker,
+    pub assignees: StatefulList<Assignee>,
+    pub priorities: StatefulList<String>,
+    /// The labels of the issue in the label editor, as Jira last reported them.
+    pub labels: StatefulList<String>,
+    /// Why Jira refused actions on issues, by issue key, so they're explained rather than tried
+    /// again. Forgotten on a hard refresh, in case permissions changed.
+    pub restrictions: BTreeMap<String, BTreeMap<IssueAction, String>>,
+    /// What Jira said the user may do to issues, by key, checked before opening pickers on them.
+    permissions: BTreeMap<String, IssuePermissions>,
+    /// Transitions by project and status, which rarely change, so the picker opens straight away.
+    transition_cache: BTreeMap<(String, String), CachedTransitions>,
+    /// What the account screen shows, fetched each time it's opened.
+    pub account: Option<AccountInfo>,
+    /// What the audit screen shows, run each time it's opened.
+    pub audit: Option<Audit>,
+    /// A work timer running against an issue.
+    pub timer: Option<WorkTimer>,
+    /// A stopped timer whose time is waiting to be logged or discarded, asked about over
+    /// everything else while it's `Some`.
+    pub stopped_timer: Option<WorkTimer>,
+    /// A repo waiting to be cloned or not, asked about over everything else while it's `Some`.
+    pub clone_offer: Option<CloneOffer>,
+    /// A branch the selected issue already has, offered instead of creating another one. Asked
+    /// about over everything else while it's `Some`.
+    pub existing_branch: Option<String>,
+    /// A new branch's name that's already taken, asked about over everything else while it's
+    /// `Some`: check the branch out, start it over or pick a suffixed name instead.
+    pub branch_collision: Option<String>,
+    pub config: Config,
+    pub input_mode: InputMode,
+    /// The modes the current one was opened on top of with `open_mode`, most recent last. Esc
+    /// goes back to them one at a time, and they're drawn dimmed underneath. Cleared by
+    /// `set_mode`, which every other change of mode goes through.
+    pub modes_below: Vec<InputMode>,
+    /// An issue key found on the clipboard at startup that can be jumped to.
+    pub clipboard_issue: Option<String>,
+    /// The last failure worth telling the user about.
+    pub error: Option<String>,
+    /// Confirmation that something finished, e.g. text being copied. Shown like errors are.
+    pub notice: Option<String>,
+    /// A replacement API token being typed in, shown over everything else while it's `Some`.
+    pub reauth_input: Option<String>,
+    /// The request budget Jira last reported, if it reports one.
+    pub rate_limit: Option<RateLimit>,
+    /// Background requests waiting for one of the `parallel_requests` slots.
+    pub queued_requests: usize,
+    /// The requests `--dry-run` held back rather than sending since the last key press.
+    pub unsent_requests: Vec<String>,
+    /// Set for a single update after Ctrl-Z or SIGTSTP, the UI hands the terminal back to the
+    /// shell and stops the process when it sees it.
+    pub suspend: bool,
+    /// Set for a single update once the process is continued, the UI takes the terminal back over
+    /// and redraws all of it when it sees it.
+    pub resumed: bool,
+    /// Set when a signal stopped the app, the process exits with it once the UI is torn down.
+    pub exit_code: Option<i32>,
+    /// The branch the app finished by checking out, printed for shell wrappers after exiting.
+    pub checked_out: Option<CheckedOut>,
+    issues_fetched: bool,
+    issues_focused: bool,
+    /// Cleared while the terminal is in the background, for terminals that say so.
+    terminal_focused: bool,
+    /// Which filter chip Tab has moved to, if any.
+    pub chip_focus: Option<usize>,
+    input: String,
+}
+
+impl State {
+    pub fn new() -> State {
+        State {
+            timer: load_timer(),
+            head_branch: get_current_repo().ok().as_ref().and_then(current_branch),
+            ..State::with_config(load_config(), load_snoozed())
+        }
+    }
+
+    /// State that doesn't read anything from disk. Keys the config can't bind are reported
+    /// straight away.
+    pub fn with_config(config: Config, snoozed: Vec<SnoozedIssue>) -> State {
+        let error = keymap_problems(&config);
+        State {
+            issues: StatefulList::new(),
+            issues_offset: 0,
+            boards: StatefulList::new(),
+            all_boards: vec![],
+            board_filter: String::new(),
+            boards_filtered_by_jira: false,
+            branches: StatefulList::new(),
+            head_branch: None,
+            branch_index: BranchIndex::default(),
+            branched: BTreeSet::new(),
+            pull_requests: BTreeSet::new(),
+            transitions: StatefulList::new(),
+            links: BTreeMap::new(),
+            links_view: None,
+            link_relations: Vec::new(),
+            link_form: None,
+            marked: BTreeSet::new(),
+            prefetched: BTreeSet::new(),
+            folded_epics: BTreeSet::new(),
+            bulk_actions: StatefulList::with_items(
+                BULK_EDIT_ACTIONS.iter().map(|action| action.to_string()).collect(),
+            ),
+            bulk_progress: None,
+            issues_loading: None,
+            animation_frame: 0,
+            checklist: StatefulList::new(),
+            checklist_focused: false,
+            breadcrumbs: Vec::new(),
+            siblings: Vec::new(),
+            subtasks: BTreeMap::new(),
+            attachments: StatefulList::new(),
+            local_files: StatefulList::new(),
+            time_tracking: BTreeMap::new(),
+            comments: Vec::new(),
+            older_comments: None,
+            comments_scroll: 0,
+            detail_scroll: 0,
+            detail_search: None,
+            description_edit: None,
+            comment_draft: None,
+            sort_menu: StatefulList::new(),
+            project_menu: StatefulList::new(),
+            view_menu: StatefulList::new(),
+            sprint: None,
+            sprint_picker: None,
+            snooze_menu: StatefulList::with_items(
+                SNOOZE_DURATIONS
+                    .iter()
+                    .map(|(label, _)| label.to_string())
+                    .collect(),
+            ),
+            snoozed: StatefulList::with_items(snoozed),
+            activity: StatefulList::new(),
+            smart_commit: SmartCommitForm::new(),
+            worklog: None,
+            reviewer_picker: ReviewerPicker::new(false),
+            assignees: StatefulList::new(),
+            priorities: StatefulList::new(),
+            labels: StatefulList::new(),
+            restrictions: BTreeMap::new(),
+            permissions: BTreeMap::new(),
+            transition_cache: BTreeMap::new(),
+            account: None,
+            audit: None,
+            timer: None,
+            stopped_timer: None,
+            clone_offer: None,
+            existing_branch: None,
+            branch_collision: None,
+            issues_focused: true,
+            terminal_focused: true,
+            chip_focus: None,
+            input_mode: InputMode::IssuesList,
+            modes_below: vec![],
+            clipboard_issue: None,
+            error,
+            notice: None,
+            reauth_input: None,
+            rate_limit: None,
+            queued_requests: 0,
+            unsent_requests: vec![],
+            suspend: false,
+            resumed: false,
+            exit_code: None,
+            checked_out: None,
+            issues_fetched: false,
+            input: String::new(),
+            config,
+        }
+    }
+
+    pub fn selected_issue_key(&self) -> Option<String> {
+        if let Some(i) = self.issues.state.selected() {
+            if let Some(issue) = self.issues.items.get(i) {
+                return Some(issue.key.clone());
+            }
+        }
+        None
+    }
+
+    /// Select the given issue, adding it to the top of the list if the current filters hid it.
+    fn jump_to_issue(&mut self, issue: IssueSummary) {
+        let index = match self.issues.items.iter().position(|i| i.key == issue.key) {
+            Some(index) => index,
+            None => {
+                self.issues.items_mut().insert(0, issue);
+                0
+            }
+        };
+        self.issues.state.select(Some(index));
+        self.branches.unselect();
+        self.issues_focused = true;
+        self.clipboard_issue = None;
+        self.clear_comments();
+    }
+
+    fn clear_comments(&mut self) {
+        self.comments.clear();
+        self.older_comments = None;
+        self.detail_scroll = 0;
+        self.comments_scroll = 0;
+    }
+
+    /// Show the selected issue's comments on their own, fetching the latest page.
+    fn open_comments(&mut self) {
+        self.clear_comments();
+        self.set_mode(InputMode::CommentsView);
+    }
+
+    /// Add a page of older comments above those already loaded. Pages for another issue, or
+    /// repeats from asking twice before the first answer came back, are dropped.
+    fn add_comments(&mut self, key: &str, page: CommentPage) {
+        if self.selected_issue_key().as_deref() != Some(key) || page.loaded != self.comments.len()
+        {
+            return;
+        }
+        let newer = std::mem::replace(&mut self.comments, page.comments);
+        self.comments.extend(newer);
+        self.older_comments = Some(page.older);
+    }
+
+    fn stop_search_jump(&mut self) {
+        if let Some(search) = self.detail_search.as_mut() {
+            search.jump = false;
+        }
+    }
+
+    /// Roughly how many lines the detail view's text takes up before wrapping, to stop
+    /// scrolling well past the end.
+    fn detail_line_count(&self) -> u16 {
+        let description = self
+            .selected_issue()
+            .map_or(0, |issue| issue.description.lines().count());
+        self.comment_line_count() + (description + DETAIL_HEADER_LINES) as u16
+    }
+
+    /// Roughly how many lines the loaded comments take up before wrapping.
+    fn comment_line_count(&self) -> u16 {
+        let comments: usize = self
+            .comments
+            .iter()
+            .map(|comment| comment.body.lines().count() + 2)
+            .sum();
+        comments as u16
+    }
+
+    pub fn selected_issue(&self) -> Option<&IssueSummary> {
+        self.issues
+            .state
+            .selected()
+            .and_then(|i| self.issues.items.get(i))
+    }
+
+    fn selected_issue_permalink(&self) -> Option<String> {
+        let selected = self.issues.state.selected();
+        selected.map(|i| self.issues.items[i].permalink.clone())
+    }
+
+    /// Offer the recently used projects, with the one before the current selected so Enter
+    /// switches back and forth.
+    fn open_project_switcher(&mut self) {
+        let recent = self.config.recent_projects.clone();
+        if recent.is_empty() {
+            self.notice = Some("No recent projects yet, press c to pick one".to_string());
+            return;
+        }
+        let current = &self.config.default_project_key;
+        let previous = recent.iter().position(|key| key != current).unwrap_or(0);
+        self.project_menu = StatefulList::with_items(recent);
+        self.project_menu.state.select(Some(previous));
+        self.open_mode(InputMode::SwitchingProject);
+    }
+
+    /// Offer the saved views, starting from the one in use if any.
+    fn open_view_picker(&mut self) {
+        self.list_views();
+        self.open_mode(InputMode::PickingView);
+    }
+
+    fn list_views(&mut self) {
+        let views = self.config.views.clone();
+        let config = &self.config;
+        let current = views.iter().position(|view| config.is_current_view(view));
+        self.view_menu = StatefulList::with_items(views);
+        match current {
+            Some(index) => self.view_menu.state.select(Some(index)),
+            None => self.view_menu.next(),
+        }
+    }
+
+    /// Save the issue list's setup under the name typed in, back in the view picker.
+    fn save_view(&mut self) {
+        let name = self.input.trim().to_string();
+        self.input.clear();
+        if !name.is_empty() {
+            let view = self.config.current_view(name.clone());
+            self.config.save_view(view);
+            match save_config(&self.config) {
+                Ok(()) => self.notice = Some(format!("Saved the view {}", name)),
+                Err(err) => self.error = Some(err.to_string()),
+            }
+        }
+        self.close_mode();
+        self.list_views();
+    }
+
+    /// Search the issue list with the JQL typed in, or go back to the filters if it's empty.
+    fn apply_jql(&mut self) {
+        let jql = self.input.trim().to_string();
+        self.input.clear();
+        self.config.jql = Some(jql).filter(|jql| !jql.is_empty());
+        if let Err(err) = save_config(&self.config) {
+            self.error = Some(err.to_string());
+        }
+        self.chip_focus = None;
+        self.close_mode();
+    }
+
+    /// Forget the view selected in the view picker.
+    fn delete_selected_view(&mut self) {
+        let name = match self.view_menu.state.selected() {
+            Some(i) => self.view_menu.items[i].name.clone(),
+            None => return,
+        };
+        self.config.views.retain(|view| view.name != name);
+        if let Err(err) = save_config(&self.config) {
+            self.error = Some(err.to_string());
+        }
+        self.open_view_picker();
+    }
+
+    /// Show the boards matching the filter, selecting the first.
+    fn filter_boards(&mut self) {
+        let filter = self.board_filter.to_lowercase();
+        let matching = self
+            .all_boards
+            .iter()
+            .filter(|board| {
+                let project = board.project.as_deref().unwrap_or("");
+                board.name.to_lowercase().contains(&filter)
+                    || project.to_lowercase().contains(&filter)
+            })
+            .cloned()
+            .collect();
+        self.boards = StatefulList::with_items(matching);
+        self.sort_boards();
+        self.boards.next();
+    }
+
+    /// Starred boards first, otherwise in the order Jira returned them.
+    fn sort_boards(&mut self) {
+        let favorites = &self.config.favorite_boards;
+        self.boards
+            .items_mut()
+            .sort_by_key(|board| !favorites.contains(&board.key));
+    }
+
+    fn toggle_favorite_board(&mut self) {
+        let board = match self.boards.state.selected() {
+            Some(i) => self.boards.items[i].key,
+            None => return,
+        };
+        if self.config.favorite_boards.contains(&board) {
+            self.config.favorite_boards.retain(|&id| id != board);
+        } else {
+            self.config.favorite_boards.push(board);
+        }
+        let _ = save_config(&self.config);
+        self.sort_boards();
+        // Keep the same board selected as it moves
+        let index = self.boards.items.iter().position(|b| b.key == board);
+        self.boards.state.select(index);
+    }
+
+    /// Scope the issue list to the selected board, or back to every board if it already is.
+    fn choose_default_board(&mut self) {
+        let board = match self.boards.state.selected() {
+            Some(i) => &self.boards.items[i],
+            None => return,
+        };
+        let already = self
+            .config
+            .default_board
+            .as_ref()
+            .is_some_and(|saved| saved.id == board.key);
+        self.config.default_board = if already {
+            None
+        } else {
+            Some(SavedBoard {
+                id: board.key,
+                name: board.name.clone(),
+            })
+        };
+        let _ = save_config(&self.config);
+        self.set_mode(InputMode::IssuesList);
+    }
+
+    /// Pick a sprint of the board to move an issue into, coming back to the current view after.
+    fn open_sprint_picker(&mut self, key: String, board_id: u64) {
+        self.sprint_picker = Some(SprintPicker {
+            key,
+            board_id,
+            sprints: StatefulList::new(),
+        });
+        self.open_mode(InputMode::PickingSprint);
+    }
+
+    /// Open the sprint view on a board's active sprint or backlog, coming back to the current
+    /// view after.
+    fn open_sprint(&mut self, board: SavedBoard, backlog: bool) {
+        self.sprint = Some(SprintView::new(board, backlog));
+        self.open_mode(InputMode::SprintView);
+    }
+
+    /// The board highlighted in the boards list, to remember or open the sprint of.
+    fn highlighted_board(&self) -> Option<SavedBoard> {
+        let board = self.boards.items.get(self.boards.state.selected()?)?;
+        Some(SavedBoard {
+            id: board.key,
+            name: board.name.clone(),
+        })
+    }
+
+    fn open_selected_board(&self) {
+        if let Some(i) = self.boards.state.selected() {
+            let link = self.boards.items[i].permalink.clone();
+            open_link(&link);
+        }
+    }
+
+    fn selected_branch(&self) -> Option<BranchEntry> {
+        let selected = self.branches.state.selected()?;
+        self.branches.items.get(selected).cloned()
+    }
+
+    /// List the selected issue's links to pick one to go to, starting with any fetched before
+    /// while they're fetched again.
+    async fn open_links(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let key = match self.selected_issue_key() {
+            Some(key) => key,
+            None => return,
+        };
+        let links = self.links.get(&key).cloned().unwrap_or_default();
+        self.links_view = Some(LinksView::new(key, links));
+        self.open_mode(InputMode::IssueLinks);
+        fetch_links(event_tx, jira, self.clone()).await;
+    }
+
+    /// Start linking the selected issue to another, fetching the kinds of links the first time.
+    async fn open_link_form(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let key = match self.selected_issue_key() {
+            Some(key) => key,
+            None => return,
+        };
+        let mut relations = StatefulList::with_items(self.link_relations.clone());
+        relations.next();
+        self.link_form = Some(LinkForm {
+            key,
+            relations,
+            targets: StatefulList::new(),
+        });
+        self.open_mode(InputMode::PickingLinkType);
+        if self.link_relations.is_empty() {
+            fetch_link_relations(event_tx, jira).await;
+        }
+    }
+
+    /// List the issues whose key starts with what's been typed, or whose summary contains it,
+    /// leaving out the issue being linked.
+    fn list_link_targets(&mut self) {
+        let query = self.input.trim().to_lowercase();
+        let form = match self.link_form.as_mut() {
+            Some(form) => form,
+            None => return,
+        };
+        let targets = if query.is_empty() {
+            vec![]
+        } else {
+            let matches = |issue: &&IssueSummary| {
+                issue.key != form.key
+                    && (issue.key.to_lowercase().starts_with(&query)
+                        || issue.summary.to_lowercase().contains(&query))
+            };
+            self.issues.items.iter().filter(matches).cloned().collect()
+        };
+        form.targets = StatefulList::with_items(targets);
+    }
+
+    /// Link the issue to the picked issue, or else to the key as typed.
+    async fn submit_link(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let form = match self.link_form.take() {
+            Some(form) => form,
+            None => return,
+        };
+        self.close_link_form();
+        let target = match form.selected_target() {
+            Some(issue) => issue.key.clone(),
+            None => self.input.trim().to_uppercase(),
+        };
+        self.input.clear();
+        if !is_issue_key(&target) {
+            self.error = Some(format!("{} isn't an issue key", target));
+            return;
+        }
+        if let Some(relation) = form.selected_relation().cloned() {
+            create_link(event_tx, jira, form.key, relation, target).await;
+        }
+    }
+
+    /// Go to the issue on the other end of the selected link. It's selected in the list when
+    /// it's there, otherwise it's fetched and put at the top. Coming from the detail view, the
+    /// issue the links belong to is added to the breadcrumbs so Esc comes back to it.
+    async fn jump_to_link(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let view = match self.links_view.take() {
+            Some(view) => view,
+            None => return,
+        };
+        self.close_mode();
+        let key = match view.selected_link() {
+            Some(link) => link.key.clone(),
+            None => return,
+        };
+        let in_detail = matches!(self.input_mode, InputMode::IssueDetail);
+        if in_detail {
+            self.breadcrumbs.push(view.key);
+        }
+        if !self.return_to_issue(&key) {
+            fetch_issue(event_tx, jira, key).await;
+            return;
+        }
+        find_relevant_branches(event_tx.clone(), self.clone()).await;
+        if in_detail {
+            fetch_issue_details(event_tx, jira, self).await;
+        }
+    }
+
+    fn open_issue_detail(&mut self) {
+        self.breadcrumbs.clear();
+        self.siblings.clear();
+        self.clear_comments();
+        self.branches.unselect();
+        self.branches.next();
+        self.load_checklist();
+        self.set_mode(InputMode::IssueDetail);
+    }
+
+    /// The sibling `offset` places away from the selected issue, wrapping around.
+    fn sibling(&self, offset: isize) -> Option<IssueSummary> {
+        let key = self.selected_issue_key()?;
+        let count = self.siblings.len() as isize;
+        let index = self.siblings.iter().position(|issue| issue.key == key)? as isize;
+        let next = (index + offset).rem_euclid(count) as usize;
+        self.siblings.get(next).cloned()
+    }
+
+    /// Select an issue that was visited before, as long as it's still in the list.
+    fn return_to_issue(&mut self, key: &str) -> bool {
+        match self.issues.items.iter().position(|issue| issue.key == key) {
+            Some(index) => {
+                self.issues.state.select(Some(index));
+                self.branches.unselect();
+                self.load_checklist();
+                self.clear_comments();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Order the issues by the configured preset, keeping the same issue selected.
+    fn sort_issues(&mut self) {
+        let selected = self.selected_issue_key();
+        if let SortPreset::Triage = self.config.sort_preset {
+            self.issues.items_mut().sort_by(|a, b| {
+                a.severity()
+                    .cmp(&b.severity())
+                    .then_with(|| a.created.cmp(&b.created))
+            });
+        }
+        if self.config.group_by_epic {
+            self.group_issues();
+        }
+        if let Some(key) = selected {
+            let index = self.issues.items.iter().position(|issue| issue.key == key);
+            self.issues.state.select(index);
+        }
+    }
+
+    /// Bring each epic's issues together, epics in the order their first issue comes in and
+    /// issues without one last. Issues keep their order within an epic.
+    fn group_issues(&mut self) {
+        let mut epics: Vec<String> = vec![];
+        for epic in self.issues.items.iter().filter_map(IssueSummary::epic_key) {
+            if !epics.iter().any(|known| known == epic) {
+                epics.push(epic.to_string());
+            }
+        }
+        self.issues.items_mut().sort_by_key(|issue| {
+            let epic = issue.epic_key();
+            let position = epics.iter().position(|known| Some(known.as_str()) == epic);
+            position.unwrap_or(epics.len())
+        });
+    }
+
+    fn is_folded(&self, epic: Option<&str>) -> bool {
+        self.folded_epics.contains(&epic.map(String::from))
+    }
+
+    /// The rows of the issue list, with a header above each epic's issues when grouping by
+    /// epic. Folded epics are down to their header.
+    pub fn issue_rows(&self) -> Vec<IssueRow> {
+        let issues = self.issues.items.iter().enumerate();
+        if !self.config.group_by_epic {
+            return issues.map(|(i, _)| IssueRow::Issue(i)).collect();
+        }
+        let mut rows = vec![];
+        let mut current: Option<(Option<&str>, usize)> = None;
+        for (i, issue) in issues {
+            let epic = issue.epic_key();
+            let header = match current {
+                Some((known, header)) if known == epic => header,
+                _ => {
+                    rows.push(IssueRow::Epic {
+                        key: epic.map(String::from),
+                        count: 0,
+                        folded: self.is_folded(epic),
+                    });
+                    rows.len() - 1
+                }
+            };
+            current = Some((epic, header));
+            if let IssueRow::Epic { count, folded, .. } = &mut rows[header] {
+                *count += 1;
+                if *folded {
+                    continue;
+                }
+            }
+            rows.push(IssueRow::Issue(i));
+        }
+        rows
+    }
+
+    /// Which of the rows is selected. An issue in a folded epic selects the epic's header.
+    pub fn selected_row(&self, rows: &[IssueRow]) -> Option<usize> {
+        let selected = self.issues.state.selected()?;
+        let issue = IssueRow::Issue(selected);
+        let row = rows.iter().position(|row| *row == issue);
+        row.or_else(|| {
+            let epic = self.issues.items.get(selected)?.epic_key();
+            rows.iter().position(|row| match row {
+                IssueRow::Epic { key, .. } => key.as_deref() == epic,
+                IssueRow::Issue(_) => false,
+            })
+        })
+    }
+
+    /// Keys of the issues hidden in folded epics. Each folded epic keeps its first issue, which
+    /// is what's selected while on its header.
+    fn folded_issues(&self) -> BTreeSet<String> {
+        if !self.config.group_by_epic || self.folded_epics.is_empty() {
+            return BTreeSet::new();
+        }
+        let mut seen = BTreeSet::new();
+        let issues = self.issues.items.iter().filter(|issue| {
+            let epic = issue.epic_key();
+            self.is_folded(epic) && !seen.insert(epic)
+        });
+        issues.map(|issue| issue.key.clone()).collect()
+    }
+
+    /// Move to the next or previous issue in the list, passing over folded epics in one step.
+    /// The keys of the issues either side of the selected one, passing over folded epics.
+    fn adjacent_issue_keys(&self) -> Vec<String> {
+        let selected = match self.issues.state.selected() {
+            Some(selected) => selected,
+            None => return vec![],
+        };
+        let hidden = self.folded_issues();
+        let shown = |issue: &&IssueSummary| !hidden.contains(&issue.key);
+        let items = &self.issues.items;
+        let (before, after) = items.split_at(selected.min(items.len()));
+        let previous = before.iter().rev().find(shown);
+        let next = after.iter().skip(1).find(shown);
+        previous
+            .into_iter()
+            .chain(next)
+            .map(|issue| issue.key.clone())
+            .collect()
+    }
+
+    fn step_issues(&mut self, forward: bool) {
+        let hidden = self.folded_issues();
+        let shown = |issue: &IssueSummary| !hidden.contains(&issue.key);
+        if forward {
+            self.issues.next_where(shown);
+        } else {
+            self.issues.previous_where(shown);
+        }
+    }
+
+    /// Fold away the selected issue's epic, or unfold it, moving to the epic's first issue.
+    fn toggle_epic_fold(&mut self) {
+        if !self.config.group_by_epic {
+            return;
+        }
+        let epic = match self.selected_issue() {
+            Some(issue) => issue.epic_key().map(String::from),
+            None => return,
+        };
+        if !self.folded_epics.remove(&epic) {
+            self.folded_epics.insert(epic.clone());
+        }
+        let mut issues = self.issues.items.iter();
+        let first = issues.position(|issue| issue.epic_key() == epic.as_deref());
+        self.issues.state.select(first);
+    }
+
+    /// Drop snoozed issues from the list, forgetting any snoozes that have run out.
+    fn hide_snoozed(&mut self) {
+        let now = now();
+        self.snoozed.items_mut().retain(|issue| issue.until > now);
+        self.snoozed.unselect();
+        let snoozed = &self.snoozed.items;
+        self.issues
+            .items_mut()
+            .retain(|issue| !snoozed.iter().any(|snoozed| snoozed.key == issue.key));
+    }
+
+    /// Snooze the selected issue for the chosen duration, removing it from the list.
+    fn snooze_selected_issue(&mut self) {
+        let seconds = match self.snooze_menu.state.selected() {
+            Some(i) => SNOOZE_DURATIONS[i].1,
+            None => return,
+        };
+        let i = match self.issues.state.selected() {
+            Some(i) if i < self.issues.items.len() => i,
+            _ => return,
+        };
+        let issue = self.issues.items_mut().remove(i);
+        self.snoozed.items_mut().push(SnoozedIssue {
+            key: issue.key,
+            summary: issue.summary,
+            until: now() + seconds,
+        });
+        let _ = save_snoozed(&self.snoozed.items);
+
+        let remaining = self.issues.items.len();
+        self.issues
+            .state
+            .select(if remaining == 0 { None } else { Some(i.min(remaining - 1)) });
+    }
+
+    /// Drop activity that's been muted, keeping the selection in bounds.
+    fn hide_muted_activity(&mut self) {
+        let config = &self.config;
+        self.activity
+            .items_mut()
+            .retain(|entry| !config.is_muted(&entry.key, &entry.field));
+        let remaining = self.activity.items.len();
+        let selected = self.activity.state.selected();
+        self.activity.state.select(
+            selected
+                .filter(|_| remaining > 0)
+                .map(|i| i.min(remaining - 1)),
+        );
+    }
+
+    /// Mute the selected activity entry's issue, or the kind of change it is.
+    fn mute_selected_activity(&mut self, by_type: bool) {
+        let entry = match self.activity.state.selected() {
+            Some(i) => match self.activity.items.get(i) {
+                Some(entry) => entry.clone(),
+                None => return,
+            },
+            None => return,
+        };
+        if by_type {
+            self.config.muted_event_types.push(entry.field);
+        } else {
+            self.config.muted_issues.push(entry.key);
+        }
+        let _ = save_config(&self.config);
+        self.hide_muted_activity();
+    }
+
+    fn open_sort_menu(&mut self) {
+        let colors = if self.config.priority_colors { "on" } else { "off" };
+        let grouped = self.config.group_by_epic;
+        let grouping = if grouped { "on" } else { "off" };
+        self.sort_menu = StatefulList::with_items(vec![
+            "Jira order".to_string(),
+            "Triage: priority, then oldest".to_string(),
+            format!("Priority colors: {}", colors),
+            format!("Group by epic: {}", grouping),
+        ]);
+        self.sort_menu.next();
+        self.open_mode(InputMode::SortMenu);
+    }
+
+    fn issue_mut(&mut self, key: &str) -> Option<&mut IssueSummary> {
+        self.issues.items_mut().iter_mut().find(|issue| issue.key == key)
+    }
+
+    fn set_description(&mut self, key: &str, description: String, updated: String) {
+        if let Some(issue) = self.issues.items_mut().iter_mut().find(|issue| issue.key == key) {
+            issue.updated = updated;
+            if issue.checklist.field == "description" {
+                issue.checklist.text = description.clone();
+            }
+            issue.description = description;
+        }
+        self.load_checklist();
+    }
+
+    fn load_checklist(&mut self) {
+        let items = match self.selected_issue() {
+            Some(issue) => parse_checklist(&issue.checklist.text),
+            None => vec![],
+        };
+        self.checklist = StatefulList::with_items(items);
+        self.checklist_focused = false;
+    }
+
+    /// Toggle the selected checklist item locally, returning the field to save.
+    fn toggle_selected_checklist_item(&mut self) -> Option<(String, TextField)> {
+        let item = self.checklist.items.get(self.checklist.state.selected()?)?.clone();
+        let i = self.issues.state.selected()?;
+        let issue = self.issues.items_mut().get_mut(i)?;
+        issue.checklist.text = toggle_item(&issue.checklist.text, &item);
+        if issue.checklist.field == "description" {
+            issue.description = issue.checklist.text.clone();
+        }
+        let update = (issue.key.clone(), issue.checklist.clone());
+
+        let selected = self.checklist.state.selected();
+        self.load_checklist();
+        self.checklist.state.select(selected);
+        self.checklist_focused = true;
+        Some(update)
+    }
+
+    /// Turn the chosen bulk action and typed value into an edit.
+    fn bulk_edit(&self) -> Option<BulkEdit> {
+        let value = self.input.trim().to_string();
+        if value.is_empty() {
+            return None;
+        }
+        match self.bulk_actions.state.selected() {
+            Some(0) => Some(BulkEdit::AddLabel(value)),
+            Some(1) => Some(BulkEdit::RemoveLabel(value)),
+            Some(2) => Some(BulkEdit::SetFixVersion(value)),
+            _ => None,
+        }
+    }
+
+    pub fn raw_input_clone(&self) -> String {
+        self.input.clone()
+    }
+
+    /// Whether the issue list has been fetched at least once since starting or a hard refresh.
+    pub fn issues_fetched(&self) -> bool {
+        self.issues_fetched
+    }
+
+    /// Whether polls and webhooks should refresh the list, only while it's being looked at in a
+    /// focused terminal and nothing's in progress.
+    fn refreshes_in_background(&self) -> bool {
+        let idle = self.reauth_input.is_none() && self.issues_focused && self.terminal_focused;
+        matches!(self.input_mode, InputMode::IssuesList) && idle
+    }
+
+    /// Where to work on the selected issue, when its project has a directory in the repo.
+    pub fn project_dir(&self) -> Option<&str> {
+        self.config.project_dir(&self.selected_issue_key()?)
+    }
+
+    /// The repo the selected issue's branches belong in, when its project has its own.
+    pub fn project_repo(&self) -> Option<PathBuf> {
+        self.config.project_repo(&self.selected_issue_key()?)
+    }
+
+    /// The repo to list, create and check out branches for the selected issue in.
+    fn issue_repo(&self) -> Result<Repository> {
+        open_repo(self.project_repo().as_deref())
+    }
+
+    /// The selected issue's repo, when it isn't there yet but can be cloned.
+    fn missing_repo(&self) -> Option<CloneOffer> {
+        let key = self.selected_issue_key()?;
+        let path = self.project_repo().filter(|path| !path.exists())?;
+        let url = self.config.project_remote(&key)?.to_string();
+        Some(CloneOffer { key, url, path })
+    }
+
+    fn checked_out_branch(
+        &self,
+        repo: &Repository,
+        branch: String,
+        created: bool,
+        previous: Option<Oid>,
+    ) -> CheckedOut {
+        let root = repo.workdir();
+        CheckedOut {
+            key: self.selected_issue_key(),
+            branch,
+            created,
+            repo: self.project_repo().and(root).map(Path::to_path_buf),
+            dir: root.zip(self.project_dir()).map(|(root, dir)| root.join(dir)),
+            previous,
+        }
+    }
+
+    pub fn new_branch_name(&self) -> String {
+        match self.selected_issue() {
+            Some(issue) => branch_name(
+                &issue.key,
+                &self.input,
+                &issue.summary,
+                self.config.branch_prefix(&issue.key),
+                self.config.branch_template.as_deref(),
+            ),
+            None => "unhandled-error".to_string(),
+        }
+    }
+
+    /// Start a timer on the selected issue, or stop the running one and ask whether to log it.
+    fn toggle_timer(&mut self) {
+        match self.timer.take() {
+            Some(timer) => self.stopped_timer = Some(timer),
+            None => {
+                let key = match self.selected_issue_key() {
+                    Some(key) => key,
+                    None => return,
+                };
+                self.notice = Some(format!("Started a timer on {}", key));
+                self.timer = Some(WorkTimer::start(key));
+            }
+        }
+        if let Err(e) = save_timer(self.timer.as_ref()) {
+            self.error = Some(format!("Couldn't save the timer: {}", e));
+        }
+    }
+
+    /// Write the issue list as shown to an HTML page, for sharing.
+    fn export_issues(&mut self) {
+        let title = match self.config.project_scope() {
+            Some(project) => format!("{} issues", project),
+            None => "Issues across all projects".to_string(),
+        };
+        let path = env::temp_dir().join(EXPORT_FILE_NAME);
+        match fs::write(&path, issues_html(&title, &self.issues.items)) {
+            Ok(()) => self.notice = Some(format!("Saved the issue list to {}", path.display())),
+            Err(e) => self.error = Some(format!("Couldn't save the issue list: {}", e)),
+        }
+    }
+
+    /// Remember that Jira refused the action on the issue with the given key, and why.
+    fn restrict(&mut self, key: &str, action: IssueAction, reason: String) {
+        let restrictions = self.restrictions.entry(key.to_string()).or_default();
+        restrictions.insert(action, reason);
+    }
+
+    /// Note what Jira says the user may do to the issue with the given key, turning off the
+    /// actions they don't hold the permission for.
+    fn apply_permissions(&mut self, key: &str, permissions: IssuePermissions) {
+        for action in IssueAction::ALL.iter().copied() {
+            if !permissions.held.contains(action.permission()) {
+                let reason = format!("You don't have the {} permission", action.permission());
+                self.restrict(key, action, reason);
+            }
+        }
+        self.permissions.insert(key.to_string(), permissions);
+    }
+
+    /// Why the picker can't be used on the issue with the given key, going by the fields its
+    /// edit screen has.
+    fn denial(&self, key: &str, picker: Picker) -> Option<String> {
+        let field = picker.field()?;
+        let permissions = self.permissions.get(key)?;
+        if permissions.editable_fields.contains(field) {
+            return None;
+        }
+        Some(format!(
+            "Can't edit the {} of {}: it isn't on the issue's edit screen",
+            field, key
+        ))
+    }
+
+    /// Whether the picker can be opened on the selected issue, explaining why not if Jira
+    /// refused before or the issue's permissions rule it out. The permissions are checked the
+    /// first time, so the picker opens straight away and closes again if they rule it out.
+    async fn preflight(&mut self, picker: Picker, event_tx: EventsTx, jira: JiraClient) -> bool {
+        let key = match self.selected_issue_key() {
+            Some(key) => key,
+            None => return false,
+        };
+        let refusal = self.refusal(&key, picker.action());
+        if let Some(refusal) = refusal.or_else(|| self.denial(&key, picker)) {
+            self.error = Some(refusal);
+            return false;
+        }
+        if !self.permissions.contains_key(&key) {
+            check_permissions(event_tx, jira, key, picker).await;
+        }
+        true
+    }
+
+    /// Close the picker if it's open on the issue with the given key but can't be used on it.
+    fn close_denied_picker(&mut self, key: &str, picker: Picker) {
+        let open = picker.is_open(&self.input_mode);
+        if !open || self.selected_issue_key().as_deref() != Some(key) {
+            return;
+        }
+        let refusal = self.refusal(key, picker.action());
+        if let Some(refusal) = refusal.or_else(|| self.denial(key, picker)) {
+            self.error = Some(refusal);
+            if let Picker::Description = picker {
+                self.description_edit = None;
+                self.set_mode(InputMode::IssueDetail);
+            } else if let Picker::Transitions = picker {
+                self.close_transition_picker();
+            } else {
+                self.close_mode();
+            }
+        }
+    }
+
+    /// Why the action can't be taken on the issue with the given key, if Jira refused it before.
+    pub fn refusal(&self, key: &str, action: IssueAction) -> Option<String> {
+        let reason = self.restrictions.get(key)?.get(&action)?;
+        Some(format!("Can't {} {}: {}", action.describe(), key, reason))
+    }
+
+    /// Whether Jira refused the action on the selected issue before, explaining why if so
+    /// instead of trying again.
+    fn refused(&mut self, action: IssueAction) -> bool {
+        let refusal = self
+            .selected_issue_key()
+            .and_then(|key| self.refusal(&key, action));
+        let refused = refusal.is_some();
+        if refused {
+            self.error = refusal;
+        }
+        refused
+    }
+
+    /// Start picking who to assign the selected issue to, from yourself or no one until
+    /// something's typed to search for.
+    fn open_assignee_picker(&mut self) {
+        if self.selected_issue_key().is_some() {
+            self.input.clear();
+            self.show_assignees(vec![]);
+            self.open_mode(InputMode::AssigningIssue);
+        }
+    }
+
+    /// List the people found by a search under the choices that are always there.
+    fn show_assignees(&mut self, users: Vec<UserSummary>) {
+        let mut assignees = vec![Assignee::Me, Assignee::Unassigned];
+        assignees.extend(users.into_iter().map(Assignee::User));
+        // The first person found is likely who's being searched for
+        let first = if assignees.len() > 2 { 2 } else { 0 };
+        self.assignees = StatefulList::with_items(assignees);
+        self.assignees.state.select(Some(first));
+    }
+
+    /// Search for people matching what's been typed, until they're found listing only the
+    /// choices that are always there.
+    async fn search_assignees(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        self.show_assignees(vec![]);
+        if let (Some(key), false) = (self.selected_issue_key(), self.input.trim().is_empty()) {
+            search_users(event_tx, jira, key, self.input.clone()).await;
+        }
+    }
+
+    /// List the priorities to pick from, starting at the selected issue's.
+    fn show_priorities(&mut self, priorities: Vec<String>) {
+        let current = self
+            .selected_issue()
+            .and_then(|issue| issue.priority.as_ref())
+            .and_then(|priority| priorities.iter().position(|name| name == priority));
+        self.priorities = StatefulList::with_items(priorities);
+        self.priorities.state.select(current.or(Some(0)));
+    }
+
+    /// The filter chip Tab has moved to.
+    pub fn focused_chip(&self) -> Option<FilterChip> {
+        let chips = FilterChip::active(&self.config);
+        chips.get(self.chip_focus?).copied()
+    }
+
+    /// Move along the filter chips, going back to the list after the last one and before the
+    /// first.
+    fn cycle_chips(&mut self, forward: bool) {
+        let count = FilterChip::active(&self.config).len();
+        self.chip_focus = match (self.chip_focus, forward) {
+            (None, true) => Some(0),
+            (None, false) => count.checked_sub(1),
+            (Some(i), true) if i + 1 < count => Some(i + 1),
+            (Some(i), false) if i > 0 => Some(i - 1),
+            (Some(_), _) => None,
+        };
+    }
+
+    /// Stop filtering by the chip Tab has moved to, staying on the chip that takes its place.
+    fn remove_focused_chip(&mut self) -> bool {
+        let chip = match self.focused_chip() {
+            Some(chip) => chip,
+            None => return false,
+        };
+        chip.remove(&mut self.config);
+        let _ = save_config(&self.config);
+        let count = FilterChip::active(&self.config).len();
+        self.chip_focus = self.chip_focus.map(|i| i.min(count - 1));
+        true
+    }
+
+    /// List the labels in the label editor, keeping the selection where it was.
+    fn show_labels(&mut self, labels: Vec<String>) {
+        let selected = self.labels.state.selected().unwrap_or(0);
+        let selected = labels.len().checked_sub(1).map(|last| selected.min(last));
+        self.labels = StatefulList::with_items(labels);
+        self.labels.state.select(selected);
+    }
+
+    /// Start writing a comment on the selected issue, coming back to the current view after.
+    fn start_comment(&mut self) {
+        if self.refused(IssueAction::Comment) {
+            return;
+        }
+        if let Some(key) = self.selected_issue_key() {
+            self.comment_draft = Some(CommentDraft {
+                key,
+                editor: TextEditor::new(""),
+            });
+            self.open_mode(InputMode::AddingComment);
+        }
+    }
+
+    /// Start putting together a smart commit message for the selected issue, fetching the
+    /// transitions it can go through.
+    async fn open_smart_commit(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        if self.selected_issue_key().is_none() {
+            return;
+        }
+        self.smart_commit = SmartCommitForm::new();
+        self.transitions = StatefulList::new();
+        self.open_mode(InputMode::SmartCommit);
+        self.load_transitions(event_tx, jira).await;
+    }
+
+    /// List the files the path typed so far could be the start of.
+    fn list_local_files(&mut self) {
+        self.local_files = StatefulList::with_items(files_starting_with(&self.input));
+    }
+
+    /// The project and status of the selected issue, which decide the transitions it has.
+    fn workflow(&self) -> Option<(String, String)> {
+        let issue = self.selected_issue()?;
+        Some((project_of(&issue.key).to_string(), issue.status.clone()))
+    }
+
+    /// Switch to a mode in place of the current one. Whatever the current one was opened on top
+    /// of is forgotten too, so Esc can't go back to a view that's no longer underneath it.
+    fn set_mode(&mut self, mode: InputMode) {
+        self.modes_below.clear();
+        self.input_mode = mode;
+    }
+
+    /// Open a mode on top of the current one, which Esc comes back to.
+    fn open_mode(&mut self, mode: InputMode) {
+        let below = std::mem::replace(&mut self.input_mode, mode);
+        self.modes_below.push(below);
+    }
+
+    /// Go back to the mode the current one was opened on top of, or the issue list.
+    fn close_mode(&mut self) {
+        self.input_mode = self.modes_below.pop().unwrap_or(InputMode::IssuesList);
+    }
+
+    /// The modes to draw, from the bottom up, ending with the current one.
+    pub fn layers(&self) -> Vec<InputMode> {
+        if let InputMode::IssuesList = self.input_mode {
+            return vec![InputMode::IssuesList];
+        }
+        let mut layers = self.modes_below.clone();
+        layers.push(self.input_mode.clone());
+        layers
+    }
+
+    /// Offer the selected issue's transitions on top of the current view.
+    async fn open_transition_picker(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let picker = self.preflight(Picker::Transitions, event_tx.clone(), jira.clone());
+        if picker.await {
+            self.load_transitions(event_tx, jira).await;
+            self.open_mode(InputMode::UpdateIssueStatus);
+        }
+    }
+
+    /// Close the transition picker and the question over it, once a transition's been made.
+    fn close_transition_picker(&mut self) {
+        while let InputMode::UpdateIssueStatus | InputMode::ConfirmTransition = self.input_mode {
+            self.close_mode();
+        }
+    }
+
+    /// Close both steps of the link form, once the link's been made.
+    fn close_link_form(&mut self) {
+        while let InputMode::PickingLinkType | InputMode::LinkingIssue = self.input_mode {
+            self.close_mode();
+        }
+    }
+
+    /// What's asked before making the transition selected in the picker, if it's one to confirm.
+    pub fn transition_question(&self) -> Option<String> {
+        let selected = self.transitions.state.selected()?;
+        let transition = self.transitions.items.get(selected)?;
+        let name = transition.name.as_str();
+        let confirm = &self.config.confirm_transitions;
+        if !confirm.iter().any(|c| c.eq_ignore_ascii_case(name)) {
+            return None;
+        }
+        let key = self.selected_issue_key()?;
+        Some(format!("Move {} to {}?", key, transition.name))
+    }
+
+    /// List the selected issue's transitions, reusing those fetched recently for an issue in the
+    /// same project and status, or else fetching them.
+    async fn load_transitions(&mut self, event_tx: EventsTx, jira: JiraClient) {
+        let ttl = Duration::from_secs(TRANSITIONS_TTL_SECS);
+        let workflow = self.workflow();
+        let cached = workflow.and_then(|workflow| self.transition_cache.get(&workflow));
+        match cached.filter(|cached| cached.fetched.elapsed() < ttl) {
+            Some(cached) => {
+                self.transitions = StatefulList::with_items(cached.transitions.clone());
+                self.transitions.next();
+            }
+            None => fetch_transitions(event_tx, jira, self.clone()).await,
+        }
+    }
+
+    /// The smart commit message as it stands.
+    pub fn smart_commit_text(&self) -> Option<String> {
+        let key = self.selected_issue_key()?;
+        let form = &self.smart_commit;
+        let transition = form
+            .transition
+            .and_then(|i| self.transitions.items.get(i))
+            .map(|transition| transition.name.as_str());
+        Some(smart_commit(&key, &form.time, &form.comment, transition))
+    }
+
+    /// Suggest reviewers for a PR for the selected issue. Its description is copied or the PR
+    /// opened once they're picked. Without anyone to suggest, the description is copied straight
+    /// away unless there's a GitHub token to open the PR with.
+    async fn start_pr_description(&mut self, event_tx: EventsTx) {
+        if let Some(key) = self.selected_issue_key() {
+            let draft = self.config.draft_pull_requests;
+            self.reviewer_picker = ReviewerPicker::new(draft);
+            suggest_reviewers(event_tx, key, self.project_repo()).await;
+        }
+    }
+
+    /// Copy a PR description generated from the selected issue to the clipboard, naming the
+    /// reviewers picked.
+    fn copy_pr_description(&mut self) {
+        let issue = match self.selected_issue() {
+            Some(issue) => issue,
+            None => return,
+        };
+        let reviewers = self.reviewer_picker.reviewers();
+        let description = pr_description(issue, &reviewers, self.config.pr_template.as_deref());
+        let key = issue.key.clone();
+        match write_clipboard(&description) {
+            Ok(()) => self.notice = Some(format!("Copied a PR description for {}", key)),
+            Err(e) => self.error = Some(e.to_string()),
+        }
+    }
+}
+
+/// Bindings are fixed in the match arms below. A key bound twice in one mode only gets an
+/// `unreachable_patterns` warning, and none at all when the first arm has a guard, so the build
+/// doesn't stop it. The issue list's keys are in a table instead, checked for keys bound twice
+/// when starting up. Keep `ui::mode_help` in step when changing them.
+async fn handle_input(
+    state: &mut State,
+    key: KeyEvent,
+    event_tx: EventsTx,
+    jira: JiraClient,
+) -> Result<()> {
+    // Chords are handled the same way in every mode, and aren't typed into text inputs
+    if key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+        return handle_chord(state, key, event_tx, jira).await;
+    }
+    match key_action(state, key.code) {
+        Some(action) => apply_action(state, action, event_tx, jira).await,
+        None => Ok(()),
+    }
+}
+
+/// Keys pressed with Ctrl or Alt held.
+async fn handle_chord(
+    state: &mut State,
+    key: KeyEvent,
+    event_tx: EventsTx,
+    jira: JiraClient,
+) -> Result<()> {
+    if !key.modifiers.contains(KeyModifiers::CONTROL) {
+        return Ok(());
+    }
+    match chord_action(state, key.code) {
+        Some(action) => apply_action(state, action, event_tx, jira).await,
+        None => Ok(()),
+    }
+}
+
+/// Check out the selected branch, or start naming a new one from the row for that.
+fn use_selected_branch(state: &mut State) -> Result<()> {
+    if let Some(offer) = state.missing_repo() {
+        state.clone_offer = Some(offer);
+        return Ok(());
+    }
+    match state.selected_branch() {
+        Some(BranchEntry::CreateNew) => {
+            // Most issues need just the one branch, so a second is checked before it's made
+            let entries = &state.branches.items;
+            let existing = entries.iter().find_map(BranchEntry::existing);
+            match existing.map(|branch| branch.name.clone()) {
+                Some(name) => state.existing_branch = Some(name),
+                None => state.open_mode(InputMode::Editing),
+            }
+        }
+        Some(BranchEntry::Existing(branch)) => use_branch(state, branch.name)?,
+        None => {}
+    }
+
+    Ok(())
+}
+
+/// Make a branch, or start a taken one over when overwriting, then check it out and finish.
+fn create_branch(
+    state: &mut State,
+    repo: &Repository,
+    branch: String,
+    overwrite: bool,
+) -> Result<()> {
+    let previous = head_commit(repo);
+    let made = if overwrite {
+        overwrite_and_use_branch(repo, branch.clone()).map(|_| true)
+    } else {
+        create_and_use_branch(repo, branch.clone())
+    };
+    match made {
+        Ok(created) => {
+            if created && state.config.initial_empty_commit {
+                let key = state.selected_issue_key().unwrap_or_default();
+                let message = format!("{}: start work", key);
+                if let Err(err) = commit_empty(repo, &message) {
+                    state.error = Some(format!("Couldn't make the first commit: {:#}", err));
+                    return Ok(());
+                }
+            }
+            let checked_out = state.checked_out_branch(repo, branch, created, previous);
+            state.checked_out = Some(checked_out);
+            bail!("Done!")
+        }
+        Err(err) => state.error = Some(format!("Couldn't make {}: {:#}", branch, err)),
+    }
+
+    Ok(())
+}
+
+/// Check out an existing branch and finish, unless it's checked out already.
+fn use_branch(state: &mut State, name: String) -> Result<()> {
+    let repo = state.issue_repo()?;
+    if current_branch(&repo).as_ref() == Some(&name) {
+        state.notice = Some(format!("Already on {}", name));
+        return Ok(());
+    }
+    let previous = head_commit(&repo);
+    match checkout_branch(&repo, name.clone()) {
+        Ok(_) => {
+            let checked_out = state.checked_out_branch(&repo, name, false, previous);
+            state.checked_out = Some(checked_out);
+            bail!("Done!")
+        }
+        Err(e) => println!("Error setting branch: {:?}", e),
+    }
+
+    Ok(())
+}
+
+mod action;
+mod reducer;
+#[cfg(test)]
+mod tests;
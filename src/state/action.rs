@@ -0,0 +1,563 @@
+//! What can be done with keys, apart from the keys that do it. Keys are looked up in the keymap
+//! for the prompt or view that's open to get an `Action`, and `reducer::apply_action` carries it
+//! out, so anything else that acts on the app, like tests, goes through the same code as a key
+//! press.
+//!
+//! The issue list's keys can be moved with `Config::keys`, and `keymap_problems` reports the ones
+//! that end up clashing.
+use super::*;
+
+/// Something done with a key, in the issue list or any of the other views and prompts. Generic
+/// ones like `Next`, `Select` and `Close` are carried out by whichever view is open.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    /// Stop a long search, keeping what's been loaded so far.
+    StopLoading,
+    /// Mark the selected issue, or toggle the selected reviewer.
+    ToggleMark,
+    OpenSortMenu,
+    ExportIssues,
+    OpenViewPicker,
+    EditJql,
+    /// The default board's active sprint, or its backlog. From the boards view it's the
+    /// highlighted board's.
+    OpenSprint {
+        backlog: bool,
+    },
+    PickSprint,
+    ToggleEpicFold,
+    CycleChips {
+        forward: bool,
+    },
+    RemoveChip,
+    AssignIssue,
+    SetPriority,
+    EditLabels,
+    ToggleWatching,
+    AccountInfo,
+    AuditBranches,
+    CopyPrDescription,
+    SmartCommit,
+    ToggleTimer,
+    Snooze,
+    ActivityFeed,
+    SnoozedIssues,
+    RenameIssue,
+    BulkEdit,
+    ToggleAllProjects,
+    BoardsList,
+    EditProjectKey,
+    SwitchProject,
+    IssueLinks,
+    LinkIssue,
+    ToggleInProgress,
+    JumpToCopiedIssue,
+    ToggleMine,
+    OpenInBrowser,
+    Quit,
+    /// Suspend the app to the shell, on Unix.
+    Suspend,
+    /// Fetch what the view shows again.
+    Refresh,
+    /// Refetch the issue list, forgetting everything cached about it.
+    HardRefresh,
+    PickTransition,
+    IssueDetail,
+    Comments,
+    /// Move from the issue to its branches or use the selected branch, pick from a list or
+    /// submit what's been typed.
+    Select,
+    FocusBranches,
+    /// Move from the branches back to the issues, or unselect the issue.
+    Back,
+    /// Move down a list or a form, or the cursor down a line in an editor.
+    Next,
+    Previous,
+    /// Make the transition from `Config::transition_shortcuts` for the key.
+    TransitionShortcut(char),
+    /// Leave the view or prompt, going back to where it was opened from.
+    Close,
+    /// Answer yes to a prompt.
+    Confirm,
+    /// Answer no to a prompt, which isn't always the same as closing it.
+    Decline,
+    Type(char),
+    DeleteBack,
+    /// Delete the selected saved view or label.
+    Delete,
+    NewLine,
+    /// Move the cursor in an editor, or change the value of a form's row.
+    Left,
+    Right,
+    PageUp,
+    PageDown,
+    /// Pick the numbered entry of a menu, counting from zero.
+    PickItem(usize),
+    /// Filter the boards, or search the detail view.
+    Search,
+    ToggleFavorite,
+    ToggleBacklog,
+    AddComment,
+    PostComment,
+    LinkPullRequest,
+    CreateSubtask,
+    Attachments,
+    UploadAttachment,
+    LogWork,
+    EditDescription,
+    /// Go back to editing the description instead of saving it.
+    KeepEditing,
+    OpenParent,
+    /// The next or previous issue in the list, from the detail view.
+    OpenSibling {
+        forward: bool,
+    },
+    NextMatch {
+        forward: bool,
+    },
+    ToggleChecklistFocus,
+    ToggleChecklistItem,
+    SaveView,
+    ToggleDraft,
+    OpenPullRequest,
+    /// Hide an issue from the activity feed.
+    MuteIssue,
+    /// Hide a kind of event from the activity feed.
+    MuteEventType,
+    ClearMutes,
+    /// Start a taken branch name over.
+    Overwrite,
+    /// Make the branch with a suffix the name doesn't have yet.
+    PickUnusedName,
+}
+
+/// The action for a key in whichever prompt or view is open. Prompts come first since they're
+/// shown over the view and take its keys.
+pub(super) fn key_action(state: &State, input: KeyCode) -> Option<Action> {
+    if state.reauth_input.is_some() {
+        return text_action(input);
+    }
+    if state.stopped_timer.is_some() || state.clone_offer.is_some() {
+        return question_action(input);
+    }
+    if state.existing_branch.is_some() {
+        return match input {
+            KeyCode::Char('c') | KeyCode::Enter => Some(Action::Confirm),
+            KeyCode::Char('n') => Some(Action::Decline),
+            KeyCode::Esc => Some(Action::Close),
+            _ => None,
+        };
+    }
+    if state.branch_collision.is_some() {
+        return match input {
+            KeyCode::Char('c') | KeyCode::Enter => Some(Action::Confirm),
+            KeyCode::Char('f') => Some(Action::Overwrite),
+            KeyCode::Char('s') => Some(Action::PickUnusedName),
+            // Back to the name to change it
+            KeyCode::Esc => Some(Action::Close),
+            _ => None,
+        };
+    }
+
+    match state.input_mode {
+        InputMode::IssuesList => issue_list_action(state, input),
+        InputMode::BoardsList => match input {
+            KeyCode::Char('f') => Some(Action::ToggleFavorite),
+            KeyCode::Char('n') => Some(Action::OpenSprint { backlog: false }),
+            KeyCode::Char('B') => Some(Action::OpenSprint { backlog: true }),
+            KeyCode::Char('/') => Some(Action::Search),
+            KeyCode::Char('o') => Some(Action::OpenInBrowser),
+            _ => list_action(input),
+        },
+        InputMode::UpdateIssueStatus | InputMode::AuditView => match input {
+            KeyCode::Char('r') => Some(Action::Refresh),
+            _ => list_action(input),
+        },
+        InputMode::ConfirmTransition => question_action(input),
+        InputMode::IssueDetail => match input {
+            KeyCode::Char('c') => Some(Action::AddComment),
+            KeyCode::Char('s') => Some(Action::PickTransition),
+            KeyCode::Char('P') => Some(Action::CopyPrDescription),
+            KeyCode::Char('Y') => Some(Action::SmartCommit),
+            KeyCode::Char('T') => Some(Action::ToggleTimer),
+            KeyCode::Char('L') => Some(Action::LinkPullRequest),
+            KeyCode::Char('a') => Some(Action::CreateSubtask),
+            KeyCode::Char('A') => Some(Action::Attachments),
+            KeyCode::Char('w') => Some(Action::LogWork),
+            KeyCode::Char('D') => Some(Action::EditDescription),
+            KeyCode::Char('g') => Some(Action::IssueLinks),
+            KeyCode::Char('K') => Some(Action::LinkIssue),
+            KeyCode::Char('u') => Some(Action::OpenParent),
+            KeyCode::Char(']') => Some(Action::OpenSibling { forward: true }),
+            KeyCode::Char('[') => Some(Action::OpenSibling { forward: false }),
+            KeyCode::Char('o') => Some(Action::OpenInBrowser),
+            KeyCode::Char('/') => Some(Action::Search),
+            KeyCode::Char('n') => Some(Action::NextMatch { forward: true }),
+            KeyCode::Char('N') => Some(Action::NextMatch { forward: false }),
+            KeyCode::Tab | KeyCode::BackTab => Some(Action::ToggleChecklistFocus),
+            KeyCode::Char(' ') => Some(Action::ToggleChecklistItem),
+            KeyCode::PageUp => Some(Action::PageUp),
+            KeyCode::PageDown => Some(Action::PageDown),
+            _ => list_action(input),
+        },
+        InputMode::IssueLinks
+        | InputMode::BulkEditAction
+        | InputMode::SortMenu
+        | InputMode::SnoozeMenu
+        | InputMode::PickingSprint
+        | InputMode::SettingPriority
+        | InputMode::PickingLinkType
+        | InputMode::BulkEditProgress
+        | InputMode::AccountInfo => list_action(input),
+        InputMode::Editing
+        | InputMode::BulkEditValue
+        | InputMode::EditingSummary
+        | InputMode::EditingJql
+        | InputMode::NamingView
+        | InputMode::CreatingSubtask
+        | InputMode::LinkingPullRequest
+        | InputMode::EditingDefaultProject
+        | InputMode::SearchingDetail => text_action(input),
+        InputMode::AssigningIssue
+        | InputMode::UploadingAttachment
+        | InputMode::LinkingIssue
+        | InputMode::FilteringBoards => filter_action(input),
+        InputMode::EditingDescription | InputMode::AddingComment => editor_action(input),
+        InputMode::ConfirmDescription => match input {
+            KeyCode::Char('e') => Some(Action::KeepEditing),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Enter => None,
+            _ => question_action(input),
+        },
+        // Digits pick from the menu straight away, so switching is two key presses
+        InputMode::SwitchingProject => menu_action(input).or_else(|| list_action(input)),
+        InputMode::PickingView => match input {
+            KeyCode::Char('s') => Some(Action::SaveView),
+            KeyCode::Delete => Some(Action::Delete),
+            _ => menu_action(input).or_else(|| list_action(input)),
+        },
+        InputMode::SprintView => match input {
+            KeyCode::Char('o') => Some(Action::OpenInBrowser),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            KeyCode::Char('B') => Some(Action::ToggleBacklog),
+            KeyCode::Char('M') => Some(Action::PickSprint),
+            _ => list_action(input),
+        },
+        InputMode::CommentsView => match input {
+            KeyCode::Char('c') => Some(Action::AddComment),
+            KeyCode::PageUp => Some(Action::PageUp),
+            KeyCode::PageDown => Some(Action::PageDown),
+            _ => list_action(input),
+        },
+        InputMode::EditingLabels => match input {
+            // Jira doesn't allow spaces in labels
+            KeyCode::Char(' ') => None,
+            KeyCode::Delete => Some(Action::Delete),
+            _ => filter_action(input),
+        },
+        InputMode::PickingReviewers => match input {
+            KeyCode::Char(' ') => Some(Action::ToggleMark),
+            KeyCode::Char('d') => Some(Action::ToggleDraft),
+            KeyCode::Char('o') => Some(Action::OpenPullRequest),
+            _ => list_action(input),
+        },
+        InputMode::SnoozedList => match input {
+            KeyCode::Char('u') => Some(Action::Select),
+            _ => list_action(input),
+        },
+        InputMode::LoggingWork | InputMode::SmartCommit => match input {
+            KeyCode::Tab => Some(Action::Next),
+            KeyCode::BackTab => Some(Action::Previous),
+            KeyCode::Left => Some(Action::Left),
+            KeyCode::Right => Some(Action::Right),
+            _ => filter_action(input),
+        },
+        InputMode::AttachmentsView => match input {
+            KeyCode::Char('s') => Some(Action::Select),
+            KeyCode::Char('u') => Some(Action::UploadAttachment),
+            _ => list_action(input),
+        },
+        InputMode::ActivityFeed => match input {
+            KeyCode::Char('x') => Some(Action::MuteIssue),
+            KeyCode::Char('t') => Some(Action::MuteEventType),
+            KeyCode::Char('c') => Some(Action::ClearMutes),
+            KeyCode::Char('r') => Some(Action::Refresh),
+            _ => list_action(input),
+        },
+    }
+}
+
+/// Keys for moving through a list, picking from it and closing it.
+fn list_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Down => Some(Action::Next),
+        KeyCode::Up => Some(Action::Previous),
+        KeyCode::Enter => Some(Action::Select),
+        KeyCode::Esc => Some(Action::Close),
+        _ => None,
+    }
+}
+
+/// Keys for typing a line of text, submitting it and closing it.
+fn text_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Char(c) => Some(Action::Type(c)),
+        KeyCode::Backspace => Some(Action::DeleteBack),
+        KeyCode::Enter => Some(Action::Select),
+        KeyCode::Esc => Some(Action::Close),
+        _ => None,
+    }
+}
+
+/// Keys for typing into a line with a list under it, to pick from what's typed.
+fn filter_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Down => Some(Action::Next),
+        KeyCode::Up => Some(Action::Previous),
+        _ => text_action(input),
+    }
+}
+
+/// Keys for a text editor, where Enter starts a new line and the arrows move the cursor.
+fn editor_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Enter => Some(Action::NewLine),
+        KeyCode::Left => Some(Action::Left),
+        KeyCode::Right => Some(Action::Right),
+        _ => filter_action(input),
+    }
+}
+
+/// Keys for a yes or no question. Esc closes it, which some prompts tell apart from no.
+fn question_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Char('y') | KeyCode::Enter => Some(Action::Confirm),
+        KeyCode::Char('n') => Some(Action::Decline),
+        KeyCode::Esc => Some(Action::Close),
+        _ => None,
+    }
+}
+
+/// Digits picking from a numbered menu.
+fn menu_action(input: KeyCode) -> Option<Action> {
+    match input {
+        KeyCode::Char(c) => {
+            let picked = c.to_digit(10).and_then(|n| (n as usize).checked_sub(1));
+            picked.map(Action::PickItem)
+        }
+        _ => None,
+    }
+}
+
+/// The action for a key pressed in the issue list, if any.
+pub(super) fn issue_list_action(state: &State, input: KeyCode) -> Option<Action> {
+    // Bound keys come first, so shortcuts can't take them over. `shadowed_shortcuts` finds the
+    // ones that lose out.
+    let keymap = issue_list_keymap(&state.config);
+    bound_action(&keymap, input).or_else(|| match input {
+        KeyCode::Char(c) if state.config.transition_shortcuts.contains_key(&c) => {
+            Some(Action::TransitionShortcut(c))
+        }
+        _ => None,
+    })
+}
+
+fn bound_action(keymap: &[Binding], input: KeyCode) -> Option<Action> {
+    let mut keys = keymap.iter();
+    keys.find(|binding| binding.key == input).map(|binding| binding.action)
+}
+
+/// A key of the issue list, and what it does.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Binding {
+    pub name: &'static str,
+    pub key: KeyCode,
+    pub action: Action,
+    /// Whether `Config::keys` moved it from its built in key.
+    pub rebound: bool,
+}
+
+/// The issue list's built in keys, by the names `Config::keys` rebinds them with.
+pub(super) const ISSUE_LIST_KEYS: &[(&str, KeyCode, Action)] = &[
+    ("stop_loading", KeyCode::Esc, Action::StopLoading),
+    ("toggle_mark", KeyCode::Char(' '), Action::ToggleMark),
+    ("open_sort_menu", KeyCode::Char('S'), Action::OpenSortMenu),
+    ("export_issues", KeyCode::Char('X'), Action::ExportIssues),
+    ("open_view_picker", KeyCode::Char('v'), Action::OpenViewPicker),
+    ("edit_jql", KeyCode::Char('/'), Action::EditJql),
+    ("open_sprint", KeyCode::Char('n'), Action::OpenSprint { backlog: false }),
+    ("open_backlog", KeyCode::Char('B'), Action::OpenSprint { backlog: true }),
+    ("pick_sprint", KeyCode::Char('M'), Action::PickSprint),
+    ("toggle_epic_fold", KeyCode::Char('f'), Action::ToggleEpicFold),
+    ("next_chip", KeyCode::Tab, Action::CycleChips { forward: true }),
+    ("previous_chip", KeyCode::BackTab, Action::CycleChips { forward: false }),
+    ("remove_chip", KeyCode::Char('x'), Action::RemoveChip),
+    ("assign_issue", KeyCode::Char('@'), Action::AssignIssue),
+    ("set_priority", KeyCode::Char('!'), Action::SetPriority),
+    ("edit_labels", KeyCode::Char('l'), Action::EditLabels),
+    ("toggle_watching", KeyCode::Char('O'), Action::ToggleWatching),
+    ("account_info", KeyCode::Char('I'), Action::AccountInfo),
+    ("audit_branches", KeyCode::Char('W'), Action::AuditBranches),
+    ("copy_pr_description", KeyCode::Char('P'), Action::CopyPrDescription),
+    ("smart_commit", KeyCode::Char('Y'), Action::SmartCommit),
+    ("toggle_timer", KeyCode::Char('T'), Action::ToggleTimer),
+    ("snooze", KeyCode::Char('z'), Action::Snooze),
+    ("activity_feed", KeyCode::Char('A'), Action::ActivityFeed),
+    ("snoozed_issues", KeyCode::Char('Z'), Action::SnoozedIssues),
+    ("rename_issue", KeyCode::Char('e'), Action::RenameIssue),
+    ("bulk_edit", KeyCode::Char('E'), Action::BulkEdit),
+    ("toggle_all_projects", KeyCode::Char('a'), Action::ToggleAllProjects),
+    ("boards_list", KeyCode::Char('b'), Action::BoardsList),
+    ("edit_project_key", KeyCode::Char('c'), Action::EditProjectKey),
+    ("switch_project", KeyCode::Char('w'), Action::SwitchProject),
+    ("issue_links", KeyCode::Char('g'), Action::IssueLinks),
+    ("link_issue", KeyCode::Char('K'), Action::LinkIssue),
+    ("toggle_in_progress", KeyCode::Char('i'), Action::ToggleInProgress),
+    ("jump_to_copied_issue", KeyCode::Char('j'), Action::JumpToCopiedIssue),
+    ("toggle_mine", KeyCode::Char('m'), Action::ToggleMine),
+    ("open_in_browser", KeyCode::Char('o'), Action::OpenInBrowser),
+    ("quit", KeyCode::Char('q'), Action::Quit),
+    ("refresh", KeyCode::Char('r'), Action::Refresh),
+    ("pick_transition", KeyCode::Char('s'), Action::PickTransition),
+    ("issue_detail", KeyCode::Char('d'), Action::IssueDetail),
+    ("comments", KeyCode::Char('C'), Action::Comments),
+    ("select", KeyCode::Enter, Action::Select),
+    ("focus_branches", KeyCode::Right, Action::FocusBranches),
+    ("back", KeyCode::Left, Action::Back),
+    ("next", KeyCode::Down, Action::Next),
+    ("previous", KeyCode::Up, Action::Previous),
+];
+
+/// The issue list's keys with `Config::keys` applied. Rebound keys are listed first, so they
+/// win over a built in key they land on.
+pub(super) fn issue_list_keymap(config: &Config) -> Vec<Binding> {
+    let bindings = ISSUE_LIST_KEYS.iter().map(|&(name, key, action)| {
+        match config.keys.get(name).and_then(|key| parse_key(key)) {
+            Some(rebound) => Binding { name, key: rebound, action, rebound: rebound != key },
+            None => Binding { name, key, action, rebound: false },
+        }
+    });
+    let (mut keymap, built_in): (Vec<_>, Vec<_>) = bindings.partition(|binding| binding.rebound);
+    keymap.extend(built_in);
+    keymap
+}
+
+/// A key as written in `Config::keys`, a single character or one of `Space`, `Enter`, `Esc`,
+/// `Tab`, `BackTab`, `Left`, `Right`, `Up` and `Down`.
+pub(super) fn parse_key(key: &str) -> Option<KeyCode> {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => return Some(KeyCode::Char(c)),
+        (None, _) => return None,
+        _ => {}
+    }
+    match key {
+        "Space" => Some(KeyCode::Char(' ')),
+        "Enter" => Some(KeyCode::Enter),
+        "Esc" => Some(KeyCode::Esc),
+        "Tab" => Some(KeyCode::Tab),
+        "BackTab" => Some(KeyCode::BackTab),
+        "Left" => Some(KeyCode::Left),
+        "Right" => Some(KeyCode::Right),
+        "Up" => Some(KeyCode::Up),
+        "Down" => Some(KeyCode::Down),
+        _ => None,
+    }
+}
+
+/// The other way round from `parse_key`.
+fn key_name(key: KeyCode) -> String {
+    match key {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        key => format!("{:?}", key),
+    }
+}
+
+/// Actions out of reach in the keymap, because a key before them in it takes their key. Each
+/// comes with the action that has the key.
+pub(super) fn unreachable_actions(keymap: &[Binding]) -> Vec<(Binding, Binding)> {
+    let mut unreachable = vec![];
+    for (i, binding) in keymap.iter().enumerate() {
+        if let Some(earlier) = keymap[..i].iter().find(|earlier| earlier.key == binding.key) {
+            unreachable.push((*binding, *earlier));
+        }
+    }
+    unreachable
+}
+
+/// Entries of `Config::keys` that don't bind anything, because there's no action by that name
+/// or the key can't be read.
+pub(super) fn unbound_keys(config: &Config) -> Vec<String> {
+    let known = |name: &String| ISSUE_LIST_KEYS.iter().any(|&(action, _, _)| action == name);
+    let entries = config.keys.iter();
+    entries
+        .filter(|&(name, key)| !known(name) || parse_key(key).is_none())
+        .map(|(name, key)| format!("{} = {:?}", name, key))
+        .collect()
+}
+
+/// Keys in `Config::transition_shortcuts` that a key of the issue list already takes, so
+/// pressing them never makes the transition.
+pub(super) fn shadowed_shortcuts(config: &Config) -> Vec<char> {
+    let keymap = issue_list_keymap(config);
+    let keys = config.transition_shortcuts.keys().copied();
+    keys.filter(|&c| bound_action(&keymap, KeyCode::Char(c)).is_some())
+        .collect()
+}
+
+/// What's wrong with the keys the config binds, shown when starting up so a key doesn't
+/// silently do nothing.
+pub(super) fn keymap_problems(config: &Config) -> Option<String> {
+    let mut problems = vec![];
+    let unbound = unbound_keys(config);
+    if !unbound.is_empty() {
+        problems.push(format!(
+            "Keys that don't bind anything, check the action names and keys: {}",
+            unbound.join(", ")
+        ));
+    }
+    let unreachable = unreachable_actions(&issue_list_keymap(config));
+    if !unreachable.is_empty() {
+        let clashes: Vec<String> = unreachable
+            .iter()
+            .map(|(lost, taken)| {
+                format!("{} ({} has {})", lost.name, taken.name, key_name(taken.key))
+            })
+            .collect();
+        problems.push(format!(
+            "Actions left without a key, bind them to others in keys: {}",
+            clashes.join(", ")
+        ));
+    }
+    let shadowed = shadowed_shortcuts(config);
+    if !shadowed.is_empty() {
+        let keys: Vec<String> = shadowed.iter().map(char::to_string).collect();
+        problems.push(format!(
+            "Transition shortcuts {} are built in keys and won't work, bind them to others in transition_shortcuts",
+            keys.join(", ")
+        ));
+    }
+    if problems.is_empty() {
+        None
+    } else {
+        Some(problems.join(" - "))
+    }
+}
+
+/// The action for a key pressed with Ctrl, these work the same in every mode apart from posting
+/// a comment.
+pub(super) fn chord_action(state: &State, input: KeyCode) -> Option<Action> {
+    match input {
+        // Raw mode turns Ctrl-C and Ctrl-Z into key presses rather than signals
+        KeyCode::Char('c') => Some(Action::Quit),
+        // Only Unix shells have job control to come back from this
+        KeyCode::Char('z') if cfg!(unix) => Some(Action::Suspend),
+        KeyCode::Char('r') if state.reauth_input.is_none() => Some(Action::HardRefresh),
+        // Enter starts a new line in a comment, so posting it takes a chord
+        KeyCode::Char('s') if matches!(state.input_mode, InputMode::AddingComment) => {
+            Some(Action::PostComment)
+        }
+        _ => None,
+    }
+}
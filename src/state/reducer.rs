@@ -0,0 +1,1482 @@
+//! Carrying out `Action`s. `action::key_action` finds the action for a key in whichever prompt
+//! or view is open, and `apply_action` is where it's done, so tests and anything else acting on
+//! the app go through the same code as a key press.
+use super::{action::Action, *};
+
+/// Carry out an action in the prompt or view that's open, as if its key had been pressed.
+pub(super) async fn apply_action(
+    state: &mut State,
+    action: Action,
+    event_tx: EventsTx,
+    jira: JiraClient,
+) -> Result<()> {
+    // The chords' actions work the same in every mode, even with a prompt open
+    match action {
+        Action::Quit => bail!("Just exiting early"),
+        Action::Suspend => {
+            state.suspend = true;
+            return Ok(());
+        }
+        // Relists branches once the issues arrive
+        Action::HardRefresh => {
+            state.issues_fetched = false;
+            state.restrictions.clear();
+            state.permissions.clear();
+            state.transition_cache.clear();
+            state.branch_index.clear();
+            state.prefetched.clear();
+            state.link_relations.clear();
+            fetch_tickets(event_tx, jira, state.clone()).await;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if let Some(token) = state.reauth_input.as_mut() {
+        match action {
+            Action::Select if !token.trim().is_empty() => {
+                let token = token.trim().to_string();
+                state.reauth_input = None;
+                match jira.reauthenticate(token) {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        state.error = Some(
+                            "New token applied for this session only, update your credentials to keep it"
+                                .to_string(),
+                        )
+                    }
+                    Err(e) => state.error = Some(format!("New token applied but not saved: {}", e)),
+                }
+                send_event(&event_tx, Event::ReauthFinished(true));
+            }
+            Action::Type(c) => token.push(c),
+            Action::DeleteBack => {
+                token.pop();
+            }
+            Action::Close => {
+                state.reauth_input = None;
+                send_event(&event_tx, Event::ReauthFinished(false));
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if let Some(timer) = state.stopped_timer.clone() {
+        match action {
+            Action::Confirm => {
+                // Leave the time to be discarded or carried on with
+                if let Some(refusal) = state.refusal(&timer.key, IssueAction::LogWork) {
+                    state.error = Some(refusal);
+                    return Ok(());
+                }
+                state.stopped_timer = None;
+                let seconds = timer.loggable_secs();
+                add_worklog(event_tx, jira.clone(), timer.key, seconds, None).await;
+            }
+            Action::Decline => state.stopped_timer = None,
+            // Carry on timing
+            Action::Close => {
+                state.stopped_timer = None;
+                state.timer = Some(timer);
+                let _ = save_timer(state.timer.as_ref());
+            }
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if let Some(offer) = state.clone_offer.clone() {
+        match action {
+            Action::Confirm => {
+                state.clone_offer = None;
+                state.notice = Some(format!("Cloning {}...", offer.url));
+                clone_project_repo(event_tx, offer).await;
+            }
+            Action::Decline | Action::Close => state.clone_offer = None,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if let Some(name) = state.existing_branch.clone() {
+        match action {
+            Action::Confirm => {
+                state.existing_branch = None;
+                use_branch(state, name)?;
+            }
+            Action::Decline => {
+                state.existing_branch = None;
+                state.open_mode(InputMode::Editing);
+            }
+            Action::Close => state.existing_branch = None,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    if let Some(name) = state.branch_collision.clone() {
+        match action {
+            Action::Confirm => {
+                state.branch_collision = None;
+                use_branch(state, name)?;
+            }
+            Action::Overwrite => {
+                state.branch_collision = None;
+                let repo = state.issue_repo()?;
+                create_branch(state, &repo, name, true)?;
+            }
+            Action::PickUnusedName => {
+                state.branch_collision = None;
+                let repo = state.issue_repo()?;
+                let name = unused_branch_name(&repo, &name);
+                create_branch(state, &repo, name, false)?;
+            }
+            Action::Close => state.branch_collision = None,
+            _ => {}
+        }
+        return Ok(());
+    }
+
+    match state.input_mode {
+        InputMode::IssuesList => apply_issue_list_action(state, action, event_tx, jira).await?,
+        InputMode::BoardsList => match action {
+            Action::Close => {
+                state.set_mode(InputMode::IssuesList);
+            }
+            Action::Select => {
+                state.choose_default_board();
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::ToggleFavorite => state.toggle_favorite_board(),
+            Action::OpenSprint { backlog } => {
+                if let Some(board) = state.highlighted_board() {
+                    state.open_sprint(board, backlog);
+                    fetch_sprint_view(event_tx, jira.clone(), state).await;
+                }
+            }
+            Action::Search => state.set_mode(InputMode::FilteringBoards),
+            Action::Next => {
+                state.boards.next();
+            }
+            Action::Previous => {
+                state.boards.previous();
+            }
+            Action::OpenInBrowser => state.open_selected_board(),
+            _ => {}
+        },
+        InputMode::UpdateIssueStatus => match action {
+            Action::Close => state.close_mode(),
+            Action::Next => {
+                state.transitions.next();
+            }
+            Action::Previous => {
+                state.transitions.previous();
+            }
+            Action::Select if state.transition_question().is_some() => {
+                state.open_mode(InputMode::ConfirmTransition);
+            }
+            Action::Select => {
+                do_selected_transition(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::Refresh => {
+                if let Some(workflow) = state.workflow() {
+                    state.transition_cache.remove(&workflow);
+                }
+                state.transitions = StatefulList::new();
+                fetch_transitions(event_tx, jira.clone(), state.clone()).await;
+            }
+            _ => {}
+        },
+        InputMode::ConfirmTransition => match action {
+            Action::Confirm => {
+                do_selected_transition(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::Decline | Action::Close => state.close_mode(),
+            _ => {}
+        },
+        InputMode::IssueDetail => match action {
+            Action::AddComment => state.start_comment(),
+            Action::PickTransition => state.open_transition_picker(event_tx, jira.clone()).await,
+            Action::CopyPrDescription => state.start_pr_description(event_tx).await,
+            Action::SmartCommit => state.open_smart_commit(event_tx, jira.clone()).await,
+            Action::ToggleTimer => state.toggle_timer(),
+            Action::LinkPullRequest => {
+                state.input.clear();
+                state.open_mode(InputMode::LinkingPullRequest);
+            }
+            Action::CreateSubtask => {
+                state.input.clear();
+                state.open_mode(InputMode::CreatingSubtask);
+            }
+            Action::Attachments if state.selected_issue_key().is_some() => {
+                state.attachments = StatefulList::new();
+                state.open_mode(InputMode::AttachmentsView);
+                fetch_attachments(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::LogWork => {
+                if state.refused(IssueAction::LogWork) {
+                    return Ok(());
+                }
+                if let Some(key) = state.selected_issue_key() {
+                    state.worklog = Some(WorklogForm::new(key));
+                    state.open_mode(InputMode::LoggingWork);
+                }
+            }
+            Action::EditDescription => {
+                let picker = state.preflight(Picker::Description, event_tx.clone(), jira.clone());
+                if !picker.await {
+                    return Ok(());
+                }
+                if let Some(key) = state.selected_issue_key() {
+                    fetch_description(event_tx, jira.clone(), key).await;
+                }
+            }
+            Action::IssueLinks => state.open_links(event_tx, jira.clone()).await,
+            Action::LinkIssue => state.open_link_form(event_tx, jira.clone()).await,
+            Action::OpenParent => {
+                let parent = state.selected_issue().and_then(|issue| issue.parent.clone());
+                if let (Some(parent), Some(key)) = (parent, state.selected_issue_key()) {
+                    state.breadcrumbs.push(key);
+                    fetch_issue(event_tx, jira.clone(), parent).await;
+                }
+            }
+            Action::OpenSibling { forward } => {
+                let offset = if forward { 1 } else { -1 };
+                let current = state.selected_issue_key();
+                if let (Some(sibling), Some(key)) = (state.sibling(offset), current) {
+                    if sibling.key != key {
+                        state.breadcrumbs.push(key);
+                        state.jump_to_issue(sibling);
+                        state.load_checklist();
+                        find_relevant_branches(event_tx.clone(), state.clone()).await;
+                        fetch_comments(event_tx, jira.clone(), state.clone()).await;
+                    }
+                }
+            }
+            Action::Close if state.detail_search.is_some() => state.detail_search = None,
+            Action::Close if !state.breadcrumbs.is_empty() => {
+                // Walk back along the trail, skipping anything no longer in the list
+                while let Some(key) = state.breadcrumbs.pop() {
+                    if state.return_to_issue(&key) {
+                        find_relevant_branches(event_tx.clone(), state.clone()).await;
+                        fetch_issue_details(event_tx, jira.clone(), state).await;
+                        break;
+                    }
+                }
+            }
+            Action::Close => {
+                state.branches.unselect();
+                state.issues_focused = true;
+                state.set_mode(InputMode::IssuesList);
+                if !state.issues_fetched {
+                    fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+                }
+            }
+            Action::OpenInBrowser => {
+                if let Some(link) = state.selected_issue_permalink() {
+                    open_link(&link);
+                }
+            }
+            // Paging up past the top loads older comments, when there are any
+            Action::PageUp if state.detail_scroll == 0 => {
+                state.stop_search_jump();
+                if state.older_comments.unwrap_or(0) > 0 {
+                    fetch_comments(event_tx, jira.clone(), state.clone()).await;
+                }
+            }
+            Action::PageUp => {
+                state.stop_search_jump();
+                state.detail_scroll = state.detail_scroll.saturating_sub(DETAIL_PAGE_LINES);
+            }
+            Action::PageDown => {
+                state.stop_search_jump();
+                state.detail_scroll =
+                    (state.detail_scroll + DETAIL_PAGE_LINES).min(state.detail_line_count());
+            }
+            Action::Search => {
+                state.detail_search = Some(DetailSearch::default());
+                state.set_mode(InputMode::SearchingDetail);
+            }
+            Action::NextMatch { forward } => {
+                if let Some(search) = state.detail_search.as_mut() {
+                    search.current += if forward { 1 } else { -1 };
+                    search.jump = true;
+                }
+            }
+            Action::ToggleChecklistFocus if !state.checklist.items.is_empty() => {
+                state.checklist_focused = !state.checklist_focused;
+                if state.checklist_focused && state.checklist.state.selected().is_none() {
+                    state.checklist.next();
+                }
+            }
+            Action::ToggleChecklistItem if state.checklist_focused => {
+                if let Some((key, field)) = state.toggle_selected_checklist_item() {
+                    save_text_field(event_tx, jira.clone(), key, field).await;
+                }
+            }
+            Action::Next => {
+                if state.checklist_focused {
+                    state.checklist.next();
+                } else {
+                    state.branches.next();
+                }
+            }
+            Action::Previous => {
+                if state.checklist_focused {
+                    state.checklist.previous();
+                } else {
+                    state.branches.previous();
+                }
+            }
+            Action::Select if !state.checklist_focused => use_selected_branch(state)?,
+            _ => {}
+        },
+        InputMode::IssueLinks => match action {
+            Action::Close => {
+                state.links_view = None;
+                state.close_mode();
+            }
+            Action::Next => {
+                if let Some(view) = state.links_view.as_mut() {
+                    view.links.next();
+                }
+            }
+            Action::Previous => {
+                if let Some(view) = state.links_view.as_mut() {
+                    view.links.previous();
+                }
+            }
+            Action::Select => state.jump_to_link(event_tx, jira.clone()).await,
+            _ => {}
+        },
+        InputMode::Editing => match action {
+            Action::Select => {
+                if let Ok(repo) = state.issue_repo() {
+                    let branch = state.new_branch_name();
+                    // Reusing the name would check out whatever is on that branch already
+                    if branch_exists(&repo, &branch) {
+                        state.branch_collision = Some(branch);
+                    } else {
+                        create_branch(state, &repo, branch, false)?;
+                    }
+                }
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::BulkEditAction => match action {
+            Action::Close => {
+                state.close_mode();
+            }
+            Action::Next => {
+                state.bulk_actions.next();
+            }
+            Action::Previous => {
+                state.bulk_actions.previous();
+            }
+            Action::Select => {
+                state.input.clear();
+                state.open_mode(InputMode::BulkEditValue);
+            }
+            _ => {}
+        },
+        InputMode::BulkEditValue => match action {
+            Action::Select => {
+                if let Some(edit) = state.bulk_edit() {
+                    let issues: Vec<(String, String)> = state
+                        .issues
+                        .items
+                        .iter()
+                        .filter(|issue| state.marked.contains(&issue.key))
+                        .map(|issue| (issue.key.clone(), issue.updated.clone()))
+                        .collect();
+                    let progress = BulkProgress::new(issues.iter().map(|(key, _)| key.clone()));
+                    let cancel = progress.cancel.clone();
+                    state.bulk_progress = Some(progress);
+                    state.open_mode(InputMode::BulkEditProgress);
+                    run_bulk_edit(event_tx, jira.clone(), issues, edit, cancel).await;
+                }
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::BulkEditProgress => {
+            let finished = state
+                .bulk_progress
+                .as_ref()
+                .is_none_or(BulkProgress::finished);
+            if !finished && action == Action::Close {
+                if let Some(progress) = &state.bulk_progress {
+                    progress.cancel.store(true, Ordering::Relaxed);
+                }
+            } else if finished && action == Action::Close {
+                state.bulk_progress = None;
+                state.marked.clear();
+                state.input.clear();
+                state.set_mode(InputMode::IssuesList);
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+        }
+        InputMode::EditingDescription => {
+            if let Some(edit) = state.description_edit.as_mut() {
+                match action {
+                    Action::Type(c) => edit.editor.insert(c),
+                    Action::NewLine => edit.editor.newline(),
+                    Action::DeleteBack => edit.editor.backspace(),
+                    Action::Left => edit.editor.left(),
+                    Action::Right => edit.editor.right(),
+                    Action::Previous => edit.editor.up(),
+                    Action::Next => edit.editor.down(),
+                    Action::Close => {
+                        if edit.editor.text() == edit.original {
+                            state.description_edit = None;
+                            state.set_mode(InputMode::IssueDetail);
+                        } else {
+                            state.set_mode(InputMode::ConfirmDescription);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        InputMode::ConfirmDescription => match action {
+            Action::Confirm => {
+                if let Some(edit) = state.description_edit.clone() {
+                    save_description(event_tx, jira.clone(), edit).await;
+                }
+            }
+            Action::KeepEditing => {
+                state.set_mode(InputMode::EditingDescription);
+            }
+            Action::Refresh => {
+                if let Some(edit) = &state.description_edit {
+                    fetch_description(event_tx, jira.clone(), edit.key.clone()).await;
+                }
+            }
+            Action::Decline | Action::Close => {
+                state.description_edit = None;
+                state.set_mode(InputMode::IssueDetail);
+            }
+            _ => {}
+        },
+        InputMode::EditingSummary => match action {
+            Action::Select => {
+                let summary = state.input.trim().to_string();
+                state.close_mode();
+                if let Some(i) = state.issues.state.selected() {
+                    let issue = &mut state.issues.items_mut()[i];
+                    if !summary.is_empty() && summary != issue.summary {
+                        let previous = std::mem::replace(&mut issue.summary, summary.clone());
+                        let (key, updated) = (issue.key.clone(), issue.updated.clone());
+                        save_summary(event_tx, jira.clone(), key, updated, previous, summary)
+                            .await;
+                    }
+                }
+                state.input.clear();
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::SortMenu => match action {
+            Action::Close => {
+                state.close_mode();
+            }
+            Action::Next => {
+                state.sort_menu.next();
+            }
+            Action::Previous => {
+                state.sort_menu.previous();
+            }
+            Action::Select => {
+                state.close_mode();
+                match state.sort_menu.state.selected() {
+                    Some(0) => {
+                        state.config.sort_preset = SortPreset::Jira;
+                        // The original order is gone once sorted, so fetch it again
+                        fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+                    }
+                    Some(1) => {
+                        state.config.sort_preset = SortPreset::Triage;
+                        state.sort_issues();
+                    }
+                    Some(2) => {
+                        state.config.priority_colors = !state.config.priority_colors;
+                    }
+                    Some(3) => {
+                        state.config.group_by_epic = !state.config.group_by_epic;
+                        let triage = state.config.sort_preset == SortPreset::Triage;
+                        if state.config.group_by_epic || triage {
+                            state.sort_issues();
+                        } else {
+                            // Like going back to Jira order, it has to be fetched again
+                            fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+                        }
+                    }
+                    _ => {}
+                }
+                let _ = save_config(&state.config);
+            }
+            _ => {}
+        },
+        InputMode::SnoozeMenu => match action {
+            Action::Close => {
+                state.close_mode();
+            }
+            Action::Next => {
+                state.snooze_menu.next();
+            }
+            Action::Previous => {
+                state.snooze_menu.previous();
+            }
+            Action::Select => {
+                state.snooze_selected_issue();
+                state.close_mode();
+                find_relevant_branches(event_tx, state.clone()).await;
+            }
+            _ => {}
+        },
+        InputMode::SwitchingProject => {
+            let picked = match action {
+                Action::Select => state.project_menu.state.selected(),
+                Action::PickItem(i) => Some(i),
+                Action::Next => {
+                    state.project_menu.next();
+                    None
+                }
+                Action::Previous => {
+                    state.project_menu.previous();
+                    None
+                }
+                Action::Close => {
+                    state.close_mode();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(key) = picked.and_then(|i| state.project_menu.items.get(i).cloned()) {
+                state.config.switch_project(key);
+                if let Err(err) = save_config(&state.config) {
+                    state.error = Some(err.to_string());
+                }
+                state.close_mode();
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+        }
+        InputMode::SprintView => {
+            let view = match &mut state.sprint {
+                Some(view) => view,
+                None => return Ok(()),
+            };
+            match action {
+                Action::Next => view.issues.next(),
+                Action::Previous => view.issues.previous(),
+                Action::OpenInBrowser => {
+                    if let Some(issue) = view.selected_issue() {
+                        open_link(&issue.permalink);
+                    }
+                }
+                Action::Refresh => {
+                    view.loaded = false;
+                    fetch_sprint_view(event_tx, jira.clone(), state).await;
+                }
+                Action::ToggleBacklog => {
+                    view.toggle_backlog();
+                    fetch_sprint_view(event_tx, jira.clone(), state).await;
+                }
+                Action::PickSprint => {
+                    let board_id = view.board.id;
+                    if let Some(key) = view.selected_issue().map(|issue| issue.key.clone()) {
+                        state.open_sprint_picker(key, board_id);
+                        fetch_open_sprints(event_tx, jira.clone(), board_id).await;
+                    }
+                }
+                Action::Close => {
+                    state.sprint = None;
+                    state.close_mode();
+                }
+                _ => {}
+            }
+        }
+        InputMode::PickingSprint => {
+            let picker = match &mut state.sprint_picker {
+                Some(picker) => picker,
+                None => return Ok(()),
+            };
+            match action {
+                Action::Next => picker.sprints.next(),
+                Action::Previous => picker.sprints.previous(),
+                Action::Select => {
+                    let key = picker.key.clone();
+                    let selected = picker.sprints.state.selected();
+                    let sprint = selected.and_then(|i| picker.sprints.items.get(i)).cloned();
+                    state.sprint_picker = None;
+                    state.close_mode();
+                    if let Some(sprint) = sprint {
+                        move_to_sprint(event_tx, jira.clone(), key, sprint.id, sprint.name).await;
+                    }
+                }
+                Action::Close => {
+                    state.sprint_picker = None;
+                    state.close_mode();
+                }
+                _ => {}
+            }
+        }
+        InputMode::PickingView => {
+            let picked = match action {
+                Action::Select => state.view_menu.state.selected(),
+                Action::SaveView => {
+                    state.input.clear();
+                    state.open_mode(InputMode::NamingView);
+                    None
+                }
+                Action::PickItem(i) => Some(i),
+                Action::Delete => {
+                    state.delete_selected_view();
+                    None
+                }
+                Action::Next => {
+                    state.view_menu.next();
+                    None
+                }
+                Action::Previous => {
+                    state.view_menu.previous();
+                    None
+                }
+                Action::Close => {
+                    state.close_mode();
+                    None
+                }
+                _ => None,
+            };
+            if let Some(view) = picked.and_then(|i| state.view_menu.items.get(i).cloned()) {
+                state.config.apply_view(&view);
+                if let Err(err) = save_config(&state.config) {
+                    state.error = Some(err.to_string());
+                }
+                state.chip_focus = None;
+                state.close_mode();
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+        }
+        InputMode::EditingJql => match action {
+            Action::Select => {
+                state.apply_jql();
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::Type(c) => state.input.push(c),
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::NamingView => match action {
+            Action::Select => state.save_view(),
+            Action::Type(c) => state.input.push(c),
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::CommentsView => match action {
+            Action::Next => {
+                state.comments_scroll = (state.comments_scroll + 1).min(state.comment_line_count());
+            }
+            Action::Previous => state.comments_scroll = state.comments_scroll.saturating_sub(1),
+            // Paging up past the top loads older comments, as in the detail view
+            Action::PageUp if state.comments_scroll == 0 && state.older_comments > Some(0) => {
+                fetch_comments(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::PageUp => {
+                state.comments_scroll = state.comments_scroll.saturating_sub(DETAIL_PAGE_LINES);
+            }
+            Action::PageDown => {
+                state.comments_scroll =
+                    (state.comments_scroll + DETAIL_PAGE_LINES).min(state.comment_line_count());
+            }
+            Action::AddComment => state.start_comment(),
+            Action::Close => state.set_mode(InputMode::IssuesList),
+            _ => {}
+        },
+        InputMode::AddingComment => {
+            if let Some(draft) = state.comment_draft.as_mut() {
+                match action {
+                    Action::Type(c) => draft.editor.insert(c),
+                    Action::NewLine => draft.editor.newline(),
+                    Action::DeleteBack => draft.editor.backspace(),
+                    Action::Left => draft.editor.left(),
+                    Action::Right => draft.editor.right(),
+                    Action::Previous => draft.editor.up(),
+                    Action::Next => draft.editor.down(),
+                    Action::PostComment => {
+                        let posting =
+                            |draft: &mut CommentDraft| !draft.editor.text().trim().is_empty();
+                        if let Some(draft) = state.comment_draft.take_if(posting) {
+                            state.close_mode();
+                            add_comment(event_tx, jira, draft.key, draft.editor.text()).await;
+                        }
+                    }
+                    Action::Close => {
+                        state.comment_draft = None;
+                        state.close_mode();
+                    }
+                    _ => {}
+                }
+            }
+        }
+        InputMode::CreatingSubtask => match action {
+            Action::Select => {
+                let summary = state.input.trim().to_string();
+                if let (Some(parent), false) = (state.selected_issue_key(), summary.is_empty()) {
+                    create_subtask(event_tx, jira.clone(), parent, summary).await;
+                }
+                state.input.clear();
+                state.close_mode();
+            }
+            Action::Type(c) => state.input.push(c),
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::LinkingPullRequest => match action {
+            Action::Select => {
+                let url = state.input.trim().to_string();
+                if let (Some(key), false) = (state.selected_issue_key(), url.is_empty()) {
+                    link_pull_request(event_tx, jira.clone(), key, url).await;
+                }
+                state.input.clear();
+                state.close_mode();
+            }
+            Action::Type(c) => state.input.push(c),
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::AccountInfo => {
+            if let Action::Close = action {
+                state.set_mode(InputMode::IssuesList);
+            }
+        }
+        InputMode::AuditView => match action {
+            Action::Close => {
+                state.set_mode(InputMode::IssuesList);
+            }
+            Action::Refresh => {
+                state.audit = None;
+                run_audit(event_tx, jira.clone()).await;
+            }
+            _ => {}
+        },
+        InputMode::SettingPriority => match action {
+            Action::Next => state.priorities.next(),
+            Action::Previous => state.priorities.previous(),
+            Action::Select => {
+                let selected = state.priorities.state.selected();
+                let name = selected.and_then(|i| state.priorities.items.get(i).cloned());
+                if let (Some(key), Some(name)) = (state.selected_issue_key(), name) {
+                    set_priority(event_tx, jira.clone(), key, name).await;
+                }
+                state.close_mode();
+            }
+            Action::Close => state.close_mode(),
+            _ => {}
+        },
+        InputMode::EditingLabels => match action {
+            Action::Next => state.labels.next(),
+            Action::Previous => state.labels.previous(),
+            Action::Type(c) => state.input.push(c),
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Select => {
+                let label = state.input.trim().to_string();
+                if let (Some(key), false) = (state.selected_issue_key(), label.is_empty()) {
+                    state.input.clear();
+                    edit_labels(event_tx, jira.clone(), key, BulkEdit::AddLabel(label)).await;
+                }
+            }
+            Action::Delete => {
+                let selected = state.labels.state.selected();
+                let label = selected.and_then(|i| state.labels.items.get(i).cloned());
+                if let (Some(key), Some(label)) = (state.selected_issue_key(), label) {
+                    edit_labels(event_tx, jira.clone(), key, BulkEdit::RemoveLabel(label)).await;
+                }
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::AssigningIssue => match action {
+            Action::Next => state.assignees.next(),
+            Action::Previous => state.assignees.previous(),
+            Action::Select => {
+                let assignee = state
+                    .assignees
+                    .state
+                    .selected()
+                    .and_then(|i| state.assignees.items.get(i).cloned());
+                if let (Some(key), Some(assignee)) = (state.selected_issue_key(), assignee) {
+                    assign_issue(event_tx, jira.clone(), key, assignee).await;
+                }
+                state.input.clear();
+                state.close_mode();
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+                state.search_assignees(event_tx, jira.clone()).await;
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+                state.search_assignees(event_tx, jira.clone()).await;
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::PickingReviewers => match action {
+            Action::Next => state.reviewer_picker.suggested.next(),
+            Action::Previous => state.reviewer_picker.suggested.previous(),
+            Action::ToggleMark => state.reviewer_picker.toggle(),
+            Action::ToggleDraft => state.reviewer_picker.draft = !state.reviewer_picker.draft,
+            Action::Select => {
+                state.close_mode();
+                state.copy_pr_description();
+            }
+            Action::OpenPullRequest => {
+                state.close_mode();
+                open_pull_request(event_tx, jira.clone(), state.clone()).await;
+            }
+            Action::Close => state.close_mode(),
+            _ => {}
+        },
+        InputMode::SnoozedList => match action {
+            Action::Close => {
+                state.set_mode(InputMode::IssuesList);
+            }
+            Action::Next => {
+                state.snoozed.next();
+            }
+            Action::Previous => {
+                state.snoozed.previous();
+            }
+            Action::Select => {
+                if let Some(i) = state.snoozed.state.selected() {
+                    state.snoozed.items_mut().remove(i);
+                    state.snoozed.unselect();
+                    state.snoozed.next();
+                    let _ = save_snoozed(&state.snoozed.items);
+                    // Bring the issue back into the list
+                    fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+                }
+            }
+            _ => {}
+        },
+        InputMode::LoggingWork => {
+            let form = match &mut state.worklog {
+                Some(form) => form,
+                None => return Ok(()),
+            };
+            let rows = WORKLOG_ROWS.len();
+            match action {
+                Action::Close => {
+                    state.worklog = None;
+                    state.close_mode();
+                }
+                // The form stays open to fix the time when it can't be read
+                Action::Select => match parse_duration(&form.time) {
+                    Ok(seconds) => {
+                        let key = form.key.clone();
+                        let comment = Some(form.comment.trim().to_string());
+                        let comment = comment.filter(|comment| !comment.is_empty());
+                        state.worklog = None;
+                        state.close_mode();
+                        add_worklog(event_tx, jira.clone(), key, seconds, comment).await;
+                    }
+                    Err(err) => state.error = Some(err.to_string()),
+                },
+                Action::Next => form.row = (form.row + 1) % rows,
+                Action::Previous => form.row = (form.row + rows - 1) % rows,
+                Action::Type(c) => form.field().push(c),
+                Action::DeleteBack => {
+                    form.field().pop();
+                }
+                _ => {}
+            }
+        }
+        InputMode::SmartCommit => {
+            let rows = SMART_COMMIT_ROWS.len();
+            let transitions = state.transitions.items.len();
+            let form = &mut state.smart_commit;
+            match action {
+                Action::Close => state.close_mode(),
+                Action::Select => {
+                    state.close_mode();
+                    if let Some(text) = state.smart_commit_text() {
+                        match write_clipboard(&text) {
+                            Ok(()) => state.notice = Some(format!("Copied: {}", text)),
+                            Err(e) => state.error = Some(e.to_string()),
+                        }
+                    }
+                }
+                Action::Next => form.row = (form.row + 1) % rows,
+                Action::Previous => form.row = (form.row + rows - 1) % rows,
+                // Cycle through no transition, then each one in turn
+                Action::Right if form.row == 2 => {
+                    form.transition = match form.transition {
+                        None if transitions > 0 => Some(0),
+                        Some(i) if i + 1 < transitions => Some(i + 1),
+                        _ => None,
+                    }
+                }
+                Action::Left if form.row == 2 => {
+                    form.transition = match form.transition {
+                        None => transitions.checked_sub(1),
+                        Some(0) => None,
+                        Some(i) => Some(i - 1),
+                    }
+                }
+                Action::Type(c) if form.row == 0 => form.time.push(c),
+                Action::Type(c) if form.row == 1 => form.comment.push(c),
+                Action::DeleteBack if form.row == 0 => {
+                    form.time.pop();
+                }
+                Action::DeleteBack if form.row == 1 => {
+                    form.comment.pop();
+                }
+                _ => {}
+            }
+        }
+        InputMode::AttachmentsView => match action {
+            Action::Close => {
+                state.close_mode();
+            }
+            Action::Next => {
+                state.attachments.next();
+            }
+            Action::Previous => {
+                state.attachments.previous();
+            }
+            Action::Select => {
+                let attachment = state
+                    .attachments
+                    .state
+                    .selected()
+                    .and_then(|i| state.attachments.items.get(i).cloned());
+                if let (Some(key), Some(attachment)) = (state.selected_issue_key(), attachment) {
+                    let dir = state.config.download_dir();
+                    state.notice = Some(format!("Downloading {}", attachment.filename));
+                    download_attachment(event_tx, jira.clone(), key, attachment, dir).await;
+                }
+            }
+            Action::UploadAttachment => {
+                state.input.clear();
+                state.list_local_files();
+                state.open_mode(InputMode::UploadingAttachment);
+            }
+            _ => {}
+        },
+        InputMode::UploadingAttachment => match action {
+            Action::Next => state.local_files.next(),
+            Action::Previous => state.local_files.previous(),
+            Action::Select => {
+                // A file picked from the list, or else the path as typed
+                let picked = state
+                    .local_files
+                    .state
+                    .selected()
+                    .and_then(|i| state.local_files.items.get(i).cloned());
+                let path = picked.unwrap_or_else(|| state.input.trim().to_string());
+                if let (Some(key), false) = (state.selected_issue_key(), path.is_empty()) {
+                    state.notice = Some(format!("Uploading {}", path));
+                    upload_attachment(event_tx, jira.clone(), key, PathBuf::from(path)).await;
+                }
+                state.input.clear();
+                state.close_mode();
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+                state.list_local_files();
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+                state.list_local_files();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::PickingLinkType => match action {
+            Action::Next => {
+                if let Some(form) = state.link_form.as_mut() {
+                    form.relations.next();
+                }
+            }
+            Action::Previous => {
+                if let Some(form) = state.link_form.as_mut() {
+                    form.relations.previous();
+                }
+            }
+            Action::Select => {
+                let form = state.link_form.as_ref();
+                if form.and_then(LinkForm::selected_relation).is_some() {
+                    state.input.clear();
+                    state.open_mode(InputMode::LinkingIssue);
+                }
+            }
+            Action::Close => {
+                state.link_form = None;
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::LinkingIssue => match action {
+            Action::Next => {
+                if let Some(form) = state.link_form.as_mut() {
+                    form.targets.next();
+                }
+            }
+            Action::Previous => {
+                if let Some(form) = state.link_form.as_mut() {
+                    form.targets.previous();
+                }
+            }
+            Action::Select => state.submit_link(event_tx, jira.clone()).await,
+            Action::Type(c) => {
+                state.input.push(c);
+                state.list_link_targets();
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+                state.list_link_targets();
+            }
+            Action::Close => {
+                state.input.clear();
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::ActivityFeed => match action {
+            Action::Close => {
+                state.set_mode(InputMode::IssuesList);
+            }
+            Action::Next => {
+                state.activity.next();
+            }
+            Action::Previous => {
+                state.activity.previous();
+            }
+            Action::MuteIssue => {
+                state.mute_selected_activity(false);
+            }
+            Action::MuteEventType => {
+                state.mute_selected_activity(true);
+            }
+            Action::ClearMutes => {
+                state.config.muted_issues.clear();
+                state.config.muted_event_types.clear();
+                let _ = save_config(&state.config);
+                fetch_activity(event_tx, jira.clone()).await;
+            }
+            Action::Refresh => {
+                fetch_activity(event_tx, jira.clone()).await;
+            }
+            Action::Select => {
+                let key = state
+                    .activity
+                    .state
+                    .selected()
+                    .and_then(|i| state.activity.items.get(i))
+                    .map(|entry| entry.key.clone());
+                if let Some(key) = key {
+                    state.set_mode(InputMode::IssuesList);
+                    fetch_issue(event_tx, jira.clone(), key).await;
+                }
+            }
+            _ => {}
+        },
+        InputMode::EditingDefaultProject => match action {
+            Action::Select => {
+                state.config.switch_project(state.input.to_string());
+                match save_config(&state.config) {
+                    Ok(_) => {
+                        state.close_mode();
+                        fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+                    }
+                    Err(e) => {
+                        state.input = e.to_string();
+                    }
+                }
+            }
+            Action::Type(c) => {
+                state.input.push(c);
+            }
+            Action::DeleteBack => {
+                state.input.pop();
+            }
+            Action::Close => {
+                state.close_mode();
+            }
+            _ => {}
+        },
+        InputMode::FilteringBoards => match action {
+            Action::Select => {
+                state.set_mode(InputMode::BoardsList);
+                // Only the first pages of boards are fetched, ask Jira for any others that match
+                if !state.board_filter.is_empty() {
+                    state.boards_filtered_by_jira = true;
+                    fetch_boards(event_tx, jira.clone(), state.clone()).await;
+                }
+            }
+            Action::Type(c) => {
+                state.board_filter.push(c);
+                state.filter_boards();
+            }
+            Action::DeleteBack => {
+                state.board_filter.pop();
+                state.filter_boards();
+            }
+            Action::Next => state.boards.next(),
+            Action::Previous => state.boards.previous(),
+            Action::Close => {
+                state.board_filter.clear();
+                state.filter_boards();
+                state.set_mode(InputMode::BoardsList);
+                if state.boards_filtered_by_jira {
+                    state.boards_filtered_by_jira = false;
+                    fetch_boards(event_tx, jira.clone(), state.clone()).await;
+                }
+            }
+            _ => {}
+        },
+        InputMode::SearchingDetail => match action {
+            Action::Select => {
+                match state.detail_search.as_mut() {
+                    Some(search) if !search.query.is_empty() => search.jump = true,
+                    _ => state.detail_search = None,
+                }
+                state.set_mode(InputMode::IssueDetail);
+            }
+            Action::Type(c) => {
+                if let Some(search) = state.detail_search.as_mut() {
+                    search.query.push(c);
+                }
+            }
+            Action::DeleteBack => {
+                if let Some(search) = state.detail_search.as_mut() {
+                    search.query.pop();
+                }
+            }
+            Action::Close => {
+                state.detail_search = None;
+                state.set_mode(InputMode::IssueDetail);
+            }
+            _ => {}
+        },
+    }
+
+    Ok(())
+}
+
+/// Carry out an action in the issue list.
+async fn apply_issue_list_action(
+    state: &mut State,
+    action: Action,
+    event_tx: EventsTx,
+    jira: JiraClient,
+) -> Result<()> {
+    match action {
+        Action::StopLoading => {
+            // Stop a long search, keeping what's been loaded so far
+            if let Some(search) = &state.issues_loading {
+                search.cancel.store(true, Ordering::Relaxed);
+            }
+        }
+        Action::ToggleMark => {
+            if let Some(key) = state.selected_issue_key() {
+                if !state.marked.remove(&key) {
+                    state.marked.insert(key);
+                }
+            }
+        }
+        Action::OpenSortMenu => {
+            state.open_sort_menu();
+        }
+        Action::ExportIssues => state.export_issues(),
+        Action::OpenViewPicker => state.open_view_picker(),
+        Action::EditJql => {
+            state.input = state.config.jql.clone().unwrap_or_default();
+            state.open_mode(InputMode::EditingJql);
+        }
+        Action::OpenSprint { backlog } => match state.config.default_board.clone() {
+            Some(board) => {
+                state.open_sprint(board, backlog);
+                fetch_sprint_view(event_tx, jira.clone(), state).await;
+            }
+            None => {
+                let notice = "Pick a board with b to see its sprint or backlog";
+                state.notice = Some(notice.to_string());
+            }
+        },
+        Action::PickSprint => {
+            let board = state.config.default_board.clone();
+            match (state.selected_issue_key(), board) {
+                (Some(key), Some(board)) => {
+                    state.open_sprint_picker(key, board.id);
+                    fetch_open_sprints(event_tx, jira.clone(), board.id).await;
+                }
+                (Some(_), None) => {
+                    let notice = "Pick a board with b to move issues into its sprints";
+                    state.notice = Some(notice.to_string());
+                }
+                (None, _) => {}
+            }
+        }
+        Action::ToggleEpicFold => {
+            state.toggle_epic_fold();
+            find_relevant_branches(event_tx, state.clone()).await;
+        }
+        Action::CycleChips { forward } => state.cycle_chips(forward),
+        Action::RemoveChip => {
+            let removed = state.remove_focused_chip();
+            if removed {
+                fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+            }
+        }
+        Action::AssignIssue => {
+            let picker = state.preflight(Picker::Assignee, event_tx, jira.clone());
+            if picker.await {
+                state.open_assignee_picker();
+            }
+        }
+        Action::SetPriority => {
+            let picker = state.preflight(Picker::Priority, event_tx.clone(), jira.clone());
+            if let (true, Some(key)) = (picker.await, state.selected_issue_key()) {
+                state.priorities = StatefulList::new();
+                state.open_mode(InputMode::SettingPriority);
+                fetch_priorities(event_tx, jira.clone(), key).await;
+            }
+        }
+        Action::EditLabels => {
+            let picker = state.preflight(Picker::Labels, event_tx.clone(), jira.clone());
+            if let (true, Some(key)) = (picker.await, state.selected_issue_key()) {
+                state.labels = StatefulList::new();
+                state.input.clear();
+                state.open_mode(InputMode::EditingLabels);
+                fetch_labels(event_tx, jira.clone(), key).await;
+            }
+        }
+        Action::ToggleWatching => {
+            let selected = state.selected_issue();
+            let toggled = selected.map(|issue| (issue.key.clone(), !issue.watching));
+            if let Some((key, watching)) = toggled {
+                // Shown straight away, and put back if Jira refuses
+                if let Some(issue) = state.issue_mut(&key) {
+                    issue.watching = watching;
+                }
+                set_watching(event_tx, jira.clone(), key, watching).await;
+            }
+        }
+        Action::AccountInfo => {
+            state.account = None;
+            state.set_mode(InputMode::AccountInfo);
+            fetch_account(event_tx, jira.clone()).await;
+        }
+        Action::AuditBranches => {
+            state.audit = None;
+            state.set_mode(InputMode::AuditView);
+            run_audit(event_tx, jira.clone()).await;
+        }
+        Action::CopyPrDescription => state.start_pr_description(event_tx).await,
+        Action::SmartCommit => state.open_smart_commit(event_tx, jira.clone()).await,
+        Action::ToggleTimer => state.toggle_timer(),
+        Action::Snooze if state.selected_issue_key().is_some() => {
+            state.snooze_menu.unselect();
+            state.snooze_menu.next();
+            state.open_mode(InputMode::SnoozeMenu);
+        }
+        Action::ActivityFeed => {
+            state.activity = StatefulList::new();
+            state.set_mode(InputMode::ActivityFeed);
+            fetch_activity(event_tx, jira.clone()).await;
+        }
+        Action::SnoozedIssues => {
+            state.snoozed.unselect();
+            state.snoozed.next();
+            state.set_mode(InputMode::SnoozedList);
+        }
+        Action::RenameIssue => {
+            let picker = state.preflight(Picker::Summary, event_tx, jira.clone());
+            if !picker.await {
+                return Ok(());
+            }
+            if let Some(issue) = state.selected_issue() {
+                state.input = issue.summary.clone();
+                state.open_mode(InputMode::EditingSummary);
+            }
+        }
+        Action::BulkEdit if !state.marked.is_empty() => {
+            state.bulk_actions.unselect();
+            state.bulk_actions.next();
+            state.open_mode(InputMode::BulkEditAction);
+        }
+        Action::ToggleAllProjects => {
+            state.config.search_all_projects = !state.config.search_all_projects;
+            let _ = save_config(&state.config);
+            // TODO fix cloning
+            fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::BoardsList => {
+            state.board_filter.clear();
+            state.boards_filtered_by_jira = false;
+            state.set_mode(InputMode::BoardsList);
+            fetch_boards(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::EditProjectKey => {
+            state.input = state.config.default_project_key.clone();
+            state.open_mode(InputMode::EditingDefaultProject);
+        }
+        Action::SwitchProject => state.open_project_switcher(),
+        Action::IssueLinks => state.open_links(event_tx, jira.clone()).await,
+        Action::LinkIssue => state.open_link_form(event_tx, jira.clone()).await,
+        Action::ToggleInProgress => {
+            state.config.filter_in_progress = !state.config.filter_in_progress;
+            let _ = save_config(&state.config);
+            // TODO fix cloning
+            fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::JumpToCopiedIssue => {
+            if let Some(key) = state.clipboard_issue.clone() {
+                fetch_issue(event_tx, jira.clone(), key).await;
+            }
+        }
+        Action::ToggleMine => {
+            state.config.filter_mine = !state.config.filter_mine;
+            let _ = save_config(&state.config);
+            // TODO fix cloning
+            fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::OpenInBrowser => {
+            if let Some(link) = state.selected_issue_permalink() {
+                open_link(&link);
+            }
+        }
+        Action::Refresh => {
+            // TODO fix cloning
+            fetch_tickets(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::PickTransition => state.open_transition_picker(event_tx, jira).await,
+        Action::IssueDetail if state.selected_issue_key().is_some() => {
+            state.open_issue_detail();
+            fetch_issue_details(event_tx, jira.clone(), state).await;
+        }
+        Action::Comments if state.selected_issue_key().is_some() => {
+            state.open_comments();
+            fetch_comments(event_tx, jira.clone(), state.clone()).await;
+        }
+        Action::Select => {
+            if state.issues_focused {
+                // Focus on first branch
+                state.branches.next();
+                state.issues_focused = false;
+            } else {
+                use_selected_branch(state)?;
+            }
+        }
+        Action::FocusBranches if state.issues_focused && state.selected_issue_key().is_some() => {
+            // Focus on first branch
+            state.branches.next();
+            state.issues_focused = false;
+        }
+        Action::Back => {
+            if state.issues_focused {
+                state.issues.unselect();
+                state.branches.set_items(vec![]);
+            } else {
+                state.branches.unselect();
+                state.issues_focused = true;
+            }
+        }
+        Action::Next => {
+            if state.issues_focused {
+                state.step_issues(true);
+                let _ = find_relevant_branches(event_tx.clone(), state.clone()).await;
+                prefetch_adjacent(event_tx, jira.clone(), state).await;
+            } else {
+                state.branches.next();
+            }
+        }
+        Action::Previous => {
+            if state.issues_focused {
+                state.step_issues(false);
+                let _ = find_relevant_branches(event_tx.clone(), state.clone()).await;
+                prefetch_adjacent(event_tx, jira.clone(), state).await;
+            } else {
+                state.branches.previous();
+            }
+        }
+        Action::TransitionShortcut(c) => {
+            let name = match state.config.transition_shortcuts.get(&c) {
+                Some(name) => name.clone(),
+                None => return Ok(()),
+            };
+            if state.refused(IssueAction::Transition) {
+                return Ok(());
+            }
+            if let Some(key) = state.selected_issue_key() {
+                transition_to(event_tx, jira.clone(), key, name).await;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
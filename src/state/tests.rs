@@ -0,0 +1,1768 @@
+//! Scripted key presses run through `handle_input` against an offline Jira client, checking the
+//! state and requests that come out the other side.
+use super::{action::Action, *};
+use crate::{config::Config, events::EventsRx, jira::RequestLog};
+use tokio::task::yield_now;
+
+/// A state and client to feed keys into. Requests the keys trigger are noted down rather than
+/// sent, and fail with a network error.
+struct Script {
+    state: State,
+    jira: JiraClient,
+    requests: RequestLog,
+    event_tx: EventsTx,
+    // Held so sending events from spawned tasks doesn't fail
+    _event_rx: EventsRx,
+    quit: bool,
+}
+
+fn issue(key: &str, summary: &str, priority: &str) -> IssueSummary {
+    IssueSummary {
+        key: key.to_string(),
+        summary: summary.to_string(),
+        priority: Some(priority.to_string()),
+        updated: format!("{}-updated", key),
+        ..Default::default()
+    }
+}
+
+impl Script {
+    fn new() -> Script {
+        let mut state = State::with_config(Config::default(), vec![]);
+        state.issues = StatefulList::with_items(vec![
+            issue("PROJ-1", "Crash when quitting", "Low"),
+            issue("PROJ-2", "Add dark mode", "Highest"),
+            issue("PROJ-3", "Typo on the login page", "Medium"),
+        ]);
+        state.issues.next();
+        state.issues_fetched = true;
+        let (jira, requests) = JiraClient::offline();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Script {
+            state,
+            jira,
+            requests,
+            event_tx,
+            _event_rx: event_rx,
+            quit: false,
+        }
+    }
+
+    /// Press keys given as space separated words. Named keys are written like `<Enter>`, chords
+    /// like `<C-r>`, anything else is typed out a character at a time, e.g. `"c ABC <Enter>"`.
+    async fn keys(&mut self, script: &str) {
+        for key in parse_keys(script) {
+            let result =
+                handle_input(&mut self.state, key, self.event_tx.clone(), self.jira.clone());
+            if result.await.is_err() {
+                self.quit = true;
+            }
+        }
+    }
+
+    /// Carry out an action straight away, the way a key bound to it would.
+    async fn act(&mut self, action: Action) {
+        let (event_tx, jira) = (self.event_tx.clone(), self.jira.clone());
+        let result = apply_action(&mut self.state, action, event_tx, jira);
+        if result.await.is_err() {
+            self.quit = true;
+        }
+    }
+
+    /// The requests made so far, once spawned tasks have had a chance to make them.
+    async fn requests(&self) -> Vec<String> {
+        for _ in 0..20 {
+            let _ = yield_now().await;
+        }
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn selected(&self) -> Option<String> {
+        self.state.selected_issue_key()
+    }
+
+    fn board_names(&self) -> Vec<&str> {
+        let boards = self.state.boards.items.iter();
+        boards.map(|board| board.name.as_str()).collect()
+    }
+}
+
+fn parse_keys(script: &str) -> Vec<KeyEvent> {
+    script
+        .split_whitespace()
+        .flat_map(|word| match word {
+            "<Enter>" => vec![KeyCode::Enter.into()],
+            "<Esc>" => vec![KeyCode::Esc.into()],
+            "<Tab>" => vec![KeyCode::Tab.into()],
+            "<S-Tab>" => vec![KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT)],
+            "<Space>" => vec![KeyCode::Char(' ').into()],
+            "<BS>" => vec![KeyCode::Backspace.into()],
+            "<Del>" => vec![KeyCode::Delete.into()],
+            "<Up>" => vec![KeyCode::Up.into()],
+            "<Down>" => vec![KeyCode::Down.into()],
+            "<Left>" => vec![KeyCode::Left.into()],
+            "<Right>" => vec![KeyCode::Right.into()],
+            "<PgUp>" => vec![KeyCode::PageUp.into()],
+            "<PgDn>" => vec![KeyCode::PageDown.into()],
+            _ if word.starts_with("<C-") && word.ends_with('>') => word[3..word.len() - 1]
+                .chars()
+                .map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL))
+                .collect(),
+            _ => word.chars().map(|c| KeyCode::Char(c).into()).collect(),
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn toggling_mine_refetches_without_the_assignee_filter() {
+    let mut script = Script::new();
+    script.keys("m").await;
+
+    assert!(!script.state.config.filter_mine);
+    let requests = script.requests().await;
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with("GET /search/jql jql=status=3 "));
+    assert!(!requests[0].contains("currentuser()"));
+}
+
+#[tokio::test]
+async fn keys_in_the_issue_list_map_to_actions() {
+    let script = Script::new();
+    let action = |input| action::key_action(&script.state, input);
+    let backlog = Action::OpenSprint { backlog: true };
+    assert_eq!(action(KeyCode::Char('B')), Some(backlog));
+    let forward = Action::CycleChips { forward: true };
+    assert_eq!(action(KeyCode::Tab), Some(forward));
+    // Transition shortcuts only get the keys left over
+    let shortcut = Action::TransitionShortcut('2');
+    assert_eq!(action(KeyCode::Char('2')), Some(shortcut));
+    assert_eq!(action(KeyCode::Char('9')), None);
+}
+
+#[tokio::test]
+async fn keys_in_other_views_and_prompts_map_to_actions() {
+    let mut script = Script::new();
+    script.state.input_mode = InputMode::IssueDetail;
+    let action = |script: &Script, input| action::key_action(&script.state, input);
+    let sibling = Action::OpenSibling { forward: false };
+    assert_eq!(action(&script, KeyCode::Char('[')), Some(sibling));
+    assert_eq!(action(&script, KeyCode::Esc), Some(Action::Close));
+
+    script.state.input_mode = InputMode::SwitchingProject;
+    assert_eq!(action(&script, KeyCode::Char('2')), Some(Action::PickItem(1)));
+    assert_eq!(action(&script, KeyCode::Char('x')), None);
+
+    // Prompts take the keys of the view they're shown over
+    script.state.existing_branch = Some("PROJ-1-fix".to_string());
+    assert_eq!(action(&script, KeyCode::Char('n')), Some(Action::Decline));
+}
+
+#[tokio::test]
+async fn actions_are_carried_out_by_whichever_view_is_open() {
+    let mut script = Script::new();
+    script.state.input_mode = InputMode::BoardsList;
+    script.act(Action::Search).await;
+    assert!(matches!(script.state.input_mode, InputMode::FilteringBoards));
+
+    script.act(Action::Type('x')).await;
+    assert_eq!(script.state.board_filter, "x");
+    script.act(Action::Close).await;
+    assert!(matches!(script.state.input_mode, InputMode::BoardsList));
+    assert!(script.state.board_filter.is_empty());
+}
+
+#[test]
+fn shortcuts_on_built_in_keys_are_reported_at_startup() {
+    let mut config = Config::default();
+    config.transition_shortcuts.insert('s', "Done".to_string());
+    config.transition_shortcuts.insert('5', "Blocked".to_string());
+    assert_eq!(action::shadowed_shortcuts(&config), ['s']);
+
+    let state = State::with_config(config, vec![]);
+    let error = state.error.unwrap();
+    assert!(error.starts_with("Transition shortcuts s are built in keys"));
+    assert!(State::with_config(Config::default(), vec![]).error.is_none());
+}
+
+#[test]
+fn issue_list_keys_are_bound_once() {
+    let keymap = action::issue_list_keymap(&Config::default());
+    assert_eq!(action::unreachable_actions(&keymap), []);
+    let names = action::ISSUE_LIST_KEYS.iter().map(|&(name, _, _)| name);
+    let names: std::collections::HashSet<_> = names.collect();
+    assert_eq!(names.len(), action::ISSUE_LIST_KEYS.len());
+}
+
+#[tokio::test]
+async fn keys_from_the_config_move_actions() {
+    let mut script = Script::new();
+    script.state.config.keys.insert("toggle_mine".to_string(), "u".to_string());
+    script.state.config.keys.insert("select".to_string(), "Space".to_string());
+    let action = |input| action::key_action(&script.state, input);
+    assert_eq!(action(KeyCode::Char('u')), Some(Action::ToggleMine));
+    assert_eq!(action(KeyCode::Char('m')), None);
+    // A rebound key wins over the built in one it lands on
+    assert_eq!(action(KeyCode::Char(' ')), Some(Action::Select));
+}
+
+#[test]
+fn keys_that_clash_or_bind_nothing_are_reported_at_startup() {
+    let mut config = Config::default();
+    config.keys.insert("toggle_mine".to_string(), "q".to_string());
+    config.keys.insert("refresh".to_string(), "Ctrl-R".to_string());
+    config.keys.insert("launch".to_string(), "L".to_string());
+    let keymap = action::issue_list_keymap(&config);
+    let unreachable = action::unreachable_actions(&keymap);
+    let names: Vec<_> = unreachable.iter().map(|(lost, taken)| (lost.name, taken.name)).collect();
+    assert_eq!(names, [("quit", "toggle_mine")]);
+    assert_eq!(action::unbound_keys(&config), ["launch = \"L\"", "refresh = \"Ctrl-R\""]);
+
+    let error = State::with_config(config, vec![]).error.unwrap();
+    assert!(error.starts_with("Keys that don't bind anything"));
+    assert!(error.contains("quit (toggle_mine has q)"));
+}
+
+#[tokio::test]
+async fn actions_go_the_same_way_as_their_keys() {
+    let mut script = Script::new();
+    script.act(Action::ToggleMine).await;
+    script.act(Action::Next).await;
+
+    assert!(!script.state.config.filter_mine);
+    assert_eq!(script.selected().as_deref(), Some("PROJ-2"));
+    assert!(script.requests().await[0].starts_with("GET /search/jql jql=status=3 "));
+
+    script.act(Action::Quit).await;
+    assert!(script.quit);
+}
+
+#[tokio::test]
+async fn filter_chips_are_removed_after_tabbing_to_them() {
+    let mut script = Script::new();
+    assert_eq!(
+        FilterChip::active(&script.state.config),
+        [FilterChip::Mine, FilterChip::Status]
+    );
+
+    script.keys("<Tab> x").await;
+    assert!(!script.state.config.filter_mine);
+    assert!(!script.requests().await[0].contains("currentuser()"));
+    // The status takes the removed chip's place, and is switched rather than removed
+    assert_eq!(script.state.focused_chip(), Some(FilterChip::Status));
+    script.keys("x").await;
+    assert!(!script.state.config.filter_in_progress);
+    assert_eq!(script.state.focused_chip(), Some(FilterChip::Status));
+
+    script.keys("<Tab>").await;
+    assert_eq!(script.state.focused_chip(), None);
+}
+
+#[tokio::test]
+async fn jql_typed_in_replaces_the_filters_until_cleared() {
+    let mut script = Script::new();
+    script.keys("/ labels=urgent <Enter>").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(script.state.config.jql.as_deref(), Some("labels=urgent"));
+    assert_eq!(FilterChip::active(&script.state.config), [FilterChip::Jql]);
+    let requests = script.requests().await;
+    assert!(requests[0].starts_with("GET /search/jql jql=labels=urgent "));
+
+    // The last query is there to edit next time
+    script.keys("/").await;
+    assert_eq!(script.state.raw_input_clone(), "labels=urgent");
+    script.keys("<Esc> <Tab> x").await;
+    assert_eq!(script.state.config.jql, None);
+    let requests = script.requests().await;
+    assert!(requests[1].starts_with("GET /search/jql jql=assignee=currentuser() AND status=3 "));
+}
+
+#[tokio::test]
+async fn changing_the_project_scopes_the_search() {
+    let mut script = Script::new();
+    script.keys("c ABC <Enter>").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(script.state.config.default_project_key, "ABC");
+    let requests = script.requests().await;
+    assert!(requests[0].contains("project = \"ABC\""));
+}
+
+#[tokio::test]
+async fn navigating_wraps_around() {
+    let mut script = Script::new();
+    script.keys("<Down> <Down>").await;
+    assert_eq!(script.selected().as_deref(), Some("PROJ-3"));
+    script.keys("<Down>").await;
+    assert_eq!(script.selected().as_deref(), Some("PROJ-1"));
+    script.keys("<Up>").await;
+    assert_eq!(script.selected().as_deref(), Some("PROJ-3"));
+}
+
+#[tokio::test]
+async fn the_issues_either_side_are_fetched_ahead_once() {
+    let mut script = Script::new();
+    script.keys("<Down>").await;
+    let mut requests = script.requests().await;
+    requests.sort();
+    let ahead = [
+        "GET /issue/PROJ-1 fields=subtasks",
+        "GET /issue/PROJ-1 fields=timetracking",
+        "GET /issue/PROJ-3 fields=subtasks",
+        "GET /issue/PROJ-3 fields=timetracking",
+    ];
+    assert_eq!(requests, ahead);
+
+    // Only PROJ-2 is new next to PROJ-3, the last issue
+    script.keys("<Down>").await;
+    assert_eq!(script.requests().await.len(), 6);
+    script.keys("<Up>").await;
+    assert_eq!(script.requests().await.len(), 6);
+}
+
+#[tokio::test]
+async fn issue_detail_opens_and_closes() {
+    let mut script = Script::new();
+    script.keys("<Down> d").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert_eq!(script.selected().as_deref(), Some("PROJ-2"));
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    // The list was already fetched, so only the issue's details are requested
+    let requests = script.requests().await;
+    let subtasks = "GET /issue/PROJ-2 fields=subtasks".to_string();
+    assert!(requests.contains(&subtasks));
+    assert_eq!(
+        requests.last().unwrap(),
+        "GET /issue/PROJ-2/comment startAt=0 maxResults=20 orderBy=-created"
+    );
+}
+
+fn comment(body: &str) -> CommentSummary {
+    CommentSummary {
+        author: "Someone".to_string(),
+        body: body.to_string(),
+        created: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn paging_up_at_the_top_loads_older_comments() {
+    let mut script = Script::new();
+    script.keys("d").await;
+    let page = CommentPage {
+        comments: vec![comment("Third"), comment("Fourth")],
+        loaded: 0,
+        older: 2,
+    };
+    script.state.add_comments("PROJ-1", page);
+
+    script.keys("<PgDn> <PgUp> <PgUp>").await;
+    assert_eq!(script.state.detail_scroll, 0);
+    let requests = script.requests().await;
+    assert_eq!(
+        requests.last().unwrap(),
+        "GET /issue/PROJ-1/comment startAt=2 maxResults=20 orderBy=-created"
+    );
+
+    // Older comments go above the ones already shown, a repeated page is dropped
+    let page = CommentPage {
+        comments: vec![comment("First"), comment("Second")],
+        loaded: 2,
+        older: 0,
+    };
+    script.state.add_comments("PROJ-1", page.clone());
+    script.state.add_comments("PROJ-1", page);
+    let bodies: Vec<&str> = script.state.comments.iter().map(|c| c.body.as_str()).collect();
+    assert_eq!(bodies, ["First", "Second", "Third", "Fourth"]);
+    assert_eq!(script.state.older_comments, Some(0));
+}
+
+#[tokio::test]
+async fn comments_view_scrolls_through_the_thread() {
+    let mut script = Script::new();
+    script.keys("<Down> C").await;
+    assert!(matches!(script.state.input_mode, InputMode::CommentsView));
+    assert_eq!(
+        script.requests().await.last().unwrap(),
+        "GET /issue/PROJ-2/comment startAt=0 maxResults=20 orderBy=-created"
+    );
+
+    let page = CommentPage {
+        comments: vec![comment("First\nSecond line"), comment("Second")],
+        loaded: 0,
+        older: 0,
+    };
+    script.state.add_comments("PROJ-2", page);
+    script.keys("<Down> <Down>").await;
+    assert_eq!(script.state.comments_scroll, 2);
+    // Scrolling stops around the end of the thread
+    script.keys("<PgDn> <PgDn>").await;
+    assert_eq!(script.state.comments_scroll, 7);
+    script.keys("<Up>").await;
+    assert_eq!(script.state.comments_scroll, 6);
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+/// Post a webhook to the listener at `address`, returning the response, which is empty when the
+/// connection was dropped instead.
+fn post_webhook(address: std::net::SocketAddr, target: &str, body: &str) -> String {
+    use std::io::{Read, Write};
+
+    let mut stream = std::net::TcpStream::connect(address).unwrap();
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+        target,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response);
+    response
+}
+
+#[tokio::test]
+async fn webhooks_arrive_as_events() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let secret = Some("s3cret".to_string());
+    let address = webhook::listen("127.0.0.1:0", secret, event_tx).unwrap();
+    let body = r#"{"webhookEvent":"comment_created","issue":{"key":"PROJ-2"}}"#;
+    let response = post_webhook(address, "/jira?secret=s3cret", body);
+    assert!(response.starts_with("HTTP/1.1 204"));
+
+    match event_rx.recv().await {
+        Some(Event::CommentsChanged(key)) => assert_eq!(key, "PROJ-2"),
+        _ => panic!("Expected the webhook's comment event"),
+    }
+}
+
+#[tokio::test]
+async fn webhooks_without_the_secret_are_turned_away() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    assert!(webhook::listen("127.0.0.1:0", None, event_tx.clone()).is_err());
+
+    let secret = Some("s3cret".to_string());
+    let address = webhook::listen("127.0.0.1:0", secret, event_tx).unwrap();
+    let body = r#"{"webhookEvent":"jira:issue_updated","issue":{"key":"PROJ-2"}}"#;
+    assert!(post_webhook(address, "/jira", body).starts_with("HTTP/1.1 401"));
+    let wrong = post_webhook(address, "/jira?secret=guess", body);
+    assert!(wrong.starts_with("HTTP/1.1 401"));
+    assert!(futures::FutureExt::now_or_never(event_rx.recv()).is_none());
+}
+
+#[tokio::test]
+async fn webhooks_arrive_past_a_sender_that_went_quiet() {
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    let secret = Some("s3cret".to_string());
+    let address = webhook::listen("127.0.0.1:0", secret, event_tx).unwrap();
+    let _quiet = std::net::TcpStream::connect(address).unwrap();
+
+    // Dropped as soon as the request line runs too long, without waiting for the rest
+    let long = format!("/jira?secret=s3cret&padding={}", "a".repeat(9000));
+    let body = r#"{"webhookEvent":"jira:issue_updated","issue":{"key":"PROJ-1"}}"#;
+    assert_eq!(post_webhook(address, &long, body), "");
+
+    let body = r#"{"webhookEvent":"jira:issue_updated","issue":{"key":"PROJ-2"}}"#;
+    let response = post_webhook(address, "/jira?secret=s3cret", body);
+    assert!(response.starts_with("HTTP/1.1 204"));
+    match event_rx.recv().await {
+        Some(Event::IssueChanged(key)) => assert_eq!(key, "PROJ-2"),
+        _ => panic!("Expected the second webhook's event"),
+    }
+}
+
+#[test]
+fn background_refreshes_wait_for_the_terminal_to_be_focused() {
+    let mut script = Script::new();
+    assert!(script.state.refreshes_in_background());
+    script.state.terminal_focused = false;
+    assert!(!script.state.refreshes_in_background());
+}
+
+#[tokio::test]
+async fn bulk_edit_applies_to_marked_issues() {
+    let mut script = Script::new();
+    script.keys("<Space> <Down> <Down> <Space> E <Enter> urgent <Enter>").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::BulkEditProgress));
+    assert_eq!(script.state.bulk_progress.as_ref().unwrap().total(), 2);
+    // Each issue is checked for conflicting edits before it's changed
+    let requests = script.requests().await;
+    assert!(requests.contains(&"GET /issue/PROJ-1 fields=updated".to_string()));
+    assert!(requests.contains(&"GET /issue/PROJ-3 fields=updated".to_string()));
+}
+
+#[tokio::test]
+async fn esc_cancels_a_running_bulk_edit() {
+    let mut script = Script::new();
+    script
+        .keys("<Space> <Down> <Space> E <Enter> urgent <Enter> <Esc>")
+        .await;
+
+    // Stays open until the issue being updated is done and the rest are skipped
+    assert!(matches!(script.state.input_mode, InputMode::BulkEditProgress));
+    let progress = script.state.bulk_progress.as_mut().unwrap();
+    assert!(progress.cancelled());
+    progress.record("PROJ-1", ItemStatus::Done);
+    progress.record("PROJ-2", ItemStatus::Skipped);
+    assert!(progress.finished());
+    assert_eq!(progress.summary(), "1 updated, 0 failed, 1 skipped");
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn esc_stops_a_long_search() {
+    let mut script = Script::new();
+    let search = SearchProgress {
+        loaded: 100,
+        total: 450,
+        cancel: Arc::new(AtomicBool::new(false)),
+    };
+    script.state.issues_loading = Some(search.clone());
+    script.keys("<Esc>").await;
+
+    assert!(search.cancelled());
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn shift_o_toggles_watching_straight_away() {
+    let mut script = Script::new();
+    script.keys("O").await;
+    assert!(script.state.issues.items[0].watching);
+    // The user's id is looked up first, which fails offline
+    assert_eq!(script.requests().await, ["GET /myself "]);
+
+    script.keys("O").await;
+    assert!(!script.state.issues.items[0].watching);
+}
+
+#[tokio::test]
+async fn transition_shortcuts_look_up_the_issues_transitions() {
+    let mut script = Script::new();
+    script.keys("<Down> 2").await;
+
+    // The picker is skipped, the transition is found by name when the key is pressed
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    let requests = script.requests().await;
+    assert_eq!(requests.last().unwrap(), "GET /issue/PROJ-2/transitions ");
+}
+
+#[tokio::test]
+async fn boards_can_be_starred_and_scope_the_issue_list() {
+    let mut script = Script::new();
+    let board = |key: u64, name: &str| BoardSummary {
+        key,
+        name: name.to_string(),
+        ..Default::default()
+    };
+    script.state.boards = StatefulList::with_items(vec![board(1, "Web"), board(2, "Mobile")]);
+    script.state.boards.next();
+    script.state.input_mode = InputMode::BoardsList;
+
+    // Starring moves the board to the top, keeping it selected
+    script.keys("<Down> f").await;
+    assert_eq!(script.board_names(), ["Mobile", "Web"]);
+    assert_eq!(script.state.config.favorite_boards, [2]);
+    assert_eq!(script.state.boards.state.selected(), Some(0));
+
+    script.keys("<Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.state.config.default_board.as_ref().unwrap().name,
+        "Mobile"
+    );
+    let requests = script.requests().await;
+    assert!(requests[0].starts_with("GET /board/2/issue jql="));
+}
+
+#[tokio::test]
+async fn boards_filter_as_you_type() {
+    let mut script = Script::new();
+    let board = |key: u64, name: &str, project: &str| BoardSummary {
+        key,
+        name: name.to_string(),
+        project: Some(project.to_string()),
+        ..Default::default()
+    };
+    script.state.all_boards = vec![
+        board(1, "Web sprints", "WEB"),
+        board(2, "Mobile", "APP"),
+        board(3, "Support", "HELP"),
+    ];
+    script.state.filter_boards();
+    script.state.input_mode = InputMode::BoardsList;
+
+    // Names and project keys both match, ignoring case
+    script.keys("/ p").await;
+    assert_eq!(script.board_names(), ["Web sprints", "Mobile", "Support"]);
+    script.keys("p").await;
+    assert_eq!(script.board_names(), ["Mobile", "Support"]);
+
+    // Enter also asks Jira, in case the board is past the pages fetched
+    script.keys("<Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::BoardsList));
+    let requests = script.requests().await;
+    assert_eq!(requests, ["GET /board maxResults=100 startAt=0 name=pp"]);
+}
+
+#[tokio::test]
+async fn recent_projects_are_two_keys_away() {
+    let mut script = Script::new();
+    script.state.config.default_project_key = "WEB".to_string();
+    script.state.config.recent_projects = vec!["WEB".to_string(), "APP".to_string()];
+
+    // The previous project is selected, so Enter switches back to it
+    script.keys("w").await;
+    assert_eq!(script.state.project_menu.state.selected(), Some(1));
+    script.keys("<Enter>").await;
+    assert_eq!(script.state.config.default_project_key, "APP");
+    assert_eq!(script.state.config.recent_projects, ["APP", "WEB"]);
+
+    script.keys("w 2").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(script.state.config.default_project_key, "WEB");
+    let requests = script.requests().await;
+    assert!(requests[0].contains("project = \"APP\""));
+    assert!(requests[1].contains("project = \"WEB\""));
+}
+
+#[tokio::test]
+async fn branch_names_start_with_the_projects_prefix() {
+    let mut script = Script::new();
+    script
+        .state
+        .config
+        .branch_prefixes
+        .insert("PROJ".to_string(), "platform/".to_string());
+    script.state.input = "fix-crash".to_string();
+    assert_eq!(script.state.new_branch_name(), "platform/PROJ-1-fix-crash");
+
+    script.state.config.branch_template = Some("feature/{prefix}{key}-{slug}".to_string());
+    assert_eq!(
+        script.state.new_branch_name(),
+        "feature/platform/PROJ-1-crash-when-quitting"
+    );
+}
+
+#[test]
+fn checked_out_lines_end_with_the_projects_dir() {
+    let mut script = Script::new();
+    script
+        .state
+        .config
+        .project_dirs
+        .insert("PROJ".to_string(), "services/web".to_string());
+    assert_eq!(script.state.project_dir(), Some("services/web"));
+
+    let checked_out = CheckedOut {
+        key: Some("PROJ-1".to_string()),
+        branch: "PROJ-1-fix-crash".to_string(),
+        created: true,
+        repo: None,
+        dir: Some(PathBuf::from("/repo/services/web")),
+        previous: None,
+    };
+    assert_eq!(
+        checked_out.describe(),
+        "key=PROJ-1 branch=PROJ-1-fix-crash created=true dir=/repo/services/web"
+    );
+}
+
+/// A fresh repo in the temp directory with a single commit, and a user to commit as.
+fn test_repo(name: &str) -> (PathBuf, Repository) {
+    let dir = env::temp_dir().join("jira-tui-tests").join(name);
+    let _ = fs::remove_dir_all(&dir);
+    let repo = Repository::init(&dir).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_str("user.name", "Test").unwrap();
+    config.set_str("user.email", "test@example.com").unwrap();
+    {
+        let signature = repo.signature().unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Start", &tree, &[])
+            .unwrap();
+    }
+    (dir, repo)
+}
+
+/// Commit a change to `path` in the repo's working directory as `author`.
+fn commit_file(repo: &Repository, path: &str, author: &str) {
+    let root = repo.workdir().unwrap();
+    let contents = fs::read_to_string(root.join(path)).unwrap_or_default();
+    fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+    fs::write(root.join(path), format!("{}{}\n", contents, author)).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new(path)).unwrap();
+    index.write().unwrap();
+    let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+    let email = format!("{}@example.com", author.to_lowercase());
+    let signature = git2::Signature::now(author, &email).unwrap();
+    let parent = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        "Change",
+        &tree,
+        &[&parent],
+    )
+    .unwrap();
+}
+
+#[test]
+fn reviewers_are_who_recently_changed_the_same_files() {
+    let (_, repo) = test_repo("reviewers");
+    commit_file(&repo, "src/lib.rs", "Alice");
+    commit_file(&repo, "src/lib.rs", "Alice");
+    commit_file(&repo, "src/lib.rs", "Carol");
+    commit_file(&repo, "README.md", "Bob");
+    commit_file(&repo, "src/lib.rs", "Test");
+    let main = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("main", &main, true).unwrap();
+    repo.branch("PROJ-1-fix-crash", &main, false).unwrap();
+    repo.set_head("refs/heads/PROJ-1-fix-crash").unwrap();
+    commit_file(&repo, "src/lib.rs", "Test");
+
+    // Bob only changed other files, and the repo's own user isn't asked to review
+    assert_eq!(recent_committers(&repo).unwrap(), vec!["Alice", "Carol"]);
+}
+
+#[test]
+fn reviewers_are_found_against_origins_default_branch() {
+    let (_, repo) = test_repo("reviewers-from-origin");
+    commit_file(&repo, "src/lib.rs", "Alice");
+    let trunk = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("PROJ-1-fix-crash", &trunk, false).unwrap();
+    repo.set_head("refs/heads/PROJ-1-fix-crash").unwrap();
+    commit_file(&repo, "src/lib.rs", "Test");
+    // Only origin knows the default branch, which is named neither main nor master
+    for branch in repo.branches(Some(git2::BranchType::Local)).unwrap() {
+        let (mut branch, _) = branch.unwrap();
+        if !branch.is_head() {
+            branch.delete().unwrap();
+        }
+    }
+    let origin_trunk = "refs/remotes/origin/trunk";
+    repo.reference(origin_trunk, trunk.id(), true, "clone").unwrap();
+    repo.reference_symbolic("refs/remotes/origin/HEAD", origin_trunk, true, "clone")
+        .unwrap();
+
+    assert_eq!(recent_committers(&repo).unwrap(), vec!["Alice"]);
+}
+
+#[tokio::test]
+async fn branches_are_listed_from_the_projects_repo() {
+    let (dir, repo) = test_repo("project-repo");
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("PROJ-1-in-its-own-repo", &commit, false).unwrap();
+
+    let mut script = Script::new();
+    let path = dir.display().to_string();
+    script
+        .state
+        .config
+        .project_repos
+        .insert("PROJ".to_string(), path);
+    assert_eq!(script.state.project_repo(), Some(dir));
+
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    find_relevant_branches(event_tx, script.state.clone()).await;
+    match event_rx.recv().await {
+        Some(Event::BranchesUpdated(key, branches, _)) => {
+            assert_eq!(key, "PROJ-1");
+            let names: Vec<_> = branches.iter().map(|branch| branch.name.as_str()).collect();
+            assert_eq!(names, vec!["PROJ-1-in-its-own-repo"]);
+        }
+        _ => panic!("Expected the branches in the project's repo"),
+    }
+}
+
+#[tokio::test]
+async fn the_checked_out_branch_is_marked_and_not_checked_out_again() {
+    let (dir, repo) = test_repo("current-branch");
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("PROJ-1-current", &commit, false).unwrap();
+    repo.set_head("refs/heads/PROJ-1-current").unwrap();
+    assert_eq!(current_branch(&repo).as_deref(), Some("PROJ-1-current"));
+
+    let mut script = Script::new();
+    let path = dir.display().to_string();
+    script
+        .state
+        .config
+        .project_repos
+        .insert("PROJ".to_string(), path);
+    let (event_tx, mut event_rx) = mpsc::unbounded_channel();
+    find_relevant_branches(event_tx, script.state.clone()).await;
+    match event_rx.recv().await {
+        Some(Event::BranchesUpdated(_, _, head)) => {
+            assert_eq!(head.as_deref(), Some("PROJ-1-current"))
+        }
+        _ => panic!("Expected the branches in the project's repo"),
+    }
+
+    script.state.branches = StatefulList::with_items(vec![BranchEntry::Existing(BranchSummary {
+        name: "PROJ-1-current".to_string(),
+    })]);
+    script.state.branches.next();
+    script.state.input_mode = InputMode::IssueDetail;
+    script.keys("<Enter>").await;
+    assert!(!script.quit);
+    assert!(script.state.checked_out.is_none());
+    assert_eq!(
+        script.state.notice.as_deref(),
+        Some("Already on PROJ-1-current")
+    );
+}
+
+#[tokio::test]
+async fn taken_branch_names_can_get_a_number_added() {
+    let (dir, repo) = test_repo("branch-collision");
+    let commit = repo.head().unwrap().peel_to_commit().unwrap();
+    repo.branch("main", &commit, false).unwrap();
+    repo.branch("PROJ-1-fix", &commit, false).unwrap();
+    repo.branch("PROJ-1-fix-2", &commit, false).unwrap();
+
+    let mut script = Script::new();
+    let path = dir.display().to_string();
+    script
+        .state
+        .config
+        .project_repos
+        .insert("PROJ".to_string(), path);
+    script.state.input = "fix".to_string();
+    script.state.input_mode = InputMode::Editing;
+    script.keys("<Enter>").await;
+    assert_eq!(script.state.branch_collision.as_deref(), Some("PROJ-1-fix"));
+    assert!(!script.quit);
+
+    script.keys("s").await;
+    assert!(script.quit);
+    let checked_out = script.state.checked_out.unwrap();
+    assert_eq!(checked_out.branch, "PROJ-1-fix-3");
+    assert!(checked_out.created);
+    assert_eq!(current_branch(&repo).as_deref(), Some("PROJ-1-fix-3"));
+}
+
+#[test]
+fn empty_commits_start_the_branch_off() {
+    let (_, repo) = test_repo("empty-commit");
+    let start = repo.head().unwrap().peel_to_commit().unwrap();
+
+    let commit = commit_empty(&repo, "PROJ-1: start work").unwrap();
+    let commit = repo.find_commit(commit).unwrap();
+    assert_eq!(commit.message(), Some("PROJ-1: start work"));
+    assert_eq!(commit.parent_id(0).unwrap(), start.id());
+    assert_eq!(commit.tree_id(), start.tree_id());
+    assert_eq!(head_commit(&repo), Some(commit.id()));
+}
+
+#[cfg(unix)]
+#[test]
+fn empty_commits_are_signed_when_the_repo_asks() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (dir, repo) = test_repo("signed-commit");
+    // Stands in for ssh-keygen, which reads the commit on stdin and prints the signature
+    let program = dir.join("sign.sh");
+    fs::write(&program, "#!/bin/sh\ncat >/dev/null\necho SIGNED\n").unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_bool("commit.gpgsign", true).unwrap();
+    config.set_str("gpg.format", "ssh").unwrap();
+    config.set_str("gpg.ssh.program", program.to_str().unwrap()).unwrap();
+    config.set_str("user.signingkey", "key::ssh-ed25519 AAAA test").unwrap();
+
+    let commit = commit_empty(&repo, "Start work").unwrap();
+    let (signature, _) = repo.extract_signature(&commit, None).unwrap();
+    assert_eq!(signature.as_str(), Some("SIGNED\n"));
+    assert_eq!(head_commit(&repo), Some(commit));
+}
+
+#[cfg(unix)]
+#[test]
+fn literal_ssh_keys_are_signed_with_from_a_private_file_removed_afterwards() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let (dir, repo) = test_repo("signed-with-literal-key");
+    // Notes where the key file was and who could read it, the key's path being the last argument
+    let program = dir.join("sign.sh");
+    let script = format!(
+        "#!/bin/sh\ncat >/dev/null\nfor key; do :; done\necho \"$key\" > {0}/key-path\nstat -c %a \"$key\" > {0}/key-mode\necho SIGNED\n",
+        dir.display()
+    );
+    fs::write(&program, script).unwrap();
+    fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+    let mut config = repo.config().unwrap();
+    config.set_bool("commit.gpgsign", true).unwrap();
+    config.set_str("gpg.format", "ssh").unwrap();
+    config.set_str("gpg.ssh.program", program.to_str().unwrap()).unwrap();
+    config.set_str("user.signingkey", "key::ssh-ed25519 AAAA test").unwrap();
+
+    commit_empty(&repo, "Start work").unwrap();
+    let key_path = fs::read_to_string(dir.join("key-path")).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("key-mode")).unwrap(), "600\n");
+    assert!(!Path::new(key_path.trim()).exists());
+}
+
+#[tokio::test]
+async fn creating_a_second_branch_offers_the_existing_one_first() {
+    let mut script = Script::new();
+    let branch = BranchEntry::Existing(BranchSummary {
+        name: "PROJ-1-fix-crash".to_string(),
+    });
+    script.state.branches = StatefulList::with_items(vec![branch, BranchEntry::CreateNew]);
+    script.state.branches.state.select(Some(1));
+    script.state.input_mode = InputMode::IssueDetail;
+
+    script.keys("<Enter>").await;
+    assert_eq!(
+        script.state.existing_branch.as_deref(),
+        Some("PROJ-1-fix-crash")
+    );
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    script.keys("<Esc>").await;
+    assert!(script.state.existing_branch.is_none());
+
+    script.keys("<Enter> n").await;
+    assert!(script.state.existing_branch.is_none());
+    assert!(matches!(script.state.input_mode, InputMode::Editing));
+}
+
+#[tokio::test]
+async fn missing_repos_are_offered_to_be_cloned_into_the_workspace() {
+    let workspace = env::temp_dir().join("jira-tui-tests").join("workspace");
+    let _ = fs::remove_dir_all(&workspace);
+    let mut script = Script::new();
+    let config = &mut script.state.config;
+    config.workspace_dir = Some(workspace.display().to_string());
+    config.project_remotes.insert(
+        "PROJ".to_string(),
+        "git@github.com:acme/platform.git".to_string(),
+    );
+    script.state.branches = StatefulList::with_items(vec![BranchEntry::CreateNew]);
+    script.state.branches.next();
+    script.state.input_mode = InputMode::IssueDetail;
+
+    script.keys("<Enter>").await;
+    assert_eq!(
+        script.state.clone_offer,
+        Some(CloneOffer {
+            key: "PROJ-1".to_string(),
+            url: "git@github.com:acme/platform.git".to_string(),
+            path: workspace.join("platform"),
+        })
+    );
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+
+    // Declining leaves things as they were, asking again next time
+    script.keys("n").await;
+    assert_eq!(script.state.clone_offer, None);
+    script.keys("<Enter>").await;
+    assert!(script.state.clone_offer.is_some());
+}
+
+#[tokio::test]
+async fn d_flips_whether_the_pr_opens_as_a_draft() {
+    let mut script = Script::new();
+    script.state.config.draft_pull_requests = true;
+    script.keys("d P").await;
+    assert!(script.state.reviewer_picker.draft);
+
+    script.state.set_mode(InputMode::PickingReviewers);
+    script.keys("d").await;
+    assert!(!script.state.reviewer_picker.draft);
+}
+
+#[test]
+fn prs_are_reviewed_by_the_logins_of_whoever_was_named() {
+    let mut config = Config::default();
+    config.github_logins.insert("Ada Lovelace".to_string(), "ada".to_string());
+    let named = ["Ada Lovelace".to_string(), "Jira User".to_string()];
+    assert_eq!(github_reviewers(&config, &named), ["ada"]);
+
+    config.default_reviewers = vec!["grace".to_string(), "ada".to_string()];
+    assert_eq!(github_reviewers(&config, &named), ["ada", "grace"]);
+}
+
+#[tokio::test]
+async fn prs_still_open_when_the_watchers_cant_be_read() {
+    let script = Script::new();
+    let mut config = Config::default();
+    let picked = ["Ada Lovelace".to_string()];
+    config.github_logins.insert("Ada Lovelace".to_string(), "ada".to_string());
+    let (reviewers, unreviewed) =
+        pull_request_reviewers(&script.jira, &config, "PROJ-1", &picked).await;
+    assert_eq!(reviewers, ["ada"]);
+    assert!(unreviewed.is_none());
+
+    // Nobody picked, so the watchers are asked for, which the offline client can't reach
+    let (reviewers, unreviewed) = pull_request_reviewers(&script.jira, &config, "PROJ-1", &[]).await;
+    assert!(reviewers.is_empty());
+    assert!(unreviewed.unwrap().starts_with("couldn't find who watches PROJ-1: "));
+    assert_eq!(script.requests().await, ["GET /issue/PROJ-1/watchers "]);
+}
+
+#[tokio::test]
+async fn pull_requests_are_linked_from_the_detail_view() {
+    let mut script = Script::new();
+    script.state.input_mode = InputMode::IssueDetail;
+    script
+        .keys("L https://github.com/acme/platform/pull/12 <Enter>")
+        .await;
+
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    let requests = script.requests().await;
+    assert_eq!(requests.len(), 1);
+    assert!(requests[0].starts_with(
+        "POST /issue/PROJ-1/remotelink {\"globalId\":\"https://github.com/acme/platform/pull/12\""
+    ));
+}
+
+#[tokio::test]
+async fn comments_are_written_over_several_lines() {
+    let mut script = Script::new();
+    script.state.input_mode = InputMode::IssueDetail;
+    // There's nothing to post until something's typed
+    script.keys("c <C-s>").await;
+    assert!(matches!(script.state.input_mode, InputMode::AddingComment));
+
+    script
+        .keys("Looks <Space> good <Enter> Ship <Space> it <C-s>")
+        .await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert!(script.state.comment_draft.is_none());
+    assert_eq!(
+        script.requests().await,
+        ["POST /issue/PROJ-1/comment {\"body\":\"Looks good\\nShip it\"}"]
+    );
+}
+
+#[tokio::test]
+async fn actions_jira_refused_are_explained_instead_of_tried_again() {
+    let mut script = Script::new();
+    script.state.input_mode = InputMode::IssueDetail;
+    script
+        .state
+        .restrict("PROJ-1", IssueAction::Comment, JiraError::Forbidden.to_string());
+
+    script.keys("c").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert_eq!(
+        script.state.error.as_deref(),
+        Some("Can't comment on PROJ-1: You don't have permission to do that in Jira")
+    );
+    assert!(script.requests().await.is_empty());
+
+    // Permissions may have changed by the time everything's reloaded
+    script.keys("<C-r> c").await;
+    assert!(matches!(script.state.input_mode, InputMode::AddingComment));
+}
+
+const PERMISSIONS_REQUEST: &str = "GET /mypermissions issueKey=PROJ-1 \
+    permissions=TRANSITION_ISSUES,EDIT_ISSUES,ADD_COMMENTS,ASSIGN_ISSUES,WORK_ON_ISSUES";
+
+#[tokio::test]
+async fn pickers_only_open_for_what_the_issue_allows() {
+    let mut script = Script::new();
+    script.keys("@").await;
+    assert!(matches!(script.state.input_mode, InputMode::AssigningIssue));
+    assert_eq!(script.requests().await, [PERMISSIONS_REQUEST]);
+
+    // The picker opened straight away, and closes once it turns out it can't be used
+    let permissions = IssuePermissions {
+        held: vec!["TRANSITION_ISSUES".to_string(), "EDIT_ISSUES".to_string()]
+            .into_iter()
+            .collect(),
+        editable_fields: vec!["description".to_string()].into_iter().collect(),
+    };
+    script.state.apply_permissions("PROJ-1", permissions);
+    script.state.close_denied_picker("PROJ-1", Picker::Assignee);
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.state.error.as_deref(),
+        Some("Can't assign PROJ-1: You don't have the ASSIGN_ISSUES permission")
+    );
+
+    script.keys("e").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.state.error.as_deref(),
+        Some("Can't edit the summary of PROJ-1: it isn't on the issue's edit screen")
+    );
+
+    // Permissions are only checked once per issue
+    script.keys("s").await;
+    let statuses = matches!(script.state.input_mode, InputMode::UpdateIssueStatus);
+    assert!(statuses);
+    let transitions = "GET /issue/PROJ-1/transitions ";
+    assert_eq!(script.requests().await, [PERMISSIONS_REQUEST, transitions]);
+}
+
+#[tokio::test]
+async fn transitions_are_reused_for_issues_in_the_same_project_and_status() {
+    let mut script = Script::new();
+    let done = TransitionSummary {
+        key: "31".to_string(),
+        name: "Done".to_string(),
+    };
+    let cached = CachedTransitions {
+        transitions: vec![done],
+        fetched: Instant::now(),
+    };
+    let workflow = ("PROJ".to_string(), String::new());
+    let cache = &mut script.state.transition_cache;
+    cache.insert(workflow.clone(), cached);
+
+    script.keys("s").await;
+    assert_eq!(script.state.transitions.items[0].name, "Done");
+    assert_eq!(script.requests().await, [PERMISSIONS_REQUEST]);
+
+    // Refreshing from the picker asks Jira again
+    script.keys("r").await;
+    assert!(script.state.transitions.items.is_empty());
+    let transitions = "GET /issue/PROJ-1/transitions ";
+    assert_eq!(script.requests().await, [PERMISSIONS_REQUEST, transitions]);
+
+    // As does opening it once the cached transitions are too old
+    let fetched = Instant::now().checked_sub(Duration::from_secs(TRANSITIONS_TTL_SECS));
+    let stale = CachedTransitions {
+        transitions: vec![],
+        fetched: fetched.unwrap(),
+    };
+    script.state.transition_cache.insert(workflow, stale);
+    script.keys("<Esc> s").await;
+    let requests = script.requests().await;
+    assert_eq!(requests[2..], [PERMISSIONS_REQUEST, transitions]);
+}
+
+#[tokio::test]
+async fn esc_backs_out_of_a_transition_question_one_level_at_a_time() {
+    let mut script = Script::new();
+    script.state.config.confirm_transitions = vec!["done".to_string()];
+    let done = TransitionSummary {
+        key: "31".to_string(),
+        name: "Done".to_string(),
+    };
+    let cached = CachedTransitions {
+        transitions: vec![done],
+        fetched: Instant::now(),
+    };
+    let workflow = ("PROJ".to_string(), String::new());
+    script.state.transition_cache.insert(workflow, cached);
+
+    script.keys("d s <Enter>").await;
+    let asking = matches!(script.state.input_mode, InputMode::ConfirmTransition);
+    assert!(asking);
+    let question = script.state.transition_question();
+    assert_eq!(question.as_deref(), Some("Move PROJ-1 to Done?"));
+    script.keys("<Esc>").await;
+    let picking = matches!(script.state.input_mode, InputMode::UpdateIssueStatus);
+    assert!(picking);
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+
+    script.keys("s <Enter> y").await;
+    let transition = "POST /issue/PROJ-1/transitions {\"transition\":{\"id\":\"31\"}}";
+    assert!(script.requests().await.iter().any(|r| r == transition));
+    // Once it's made, both close and the detail view is back
+    script.state.close_transition_picker();
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+}
+
+#[tokio::test]
+async fn switching_away_from_the_picker_forgets_the_view_under_it() {
+    let mut script = Script::new();
+    let todo = TransitionSummary {
+        key: "11".to_string(),
+        name: "To Do".to_string(),
+    };
+    let cached = CachedTransitions {
+        transitions: vec![todo],
+        fetched: Instant::now(),
+    };
+    let workflow = ("PROJ".to_string(), String::new());
+    script.state.transition_cache.insert(workflow, cached);
+
+    script.keys("d s").await;
+    assert_eq!(script.state.layers().len(), 2);
+    // A finished clone carries on to naming the branch in place of whatever's open
+    script.state.set_mode(InputMode::Editing);
+    assert!(script.state.modes_below.is_empty());
+    assert_eq!(script.state.layers().len(), 1);
+
+    script.keys("<Esc> s").await;
+    assert_eq!(script.state.modes_below.len(), 1);
+    // Back to the list it was opened from, not the detail view from before
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn popups_close_back_to_whatever_they_were_opened_on() {
+    let mut script = Script::new();
+    script.keys("d A u").await;
+    let layers = script.state.layers();
+    assert!(matches!(
+        layers[..],
+        [InputMode::IssueDetail, InputMode::AttachmentsView, InputMode::UploadingAttachment]
+    ));
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::AttachmentsView));
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+}
+
+#[tokio::test]
+async fn priorities_are_picked_starting_from_the_current_one() {
+    let mut script = Script::new();
+    script.keys("<Down> <Down> !").await;
+    let picking = matches!(script.state.input_mode, InputMode::SettingPriority);
+    assert!(picking);
+    assert_eq!(script.requests().await.last().unwrap(), "GET /priority ");
+
+    let priorities = ["Highest", "High", "Medium", "Low"];
+    script
+        .state
+        .show_priorities(priorities.iter().map(|p| p.to_string()).collect());
+    assert_eq!(script.state.priorities.state.selected(), Some(2));
+    script.keys("<Down> <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.requests().await.last().unwrap(),
+        "PUT /issue/PROJ-3 {\"fields\":{\"priority\":{\"name\":\"Low\"}}}"
+    );
+}
+
+#[tokio::test]
+async fn labels_are_added_and_removed_in_the_label_editor() {
+    let mut script = Script::new();
+    script.keys("l").await;
+    assert!(matches!(script.state.input_mode, InputMode::EditingLabels));
+    let labels = "GET /issue/PROJ-1 fields=labels";
+    assert_eq!(script.requests().await[1], labels);
+
+    let labels = vec!["backend".to_string(), "flaky".to_string()];
+    script.state.show_labels(labels);
+    // Spaces aren't allowed in labels
+    script
+        .keys("needs <Space> triage <Enter> <Down> <Del>")
+        .await;
+    assert_eq!(
+        script.requests().await[2..],
+        [
+            "PUT /issue/PROJ-1 {\"update\":{\"labels\":[{\"add\":\"needstriage\"}]}}",
+            "PUT /issue/PROJ-1 {\"update\":{\"labels\":[{\"remove\":\"flaky\"}]}}",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn subtasks_are_listed_and_created_from_the_detail_view() {
+    let mut script = Script::new();
+    script.keys("d").await;
+    let subtasks = "GET /issue/PROJ-1 fields=subtasks";
+    let time_tracking = "GET /issue/PROJ-1 fields=timetracking";
+    assert_eq!(script.requests().await[..2], [subtasks, time_tracking]);
+
+    script.keys("a Fix <Space> it <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    // The project is looked up first, for its sub-task issue type
+    assert_eq!(script.requests().await[4..], ["GET /project/PROJ "]);
+    assert!(script.state.input.is_empty());
+
+    // Nothing is created without a summary
+    script.keys("a <Enter>").await;
+    assert_eq!(script.requests().await.len(), 5);
+}
+
+#[tokio::test]
+async fn issues_are_assigned_to_people_searched_for() {
+    let mut script = Script::new();
+    script.keys("@ al").await;
+    assert!(matches!(script.state.input_mode, InputMode::AssigningIssue));
+    assert_eq!(
+        script.requests().await,
+        [
+            PERMISSIONS_REQUEST,
+            "GET /user/search query=a maxResults=20",
+            "GET /user/search query=al maxResults=20"
+        ]
+    );
+
+    let alice = UserSummary {
+        id: "alice-id".to_string(),
+        display_name: "Alice".to_string(),
+    };
+    script.state.show_assignees(vec![alice.clone()]);
+    assert_eq!(
+        script.state.assignees.items.as_slice(),
+        [Assignee::Me, Assignee::Unassigned, Assignee::User(alice)]
+    );
+    script.keys("<Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.requests().await[3],
+        "PUT /issue/PROJ-1/assignee {\"accountId\":\"alice-id\"}"
+    );
+}
+
+#[tokio::test]
+async fn renaming_updates_the_summary_straight_away() {
+    let mut script = Script::new();
+    script.keys("e").await;
+    for _ in 0.."Crash when quitting".len() {
+        script.keys("<BS>").await;
+    }
+    script.keys("Crash <Space> on <Space> exit <Enter>").await;
+
+    assert_eq!(script.state.issues.items[0].summary, "Crash on exit");
+    assert!(script
+        .requests()
+        .await
+        .contains(&"GET /issue/PROJ-1 fields=updated".to_string()));
+}
+
+#[tokio::test]
+async fn triage_sort_puts_the_most_severe_first() {
+    let mut script = Script::new();
+    script.keys("S <Down> <Enter>").await;
+
+    assert_eq!(script.state.config.sort_preset, SortPreset::Triage);
+    let keys: Vec<&str> = script.state.issues.items.iter().map(|i| i.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-2", "PROJ-3", "PROJ-1"]);
+    // The same issue stays selected
+    assert_eq!(script.selected().as_deref(), Some("PROJ-1"));
+}
+
+#[tokio::test]
+async fn grouping_by_epic_folds_epics_into_their_header() {
+    let mut script = Script::new();
+    for key in ["PROJ-1", "PROJ-3"].iter() {
+        script.state.issue_mut(key).unwrap().epic = Some("PROJ-9".to_string());
+    }
+    script.keys("S <Down> <Down> <Down> <Enter>").await;
+    assert!(script.state.config.group_by_epic);
+    let issues = script.state.issues.items.iter();
+    let keys: Vec<&str> = issues.map(|i| i.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-1", "PROJ-3", "PROJ-2"]);
+
+    script.keys("f").await;
+    let epic = IssueRow::Epic {
+        key: Some("PROJ-9".to_string()),
+        count: 2,
+        folded: true,
+    };
+    let rows = script.state.issue_rows();
+    assert_eq!(rows[0], epic);
+    assert_eq!(script.state.selected_row(&rows), Some(0));
+    // Moving down passes over the rest of the folded epic
+    script.keys("<Down>").await;
+    assert_eq!(script.selected().as_deref(), Some("PROJ-2"));
+}
+
+#[tokio::test]
+async fn saved_views_bring_back_the_filters_and_layout() {
+    let mut script = Script::new();
+    script.keys("v s Mine <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::PickingView));
+    assert_eq!(script.state.config.views.len(), 1);
+    assert_eq!(script.state.config.views[0].name, "Mine");
+
+    // Change the setup, then switch back to the saved view by its number
+    script.keys("<Esc> m").await;
+    assert!(!script.state.config.filter_mine);
+    script.keys("v 1").await;
+    assert!(script.state.config.filter_mine);
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    let search = script.requests().await.pop().unwrap();
+    assert!(search.starts_with("GET /search/jql "));
+
+    script.keys("v <Del>").await;
+    assert!(script.state.config.views.is_empty());
+}
+
+#[tokio::test]
+async fn n_shows_the_default_boards_active_sprint() {
+    let mut script = Script::new();
+    script.keys("n").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert!(script.state.notice.is_some());
+
+    script.state.config.default_board = Some(SavedBoard {
+        id: 12,
+        name: "PROJ board".to_string(),
+    });
+    script.keys("n").await;
+    assert!(matches!(script.state.input_mode, InputMode::SprintView));
+    let requests = script.requests().await;
+    assert_eq!(requests, ["GET /board/12/sprint state=active"]);
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert!(script.state.sprint.is_none());
+}
+
+#[tokio::test]
+async fn shift_b_lists_the_backlog_and_switches_to_the_sprint() {
+    let mut script = Script::new();
+    script.state.config.default_board = Some(SavedBoard {
+        id: 12,
+        name: "PROJ board".to_string(),
+    });
+    script.keys("B").await;
+    assert!(matches!(script.state.input_mode, InputMode::SprintView));
+    assert!(script.state.sprint.as_ref().unwrap().backlog);
+    let requests = script.requests().await;
+    assert!(requests[0].starts_with("GET /board/12/backlog jql=ORDER BY Rank ASC "));
+
+    script.keys("B").await;
+    assert!(!script.state.sprint.as_ref().unwrap().backlog);
+    let requests = script.requests().await;
+    assert_eq!(requests[1], "GET /board/12/sprint state=active");
+}
+
+#[tokio::test]
+async fn shift_m_moves_the_issue_into_a_picked_sprint() {
+    let mut script = Script::new();
+    script.keys("M").await;
+    assert!(script.state.notice.is_some());
+
+    script.state.config.default_board = Some(SavedBoard {
+        id: 12,
+        name: "PROJ board".to_string(),
+    });
+    script.keys("M").await;
+    assert!(matches!(script.state.input_mode, InputMode::PickingSprint));
+    let requests = script.requests().await;
+    assert_eq!(requests, ["GET /board/12/sprint state=active,future"]);
+
+    let sprint = |id: u64, name: &str| SprintSummary {
+        id,
+        name: name.to_string(),
+        ..Default::default()
+    };
+    let picker = script.state.sprint_picker.as_mut().unwrap();
+    picker.sprints = StatefulList::with_items(vec![sprint(7, "Sprint 7"), sprint(8, "Sprint 8")]);
+    picker.sprints.next();
+    script.keys("<Down> <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert!(script.state.sprint_picker.is_none());
+    let requests = script.requests().await;
+    let moved = "POST /sprint/8/issue {\"issues\":[\"PROJ-1\"]}";
+    assert_eq!(requests[1], moved);
+}
+
+#[tokio::test]
+async fn snoozing_hides_the_issue() {
+    let mut script = Script::new();
+    script.keys("z <Enter>").await;
+
+    assert_eq!(script.state.issues.items.len(), 2);
+    assert_eq!(script.state.snoozed.items[0].key, "PROJ-1");
+    assert_eq!(script.selected().as_deref(), Some("PROJ-2"));
+}
+
+#[tokio::test]
+async fn reauthenticating_takes_over_input_until_done() {
+    let mut script = Script::new();
+    script.state.reauth_input = Some(String::new());
+    script.keys("new-token").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(script.state.reauth_input.as_deref(), Some("new-token"));
+
+    script.keys("<Enter>").await;
+    assert!(script.state.reauth_input.is_none());
+    // Env var credentials can't be updated, so the new token only lasts for the session
+    assert!(script.state.error.is_some());
+}
+
+#[tokio::test]
+async fn q_quits() {
+    let mut script = Script::new();
+    script.keys("q").await;
+    assert!(script.quit);
+}
+
+#[tokio::test]
+async fn ctrl_c_quits_even_while_typing() {
+    let mut script = Script::new();
+    script.keys("c AB <C-c>").await;
+    assert!(script.quit);
+    assert_eq!(script.state.input, "AB");
+}
+
+#[tokio::test]
+async fn ctrl_r_refetches_from_the_detail_view() {
+    let mut script = Script::new();
+    script.keys("d <C-r>").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert!(!script.state.issues_fetched);
+    // Opening the detail view fetches its sub-tasks, time tracking, links and comments first
+    let requests = script.requests().await;
+    assert_eq!(requests.len(), 5);
+    assert!(requests[4].starts_with("GET /search/jql "));
+}
+
+#[tokio::test]
+async fn ctrl_z_asks_the_ui_to_suspend() {
+    let mut script = Script::new();
+    script.keys("<C-z>").await;
+    assert_eq!(script.state.suspend, cfg!(unix));
+    assert!(!script.quit);
+}
+
+#[tokio::test]
+async fn smart_commit_is_built_from_the_form() {
+    let mut script = Script::new();
+    script.keys("Y 1h <Space> 30m <Tab> Fixed <Space> it <Tab>").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::SmartCommit));
+    assert_eq!(
+        script.state.smart_commit_text().as_deref(),
+        Some("PROJ-1 #time 1h 30m #comment Fixed it")
+    );
+    assert!(script
+        .requests()
+        .await
+        .contains(&"GET /issue/PROJ-1/transitions ".to_string()));
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn stopping_a_timer_asks_before_logging() {
+    let mut script = Script::new();
+    script.keys("T").await;
+    assert_eq!(script.state.timer.as_ref().map(|t| t.key.as_str()), Some("PROJ-1"));
+
+    script.keys("T").await;
+    assert!(script.state.timer.is_none());
+    assert_eq!(script.state.stopped_timer.as_ref().map(|t| t.loggable_secs()), Some(60));
+
+    script.keys("y").await;
+    assert!(script.state.stopped_timer.is_none());
+    let requests = script.requests().await;
+    assert_eq!(requests, ["POST /issue/PROJ-1/worklog {\"timeSpentSeconds\":60}"]);
+}
+
+#[tokio::test]
+async fn w_logs_typed_time_with_a_comment() {
+    let mut script = Script::new();
+    script.keys("d w 90 <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::LoggingWork));
+    assert!(script.state.error.is_some());
+
+    script.keys("<BS> <BS> 1h <Space> 30m").await;
+    script.keys("<Tab> Reviewed <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert!(script.state.worklog.is_none());
+    let logged = "POST /issue/PROJ-1/worklog {\"timeSpentSeconds\":5400,\"comment\":\"Reviewed\"}";
+    assert!(script.requests().await.contains(&logged.to_string()));
+}
+
+#[tokio::test]
+async fn u_attaches_a_file_picked_from_the_current_directory() {
+    let mut script = Script::new();
+    script.keys("d A u Cargo.t").await;
+    let mode = &script.state.input_mode;
+    assert!(matches!(mode, InputMode::UploadingAttachment));
+    assert_eq!(script.state.local_files.items.as_slice(), ["Cargo.toml"]);
+
+    script.keys("<Down> <Enter>").await;
+    let mode = &script.state.input_mode;
+    assert!(matches!(mode, InputMode::AttachmentsView));
+    let requests = script.requests().await;
+    let uploaded = "POST /issue/PROJ-1/attachments file=Cargo.toml";
+    assert_eq!(requests.last().unwrap(), uploaded);
+
+    // A typed path is read before anything's sent
+    script.keys("u missing.log <Enter>").await;
+    assert_eq!(script.requests().await.len(), requests.len());
+}
+
+#[tokio::test]
+async fn shift_a_lists_attachments_to_download() {
+    let mut script = Script::new();
+    script.keys("d A").await;
+    let mode = &script.state.input_mode;
+    assert!(matches!(mode, InputMode::AttachmentsView));
+    let requests = script.requests().await;
+    let listed = "GET /issue/PROJ-1 fields=attachment";
+    assert_eq!(requests.last().unwrap(), listed);
+
+    let attachment = AttachmentSummary {
+        filename: "crash.log".to_string(),
+        size: 2048,
+        url: "https://example.atlassian.net/rest/api/2/attachment/content/10100".to_string(),
+        author: "Alice".to_string(),
+        created: "2021-03-02T10:15:00.000+0000".to_string(),
+    };
+    script.state.attachments = StatefulList::with_items(vec![attachment]);
+    script.state.attachments.next();
+    script.keys("<Enter>").await;
+    // The content is fetched from the attachment's own URL
+    let requests = script.requests().await;
+    let downloaded = "GET /rest/api/2/attachment/content/10100";
+    assert_eq!(requests.last().unwrap(), downloaded);
+
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+}
+
+#[tokio::test]
+async fn searching_the_detail_view_steps_through_matches() {
+    let mut script = Script::new();
+    script.keys("d / crash <Enter> n n N").await;
+
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    let search = script.state.detail_search.as_ref().unwrap();
+    assert_eq!(search.query, "crash");
+    assert_eq!(search.current, 1);
+    assert!(search.jump);
+
+    // The first Esc clears the search, the next leaves the detail view
+    script.keys("<Esc>").await;
+    assert!(script.state.detail_search.is_none());
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn background_requests_past_the_limit_wait_their_turn() {
+    let tasks = crate::tasks::TaskPool::new(1);
+    let (event_tx, _event_rx) = mpsc::unbounded_channel();
+    let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+    tasks.spawn(event_tx.clone(), |_| async move {
+        let _ = done_rx.await;
+    });
+    tasks.spawn(event_tx, |_| async {});
+    let _ = yield_now().await;
+    assert_eq!(tasks.queued(), 1);
+
+    done_tx.send(()).unwrap();
+    for _ in 0..5 {
+        let _ = yield_now().await;
+    }
+    assert_eq!(tasks.queued(), 0);
+}
+
+fn link(key: &str) -> IssueLinkSummary {
+    IssueLinkSummary {
+        relation: "blocks".to_string(),
+        key: key.to_string(),
+        summary: format!("{} summary", key),
+        status: "To Do".to_string(),
+        resolved: false,
+        blocker: false,
+    }
+}
+
+#[tokio::test]
+async fn enter_on_a_link_selects_the_linked_issue_in_the_list() {
+    let mut script = Script::new();
+    script.keys("g").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueLinks));
+    assert_eq!(script.requests().await, ["GET /issue/PROJ-1 "]);
+
+    let view = script.state.links_view.as_mut().unwrap();
+    view.list(vec![link("PROJ-9"), link("PROJ-2")]);
+    script.keys("<Down> <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(script.selected().as_deref(), Some("PROJ-2"));
+    assert!(script.state.links_view.is_none());
+}
+
+#[tokio::test]
+async fn links_missing_from_the_list_are_fetched_and_leave_a_breadcrumb() {
+    let mut script = Script::new();
+    script.keys("d g").await;
+    let view = script.state.links_view.as_mut().unwrap();
+    view.list(vec![link("PROJ-9")]);
+    script.keys("<Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssueDetail));
+    assert_eq!(script.state.breadcrumbs, ["PROJ-1"]);
+    let requests = script.requests().await;
+    let fetched = requests.last().unwrap();
+    assert!(fetched.starts_with("GET /issue/PROJ-9 fields="));
+}
+
+#[tokio::test]
+async fn link_types_are_fetched_once_for_linking() {
+    let mut script = Script::new();
+    script.keys("K").await;
+    assert!(matches!(
+        script.state.input_mode,
+        InputMode::PickingLinkType
+    ));
+    assert_eq!(script.requests().await, ["GET /issueLinkType "]);
+
+    // Picking needs the link types, so Enter waits for them
+    script.keys("<Enter>").await;
+    assert!(matches!(
+        script.state.input_mode,
+        InputMode::PickingLinkType
+    ));
+    script.keys("<Esc>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+}
+
+#[tokio::test]
+async fn k_links_the_issue_to_one_picked_by_its_summary() {
+    let mut script = Script::new();
+    script.state.link_relations = vec![
+        LinkRelation {
+            link_type: "Blocks".to_string(),
+            description: "blocks".to_string(),
+            outward: true,
+        },
+        LinkRelation {
+            link_type: "Blocks".to_string(),
+            description: "is blocked by".to_string(),
+            outward: false,
+        },
+    ];
+    script.keys("K <Down> <Enter> dark").await;
+    assert!(matches!(script.state.input_mode, InputMode::LinkingIssue));
+    let targets = &script.state.link_form.as_ref().unwrap().targets.items;
+    let keys: Vec<&str> = targets.iter().map(|issue| issue.key.as_str()).collect();
+    assert_eq!(keys, ["PROJ-2"]);
+
+    script.keys("<Down> <Enter>").await;
+    assert!(matches!(script.state.input_mode, InputMode::IssuesList));
+    assert_eq!(
+        script.requests().await,
+        ["POST /issueLink {\"inwardIssue\":{\"key\":\"PROJ-2\"},\"outwardIssue\":{\"key\":\"PROJ-1\"},\"type\":{\"name\":\"Blocks\"}}"]
+    );
+}
+
+#[tokio::test]
+async fn background_tasks_stop_once_nothing_listens_for_their_events() {
+    let tasks = crate::tasks::TaskPool::new(1);
+    let (event_tx, event_rx) = mpsc::unbounded_channel();
+    let (_reply_tx, reply_rx) = tokio::sync::oneshot::channel::<()>();
+    let (finished_tx, finished_rx) = tokio::sync::oneshot::channel();
+    tasks.spawn(event_tx, move |event_tx| async move {
+        // Waits on a reply that never comes, like a request hanging as the app quits
+        let _ = reply_rx.await;
+        send_event(&event_tx, Event::Tick);
+        let _ = finished_tx.send(());
+    });
+    let _ = yield_now().await;
+
+    drop(event_rx);
+    assert!(finished_rx.await.is_err());
+}
@@ -0,0 +1,80 @@
+//! A limit on how many requests run in the background at once, so moving quickly through the
+//! app queues them up instead of opening dozens of connections to Jira.
+use crate::events::EventsTx;
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use tokio::sync::Semaphore;
+
+/// How many background tasks run at once when the config doesn't say.
+pub const DEFAULT_PARALLELISM: usize = 4;
+
+/// Spawns tasks that wait for one of a fixed number of slots before running. Clones share the
+/// slots, so every clone of a `JiraClient` draws from the same pool.
+#[derive(Clone)]
+pub struct TaskPool {
+    slots: Arc<Semaphore>,
+    parallelism: usize,
+    /// Tasks spawned but still waiting for a slot.
+    queued: Arc<AtomicUsize>,
+}
+
+impl TaskPool {
+    pub fn new(parallelism: usize) -> TaskPool {
+        TaskPool {
+            // A pool of none would never run anything
+            slots: Arc::new(Semaphore::new(parallelism.max(1))),
+            parallelism: parallelism.max(1),
+            queued: Arc::default(),
+        }
+    }
+
+    /// Run the task in the background once a slot is free, handing it the sender for its
+    /// events. Tasks start in the order they were spawned. They're dropped, whether waiting or
+    /// partway through, once nothing is listening for events any more as the app shuts down.
+    pub fn spawn<T, F>(&self, event_tx: EventsTx, task: T)
+    where
+        T: FnOnce(EventsTx) -> F + Send + 'static,
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let slots = self.slots.clone();
+        let queued = self.queued.clone();
+        queued.fetch_add(1, Ordering::Relaxed);
+        tokio::spawn(async move {
+            let listener = event_tx.clone();
+            let run = async move {
+                let permit = slots.acquire_owned().await;
+                queued.fetch_sub(1, Ordering::Relaxed);
+                // The semaphore is never closed, so there's always a permit eventually
+                if let Ok(_permit) = permit {
+                    task(event_tx).await;
+                }
+            };
+            tokio::select! {
+                _ = run => {}
+                _ = listener.closed() => {}
+            }
+        });
+    }
+
+    /// How many tasks run at once, for work done outside the pool that should keep to the same
+    /// limit.
+    pub fn parallelism(&self) -> usize {
+        self.parallelism
+    }
+
+    /// How many tasks are waiting for a slot, for the status bar.
+    pub fn queued(&self) -> usize {
+        self.queued.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for TaskPool {
+    fn default() -> TaskPool {
+        TaskPool::new(DEFAULT_PARALLELISM)
+    }
+}
@@ -0,0 +1,204 @@
+//! Text generated from an issue through templates set in the config file, e.g. PR descriptions.
+//! Templates refer to values by name in braces, like `{key}`.
+use crate::jira::IssueSummary;
+
+pub const DEFAULT_PR_TEMPLATE: &str = "{summary}
+
+{excerpt}
+
+Acceptance criteria:
+{acceptance_criteria}
+
+Jira: {link}";
+
+pub const DEFAULT_BRANCH_TEMPLATE: &str = "{prefix}{key}-{name}";
+
+/// Bullets in markdown and wiki markup, and wiki markup's numbered items.
+const LIST_MARKERS: &[char] = &['*', '-', '#'];
+
+/// Descriptions are cut down to their first paragraph, up to this many characters.
+const EXCERPT_CHARS: usize = 400;
+
+/// Swap each `{name}` in the template for its value, leaving unknown names as they are.
+pub fn render(template: &str, values: &[(&str, String)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), value)
+        })
+}
+
+/// A PR description for the issue. Available values are `key`, `summary`, `link`, `status`,
+/// `excerpt`, `description`, `acceptance_criteria` and `reviewers`, the names picked separated
+/// by commas. Templates that don't place `{reviewers}` get a line for them at the end when any
+/// were picked.
+pub fn pr_description(
+    issue: &IssueSummary,
+    reviewers: &[String],
+    template: Option<&str>,
+) -> String {
+    let criteria = acceptance_criteria(&issue.description);
+    let criteria = if criteria.is_empty() {
+        "- None listed".to_string()
+    } else {
+        criteria
+            .iter()
+            .map(|item| format!("- {}", item))
+            .collect::<Vec<String>>()
+            .join("\n")
+    };
+    let template = template.unwrap_or(DEFAULT_PR_TEMPLATE);
+    let template = if reviewers.is_empty() || template.contains("{reviewers}") {
+        template.to_string()
+    } else {
+        format!("{}\n\nReviewers: {{reviewers}}", template)
+    };
+    render(
+        &template,
+        &[
+            ("key", issue.key.clone()),
+            ("summary", issue.summary.clone()),
+            ("link", issue.permalink.clone()),
+            ("status", issue.status.clone()),
+            ("excerpt", excerpt(&issue.description)),
+            ("description", issue.description.clone()),
+            ("acceptance_criteria", criteria),
+            ("reviewers", reviewers.join(", ")),
+        ],
+    )
+}
+
+/// A branch name for the issue from the name typed in. Available values are `key`, `name`,
+/// `slug`, the summary lowercased with dashes between words, and `prefix`, the project's branch
+/// prefix. Templates that don't place `{prefix}` get it at the start.
+pub fn branch_name(
+    key: &str,
+    name: &str,
+    summary: &str,
+    prefix: &str,
+    template: Option<&str>,
+) -> String {
+    let template = template.unwrap_or(DEFAULT_BRANCH_TEMPLATE);
+    let template = if template.contains("{prefix}") {
+        template.to_string()
+    } else {
+        format!("{{prefix}}{}", template)
+    };
+    render(
+        &template,
+        &[
+            ("prefix", prefix.to_string()),
+            ("key", key.to_string()),
+            ("name", name.to_string()),
+            ("slug", slug(summary)),
+        ],
+    )
+}
+
+/// A commit message using Jira's smart commit commands, e.g.
+/// `PROJ-123 #time 2h #comment Fixed the crash #in-review`. Transitions are written as their name
+/// in lowercase with dashes between words, the way Jira matches them.
+pub fn smart_commit(key: &str, time: &str, comment: &str, transition: Option<&str>) -> String {
+    let mut parts = vec![key.to_string()];
+    if !time.trim().is_empty() {
+        parts.push(format!("#time {}", time.trim()));
+    }
+    if !comment.trim().is_empty() {
+        parts.push(format!("#comment {}", comment.trim()));
+    }
+    if let Some(transition) = transition {
+        parts.push(format!("#{}", slug(transition)));
+    }
+    parts.join(" ")
+}
+
+fn slug(text: &str) -> String {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<String>>()
+        .join("-")
+}
+
+/// The description's first paragraph, leaving out the acceptance criteria.
+fn excerpt(description: &str) -> String {
+    let paragraph = description
+        .split("\n\n")
+        .map(str::trim)
+        .find(|paragraph| !paragraph.is_empty() && !is_criteria_heading(first_line(paragraph)))
+        .unwrap_or("");
+    if paragraph.chars().count() > EXCERPT_CHARS {
+        format!(
+            "{}...",
+            paragraph.chars().take(EXCERPT_CHARS).collect::<String>()
+        )
+    } else {
+        paragraph.to_string()
+    }
+}
+
+fn first_line(text: &str) -> &str {
+    text.lines().next().unwrap_or("")
+}
+
+/// The items listed under an "Acceptance criteria" heading, in wiki markup, markdown or plain
+/// text. The list runs to the next heading, or for marked up lists, the first unmarked line.
+fn acceptance_criteria(description: &str) -> Vec<String> {
+    let lines: Vec<&str> = description
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| !is_criteria_heading(line))
+        .skip(1)
+        .filter(|line| !line.is_empty())
+        .collect();
+    let marked = lines.first().is_some_and(|line| is_list_item(line));
+
+    let mut items = vec![];
+    for line in lines {
+        if is_heading(line) || (marked && !is_list_item(line)) {
+            break;
+        }
+        let item = line.trim_start_matches(LIST_MARKERS);
+        // Checklist items keep their text, not their checkbox
+        let item = item
+            .trim()
+            .trim_start_matches("[ ]")
+            .trim_start_matches("[x]")
+            .trim();
+        if !item.is_empty() {
+            items.push(item.to_string());
+        }
+    }
+    items
+}
+
+fn is_list_item(line: &str) -> bool {
+    line.starts_with(LIST_MARKERS)
+}
+
+fn is_criteria_heading(line: &str) -> bool {
+    let text = strip_heading_marker(line.trim())
+        .trim_start_matches('*')
+        .trim_end_matches(&[':', '*'][..])
+        .trim()
+        .to_lowercase();
+    text == "acceptance criteria" || text == "ac"
+}
+
+/// Markdown `##` headings and wiki markup `h2.` headings. A single `#` is a numbered list item
+/// in wiki markup, so isn't counted.
+fn is_heading(line: &str) -> bool {
+    line.starts_with("##") || is_wiki_heading(line)
+}
+
+fn is_wiki_heading(line: &str) -> bool {
+    let bytes = line.as_bytes();
+    bytes.len() > 2 && bytes[0] == b'h' && bytes[1].is_ascii_digit() && bytes[2] == b'.'
+}
+
+fn strip_heading_marker(line: &str) -> &str {
+    if is_wiki_heading(line) {
+        return line[3..].trim_start();
+    }
+    line.trim_start_matches('#').trim_start()
+}
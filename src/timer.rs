@@ -0,0 +1,93 @@
+//! A work timer running against an issue. It's saved to disk while running, so closing the app
+//! doesn't lose the time.
+use crate::{config::app_file_path, snooze::now};
+use anyhow::{anyhow, bail, Result};
+use std::fs;
+
+const TIMER_FILE_NAME: &str = "timer.json";
+
+/// The units Jira takes time spent in and their seconds, with its default 8 hour days and 5 day
+/// weeks.
+const DURATION_UNITS: [(char, u64); 4] =
+    [('w', 5 * 8 * 3600), ('d', 8 * 3600), ('h', 3600), ('m', 60)];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkTimer {
+    pub key: String,
+    /// Unix timestamp of when the timer was started.
+    pub started: u64,
+}
+
+impl WorkTimer {
+    pub fn start(key: String) -> WorkTimer {
+        WorkTimer {
+            key,
+            started: now(),
+        }
+    }
+
+    pub fn elapsed_secs(&self) -> u64 {
+        now().saturating_sub(self.started)
+    }
+
+    /// Seconds to log for the time so far. Jira won't take less than a minute, and partial
+    /// minutes are rounded up.
+    pub fn loggable_secs(&self) -> u64 {
+        self.elapsed_secs().div_ceil(60).max(1) * 60
+    }
+}
+
+pub fn load_timer() -> Option<WorkTimer> {
+    let contents = fs::read_to_string(app_file_path(TIMER_FILE_NAME).ok()?).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Save the running timer, or remove the saved one once it's stopped.
+pub fn save_timer(timer: Option<&WorkTimer>) -> Result<()> {
+    let path = app_file_path(TIMER_FILE_NAME)?;
+    match timer {
+        Some(timer) => fs::write(path, serde_json::to_string(timer)?)?,
+        None if path.exists() => fs::remove_file(path)?,
+        None => {}
+    }
+    Ok(())
+}
+
+/// A duration the way Jira writes them, e.g. "1h 5m".
+pub fn describe_duration(secs: u64) -> String {
+    let (hours, minutes) = (secs / 3600, secs % 3600 / 60);
+    match (hours, minutes) {
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h {}m", hours, minutes),
+    }
+}
+
+/// Seconds for time spent written the way Jira takes it, e.g. "1h 30m" or "2d".
+pub fn parse_duration(text: &str) -> Result<u64> {
+    let mut total = 0;
+    let mut number = String::new();
+    for c in text.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let c = c.to_ascii_lowercase();
+        let seconds = match DURATION_UNITS.iter().find(|(unit, _)| *unit == c) {
+            Some((_, seconds)) => seconds,
+            None => bail!("{} isn't a unit, write the time like 1h 30m", c),
+        };
+        let count: u64 = number
+            .parse()
+            .map_err(|_| anyhow!("Put a number before the {}", c))?;
+        total += count * seconds;
+        number.clear();
+    }
+    if !number.is_empty() {
+        bail!("{} needs a unit, like {}m", number, number);
+    }
+    if total == 0 {
+        bail!("Type the time spent, like 1h 30m");
+    }
+    Ok(total)
+}
@@ -1,20 +1,45 @@
-use crate::state::{InputMode, State, StateRx};
+mod plain;
+mod theme;
+
+use self::{plain::run_plain, theme::Theme};
+use crate::{
+    audit::report_lines,
+    config::SortPreset,
+    credentials::CredentialSource,
+    editor::{diff_lines, DiffLine, TextEditor},
+    jira::{IssueLinkSummary, IssueSummary, RateLimit, TimeTracking},
+    locale::{mode_help, text, Locale, Text},
+    platform::stop_process,
+    snooze::describe_remaining,
+    timer::{describe_duration, WorkTimer},
+    state::{
+        Assignee, BranchEntry, CloneOffer, FilterChip, InputMode, IssueRow, ItemStatus,
+        SearchProgress, State, StateRx, SMART_COMMIT_ROWS, WORKLOG_ROWS,
+    },
+};
 use anyhow::Result;
 use crossterm::{
     event::{DisableMouseCapture, EnableMouseCapture},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::io::{stdout, Write};
+use crossterm_events::event::{DisableFocusChange, EnableFocusChange};
+use std::{
+    collections::BTreeSet,
+    io::{stderr, stdout, Write},
+};
 use tui::{
     backend::CrosstermBackend,
+    buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::Spans,
-    widgets::{Block, Borders, Clear, List, ListItem, Paragraph},
+    style::{Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Clear, Gauge, List, ListItem, ListState, Paragraph, Widget, Wrap},
     Frame, Terminal,
 };
 
+/// Each frame is drawn into an empty buffer and only the cells that differ from the last frame
+/// are written out, so `Clear` is only needed under popups drawn over something else.
 pub fn draw<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State) {
     let size = f.size();
 
@@ -24,174 +49,1869 @@ pub fn draw<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State) {
         .split(size);
     draw_help(f, app, help_drawer[1]);
 
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-        .split(help_drawer[0]);
+    // Popups are drawn over the modes they were opened on, dimming what's underneath. Nothing
+    // under a screen that fills the window would show, so drawing starts from the topmost one.
+    let layers = app.layers();
+    let first = layers.iter().rposition(fills_screen).unwrap_or(0);
+    for (depth, mode) in layers.iter().enumerate().skip(first) {
+        if depth > first {
+            f.render_widget(Dim, help_drawer[0]);
+        }
+        draw_mode(f, app, mode, size);
+    }
+
+    if let Some(timer) = &app.stopped_timer {
+        draw_stopped_timer(f, app, timer, size);
+    }
+    if let Some(offer) = &app.clone_offer {
+        draw_clone_offer(f, app, offer, size);
+    }
+    if let Some(name) = &app.existing_branch {
+        let question = format!("{} is already there, check it out instead?", name);
+        draw_branch_question(f, app, question, size);
+    }
+    if let Some(name) = &app.branch_collision {
+        let question = format!("{} is taken, what should happen?", name);
+        draw_branch_question(f, app, question, size);
+    }
+    if app.reauth_input.is_some() {
+        draw_reauth_input(f, app, size);
+    }
+}
+
+/// Whether a mode is drawn over the whole window, rather than as a popup over another.
+fn fills_screen(mode: &InputMode) -> bool {
+    matches!(
+        mode,
+        InputMode::IssuesList
+            | InputMode::BoardsList
+            | InputMode::FilteringBoards
+            | InputMode::IssueLinks
+            | InputMode::IssueDetail
+            | InputMode::SearchingDetail
+            | InputMode::EditingDescription
+            | InputMode::ConfirmDescription
+            | InputMode::SnoozedList
+            | InputMode::ActivityFeed
+            | InputMode::SprintView
+            | InputMode::CommentsView
+            | InputMode::AddingComment
+            | InputMode::AccountInfo
+            | InputMode::AuditView
+    )
+}
+
+/// The screen for a mode, or the popup for those opened on top of another.
+fn draw_mode<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app: &mut State,
+    mode: &InputMode,
+    size: Rect,
+) {
+    let help_drawer = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(10), Constraint::Length(2)])
+        .split(size);
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(help_drawer[0]);
+
+    match mode {
+        InputMode::IssuesList => {
+            draw_issues(f, app, chunks[0]);
+            draw_branches(f, app, chunks[1]);
+        }
+        InputMode::BoardsList | InputMode::FilteringBoards => {
+            draw_boards(f, app, chunks[0]);
+        }
+        InputMode::Editing => draw_branch_input(f, app, size),
+        InputMode::UpdateIssueStatus => draw_update_issue_status(f, app, size),
+        InputMode::ConfirmTransition => draw_transition_question(f, app, size),
+        InputMode::IssueLinks => draw_issue_links(f, app, help_drawer[0]),
+        InputMode::IssueDetail | InputMode::SearchingDetail => {
+            draw_issue_detail(f, app, chunks[0]);
+            draw_branches(f, app, chunks[1]);
+        }
+        InputMode::EditingDescription => draw_description_editor(f, app, help_drawer[0]),
+        InputMode::ConfirmDescription => draw_description_diff(f, app, help_drawer[0]),
+        InputMode::BulkEditAction => draw_bulk_edit_actions(f, app, size),
+        InputMode::BulkEditValue => draw_bulk_edit_value(f, app, size),
+        InputMode::BulkEditProgress => draw_bulk_edit_progress(f, app, size),
+        InputMode::EditingSummary => draw_summary_input(f, app, size),
+        InputMode::SortMenu => draw_sort_menu(f, app, size),
+        InputMode::SnoozeMenu => draw_snooze_menu(f, app, size),
+        InputMode::SnoozedList => draw_snoozed(f, app, help_drawer[0]),
+        InputMode::ActivityFeed => draw_activity(f, app, help_drawer[0]),
+        InputMode::SprintView => draw_sprint(f, app, help_drawer[0]),
+        InputMode::PickingSprint => draw_sprint_picker(f, app, size),
+        InputMode::EditingDefaultProject => draw_project_input(f, app, size),
+        InputMode::SwitchingProject => draw_project_switcher(f, app, size),
+        InputMode::PickingView => draw_view_picker(f, app, size),
+        InputMode::NamingView => draw_view_name_input(f, app, size),
+        InputMode::EditingJql => draw_jql_input(f, app, size),
+        InputMode::SmartCommit => draw_smart_commit(f, app, size),
+        InputMode::LoggingWork => draw_worklog_form(f, app, size),
+        InputMode::CommentsView => draw_comments(f, app, help_drawer[0]),
+        InputMode::AddingComment => draw_comment_editor(f, app, help_drawer[0]),
+        InputMode::LinkingPullRequest => draw_pull_request_input(f, app, size),
+        InputMode::CreatingSubtask => draw_subtask_input(f, app, size),
+        InputMode::AttachmentsView => draw_attachments(f, app, size),
+        InputMode::UploadingAttachment => draw_upload_input(f, app, size),
+        InputMode::PickingLinkType => draw_link_type_picker(f, app, size),
+        InputMode::LinkingIssue => draw_link_target_input(f, app, size),
+        InputMode::PickingReviewers => draw_reviewer_picker(f, app, size),
+        InputMode::AccountInfo => draw_account(f, app, help_drawer[0]),
+        InputMode::AuditView => draw_audit(f, app, help_drawer[0]),
+        InputMode::EditingLabels => draw_label_editor(f, app, size),
+        InputMode::SettingPriority => draw_priority_picker(f, app, size),
+        InputMode::AssigningIssue => draw_assignee_picker(f, app, size),
+    }
+}
+
+/// Fades what's been drawn in the area, under a mode opened on top of it.
+struct Dim;
+
+impl Widget for Dim {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        buf.set_style(area, Style::default().add_modifier(Modifier::DIM));
+    }
+}
+
+fn draw_issues<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = match &app.issues_loading {
+        Some(loading) => {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(3), Constraint::Length(3)].as_ref())
+                .split(area);
+            draw_issues_loading(f, app, loading, chunks[1]);
+            chunks[0]
+        }
+        None => area,
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3)].as_ref())
+        .split(area);
+    draw_filter_chips(f, app, chunks[0]);
+    let area = chunks[1];
+
+    // Only the rows that fit on screen are turned into list items, the list can be thousands
+    // long. The window stays put while the selection moves within it, and moves just far enough
+    // to bring the selection back when it goes off either end.
+    let rows = app.issue_rows();
+    let height = area.height.saturating_sub(2).max(1) as usize;
+    let selected = app.selected_row(&rows);
+    let mut start = app.issues_offset.min(rows.len().saturating_sub(height));
+    if let Some(i) = selected {
+        if i < start {
+            start = i;
+        } else if i >= start + height {
+            start = i + 1 - height;
+        }
+    }
+    app.issues_offset = start;
+    let mut window_state = ListState::default();
+    window_state.select(selected.map(|i| i - start));
+
+    let issues: Vec<ListItem> = rows
+        .iter()
+        .skip(start)
+        .take(height)
+        .map(|row| match row {
+            IssueRow::Issue(i) => issue_item(app, &app.issues.items[*i]),
+            IssueRow::Epic { key, count, folded } => {
+                epic_header(app, key.as_deref(), *count, *folded)
+            }
+        })
+        .collect();
+    // The filters are shown as chips above the list
+    let mut title = "Jira Issues".to_string();
+    if app.config.search_all_projects {
+        title = format!("All Projects - {}", title)
+    }
+    if let SortPreset::Triage = app.config.sort_preset {
+        title = format!("{} (Triage)", title)
+    }
+    let issues = List::new(issues)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+    f.render_stateful_widget(issues, area, &mut window_state);
+}
+
+fn issue_item(app: &State, issue: &IssueSummary) -> ListItem<'static> {
+    // Issues are indented under their epic's header
+    let indent = if app.config.group_by_epic { "  " } else { "" };
+    let marked = app.marked.contains(&issue.key);
+    let mark = if marked { "[x] " } else { "" };
+    let key_style = if app.config.priority_colors {
+        Theme::new(&app.config).severity(issue.severity())
+    } else {
+        Style::default()
+    };
+    // Issues from extra hosts are badged with where they're from
+    let host = match &issue.host {
+        Some(host) => format!("[{}] ", host),
+        None => String::new(),
+    };
+    let started = started_work(app, &issue.key);
+    let lines = vec![Spans::from(vec![
+        Span::raw(format!("{}{}", indent, mark)),
+        Span::styled(started, Theme::new(&app.config).added()),
+        Span::styled(host, Theme::new(&app.config).faint()),
+        Span::styled(issue.key.clone(), key_style),
+        Span::raw(format!(": {}", issue.summary)),
+        Span::styled(watch_marker(issue), Theme::new(&app.config).faint()),
+    ])];
+    ListItem::new(lines).style(Theme::new(&app.config).item())
+}
+
+/// Put after the summary of issues you're watching.
+fn watch_marker(issue: &IssueSummary) -> &'static str {
+    if issue.watching {
+        " ◉"
+    } else {
+        ""
+    }
+}
+
+/// A column marking issues with a local branch and an open PR, shown once any issue has one.
+fn started_work(app: &State, key: &str) -> String {
+    if app.branched.is_empty() && app.pull_requests.is_empty() {
+        return String::new();
+    }
+    let marker = |keys: &BTreeSet<String>, marker: &str| {
+        if keys.contains(key) {
+            marker.to_string()
+        } else {
+            " ".repeat(marker.chars().count())
+        }
+    };
+    let branch = marker(&app.branched, "⎇");
+    format!("{} {} ", branch, marker(&app.pull_requests, "PR"))
+}
+
+/// The header above an epic's issues, with the epic's summary when it's in the list as well.
+fn epic_header(app: &State, key: Option<&str>, count: usize, folded: bool) -> ListItem<'static> {
+    let name = match key {
+        Some(key) => match app.issues.items.iter().find(|issue| issue.key == key) {
+            Some(epic) => format!("{}: {}", key, epic.summary),
+            None => key.to_string(),
+        },
+        None => text(app.config.locale, Text::NoEpic).to_string(),
+    };
+    let fold = if folded { "+" } else { "-" };
+    let header = format!("{} {} ({})", fold, name, count);
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    ListItem::new(Spans::from(Span::styled(header, heading)))
+}
+
+/// The filters the list is searched with, the one Tab has moved to highlighted.
+fn draw_filter_chips<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let theme = Theme::new(&app.config);
+    let focused = app.focused_chip();
+    let mut chips = vec![];
+    for chip in FilterChip::active(&app.config) {
+        let style = if Some(chip) == focused {
+            theme.selected()
+        } else {
+            theme.chip()
+        };
+        let label = format!(" {} ", chip.describe(&app.config));
+        chips.push(Span::styled(label, style));
+        chips.push(Span::raw(" "));
+    }
+    f.render_widget(Paragraph::new(Spans::from(chips)), area);
+}
+
+fn draw_sort_menu<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let options: Vec<ListItem> = app
+        .sort_menu
+        .items
+        .iter()
+        .map(|i| ListItem::new(vec![Spans::from(i.to_string())]))
+        .collect();
+    let options = List::new(options)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Sort)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut app.sort_menu.state);
+}
+
+fn draw_snooze_menu<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let options: Vec<ListItem> = app
+        .snooze_menu
+        .items
+        .iter()
+        .map(|i| ListItem::new(vec![Spans::from(i.to_string())]))
+        .collect();
+    let options = List::new(options)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::SnoozeFor)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut app.snooze_menu.state);
+}
+
+fn draw_project_switcher<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let current = &app.config.default_project_key;
+    let options: Vec<ListItem> = app
+        .project_menu
+        .items
+        .iter()
+        .enumerate()
+        .map(|(i, key)| {
+            let marker = if key == current { " (current)" } else { "" };
+            ListItem::new(format!("{}  {}{}", i + 1, key, marker))
+        })
+        .collect();
+    let options = List::new(options)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::RecentProjects)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut app.project_menu.state);
+}
+
+fn draw_reviewer_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let picker = &mut app.reviewer_picker;
+    let options: Vec<ListItem> = picker
+        .suggested
+        .items
+        .iter()
+        .map(|name| {
+            let checkbox = if picker.picked.contains(name) { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", checkbox, name))
+        })
+        .collect();
+    let locale = app.config.locale;
+    let title = if picker.draft {
+        format!(
+            "{} ({})",
+            text(locale, Text::SuggestedReviewers),
+            text(locale, Text::DraftPullRequest)
+        )
+    } else {
+        text(locale, Text::SuggestedReviewers).to_string()
+    };
+    let options = List::new(options)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut picker.suggested.state);
+}
+
+/// A search box for people to assign the selected issue to, above what's been found.
+fn draw_assignee_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 40, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+        .split(area);
+    let locale = app.config.locale;
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(locale, Text::AssignTo)),
+        );
+    let options: Vec<ListItem> = app
+        .assignees
+        .items
+        .iter()
+        .map(|assignee| ListItem::new(describe_assignee(locale, assignee)))
+        .collect();
+    let options = List::new(options)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(options, chunks[1], &mut app.assignees.state);
+    f.set_cursor(
+        chunks[0].x + app.raw_input_clone().len() as u16 + 1,
+        chunks[0].y + 1,
+    );
+}
+
+fn draw_label_editor<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 40, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+        .split(area);
+    let locale = app.config.locale;
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(locale, Text::AddLabel)),
+        );
+    let labels: Vec<ListItem> = app
+        .labels
+        .items
+        .iter()
+        .map(|label| ListItem::new(label.as_str()))
+        .collect();
+    let labels = List::new(labels)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(locale, Text::Labels)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(labels, chunks[1], &mut app.labels.state);
+    f.set_cursor(
+        chunks[0].x + app.raw_input_clone().len() as u16 + 1,
+        chunks[0].y + 1,
+    );
+}
+
+fn describe_assignee(locale: Locale, assignee: &Assignee) -> String {
+    match assignee {
+        Assignee::Me => text(locale, Text::Me).to_string(),
+        Assignee::Unassigned => text(locale, Text::Unassigned).to_string(),
+        Assignee::User(user) => user.display_name.clone(),
+    }
+}
+
+fn draw_snoozed<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let snoozed: Vec<ListItem> = app
+        .snoozed
+        .items
+        .iter()
+        .map(|i| {
+            let line_content = format!(
+                "{}: {} (wakes in {})",
+                i.key,
+                i.summary,
+                describe_remaining(i.until)
+            );
+            ListItem::new(vec![Spans::from(line_content)]).style(Theme::new(&app.config).item())
+        })
+        .collect();
+    let snoozed = List::new(snoozed)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::SnoozedIssues)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(snoozed, area, &mut app.snoozed.state);
+}
+
+fn draw_account<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let lines: Vec<Spans> = account_lines(app).into_iter().map(Spans::from).collect();
+    let account = Paragraph::new(lines).wrap(Wrap { trim: true }).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(text(app.config.locale, Text::Account)),
+    );
+    f.render_widget(account, area);
+}
+
+fn draw_audit<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let lines = match &app.audit {
+        Some(audit) => report_lines(audit),
+        None => vec![text(app.config.locale, Text::Loading).to_string()],
+    };
+    let lines: Vec<Spans> = lines.into_iter().map(Spans::from).collect();
+    let audit = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(text(app.config.locale, Text::BranchAudit)),
+    );
+    f.render_widget(audit, area);
+}
+
+/// What the account screen shows, a line per fact.
+fn account_lines(app: &State) -> Vec<String> {
+    let account = match &app.account {
+        Some(account) => account,
+        None => return vec![text(app.config.locale, Text::Loading).to_string()],
+    };
+    let user = match &account.email {
+        Some(email) => format!("User: {} <{}>", account.display_name, email),
+        None => format!("User: {}", account.display_name),
+    };
+    let token_from = match account.credential_source {
+        CredentialSource::Env => "JIRA_PASS",
+        CredentialSource::Command => "credential_command",
+        CredentialSource::Netrc => "~/.netrc",
+    };
+    let groups = if account.groups.is_empty() {
+        "none".to_string()
+    } else {
+        account.groups.join(", ")
+    };
+    let mut lines = vec![
+        user,
+        format!("Account: {}", account.id.as_deref().unwrap_or("unknown")),
+        format!("Logged in as {}, token from {}", account.login, token_from),
+        format!("Groups: {}", groups),
+        String::new(),
+        format!(
+            "Jira: {:?} {}",
+            account.deployment,
+            account.version.as_deref().unwrap_or("")
+        ),
+        format!("Host: {}", account.host),
+    ];
+    // Jira hands out links using its base URL, which trips people up when it's not the host
+    match &account.base_url {
+        Some(base_url) if *base_url != account.host => {
+            lines.push(format!("Base URL: {}", base_url));
+        }
+        _ => {}
+    }
+    lines.push(match &app.rate_limit {
+        Some(limit) => describe_rate_limit(limit),
+        None => "No rate limit reported".to_string(),
+    });
+    lines.push(String::new());
+    lines.push(format!("App version: {}", env!("CARGO_PKG_VERSION")));
+    lines
+}
+
+fn draw_activity<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let activity: Vec<ListItem> = app
+        .activity
+        .items
+        .iter()
+        .map(|i| {
+            let change = format!("  {} changed {}: {} -> {}", i.author, i.field, i.from, i.to);
+            let lines = vec![
+                Spans::from(format!("{}: {}", i.key, i.summary)),
+                Spans::from(Span::styled(change, Theme::new(&app.config).faint())),
+            ];
+            ListItem::new(lines).style(Theme::new(&app.config).item())
+        })
+        .collect();
+    let muted = app.config.muted_issues.len() + app.config.muted_event_types.len();
+    let activity = List::new(activity)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!("Activity ({} muted)", muted)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(activity, area, &mut app.activity.state);
+}
+
+fn draw_sprint<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let locale = app.config.locale;
+    let theme = Theme::new(&app.config);
+    let view = match &mut app.sprint {
+        Some(view) => view,
+        None => return,
+    };
+    let title = match (&view.sprint, view.loaded) {
+        _ if view.backlog && view.loaded => {
+            let count = view.issues.items.len();
+            let backlog = text(locale, Text::Backlog);
+            format!("{} - {} ({})", view.board.name, backlog, count)
+        }
+        (Some(sprint), _) => {
+            let ends = match &sprint.end_date {
+                // Only the date part of the timestamp
+                Some(end) => format!(", ends {}", end.get(..10).unwrap_or(end)),
+                None => String::new(),
+            };
+            format!("{} - {}{}", view.board.name, sprint.name, ends)
+        }
+        (None, true) => {
+            let missing = text(locale, Text::NoActiveSprint);
+            format!("{} - {}", view.board.name, missing)
+        }
+        (None, false) => format!("{} - {}", view.board.name, text(locale, Text::Loading)),
+    };
+    let sprint = view.sprint.as_ref();
+    let goal = sprint.map_or("", |sprint| sprint.goal.as_str());
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(1), Constraint::Min(3)].as_ref())
+        .split(area);
+    if !goal.is_empty() {
+        let goal = Span::styled(format!("Goal: {}", goal), theme.faint());
+        f.render_widget(Paragraph::new(Spans::from(goal)), chunks[0]);
+    }
+
+    let issues: Vec<ListItem> = view
+        .issues
+        .items
+        .iter()
+        .map(|i| {
+            let lines = vec![
+                Spans::from(format!("{}: {}", i.key, i.summary)),
+                Spans::from(Span::styled(
+                    format!("  {} - {}", i.status, i.assignee_name),
+                    theme.faint(),
+                )),
+            ];
+            ListItem::new(lines).style(theme.item())
+        })
+        .collect();
+    let issues = List::new(issues)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(theme.selected())
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(issues, chunks[1], &mut view.issues.state);
+}
+
+fn draw_branches<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let locale = app.config.locale;
+    let theme = Theme::new(&app.config);
+    let branches: Vec<ListItem> = app
+        .branches
+        .items
+        .iter()
+        .map(|entry| {
+            let line = match entry {
+                BranchEntry::Existing(branch) => {
+                    // Checking out the current branch does nothing, so it's marked like `git
+                    // branch` does
+                    let current = app.head_branch.as_ref() == Some(&branch.name);
+                    let mark = if current { "* " } else { "" };
+                    Spans::from(format!("{}{}", mark, branch.name))
+                }
+                BranchEntry::CreateNew => {
+                    let create = text(locale, Text::CreateNewBranch);
+                    Spans::from(Span::styled(create, theme.faint()))
+                }
+            };
+            ListItem::new(vec![line]).style(theme.item())
+        })
+        .collect();
+    let mut title = text(app.config.locale, Text::ExistingBranches).to_string();
+    if let Some(repo) = app.project_repo() {
+        let in_repo = text(app.config.locale, Text::InRepo);
+        title = format!("{} {} {}", title, in_repo, repo.display());
+    }
+    let branches = List::new(branches)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(branches, area, &mut app.branches.state);
+}
+
+fn draw_boards<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let boards: Vec<ListItem> = app
+        .boards
+        .items
+        .iter()
+        .map(|i| {
+            let star = if app.config.favorite_boards.contains(&i.key) {
+                "* "
+            } else {
+                ""
+            };
+            let default_board = app.config.default_board.as_ref();
+            let scope = if default_board.is_some_and(|board| board.id == i.key) {
+                " (issues scoped to this board)"
+            } else {
+                ""
+            };
+            let project = match &i.project {
+                Some(project) => format!(" ({})", project),
+                None => String::new(),
+            };
+            let lines = vec![Spans::from(format!(
+                "{}{}{}{}",
+                star, i.name, project, scope
+            ))];
+            ListItem::new(lines).style(Theme::new(&app.config).item())
+        })
+        .collect();
+    let mut title = text(app.config.locale, Text::ExistingBranches).to_string();
+    let filtering = matches!(app.input_mode, InputMode::FilteringBoards);
+    if filtering || !app.board_filter.is_empty() {
+        title = format!("{} - /{}", title, app.board_filter);
+    }
+    let boards = List::new(boards)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(boards, area, &mut app.boards.state);
+}
+
+fn draw_help<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let locale = app.config.locale;
+    let help_text = mode_help(locale, &app.input_mode);
+
+    let help_text = match (&app.input_mode, &app.clipboard_issue) {
+        _ if app.reauth_input.is_some() => text(locale, Text::ReauthHelp).to_string(),
+        _ if app.stopped_timer.is_some() => text(locale, Text::StoppedTimerHelp).to_string(),
+        _ if app.clone_offer.is_some() => text(locale, Text::CloneOfferHelp).to_string(),
+        _ if app.existing_branch.is_some() => text(locale, Text::ExistingBranchHelp).to_string(),
+        _ if app.branch_collision.is_some() => text(locale, Text::BranchCollisionHelp).to_string(),
+        (InputMode::IssuesList, Some(key)) => format!(
+            "{} - {}",
+            text(locale, Text::JumpFromClipboard).replace("{key}", key),
+            help_text
+        ),
+        _ => help_text.to_string(),
+    };
+
+    let help_text = match &app.rate_limit {
+        Some(limit) => format!("{} | {}", describe_rate_limit(limit), help_text),
+        None => help_text,
+    };
+
+    let help_text = match app.queued_requests {
+        0 => help_text,
+        count => {
+            let queued = text(locale, Text::RequestsQueued).replace("{count}", &count.to_string());
+            format!("{} | {}", queued, help_text)
+        }
+    };
+
+    let help_text = match app.unsent_requests.as_slice() {
+        [] => help_text,
+        requests => {
+            let not_sent = text(locale, Text::NotSent).replace("{request}", &requests.join("; "));
+            format!("{} | {}", not_sent, help_text)
+        }
+    };
+
+    let help_text = match &app.head_branch {
+        Some(branch) => {
+            let on_branch = text(locale, Text::OnBranch).replace("{branch}", branch);
+            format!("{} | {}", on_branch, help_text)
+        }
+        None => help_text,
+    };
+
+    let help_text = match &app.timer {
+        Some(timer) => format!(
+            "{} | {}",
+            describe_timer(timer, app.config.pomodoro_minutes),
+            help_text
+        ),
+        None => help_text,
+    };
+
+    let theme = Theme::new(&app.config);
+    let (help_text, style) = match (&app.error, &app.notice) {
+        (Some(error), _) => (error.clone(), theme.error()),
+        (None, Some(notice)) => (notice.clone(), theme.notice()),
+        (None, None) => (help_text, theme.help()),
+    };
+
+    let help = Paragraph::new(help_text)
+        .style(style)
+        .block(Block::default().borders(Borders::NONE));
+    f.render_widget(help, area);
+}
+
+/// e.g. "API budget 40/100, resets 14:05"
+fn describe_rate_limit(limit: &RateLimit) -> String {
+    let budget = match limit.limit {
+        Some(total) => format!("API budget {}/{}", limit.remaining, total),
+        None => format!("API budget {}", limit.remaining),
+    };
+    // Show just the time of day from timestamps like 2021-03-01T14:05:00Z
+    match limit.reset.as_deref().and_then(|reset| reset.get(11..16)) {
+        Some(time) => format!("{}, resets {}", budget, time),
+        None => budget,
+    }
+}
+
+fn draw_update_issue_status<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let transitions: Vec<ListItem> = app
+        .transitions
+        .items
+        .iter()
+        .map(|i| {
+            let lines = vec![Spans::from(i.name.to_string())];
+            ListItem::new(lines).style(Theme::new(&app.config).item())
+        })
+        .collect();
+    let transitions = List::new(transitions)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Transitions)),
+        )
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(transitions, area, &mut app.transitions.state);
+}
+
+fn draw_priority_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let theme = Theme::new(&app.config);
+    let priorities: Vec<ListItem> = app
+        .priorities
+        .items
+        .iter()
+        .map(|name| ListItem::new(name.as_str()).style(theme.item()))
+        .collect();
+    let priorities = List::new(priorities)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Priorities)),
+        )
+        .highlight_style(theme.selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(priorities, area, &mut app.priorities.state);
+}
+
+fn draw_issue_detail<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let chunks = if app.checklist.items.is_empty() {
+        vec![area]
+    } else {
+        let height = app.checklist.items.len() as u16 + 2;
+        Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(height)].as_ref())
+            .split(area)
+    };
+
+    let lines = match app.selected_issue() {
+        Some(issue) => {
+            let mut lines = vec![
+                Spans::from(Span::styled(
+                    format!("{}: {}", issue.key, issue.summary),
+                    Style::default().add_modifier(Modifier::BOLD),
+                )),
+                Spans::from(""),
+                Spans::from(format!("Status: {}", issue.status)),
+                Spans::from(format!("Assignee: {}", issue.assignee_name)),
+            ];
+            if let Some(parent) = &issue.parent {
+                lines.push(Spans::from(format!("Parent: {}", parent)));
+            }
+            if let Some(index) = app.siblings.iter().position(|i| i.key == issue.key) {
+                lines.push(Spans::from(format!(
+                    "Sibling {} of {}",
+                    index + 1,
+                    app.siblings.len()
+                )));
+            }
+            let custom_fields = [
+                ("Story Points", &issue.story_points),
+                ("Epic", &issue.epic),
+                ("Sprint", &issue.sprint),
+                ("Team", &issue.team),
+            ];
+            for (name, value) in custom_fields.iter() {
+                if let Some(value) = value {
+                    lines.push(Spans::from(format!("{}: {}", name, value)));
+                }
+            }
+            if let Some(tracking) = app.time_tracking.get(&issue.key) {
+                lines.extend(time_tracking_line(app, tracking));
+            }
+            lines.push(Spans::from(format!("Link: {}", issue.permalink)));
+            // Say why the keys for actions Jira refused do nothing
+            let restrictions = app.restrictions.get(&issue.key).into_iter().flatten();
+            for (action, reason) in restrictions {
+                let refusal = format!("Can't {}: {}", action.describe(), reason);
+                let faint = Theme::new(&app.config).faint();
+                lines.push(Spans::from(Span::styled(refusal, faint)));
+            }
+            lines.push(Spans::from(""));
+            lines.extend(issue.description.lines().map(Spans::from));
+            lines.push(Spans::from(""));
+            if let Some(subtasks) = app.subtasks.get(&issue.key).filter(|s| !s.is_empty()) {
+                lines.extend(subtask_lines(subtasks));
+                lines.push(Spans::from(""));
+            }
+            if let Some(links) = app.links.get(&issue.key).filter(|l| !l.is_empty()) {
+                lines.extend(link_lines(app, links));
+                lines.push(Spans::from(""));
+            }
+            lines.extend(comment_lines(app));
+            lines
+        }
+        None => vec![Spans::from(text(app.config.locale, Text::LoadingIssue))],
+    };
+
+    // Show the trail of issues navigated through to get here
+    let mut title = if app.breadcrumbs.is_empty() {
+        "Issue".to_string()
+    } else {
+        format!("Issue ({} >)", app.breadcrumbs.join(" > "))
+    };
+
+    let mut scroll = app.detail_scroll;
+    let lines = match &app.detail_search {
+        Some(search) if !search.query.is_empty() => {
+            let theme = Theme::new(&app.config);
+            let (lines, matches) = highlight_matches(lines, &search.query, theme);
+            if matches.is_empty() {
+                title = format!("{} - /{} (no matches)", title, search.query);
+            } else {
+                let current = search.current.rem_euclid(matches.len() as isize) as usize;
+                title = format!(
+                    "{} - /{} ({} of {})",
+                    title,
+                    search.query,
+                    current + 1,
+                    matches.len()
+                );
+                if search.jump {
+                    let width = chunks[0].width.saturating_sub(2).max(1) as usize;
+                    // Leave a little context above the match
+                    scroll = wrapped_height(&lines[..matches[current]], width).saturating_sub(2);
+                }
+            }
+            lines
+        }
+        Some(search) => {
+            title = format!("{} - /{}", title, search.query);
+            lines
+        }
+        None => lines,
+    };
+    let detail = Paragraph::new(lines)
+        .wrap(Wrap { trim: true })
+        .scroll((scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(detail, chunks[0]);
+
+    if let Some(area) = chunks.get(1) {
+        draw_checklist(f, app, *area);
+    }
+}
+
+/// Highlight case-insensitive matches of the query, returning the indices of the lines they're
+/// on. Matches are looked for within each span, which covers the detail view's lines.
+fn highlight_matches<'a>(
+    lines: Vec<Spans<'a>>,
+    query: &str,
+    theme: Theme,
+) -> (Vec<Spans<'a>>, Vec<usize>) {
+    let query = query.to_ascii_lowercase();
+    let mut matches = vec![];
+    let lines = lines
+        .into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let mut found = false;
+            let mut spans = vec![];
+            for span in line.0 {
+                // ASCII lowercasing keeps byte offsets the same as in the original text
+                let lower = span.content.to_ascii_lowercase();
+                if !lower.contains(&query) {
+                    spans.push(span);
+                    continue;
+                }
+                found = true;
+                let mut start = 0;
+                for (at, _) in lower.match_indices(&query) {
+                    let end = at + query.len();
+                    spans.push(Span::styled(
+                        span.content[start..at].to_string(),
+                        span.style,
+                    ));
+                    spans.push(Span::styled(
+                        span.content[at..end].to_string(),
+                        span.style.patch(theme.search_match()),
+                    ));
+                    start = end;
+                }
+                spans.push(Span::styled(span.content[start..].to_string(), span.style));
+            }
+            if found {
+                matches.push(i);
+            }
+            Spans::from(spans)
+        })
+        .collect();
+    (lines, matches)
+}
+
+/// How many rows the lines take up once wrapped to the width, near enough.
+fn wrapped_height(lines: &[Spans], width: usize) -> u16 {
+    lines
+        .iter()
+        .map(|line| line.width().max(1).div_ceil(width) as u16)
+        .sum()
+}
+
+/// Time tracking in red once the estimate's been overrun.
+fn time_tracking_line(app: &State, tracking: &TimeTracking) -> Option<Spans<'static>> {
+    let line = tracking.describe()?;
+    Some(if tracking.over_budget {
+        Spans::from(Span::styled(line, Theme::new(&app.config).error()))
+    } else {
+        Spans::from(line)
+    })
+}
+
+fn subtask_lines(subtasks: &[IssueSummary]) -> Vec<Spans<'static>> {
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![Spans::from(Span::styled(
+        format!("Sub-tasks ({})", subtasks.len()),
+        heading,
+    ))];
+    lines.extend(subtasks.iter().map(|subtask| {
+        Spans::from(format!(
+            "{}: {} [{}]",
+            subtask.key, subtask.summary, subtask.status
+        ))
+    }));
+    lines
+}
+
+/// The comments loaded so far, with a marker for any older ones still to be fetched.
+fn comment_lines(app: &State) -> Vec<Spans<'static>> {
+    let older = match app.older_comments {
+        Some(older) => older,
+        None => return vec![Spans::from(text(app.config.locale, Text::LoadingComments))],
+    };
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![Spans::from(Span::styled(
+        format!("Comments ({})", app.comments.len() + older),
+        heading,
+    ))];
+    if older > 0 {
+        lines.push(Spans::from(Span::styled(
+            format!("{} older comments - PageUp at the top to load more", older),
+            Theme::new(&app.config).faint(),
+        )));
+    }
+    for comment in &app.comments {
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(Span::styled(
+            format!("{} - {}", comment.author, comment.created),
+            heading,
+        )));
+        lines.extend(
+            comment
+                .body
+                .lines()
+                .map(|line| Spans::from(line.to_string())),
+        );
+    }
+    lines
+}
+
+fn draw_comments<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let title = match app.selected_issue() {
+        Some(issue) => format!("{}: {}", issue.key, issue.summary),
+        None => text(app.config.locale, Text::Comments).to_string(),
+    };
+    let comments = Paragraph::new(comment_lines(app))
+        .wrap(Wrap { trim: true })
+        .scroll((app.comments_scroll, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(comments, area);
+}
+
+fn draw_checklist<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let items: Vec<ListItem> = app
+        .checklist
+        .items
+        .iter()
+        .map(|item| {
+            let checkbox = if item.checked { "[x]" } else { "[ ]" };
+            ListItem::new(format!("{} {}", checkbox, item.text))
+        })
+        .collect();
+    let highlight = if app.checklist_focused {
+        Theme::new(&app.config).selected()
+    } else {
+        Style::default()
+    };
+    let checklist = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Checklist)),
+        )
+        .highlight_style(highlight)
+        .highlight_symbol(">> ");
+
+    f.render_stateful_widget(checklist, area, &mut app.checklist.state);
+}
+
+/// e.g. "is blocked by PROJ-7: Fix the login page [In Progress]"
+fn describe_link(link: &IssueLinkSummary) -> String {
+    format!(
+        "{} {}: {} [{}]",
+        link.relation, link.key, link.summary, link.status
+    )
+}
+
+fn link_style(app: &State, link: &IssueLinkSummary) -> Style {
+    if link.blocker && !link.resolved {
+        Theme::new(&app.config).blocker()
+    } else {
+        Style::default()
+    }
+}
+
+fn link_lines(app: &State, links: &[IssueLinkSummary]) -> Vec<Spans<'static>> {
+    let heading = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![Spans::from(Span::styled(
+        format!("Links ({})", links.len()),
+        heading,
+    ))];
+    lines.extend(
+        links
+            .iter()
+            .map(|link| Spans::from(Span::styled(describe_link(link), link_style(app, link)))),
+    );
+    lines
+}
+
+fn draw_issue_links<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let view = match &app.links_view {
+        Some(view) => view,
+        None => return,
+    };
+    let mut lines = vec![Spans::from(Span::styled(
+        view.key.clone(),
+        Style::default().add_modifier(Modifier::BOLD),
+    ))];
+    let count = view.links.items.len();
+    for (i, link) in view.links.items.iter().enumerate() {
+        let branch = if i + 1 == count { "└── " } else { "├── " };
+        let mut style = link_style(app, link);
+        if view.links.state.selected() == Some(i) {
+            style = style.patch(Theme::new(&app.config).selected());
+        }
+        lines.push(Spans::from(vec![
+            Span::raw(branch),
+            Span::styled(describe_link(link), style),
+        ]));
+    }
+    if count == 0 {
+        lines.push(Spans::from("└── No linked issues"));
+    }
+
+    let graph = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(text(app.config.locale, Text::IssueLinks)),
+    );
+    f.render_widget(graph, area);
+}
+
+fn draw_bulk_edit_actions<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let actions: Vec<ListItem> = app
+        .bulk_actions
+        .items
+        .iter()
+        .map(|i| ListItem::new(vec![Spans::from(i.to_string())]))
+        .collect();
+    let title = format!("Bulk edit {} issues", app.marked.len());
+    let actions = List::new(actions)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(actions, area, &mut app.bulk_actions.state);
+}
+
+fn draw_bulk_edit_value<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let title = match app.bulk_actions.state.selected() {
+        Some(i) => app.bulk_actions.items[i].clone(),
+        None => "Value".to_string(),
+    };
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
+
+fn draw_bulk_edit_progress<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let progress = match &app.bulk_progress {
+        Some(progress) => progress,
+        None => return,
+    };
+    let theme = Theme::new(&app.config);
+    let area = centered_rect(60, 50, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+        .split(area);
+
+    let mut label = format!("{}/{}", progress.done(), progress.total());
+    if progress.finished() && progress.cancelled() {
+        label = format!("Cancelled, {}", progress.summary());
+    } else if !progress.finished() {
+        label = format!("{} {}", spinner(app.animation_frame), label);
+    }
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::BulkEdit)),
+        )
+        .gauge_style(theme.progress())
+        .label(label)
+        .ratio(ratio(progress.done(), progress.total()));
+
+    let current = progress.current();
+    let report: Vec<ListItem> = progress
+        .items
+        .iter()
+        .map(|(key, status)| {
+            let (description, style) = match status {
+                ItemStatus::Pending if progress.cancelled() => {
+                    ("cancelling".to_string(), theme.faint())
+                }
+                ItemStatus::Pending if current == Some(key.as_str()) => (
+                    format!("{} updating", spinner(app.animation_frame)),
+                    theme.focused(),
+                ),
+                ItemStatus::Pending => ("waiting".to_string(), theme.faint()),
+                ItemStatus::Done => ("updated".to_string(), theme.added()),
+                ItemStatus::Failed(err) => (err.clone(), theme.error()),
+                ItemStatus::Skipped => ("skipped".to_string(), theme.faint()),
+            };
+            ListItem::new(format!("{}: {}", key, description)).style(style)
+        })
+        .collect();
+    let report = List::new(report).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(text(app.config.locale, Text::Issues)),
+    );
+
+    f.render_widget(Clear, area);
+    f.render_widget(gauge, chunks[0]);
+    f.render_widget(report, chunks[1]);
+}
+
+/// A gauge along the bottom of the issues pane while a search spanning several pages loads.
+fn draw_issues_loading<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app: &State,
+    search: &SearchProgress,
+    area: Rect,
+) {
+    let gauge = Gauge::default()
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Loading)),
+        )
+        .gauge_style(Theme::new(&app.config).progress())
+        .label(format!(
+            "{} {}/{}",
+            spinner(app.animation_frame),
+            search.loaded,
+            search.total
+        ))
+        .ratio(ratio(search.loaded, search.total));
+    f.render_widget(gauge, area);
+}
+
+fn ratio(done: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        (done as f64 / total as f64).min(1.0)
+    }
+}
+
+fn spinner(frame: usize) -> &'static str {
+    const FRAMES: [&str; 4] = ["|", "/", "-", "\\"];
+    FRAMES[frame % FRAMES.len()]
+}
+
+fn draw_description_editor<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    if let Some(edit) = &app.description_edit {
+        let title = format!("Editing description of {}", edit.key);
+        draw_text_editor(f, &edit.editor, title, area);
+    }
+}
+
+fn draw_comment_editor<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    if let Some(draft) = &app.comment_draft {
+        let new_comment = text(app.config.locale, Text::NewCommentOn);
+        let title = format!("{} {}", new_comment, draft.key);
+        draw_text_editor(f, &draft.editor, title, area);
+    }
+}
+
+fn draw_text_editor<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    editor: &TextEditor,
+    title: String,
+    area: Rect,
+) {
+    let (col, row) = editor.cursor();
+    // Keep the cursor on screen by scrolling once it passes the bottom of the box
+    let visible_rows = area.height.saturating_sub(2) as usize;
+    let scroll = (row + 1).saturating_sub(visible_rows);
+    let lines: Vec<Spans> = editor
+        .lines()
+        .iter()
+        .map(|line| Spans::from(line.as_str()))
+        .collect();
+    let paragraph = Paragraph::new(lines)
+        .scroll((scroll as u16, 0))
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(paragraph, area);
+    f.set_cursor(
+        area.x + col as u16 + 1,
+        area.y + (row - scroll) as u16 + 1,
+    );
+}
+
+fn draw_description_diff<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let edit = match &app.description_edit {
+        Some(edit) => edit,
+        None => return,
+    };
+    let lines: Vec<Spans> = diff_lines(&edit.original, &edit.editor.text())
+        .into_iter()
+        .map(|line| match line {
+            DiffLine::Same(text) => Spans::from(format!("  {}", text)),
+            DiffLine::Added(text) => Spans::from(Span::styled(
+                format!("+ {}", text),
+                Theme::new(&app.config).added(),
+            )),
+            DiffLine::Removed(text) => Spans::from(Span::styled(
+                format!("- {}", text),
+                Theme::new(&app.config).removed(),
+            )),
+        })
+        .collect();
+    let diff = Paragraph::new(lines).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!("Save these changes to {}?", edit.key)),
+    );
+    f.render_widget(diff, area);
+}
+
+fn draw_branch_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let mut title = text(app.config.locale, Text::NewBranchName).to_string();
+    if let Some(dir) = app.project_dir() {
+        title = format!("{} - {}: {}", title, text(app.config.locale, Text::WorkIn), dir);
+    }
+    let input = Paragraph::new(app.new_branch_name())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.new_branch_name().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
+
+fn draw_summary_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let title = match app.selected_issue_key() {
+        Some(key) => format!("Rename {}", key),
+        None => "Rename issue".to_string(),
+    };
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
 
-    match app.input_mode {
-        InputMode::IssuesList => {
-            draw_issues(f, app, chunks[0]);
-            draw_branches(f, app, chunks[1]);
-        }
-        InputMode::BoardsList => {
-            draw_boards(f, app, chunks[0]);
-        }
-        InputMode::Editing => draw_branch_input(f, app, size),
-        InputMode::UpdateIssueStatus => draw_update_issue_status(f, app, size),
-        InputMode::EditingDefaultProject => draw_project_input(f, app, size),
-    }
+fn draw_pull_request_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let title = match app.selected_issue_key() {
+        Some(key) => format!("Link a PR to {}", key),
+        None => "Link a PR".to_string(),
+    };
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
 }
 
-fn draw_issues<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
-    let issues: Vec<ListItem> = app
-        .issues
+fn draw_view_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let options: Vec<ListItem> = app
+        .view_menu
         .items
         .iter()
-        .map(|i| {
-            let line_content = format!("{}: {}", i.key, i.summary);
-            let lines = vec![Spans::from(line_content)];
-            ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
+        .enumerate()
+        .map(|(i, view)| {
+            let current = app.config.is_current_view(view);
+            let marker = if current { " (current)" } else { "" };
+            ListItem::new(format!("{}  {}{}", i + 1, view.name, marker))
         })
         .collect();
-    let mut title = "Jira Issues".to_string();
-    if app.config.filter_in_progress {
-        title = format!("In Progress {}", title)
-    }
-    if app.config.default_project_key != "" {
-        title = format!("Project: {} - {}", app.config.default_project_key, title)
-    }
-    if app.config.filter_mine {
-        title = format!("{} Owned by Me", title)
-    }
-    let issues = List::new(issues)
-        .block(Block::default().borders(Borders::ALL).title(title))
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
+    let options = List::new(options)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::SavedViews)),
         )
+        .highlight_style(Theme::new(&app.config).selected())
         .highlight_symbol(">> ");
-    f.render_stateful_widget(issues, area, &mut app.issues.state);
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut app.view_menu.state);
 }
 
-fn draw_branches<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
-    let branches: Vec<ListItem> = app
-        .branches
+fn draw_sprint_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let picker = match &mut app.sprint_picker {
+        Some(picker) => picker,
+        None => return,
+    };
+    let options: Vec<ListItem> = picker
+        .sprints
         .items
         .iter()
-        .map(|i| {
-            let lines = vec![Spans::from(i.name.to_string())];
-            ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
+        .map(|sprint| {
+            let marker = if sprint.active { " (active)" } else { "" };
+            ListItem::new(format!("{}{}", sprint.name, marker))
         })
         .collect();
-    let branches = List::new(branches)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("Existing Branches"),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
-        )
+    let title = text(app.config.locale, Text::MoveToSprint);
+    let options = List::new(options)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(branches, area, &mut app.branches.state);
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(options, area, &mut picker.sprints.state);
 }
 
-fn draw_boards<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
-    let boards: Vec<ListItem> = app
-        .boards
+fn draw_attachments<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(70, 40, area);
+    let faint = Theme::new(&app.config).faint();
+    let attachments: Vec<ListItem> = app
+        .attachments
         .items
         .iter()
-        .map(|i| {
-            let lines = vec![Spans::from(i.name.to_string())];
-            ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
+        .map(|attachment| {
+            // Only the day it was added, not the time
+            let added = attachment.created.get(..10).unwrap_or(&attachment.created);
+            let details = format!(
+                "  {} - {}, {}",
+                describe_size(attachment.size),
+                attachment.author,
+                added
+            );
+            ListItem::new(Spans::from(vec![
+                Span::raw(attachment.filename.clone()),
+                Span::styled(details, faint),
+            ]))
         })
         .collect();
-    let boards = List::new(boards)
+    let dir = app.config.download_dir();
+    let title = format!("Attachments - saved to {}", dir.display());
+    let attachments = List::new(attachments)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Theme::new(&app.config).selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(attachments, area, &mut app.attachments.state);
+}
+
+fn draw_upload_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 40, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+        .split(area);
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Existing Branches"),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
-        )
+                .title(text(app.config.locale, Text::AttachFile)),
+        );
+    let files: Vec<ListItem> = app
+        .local_files
+        .items
+        .iter()
+        .map(|name| ListItem::new(name.as_str()))
+        .collect();
+    let files = List::new(files)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Theme::new(&app.config).selected())
         .highlight_symbol(">> ");
 
-    f.render_stateful_widget(boards, area, &mut app.boards.state);
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(files, chunks[1], &mut app.local_files.state);
+    f.set_cursor(
+        chunks[0].x + app.raw_input_clone().len() as u16 + 1,
+        chunks[0].y + 1,
+    );
 }
 
-fn draw_help<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
-    let help_text = match app.input_mode {
-        InputMode::IssuesList => {
-            "Up/Down: Navigate issues - Enter/Right: Create new branch - b: Go to list of Jira Boards - m: Filter to issues owned by me - p: Change project key - i: Filter in/not in progress - q: Quit this application"
-        }
-        InputMode::BoardsList => {
-            "Boards"
-        }
-        InputMode::UpdateIssueStatus => "Update Issue Status",
-        InputMode::Editing =>  {
-            "Editing"
-        }
-        InputMode::EditingDefaultProject =>  {
-            "Editing"
+fn draw_link_type_picker<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(40, 30, area);
+    let theme = Theme::new(&app.config);
+    let title = text(app.config.locale, Text::LinkTypes);
+    let form = match app.link_form.as_mut() {
+        Some(form) => form,
+        None => return,
+    };
+    let relations: Vec<ListItem> = form
+        .relations
+        .items
+        .iter()
+        .map(|relation| ListItem::new(relation.description.as_str()).style(theme.item()))
+        .collect();
+    let relations = List::new(relations)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(theme.selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_stateful_widget(relations, area, &mut form.relations.state);
+}
+
+fn draw_link_target_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+    let area = centered_rect(60, 40, area);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)].as_ref())
+        .split(area);
+    let theme = Theme::new(&app.config);
+    let typed = app.raw_input_clone();
+    let form = match app.link_form.as_mut() {
+        Some(form) => form,
+        None => return,
+    };
+    // e.g. "PROJ-1 is blocked by"
+    let selected = form.relations.state.selected();
+    let relation = selected.and_then(|i| form.relations.items.get(i));
+    let title = match relation {
+        Some(relation) => format!("{} {}", form.key, relation.description),
+        None => form.key.clone(),
+    };
+    let input = Paragraph::new(typed.clone())
+        .style(theme.input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    let targets: Vec<ListItem> = form
+        .targets
+        .items
+        .iter()
+        .map(|issue| ListItem::new(format!("{}: {}", issue.key, issue.summary)))
+        .collect();
+    let targets = List::new(targets)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(theme.selected())
+        .highlight_symbol(">> ");
+
+    f.render_widget(Clear, area);
+    f.render_widget(input, chunks[0]);
+    f.render_stateful_widget(targets, chunks[1], &mut form.targets.state);
+    f.set_cursor(chunks[0].x + typed.len() as u16 + 1, chunks[0].y + 1);
+}
+
+/// A file size rounded to the nearest unit, e.g. `1.5 MB`.
+fn describe_size(bytes: u64) -> String {
+    const UNITS: [&str; 3] = ["KB", "MB", "GB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+fn draw_view_name_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let title = text(app.config.locale, Text::ViewName);
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
+
+fn draw_jql_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(80, 20, area);
+    let title = text(app.config.locale, Text::JqlQuery);
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
+
+fn draw_subtask_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    let title = match app.selected_issue_key() {
+        Some(key) => format!("New sub-task of {}", key),
+        None => "New sub-task".to_string(),
+    };
+    let input = Paragraph::new(app.raw_input_clone())
+        .style(Theme::new(&app.config).input())
+        .block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(Clear, area);
+    f.render_widget(input, area);
+    f.set_cursor(
+        // Put cursor past the end of the input text
+        area.x + app.raw_input_clone().len() as u16 + 1,
+        // Move one line down, from the border to the input line
+        area.y + 1,
+    );
+}
+
+fn draw_smart_commit<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 30, area);
+    let form = &app.smart_commit;
+    let transition = match form.transition.and_then(|i| app.transitions.items.get(i)) {
+        Some(transition) => transition.name.clone(),
+        None if app.transitions.items.is_empty() => {
+            text(app.config.locale, Text::Loading).to_string()
         }
+        None => text(app.config.locale, Text::LeaveAsIs).to_string(),
     };
+    let values = [
+        form.time.clone(),
+        form.comment.clone(),
+        format!("< {} >", transition),
+    ];
+    let mut lines: Vec<Spans> = SMART_COMMIT_ROWS
+        .iter()
+        .zip(values.iter())
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let style = if i == form.row {
+                Theme::new(&app.config).focused()
+            } else {
+                Style::default()
+            };
+            Spans::from(Span::styled(format!("{}: {}", label, value), style))
+        })
+        .collect();
+    lines.push(Spans::from(""));
+    lines.push(Spans::from(app.smart_commit_text().unwrap_or_default()));
 
-    let help = Paragraph::new(help_text)
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().borders(Borders::NONE));
+    let title = match app.selected_issue_key() {
+        Some(key) => format!("Smart commit for {}", key),
+        None => "Smart commit".to_string(),
+    };
+    let form = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
     f.render_widget(Clear, area);
-    f.render_widget(help, area);
+    f.render_widget(form, area);
 }
 
-fn draw_update_issue_status<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
+fn draw_worklog_form<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
     let area = centered_rect(60, 20, area);
-    let transitions: Vec<ListItem> = app
-        .transitions
-        .items
+    let form = match &app.worklog {
+        Some(form) => form,
+        None => return,
+    };
+    let values = [&form.time, &form.comment];
+    let lines: Vec<Spans> = WORKLOG_ROWS
         .iter()
-        .map(|i| {
-            let lines = vec![Spans::from(i.name.to_string())];
-            ListItem::new(lines).style(Style::default().fg(Color::Black).bg(Color::White))
+        .zip(values.iter())
+        .enumerate()
+        .map(|(i, (label, value))| {
+            let style = if i == form.row {
+                Theme::new(&app.config).focused()
+            } else {
+                Style::default()
+            };
+            Spans::from(Span::styled(format!("{}: {}", label, value), style))
         })
         .collect();
-    let transitions = List::new(transitions)
+
+    let title = format!("Log work on {}", form.key);
+    let form = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .wrap(Wrap { trim: false });
+    f.render_widget(Clear, area);
+    f.render_widget(form, area);
+}
+
+/// The running timer for the help bar, e.g. "PROJ-1: 25m, time for a break".
+fn describe_timer(timer: &WorkTimer, pomodoro_minutes: Option<u64>) -> String {
+    let elapsed = timer.elapsed_secs();
+    let text = format!("{}: {}", timer.key, describe_duration(elapsed));
+    match pomodoro_minutes {
+        Some(minutes) if minutes > 0 && elapsed >= minutes * 60 => {
+            format!("{}, time for a break", text)
+        }
+        _ => text,
+    }
+}
+
+fn draw_stopped_timer<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app: &State,
+    timer: &WorkTimer,
+    area: Rect,
+) {
+    let area = centered_rect(60, 20, area);
+    let question = format!(
+        "Log {} on {}?",
+        describe_duration(timer.loggable_secs()),
+        timer.key
+    );
+    let prompt = Paragraph::new(question)
+        .style(Theme::new(&app.config).input())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Transitions"),
-        )
-        .highlight_style(
-            Style::default()
-                .bg(Color::LightGreen)
-                .add_modifier(Modifier::BOLD),
-        )
-        .highlight_symbol(">> ");
+                .title(text(app.config.locale, Text::TimerStopped)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
 
-    f.render_stateful_widget(transitions, area, &mut app.transitions.state);
+fn draw_clone_offer<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app: &State,
+    offer: &CloneOffer,
+    area: Rect,
+) {
+    let area = centered_rect(60, 20, area);
+    let question = format!("Clone {} into {}?", offer.url, offer.path.display());
+    let prompt = Paragraph::new(question)
+        .style(Theme::new(&app.config).input())
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::CloneRepo)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
 }
 
-fn draw_branch_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+fn draw_transition_question<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
     let area = centered_rect(60, 20, area);
-    let input = Paragraph::new(app.new_branch_name())
-        .style(Style::default().fg(Color::Yellow))
+    let question = app.transition_question().unwrap_or_default();
+    let prompt = Paragraph::new(question)
+        .style(Theme::new(&app.config).input())
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::Transitions)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
+fn draw_branch_question<B: tui::backend::Backend>(
+    f: &mut Frame<B>,
+    app: &State,
+    question: String,
+    area: Rect,
+) {
+    let area = centered_rect(60, 20, area);
+    let prompt = Paragraph::new(question)
+        .style(Theme::new(&app.config).input())
+        .wrap(Wrap { trim: false })
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(text(app.config.locale, Text::BranchExists)),
+        );
+    f.render_widget(Clear, area);
+    f.render_widget(prompt, area);
+}
+
+fn draw_reauth_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, area: Rect) {
+    let area = centered_rect(60, 20, area);
+    // Don't show the token itself in case the screen is being shared
+    let masked = "*".repeat(app.reauth_input.as_ref().map_or(0, |token| token.chars().count()));
+    let input = Paragraph::new(masked.clone())
+        .style(Theme::new(&app.config).input())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Enter new branch name"),
+                .title(text(app.config.locale, Text::TokenRejected)),
         );
     f.render_widget(Clear, area);
     f.render_widget(input, area);
     f.set_cursor(
         // Put cursor past the end of the input text
-        area.x + app.new_branch_name().len() as u16 + 1,
+        area.x + masked.len() as u16 + 1,
         // Move one line down, from the border to the input line
         area.y + 1,
     );
@@ -200,13 +1920,12 @@ fn draw_branch_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &State, ar
 fn draw_project_input<B: tui::backend::Backend>(f: &mut Frame<B>, app: &mut State, area: Rect) {
     let area = centered_rect(60, 20, area);
     let input = Paragraph::new(app.raw_input_clone())
-        .style(Style::default().fg(Color::Yellow))
+        .style(Theme::new(&app.config).input())
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .title("Enter default project key"),
+                .title(text(app.config.locale, Text::DefaultProjectKey)),
         );
-    f.render_widget(Clear, area);
     f.render_widget(input, area);
     f.set_cursor(
         // Put cursor past the end of the input text
@@ -242,34 +1961,102 @@ fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
         .split(popup_layout[1])[1]
 }
 
-pub async fn init_ui<'a>(mut state_rx: StateRx) -> Result<()> {
-    // Write to stdout, and enter an alternate screen, to avoid overwriting existing
-    // terminal output
-    let mut stdout = stdout();
-
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-
-    // Drop into 'raw' mode, to enable direct drawing to the terminal
-    enable_raw_mode()?;
+/// Draw states until the updater stops, returning the last one, which says how the app finished.
+/// Drawing to stderr leaves stdout free for output meant for scripts.
+pub async fn init_ui(state_rx: StateRx, to_stderr: bool, plain: bool) -> Result<Option<State>> {
+    match (plain, to_stderr) {
+        (true, true) => run_plain(state_rx, stderr()).await,
+        (true, false) => run_plain(state_rx, stdout()).await,
+        (false, true) => run_ui(state_rx, stderr()).await,
+        (false, false) => run_ui(state_rx, stdout()).await,
+    }
+}
 
+async fn run_ui<W: Write>(mut state_rx: StateRx, out: W) -> Result<Option<State>> {
     // Build terminal. We're using crossterm for *nix + Windows support
-    let backend = CrosstermBackend::new(stdout);
+    let backend = CrosstermBackend::new(out);
     let mut terminal = Terminal::new(backend)?;
+    take_over_terminal(&mut terminal)?;
 
-    // Clear the screen, readying it for output
-    terminal.clear()?;
-
+    let mut last_state: Option<State> = None;
+    // Nothing's drawn between giving the terminal back and being continued
+    let mut suspended = false;
     while let Some(mut state) = state_rx.recv().await {
-        terminal.draw(|f| draw(f, &mut state))?;
+        if state.exit_code.is_some() {
+            last_state = Some(state);
+            break;
+        }
+        // Where the issue list is scrolled to is only known from drawing it
+        if let Some(last) = &last_state {
+            state.issues_offset = last.issues_offset;
+        }
+        if state.suspend && !suspended {
+            restore_terminal(&mut terminal)?;
+            suspended = true;
+            stop_process();
+        }
+        // The shell had the terminal while the app was stopped, so it's set up again from scratch
+        if state.resumed {
+            take_over_terminal(&mut terminal)?;
+            suspended = false;
+        }
+        if !suspended {
+            draw_frame(&mut terminal, &mut state)?;
+        }
+        last_state = Some(state);
     }
 
+    restore_terminal(&mut terminal)?;
+
+    Ok(last_state)
+}
+
+type CrosstermTerminal<W> = Terminal<CrosstermBackend<W>>;
+
+/// Terminals supporting synchronized output hold off showing changes until the whole frame has
+/// been written, others ignore these. crossterm only has commands for them from 0.26.
+const BEGIN_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b[?2026h";
+const END_SYNCHRONIZED_UPDATE: &[u8] = b"\x1b[?2026l";
+
+fn draw_frame<W: Write>(terminal: &mut CrosstermTerminal<W>, state: &mut State) -> Result<()> {
+    let _span = tracing::info_span!("draw").entered();
+    terminal.backend_mut().write_all(BEGIN_SYNCHRONIZED_UPDATE)?;
+    terminal.draw(|f| draw(f, state))?;
+    terminal.backend_mut().write_all(END_SYNCHRONIZED_UPDATE)?;
+    terminal.backend_mut().flush()?;
+    Ok(())
+}
+
+fn take_over_terminal<W: Write>(terminal: &mut CrosstermTerminal<W>) -> Result<()> {
+    // Enter an alternate screen, to avoid overwriting existing terminal output
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableMouseCapture
+    )?;
+    // Input is read with the newer crossterm, so it's the one that asks for focus events
+    crossterm_events::execute!(terminal.backend_mut(), EnableFocusChange)?;
+
+    // Drop into 'raw' mode, to enable direct drawing to the terminal
+    enable_raw_mode()?;
+
+    // Clear the screen, readying it for output. This is the only full redraw, on starting and
+    // after resuming from Ctrl-Z, when the screen's contents are unknown
+    terminal.clear()?;
+    Ok(())
+}
+
+fn restore_terminal<W: Write>(terminal: &mut CrosstermTerminal<W>) -> Result<()> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
         DisableMouseCapture
     )?;
+    crossterm_events::execute!(terminal.backend_mut(), DisableFocusChange)?;
     disable_raw_mode()?;
     terminal.show_cursor()?;
-
     Ok(())
 }
+
+#[cfg(test)]
+mod tests;
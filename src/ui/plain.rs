@@ -0,0 +1,400 @@
+//! A frontend for terminal screen readers, started with `--plain`. Rather than redrawing the
+//! screen, each change worth knowing about (a new selection, mode, error) is printed as a line of
+//! its own, so it's read out once and stays in the scrollback.
+use super::*;
+use crate::utils::StatefulList;
+use std::mem::discriminant;
+
+pub async fn run_plain<W: Write>(mut state_rx: StateRx, mut out: W) -> Result<Option<State>> {
+    // Raw mode is still needed to get key presses as they happen, but there's no alternate
+    // screen, so everything printed is left behind
+    enable_raw_mode()?;
+
+    let mut last_state: Option<State> = None;
+    while let Some(state) = state_rx.recv().await {
+        if state.exit_code.is_some() {
+            last_state = Some(state);
+            break;
+        }
+        if state.suspend {
+            disable_raw_mode()?;
+            stop_process();
+        }
+        if state.resumed {
+            enable_raw_mode()?;
+        }
+        for line in announcements(last_state.as_ref(), &state) {
+            // Raw mode doesn't turn a newline into a carriage return as well
+            write!(out, "{}\r\n", line)?;
+        }
+        out.flush()?;
+        last_state = Some(state);
+    }
+
+    disable_raw_mode()?;
+    Ok(last_state)
+}
+
+/// A name read out before whatever's selected in a list, and how to find the selection.
+type Selection = (&'static str, fn(&State) -> Option<String>);
+
+/// Lines describing what's different about `after`, or everything worth reading out when
+/// there's nothing to compare with.
+pub fn announcements(before: Option<&State>, after: &State) -> Vec<String> {
+    let mut lines = vec![];
+    let locale = after.config.locale;
+    let changed = |field: fn(&State) -> Option<String>| {
+        let value = field(after);
+        if before.map(field) != Some(value.clone()) {
+            value
+        } else {
+            None
+        }
+    };
+
+    if let Some(error) = changed(|state| state.error.clone()) {
+        lines.push(format!("Error: {}", error));
+    }
+    if let Some(notice) = changed(|state| state.notice.clone()) {
+        lines.push(notice);
+    }
+    // Requests are added to the list as they're held back, so only the new ones are read out
+    let unsent_before = before.map_or(&[][..], |state| &state.unsent_requests[..]);
+    let unsent = match after.unsent_requests.strip_prefix(unsent_before) {
+        Some(added) => added,
+        None => &after.unsent_requests[..],
+    };
+    for request in unsent {
+        lines.push(text(locale, Text::NotSent).replace("{request}", request));
+    }
+    if changed(|state| state.reauth_input.as_ref().map(|_| String::new())).is_some() {
+        lines.push(text(locale, Text::TokenRejected).to_string());
+        lines.push(text(locale, Text::ReauthHelp).to_string());
+    }
+    if let Some(key) = changed(|state| state.stopped_timer.as_ref().map(|t| t.key.clone())) {
+        if let Some(timer) = &after.stopped_timer {
+            let duration = describe_duration(timer.loggable_secs());
+            lines.push(format!("Log {} on {}?", duration, key));
+            lines.push(text(locale, Text::StoppedTimerHelp).to_string());
+        }
+    }
+    if changed(|state| state.clone_offer.as_ref().map(|o| o.url.clone())).is_some() {
+        if let Some(offer) = &after.clone_offer {
+            lines.push(format!(
+                "Clone {} into {}?",
+                offer.url,
+                offer.path.display()
+            ));
+            lines.push(text(locale, Text::CloneOfferHelp).to_string());
+        }
+    }
+    if let Some(name) = changed(|state| state.existing_branch.clone()) {
+        lines.push(format!("{} is already there, check it out instead?", name));
+        lines.push(text(locale, Text::ExistingBranchHelp).to_string());
+    }
+    if let Some(name) = changed(|state| state.branch_collision.clone()) {
+        lines.push(format!("{} is taken, what should happen?", name));
+        lines.push(text(locale, Text::BranchCollisionHelp).to_string());
+    }
+
+    let mode_changed = before
+        .is_none_or(|state| discriminant(&state.input_mode) != discriminant(&after.input_mode));
+    if mode_changed {
+        lines.push(mode_help(locale, &after.input_mode).to_string());
+    }
+
+    if after.issues_fetched() {
+        if let Some(count) = changed(|state| Some(state.issues.items.len().to_string())) {
+            lines.push(format!("{} issues", count));
+        }
+    }
+    let issue_changed = changed(|state| state.selected_issue_key()).is_some();
+    if let Some(issue) = after.selected_issue().filter(|_| issue_changed) {
+        let host = match &issue.host {
+            Some(host) => format!("[{}] ", host),
+            None => String::new(),
+        };
+        lines.push(format!(
+            "{}{}: {}, {}, {}",
+            host, issue.key, issue.summary, issue.status, issue.assignee_name
+        ));
+        // The list's markers for work already started
+        let started = [
+            (&after.branched, "Has a branch"),
+            (&after.pull_requests, "Has an open PR"),
+        ];
+        let started = started.iter().filter(|(keys, _)| keys.contains(&issue.key));
+        lines.extend(started.map(|(_, line)| line.to_string()));
+        if issue.watching {
+            lines.push("Watching".to_string());
+        }
+    }
+
+    // Time tracking arrives after the detail view opens
+    let time_tracking = |state: &State| {
+        let key = state.selected_issue_key()?;
+        state.time_tracking.get(&key)?.describe()
+    };
+    if let InputMode::IssueDetail = after.input_mode {
+        if let Some(line) = changed(time_tracking) {
+            lines.push(line);
+        }
+    }
+
+    let comment_count = |state: &State| {
+        let older = state.older_comments?;
+        Some((older + state.comments.len()).to_string())
+    };
+    let audit_report = |state: &State| Some(report_lines(state.audit.as_ref()?).join("\n"));
+    match after.input_mode {
+        InputMode::IssueDetail if mode_changed || issue_changed => {
+            if let Some(issue) = after.selected_issue() {
+                lines.extend(issue.description.lines().map(String::from));
+            }
+        }
+        InputMode::IssuesList | InputMode::IssueDetail => {}
+        // The account arrives after the screen opens
+        InputMode::AccountInfo
+            if changed(|state| state.account.as_ref().map(|a| a.login.clone())).is_some() =>
+        {
+            lines.extend(account_lines(after));
+        }
+        // Comments arrive after the view opens, and older ones are added above
+        // The report arrives after the screen opens, and again after running it over
+        InputMode::AuditView => {
+            if let Some(report) = changed(audit_report) {
+                lines.extend(report.lines().map(String::from));
+            }
+        }
+        InputMode::CommentsView if changed(comment_count).is_some() => {
+            for comment in &after.comments {
+                lines.push(format!("{} - {}", comment.author, comment.created));
+                lines.extend(comment.body.lines().map(String::from));
+            }
+        }
+        InputMode::ConfirmTransition if mode_changed => {
+            if let Some(question) = after.transition_question() {
+                lines.push(question);
+            }
+        }
+        _ if mode_changed => {}
+        InputMode::Editing
+        | InputMode::EditingSummary
+        | InputMode::EditingDefaultProject
+        | InputMode::LinkingPullRequest
+        | InputMode::CreatingSubtask
+        | InputMode::NamingView
+        | InputMode::EditingJql
+        | InputMode::AssigningIssue
+        | InputMode::UploadingAttachment
+        | InputMode::LinkingIssue
+        | InputMode::EditingLabels
+        | InputMode::BulkEditValue => {
+            // Typed characters aren't echoed in raw mode, so read back what's been typed
+            if let Some(input) = changed(|state| Some(state.raw_input_clone())) {
+                lines.push(input);
+            }
+        }
+        // Read back the line of the comment being typed on
+        InputMode::AddingComment => {
+            let line = |state: &State| {
+                let draft = state.comment_draft.as_ref()?;
+                let (_, row) = draft.editor.cursor();
+                draft.editor.lines().get(row).cloned()
+            };
+            if let Some(line) = changed(line) {
+                lines.push(line);
+            }
+        }
+        InputMode::LoggingWork => {
+            let field = |state: &State| {
+                let form = state.worklog.as_ref()?;
+                let values = [&form.time, &form.comment];
+                Some(format!("{}: {}", WORKLOG_ROWS[form.row], values[form.row]))
+            };
+            if let Some(field) = changed(field) {
+                lines.push(field);
+            }
+        }
+        InputMode::FilteringBoards => {
+            if let Some(filter) = changed(|state| Some(state.board_filter.clone())) {
+                lines.push(filter);
+            }
+        }
+        _ => {}
+    }
+    if let Some(count) = changed(comment_count) {
+        lines.push(format!("{} comments", count));
+    }
+
+    let bulk_progress = |state: &State| {
+        let progress = state.bulk_progress.as_ref()?;
+        Some(format!("{} of {} done", progress.done(), progress.total()))
+    };
+    if let Some(progress) = changed(bulk_progress) {
+        lines.push(progress);
+    }
+    // Issues are updated in order, so the last failure is the latest
+    let latest_failure = |state: &State| {
+        let progress = state.bulk_progress.as_ref()?;
+        progress
+            .items
+            .iter()
+            .rev()
+            .find_map(|(key, status)| match status {
+                ItemStatus::Failed(err) => Some(format!("{}: {}", key, err)),
+                _ => None,
+            })
+    };
+    if let Some(failure) = changed(latest_failure) {
+        lines.push(failure);
+    }
+    let issues_loading = |state: &State| {
+        let search = state.issues_loading.as_ref()?;
+        Some(format!(
+            "Loaded {} of {} issues",
+            search.loaded, search.total
+        ))
+    };
+    if let Some(loading) = changed(issues_loading) {
+        lines.push(loading);
+    }
+
+    let filters = |state: &State| {
+        let chips = FilterChip::active(&state.config);
+        let chips: Vec<String> = chips.iter().map(|c| c.describe(&state.config)).collect();
+        Some(chips.join(", "))
+    };
+    if let Some(filters) = changed(filters) {
+        lines.push(format!("Filters: {}", filters));
+    }
+    let focused_chip = |state: &State| {
+        let chip = state.focused_chip()?;
+        Some(chip.describe(&state.config))
+    };
+    if let Some(chip) = changed(focused_chip) {
+        lines.push(format!("Filter: {}", chip));
+    }
+    // Sub-tasks arrive after the detail view opens
+    let subtasks = |state: &State| {
+        let subtasks = state.subtasks.get(&state.selected_issue_key()?)?;
+        let keys: Vec<&str> = subtasks.iter().map(|issue| issue.key.as_str()).collect();
+        Some(keys.join(", ")).filter(|keys| !keys.is_empty())
+    };
+    if let Some(subtasks) = changed(subtasks) {
+        lines.push(format!("Sub-tasks: {}", subtasks));
+    }
+
+    let head_branch = |state: &State| state.head_branch.clone();
+    if let Some(branch) = changed(head_branch) {
+        lines.push(format!("On branch {}", branch));
+    }
+
+    // The sprint's or backlog's issues arrive after the view opens
+    let sprint = |state: &State| {
+        let view = state.sprint.as_ref().filter(|view| view.loaded)?;
+        let sprint = view.sprint.as_ref();
+        let name = match sprint {
+            _ if view.backlog => "Backlog",
+            Some(sprint) => &sprint.name,
+            None => "No sprint",
+        };
+        Some(format!("{}: {} issues", name, view.issues.items.len()))
+    };
+    if let Some(sprint) = changed(sprint) {
+        lines.push(sprint);
+    }
+
+    let selections: [Selection; 19] = [
+        ("Branch", |state| {
+            selected(&state.branches, |entry| match entry {
+                BranchEntry::Existing(branch) => branch.name.clone(),
+                BranchEntry::CreateNew => "Create new branch".to_string(),
+            })
+        }),
+        ("Board", |state| selected(&state.boards, |b| b.name.clone())),
+        ("Transition", |state| {
+            selected(&state.transitions, |t| t.name.clone())
+        }),
+        ("Sort", |state| selected(&state.sort_menu, String::clone)),
+        ("Project", |state| {
+            selected(&state.project_menu, String::clone)
+        }),
+        ("Move to", |state| {
+            let picker = state.sprint_picker.as_ref()?;
+            selected(&picker.sprints, |sprint| sprint.name.clone())
+        }),
+        ("View", |state| {
+            selected(&state.view_menu, |view| view.name.clone())
+        }),
+        ("Sprint issue", |state| {
+            let view = state.sprint.as_ref()?;
+            selected(&view.issues, |i| {
+                format!("{}: {}, {}", i.key, i.summary, i.status)
+            })
+        }),
+        ("Reviewer", |state| {
+            let picker = &state.reviewer_picker;
+            selected(&picker.suggested, |name| {
+                let picked = if picker.picked.contains(name) {
+                    "picked"
+                } else {
+                    "not picked"
+                };
+                format!("{}, {}", name, picked)
+            })
+        }),
+        ("Assignee", |state| {
+            selected(&state.assignees, |assignee| {
+                describe_assignee(state.config.locale, assignee)
+            })
+        }),
+        ("Priority", |state| {
+            selected(&state.priorities, String::clone)
+        }),
+        ("Label", |state| selected(&state.labels, String::clone)),
+        ("Link as", |state| {
+            let form = state.link_form.as_ref()?;
+            selected(&form.relations, |r| r.description.clone())
+        }),
+        ("Link to", |state| {
+            let form = state.link_form.as_ref()?;
+            selected(&form.targets, |i| format!("{}: {}", i.key, i.summary))
+        }),
+        ("Linked issue", |state| {
+            let view = state.links_view.as_ref()?;
+            selected(&view.links, |l| {
+                format!("{} {}: {}, {}", l.relation, l.key, l.summary, l.status)
+            })
+        }),
+        ("Attachment", |state| {
+            selected(&state.attachments, |a| {
+                format!("{}, {}", a.filename, describe_size(a.size))
+            })
+        }),
+        ("File", |state| selected(&state.local_files, String::clone)),
+        ("Snooze", |state| {
+            selected(&state.snooze_menu, String::clone)
+        }),
+        ("Checklist", |state| {
+            selected(&state.checklist, |item| {
+                let checkbox = if item.checked { "done" } else { "not done" };
+                format!("{}, {}", item.text, checkbox)
+            })
+        }),
+    ];
+    for (name, field) in selections.iter() {
+        if let Some(value) = changed(*field) {
+            lines.push(format!("{}: {}", name, value));
+        }
+    }
+
+    lines
+}
+
+fn selected<T>(list: &StatefulList<T>, describe: impl Fn(&T) -> String) -> Option<String> {
+    list.state
+        .selected()
+        .and_then(|i| list.items.get(i))
+        .map(describe)
+}
@@ -0,0 +1,448 @@
+//! Snapshot tests for rendering. Each test draws a state into an in-memory terminal at a few sizes
+//! and compares the screen with `src/ui/snapshots/<name>_<width>x<height>.txt`.
+//!
+//! Missing snapshots are written on the first run, look them over before committing them. After
+//! an intended change to the UI, run `UPDATE_SNAPSHOTS=1 cargo test` to accept the new rendering.
+use super::*;
+use crate::{
+    audit::Audit,
+    config::Config,
+    git::BranchSummary,
+    jira::{IssueLinkSummary, IssueSummary, TransitionSummary},
+    locale::Locale,
+    state::LinksView,
+    utils::StatefulList,
+};
+use std::{env, fs, path::PathBuf};
+use tui::{backend::TestBackend, buffer::Buffer, style::Color};
+
+const SIZES: [(u16, u16); 2] = [(80, 24), (140, 40)];
+
+fn render(state: &State, width: u16, height: u16) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    let mut state = state.clone();
+    terminal.draw(|f| draw(f, &mut state)).unwrap();
+    buffer_text(terminal.backend().buffer())
+}
+
+/// The screen as plain text, one line per row with trailing spaces trimmed.
+fn buffer_text(buffer: &Buffer) -> String {
+    let width = buffer.area.width as usize;
+    let rows: Vec<String> = buffer
+        .content
+        .chunks(width)
+        .map(|row| {
+            let line: String = row.iter().map(|cell| cell.symbol.as_str()).collect();
+            line.trim_end().to_string()
+        })
+        .collect();
+    format!("{}\n", rows.join("\n"))
+}
+
+fn assert_snapshot(name: &str, state: &State) {
+    for &(width, height) in SIZES.iter() {
+        let name = format!("{}_{}x{}", name, width, height);
+        let actual = render(state, width, height);
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/ui/snapshots")
+            .join(format!("{}.txt", name));
+        match fs::read_to_string(&path) {
+            Ok(expected) if env::var("UPDATE_SNAPSHOTS").is_err() => assert_eq!(
+                expected, actual,
+                "{} renders differently, rerun with UPDATE_SNAPSHOTS=1 if that's intended",
+                name
+            ),
+            _ => {
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, actual).unwrap();
+            }
+        }
+    }
+}
+
+fn issue(key: &str, summary: &str, priority: &str) -> IssueSummary {
+    IssueSummary {
+        key: key.to_string(),
+        summary: summary.to_string(),
+        description: "Steps to reproduce:\n1. Open the app\n2. Press q".to_string(),
+        permalink: format!("https://example.atlassian.net/browse/{}", key),
+        status: "In Progress".to_string(),
+        assignee_name: "Ada Lovelace".to_string(),
+        priority: Some(priority.to_string()),
+        created: "2021-03-01T09:00:00.000+0000".to_string(),
+        updated: "2021-03-02T09:00:00.000+0000".to_string(),
+        ..Default::default()
+    }
+}
+
+fn state_with_issues() -> State {
+    let config = Config {
+        default_project_key: "PROJ".to_string(),
+        ..Default::default()
+    };
+    let mut state = State::with_config(config, vec![]);
+    state.issues = StatefulList::with_items(vec![
+        issue("PROJ-1", "Crash when quitting", "Highest"),
+        issue("PROJ-2", "Add dark mode", "Medium"),
+        issue("PROJ-3", "Typo on the login page", "Low"),
+    ]);
+    state.issues.next();
+    state.branches = StatefulList::with_items(vec![
+        BranchEntry::Existing(BranchSummary {
+            name: "PROJ-1-fix-crash".to_string(),
+        }),
+        BranchEntry::CreateNew,
+    ]);
+    state
+}
+
+#[test]
+fn issues_list() {
+    assert_snapshot("issues_list", &state_with_issues());
+}
+
+#[test]
+fn issues_grouped_by_epic() {
+    let mut state = state_with_issues();
+    state.config.group_by_epic = true;
+    for issue in state.issues.items_mut().iter_mut().take(2) {
+        issue.epic = Some("PROJ-9".to_string());
+    }
+    assert_snapshot("issues_grouped_by_epic", &state);
+
+    state.folded_epics.insert(Some("PROJ-9".to_string()));
+    assert_snapshot("issues_grouped_by_epic_folded", &state);
+}
+
+#[test]
+fn issues_marked_with_started_work() {
+    let mut state = state_with_issues();
+    state.branched.insert("PROJ-1".to_string());
+    state.branched.insert("PROJ-2".to_string());
+    state.pull_requests.insert("PROJ-2".to_string());
+    assert_snapshot("issues_marked_with_started_work", &state);
+}
+
+#[test]
+fn issues_list_only_scrolls_once_the_selection_leaves_it() {
+    let mut state = state_with_issues();
+    let issues = (1..=30).map(|n| issue(&format!("PROJ-{}", n), "Chore", "Low"));
+    state.issues = StatefulList::with_items(issues.collect());
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    let mut select = |state: &mut State, i: usize| {
+        state.issues.state.select(Some(i));
+        terminal.draw(|f| draw(f, state)).unwrap();
+        buffer_text(terminal.backend().buffer())
+    };
+
+    // Moving down past the bottom brings the selection onto the last row
+    select(&mut state, 29);
+    let bottom = state.issues_offset;
+    assert!(bottom > 0);
+    // Moving back up within the window leaves it where it is
+    let screen = select(&mut state, 27);
+    assert_eq!(state.issues_offset, bottom);
+    assert!(screen.contains(">> PROJ-28: Chore"));
+    assert!(screen.contains("PROJ-30: Chore"));
+    // And past the top brings the selection onto the first row
+    select(&mut state, bottom - 1);
+    assert_eq!(state.issues_offset, bottom - 1);
+}
+
+#[test]
+fn empty_issues_list() {
+    let state = State::with_config(Config::default(), vec![]);
+    assert_snapshot("empty_issues_list", &state);
+}
+
+#[test]
+fn help_bar_shows_errors() {
+    let mut state = state_with_issues();
+    state.error = Some("Couldn't reach Jira, check JIRA_HOST and your connection".to_string());
+    assert_snapshot("help_bar_error", &state);
+}
+
+#[test]
+fn help_bar_counts_queued_requests() {
+    let mut state = state_with_issues();
+    state.queued_requests = 3;
+    assert_snapshot("help_bar_queued_requests", &state);
+}
+
+#[test]
+fn help_bar_shows_clipboard_and_rate_limit() {
+    let mut state = state_with_issues();
+    state.clipboard_issue = Some("PROJ-42".to_string());
+    state.rate_limit = Some(RateLimit {
+        remaining: 40,
+        limit: Some(100),
+        reset: Some("2021-03-01T14:05:00Z".to_string()),
+    });
+    assert_snapshot("help_bar_clipboard_rate_limit", &state);
+}
+
+#[test]
+fn issue_detail() {
+    let mut state = state_with_issues();
+    state.input_mode = InputMode::IssueDetail;
+    assert_snapshot("issue_detail", &state);
+}
+
+#[test]
+fn issue_detail_over_its_estimate() {
+    let mut state = state_with_issues();
+    state.input_mode = InputMode::IssueDetail;
+    let tracking = TimeTracking {
+        estimate: Some("1d".to_string()),
+        spent: Some("1d 2h".to_string()),
+        remaining: Some("0m".to_string()),
+        over_budget: true,
+    };
+    state.time_tracking.insert("PROJ-1".to_string(), tracking);
+    assert_snapshot("issue_detail_over_its_estimate", &state);
+}
+
+fn links() -> Vec<IssueLinkSummary> {
+    vec![
+        IssueLinkSummary {
+            relation: "is blocked by".to_string(),
+            key: "PROJ-7".to_string(),
+            summary: "Fix the login page".to_string(),
+            status: "In Progress".to_string(),
+            resolved: false,
+            blocker: true,
+        },
+        IssueLinkSummary {
+            relation: "relates to".to_string(),
+            key: "OPS-3".to_string(),
+            summary: "Rotate certificates".to_string(),
+            status: "Done".to_string(),
+            resolved: true,
+            blocker: false,
+        },
+    ]
+}
+
+#[test]
+fn issue_detail_lists_links() {
+    let mut state = state_with_issues();
+    state.input_mode = InputMode::IssueDetail;
+    state.links.insert("PROJ-1".to_string(), links());
+    assert_snapshot("issue_detail_links", &state);
+}
+
+#[test]
+fn links_view_highlights_the_link_to_go_to() {
+    let mut state = state_with_issues();
+    state.input_mode = InputMode::IssueLinks;
+    let mut view = LinksView {
+        key: "PROJ-1".to_string(),
+        links: StatefulList::with_items(links()),
+    };
+    view.links.state.select(Some(1));
+    state.links_view = Some(view);
+    assert_snapshot("links_view", &state);
+}
+
+#[test]
+fn watched_issues_are_marked_in_the_list() {
+    let mut state = state_with_issues();
+    state.issues.items_mut()[1].watching = true;
+    assert_snapshot("watched_issues", &state);
+}
+
+#[test]
+fn audit_report() {
+    let mut state = state_with_issues();
+    state.input_mode = InputMode::AuditView;
+    state.audit = Some(Audit {
+        finished: vec![("PROJ-1-fix-crash".to_string(), "Done".to_string())],
+        untracked: vec!["PROJ-404-gone".to_string()],
+        failed: vec![(
+            "PROJ-7-flaky-test".to_string(),
+            "Jira had a problem handling the request (500)".to_string(),
+        )],
+        unstarted: vec![issue("PROJ-2", "Add dark mode", "Medium")],
+    });
+    assert_snapshot("audit_report", &state);
+}
+
+#[test]
+fn sort_menu_popup() {
+    let mut state = state_with_issues();
+    state.sort_menu = StatefulList::with_items(vec![
+        "Jira order".to_string(),
+        "Triage: priority, then oldest".to_string(),
+        "Priority colors: off".to_string(),
+    ]);
+    state.sort_menu.next();
+    state.modes_below = vec![InputMode::IssuesList];
+    state.input_mode = InputMode::SortMenu;
+    assert_snapshot("sort_menu", &state);
+}
+
+#[test]
+fn rename_popup() {
+    let mut state = state_with_issues();
+    state.modes_below = vec![InputMode::IssuesList];
+    state.input_mode = InputMode::EditingSummary;
+    assert_snapshot("rename_popup", &state);
+}
+
+#[test]
+fn reauth_popup_masks_the_token() {
+    let mut state = state_with_issues();
+    state.reauth_input = Some("secret-token".to_string());
+    let screen = render(&state, 80, 24);
+    assert!(!screen.contains("secret-token"));
+    assert_snapshot("reauth_popup", &state);
+}
+
+#[test]
+fn transition_question_opens_over_the_picker_and_dims_the_list() {
+    let mut state = state_with_issues();
+    state.config.confirm_transitions = vec!["Done".to_string()];
+    state.transitions = StatefulList::with_items(vec![TransitionSummary {
+        key: "31".to_string(),
+        name: "Done".to_string(),
+    }]);
+    state.transitions.next();
+    state.modes_below = vec![InputMode::IssuesList, InputMode::UpdateIssueStatus];
+    state.input_mode = InputMode::ConfirmTransition;
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|f| draw(f, &mut state.clone())).unwrap();
+
+    // Everything but the help bar is faded under the question
+    let buffer = terminal.backend().buffer();
+    assert!(buffer.get(0, 0).modifier.contains(Modifier::DIM));
+    assert!(!buffer.get(0, 23).modifier.contains(Modifier::DIM));
+    assert_snapshot("transition_question", &state);
+}
+
+#[test]
+fn monochrome_draws_without_colors() {
+    let mut state = state_with_issues();
+    state.config.monochrome = true;
+    state.config.priority_colors = true;
+    state.error = Some("Jira is down".to_string());
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+    terminal.draw(|f| draw(f, &mut state)).unwrap();
+
+    let cells = &terminal.backend().buffer().content;
+    assert!(cells
+        .iter()
+        .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset));
+    // The selection still stands out
+    assert!(cells
+        .iter()
+        .any(|cell| cell.modifier.contains(Modifier::REVERSED)));
+}
+
+/// Keys are bound in `handle_input`'s match arms, where a key bound twice in the same mode is only
+/// a warning, and in the issue list's keymap, which is checked when starting up. The help text
+/// is written separately, in each language, so check it doesn't promise the same key for two
+/// things.
+#[test]
+fn help_lists_each_key_once_per_mode() {
+    let modes = [
+        InputMode::IssuesList,
+        InputMode::BoardsList,
+        InputMode::Editing,
+        InputMode::UpdateIssueStatus,
+        InputMode::IssueLinks,
+        InputMode::IssueDetail,
+        InputMode::BulkEditAction,
+        InputMode::BulkEditValue,
+        InputMode::BulkEditProgress,
+        InputMode::EditingDescription,
+        InputMode::ConfirmDescription,
+        InputMode::EditingSummary,
+        InputMode::SortMenu,
+        InputMode::SnoozeMenu,
+        InputMode::SnoozedList,
+        InputMode::ActivityFeed,
+        InputMode::EditingDefaultProject,
+        InputMode::SmartCommit,
+        InputMode::LoggingWork,
+        InputMode::SearchingDetail,
+        InputMode::FilteringBoards,
+        InputMode::SwitchingProject,
+        InputMode::PickingReviewers,
+        InputMode::LinkingPullRequest,
+        InputMode::CommentsView,
+        InputMode::AddingComment,
+        InputMode::AssigningIssue,
+        InputMode::AccountInfo,
+        InputMode::SettingPriority,
+        InputMode::EditingLabels,
+        InputMode::CreatingSubtask,
+        InputMode::SprintView,
+        InputMode::PickingSprint,
+        InputMode::PickingView,
+        InputMode::NamingView,
+        InputMode::AttachmentsView,
+        InputMode::UploadingAttachment,
+        InputMode::AuditView,
+        InputMode::PickingLinkType,
+        InputMode::LinkingIssue,
+        InputMode::EditingJql,
+        InputMode::ConfirmTransition,
+    ];
+    let locales = [Locale::En, Locale::De, Locale::Es];
+    let helps = modes
+        .iter()
+        .flat_map(|mode| locales.iter().map(move |&locale| mode_help(locale, mode)));
+    for help in helps {
+        let mut seen = vec![];
+        for entry in help.split(" - ") {
+            let keys = match entry.split_once(": ") {
+                Some((keys, _)) => keys,
+                None => continue,
+            };
+            // `/` on its own is the search key, otherwise it separates keys doing the same thing
+            let keys: Vec<&str> = if keys == "/" {
+                vec![keys]
+            } else {
+                keys.split('/').collect()
+            };
+            for key in keys {
+                assert!(!seen.contains(&key), "{} is listed twice in: {}", key, help);
+                seen.push(key);
+            }
+        }
+    }
+}
+
+#[test]
+fn plain_output_reads_out_changes() {
+    let mut state = state_with_issues();
+    let first = plain::announcements(None, &state);
+    assert!(first.contains(&mode_help(Locale::En, &InputMode::IssuesList).to_string()));
+    assert!(first.iter().any(|line| line.starts_with("PROJ-1: ")));
+
+    let before = state.clone();
+    state.issues.next();
+    state.error = Some("Jira is down".to_string());
+    let lines = plain::announcements(Some(&before), &state);
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0], "Error: Jira is down");
+    assert!(lines[1].starts_with("PROJ-2: "));
+
+    // Nothing changed, nothing to say
+    assert!(plain::announcements(Some(&state), &state).is_empty());
+}
+
+#[test]
+fn every_request_held_back_is_listed() {
+    let mut state = state_with_issues();
+    state.unsent_requests = vec!["PUT /issue/PROJ-1".to_string()];
+    let before = state.clone();
+    state
+        .unsent_requests
+        .push("POST /issue/PROJ-1/comment".to_string());
+    let lines = plain::announcements(Some(&before), &state);
+    assert_eq!(lines, ["Dry run, not sent: POST /issue/PROJ-1/comment"]);
+
+    let screen = render(&state, 140, 12);
+    assert!(screen.contains("Dry run, not sent: PUT /issue/PROJ-1; POST /issue/PROJ-1/comment"));
+}
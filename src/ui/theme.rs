@@ -0,0 +1,152 @@
+//! Styles for each kind of thing drawn. With `NO_COLOR` set, or `monochrome` in the config, they
+//! use only bold, underline, dim and reverse video, so nothing relies on telling colors apart.
+use crate::config::Config;
+use std::env;
+use tui::style::{Color, Modifier, Style};
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    monochrome: bool,
+}
+
+impl Theme {
+    /// See https://no-color.org, any non-empty value turns colors off.
+    pub fn new(config: &Config) -> Theme {
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        Theme {
+            monochrome: config.monochrome || no_color,
+        }
+    }
+
+    fn pick(self, color: Style, monochrome: Style) -> Style {
+        if self.monochrome {
+            monochrome
+        } else {
+            color
+        }
+    }
+
+    /// Entries in lists.
+    pub fn item(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Black).bg(Color::White),
+            Style::default(),
+        )
+    }
+
+    /// The selected entry of the focused list.
+    pub fn selected(self) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        self.pick(
+            style.bg(Color::LightGreen),
+            style.add_modifier(Modifier::REVERSED),
+        )
+    }
+
+    /// Filters shown above the issue list.
+    pub fn chip(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Black).bg(Color::Cyan),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )
+    }
+
+    /// The field being filled in on a form.
+    pub fn focused(self) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        self.pick(
+            style.fg(Color::Yellow),
+            style.add_modifier(Modifier::UNDERLINED),
+        )
+    }
+
+    /// Text typed into popups.
+    pub fn input(self) -> Style {
+        self.pick(Style::default().fg(Color::Yellow), Style::default())
+    }
+
+    /// Secondary details, like what changed in the activity feed.
+    pub fn faint(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::DarkGray),
+            Style::default().add_modifier(Modifier::DIM),
+        )
+    }
+
+    pub fn help(self) -> Style {
+        self.pick(Style::default().fg(Color::White), Style::default())
+    }
+
+    pub fn error(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Red),
+            Style::default().add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+        )
+    }
+
+    pub fn notice(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Green),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    /// Linked issues that block the current one and are still open.
+    pub fn blocker(self) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        self.pick(
+            style.fg(Color::Red),
+            style.add_modifier(Modifier::UNDERLINED),
+        )
+    }
+
+    pub fn added(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Green),
+            Style::default().add_modifier(Modifier::BOLD),
+        )
+    }
+
+    pub fn removed(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::Red),
+            Style::default().add_modifier(Modifier::DIM),
+        )
+    }
+
+    /// Search hits in the detail view.
+    pub fn search_match(self) -> Style {
+        self.pick(
+            Style::default().bg(Color::Yellow).fg(Color::Black),
+            Style::default().add_modifier(Modifier::REVERSED | Modifier::UNDERLINED),
+        )
+    }
+
+    pub fn progress(self) -> Style {
+        self.pick(
+            Style::default().fg(Color::LightGreen),
+            Style::default().add_modifier(Modifier::REVERSED),
+        )
+    }
+
+    /// Issue keys colored by priority, 0 being the most severe. Without colors only the two
+    /// most severe levels stand out.
+    pub fn severity(self, severity: u8) -> Style {
+        let style = Style::default().add_modifier(Modifier::BOLD);
+        if !self.monochrome {
+            let color = match severity {
+                0 => Color::Red,
+                1 => Color::LightRed,
+                2 => Color::Yellow,
+                3 => Color::Green,
+                _ => Color::Blue,
+            };
+            return style.fg(color);
+        }
+        match severity {
+            0 => style.add_modifier(Modifier::UNDERLINED),
+            1 => style,
+            _ => Style::default(),
+        }
+    }
+}
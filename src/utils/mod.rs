@@ -1,26 +1,40 @@
+use std::sync::Arc;
 use tui::widgets::ListState;
 
+/// Items are shared between clones, since state is cloned for every update sent to the UI and
+/// some lists can run to thousands of items. They're copied only when changed while shared.
 #[derive(Clone)]
 pub struct StatefulList<T> {
     pub state: ListState,
-    pub items: Vec<T>,
+    pub items: Arc<Vec<T>>,
 }
 
 impl<T> StatefulList<T> {
     pub fn new() -> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
-            items: Vec::new(),
+            items: Arc::new(Vec::new()),
         }
     }
 
     pub fn with_items(items: Vec<T>) -> StatefulList<T> {
         StatefulList {
             state: ListState::default(),
-            items,
+            items: Arc::new(items),
         }
     }
 
+    pub fn set_items(&mut self, items: Vec<T>) {
+        self.items = Arc::new(items);
+    }
+
+    pub fn items_mut(&mut self) -> &mut Vec<T>
+    where
+        T: Clone,
+    {
+        Arc::make_mut(&mut self.items)
+    }
+
     pub fn next(&mut self) {
         let i = match self.state.selected() {
             Some(i) => {
@@ -53,6 +67,33 @@ impl<T> StatefulList<T> {
         }
     }
 
+    /// Like `next`, but skipping items that aren't `shown`, for lists with parts folded away.
+    pub fn next_where(&mut self, shown: impl Fn(&T) -> bool) {
+        self.step_where(true, shown);
+    }
+
+    /// Like `previous`, but skipping items that aren't `shown`.
+    pub fn previous_where(&mut self, shown: impl Fn(&T) -> bool) {
+        self.step_where(false, shown);
+    }
+
+    fn step_where(&mut self, forward: bool, shown: impl Fn(&T) -> bool) {
+        let count = self.items.len();
+        let selected = self.state.selected();
+        // Wrap around like `next` and `previous`, stopping short of a full lap
+        let found = (1..=count)
+            .map(|step| match (selected, forward) {
+                (Some(i), true) => (i + step) % count,
+                (Some(i), false) => (i + count - step % count) % count,
+                (None, true) => step - 1,
+                (None, false) => (count + 1 - step) % count,
+            })
+            .find(|&i| shown(&self.items[i]));
+        if found.is_some() {
+            self.state.select(found);
+        }
+    }
+
     // pub fn get_selected(&mut self) -> Option<T> {
     //     match self.state.selected() {
     //         Some(i) => {
@@ -66,3 +107,27 @@ impl<T> StatefulList<T> {
         self.state.select(None);
     }
 }
+
+/// Pull a Jira issue key out of either a bare key (`PROJ-123`) or a link to an issue
+/// (`https://yourorg.atlassian.net/browse/PROJ-123`).
+pub fn extract_issue_key(text: &str) -> Option<String> {
+    text.split(|c: char| c.is_whitespace() || "/?#&=".contains(c))
+        .find(|part| is_issue_key(part))
+        .map(|key| key.to_string())
+}
+
+pub fn is_issue_key(text: &str) -> bool {
+    let mut parts = text.splitn(2, '-');
+    let (project, number) = match (parts.next(), parts.next()) {
+        (Some(project), Some(number)) => (project, number),
+        _ => return false,
+    };
+
+    project.len() > 1
+        && project.starts_with(|c: char| c.is_ascii_uppercase())
+        && project
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+}
@@ -0,0 +1,164 @@
+//! Listens for Jira webhooks, so changes made elsewhere show up as they happen rather than at
+//! the next poll. Jira has to be able to reach the address, e.g. through a tunnel to this machine.
+use crate::events::{Event, EventsTx};
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Webhook bodies are far smaller than this, anything bigger is turned away unread.
+const MAX_BODY_BYTES: usize = 1 << 20;
+/// The longest request or header line read, and how many header lines there can be.
+const MAX_LINE_BYTES: u64 = 8192;
+const MAX_HEADERS: usize = 100;
+/// How long the whole request has to arrive in, so a sender trickling it in a byte at a time
+/// can't keep a connection open.
+const REQUEST_DEADLINE_SECS: u64 = 5;
+/// Connections handled at once, any more are closed straight away.
+const MAX_CONNECTIONS: usize = 8;
+/// Where senders that can set headers put the secret, instead of on the URL.
+const SECRET_HEADER: &str = "x-webhook-secret";
+
+/// Listen on `address` from a thread of its own, sending an event for each webhook that arrives
+/// with `secret`. Each connection is read on a thread of its own too, so a slow one doesn't hold
+/// up the rest. Returns the address listened on, which has the port picked when asking for
+/// port 0.
+pub fn listen(address: &str, secret: Option<String>, event_tx: EventsTx) -> Result<SocketAddr> {
+    let secret = match secret {
+        Some(secret) if !secret.is_empty() => secret,
+        // Anyone who can reach the port could otherwise make the app refetch at will
+        _ => bail!("Set webhook_secret to listen for webhooks on {}", address),
+    };
+    let listener = TcpListener::bind(address)
+        .with_context(|| format!("Couldn't listen for webhooks on {}", address))?;
+    let address = listener.local_addr()?;
+    let open = Arc::new(AtomicUsize::new(0));
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if event_tx.is_closed() {
+                break;
+            }
+            if open.fetch_add(1, Ordering::SeqCst) >= MAX_CONNECTIONS {
+                open.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+            let (open, secret, event_tx) = (open.clone(), secret.clone(), event_tx.clone());
+            thread::spawn(move || {
+                // Requests that can't be read are dropped, Jira retries failed deliveries
+                if let Ok(Some(event)) = receive(stream, &secret) {
+                    let _ = event_tx.send(event);
+                }
+                open.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    });
+    Ok(address)
+}
+
+/// Reads that give up once the deadline has passed, however the time was spent.
+struct Deadline {
+    stream: TcpStream,
+    deadline: Instant,
+}
+
+impl Read for Deadline {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let left = self.deadline.saturating_duration_since(Instant::now());
+        if left == Duration::ZERO {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.stream.set_read_timeout(Some(left))?;
+        self.stream.read(buf)
+    }
+}
+
+/// Read a line of the request into `line`, failing rather than reading on when it's longer than
+/// `MAX_LINE_BYTES`. Returns how many bytes were read, none at the end of the stream.
+fn read_line<R: BufRead>(reader: &mut R, line: &mut String) -> Result<usize> {
+    line.clear();
+    let read = reader.by_ref().take(MAX_LINE_BYTES).read_line(line)?;
+    if read as u64 == MAX_LINE_BYTES && !line.ends_with('\n') {
+        bail!("A line of the request was too long");
+    }
+    Ok(read)
+}
+
+/// Read a request and answer it, returning the event for the webhook it carried, if any.
+/// Requests without the secret are turned away before their body is read.
+fn receive(mut stream: TcpStream, secret: &str) -> Result<Option<Event>> {
+    let mut reader = BufReader::new(Deadline {
+        stream: stream.try_clone()?,
+        deadline: Instant::now() + Duration::from_secs(REQUEST_DEADLINE_SECS),
+    });
+    let mut line = String::new();
+    read_line(&mut reader, &mut line)?;
+    // e.g. `POST /jira?secret=... HTTP/1.1`
+    let target = line.split_whitespace().nth(1).unwrap_or_default();
+    let mut given = query_param(target, "secret").map(str::to_string);
+    let mut length = 0;
+    // Headers end at the first empty line
+    let mut headers = 0;
+    while read_line(&mut reader, &mut line)? > 0 && !line.trim_end().is_empty() {
+        headers += 1;
+        if headers > MAX_HEADERS {
+            bail!("The request had too many headers");
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse().unwrap_or(0);
+            } else if name.eq_ignore_ascii_case(SECRET_HEADER) {
+                given = Some(value.trim().to_string());
+            }
+        }
+    }
+    if !given.is_some_and(|given| secrets_match(&given, secret)) {
+        stream.write_all(b"HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n")?;
+        return Ok(None);
+    }
+    if length > MAX_BODY_BYTES {
+        stream.write_all(b"HTTP/1.1 413 Payload Too Large\r\nContent-Length: 0\r\n\r\n")?;
+        return Ok(None);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    stream.write_all(b"HTTP/1.1 204 No Content\r\n\r\n")?;
+    let payload: Value = serde_json::from_slice(&body)?;
+    Ok(webhook_event(&payload))
+}
+
+/// The value of a parameter in a request target's query string, as it was sent.
+fn query_param<'a>(target: &'a str, name: &str) -> Option<&'a str> {
+    let (_, query) = target.split_once('?')?;
+    query.split('&').find_map(|param| match param.split_once('=') {
+        Some((key, value)) if key == name => Some(value),
+        _ => None,
+    })
+}
+
+/// Compare the whole of both secrets, so how long it takes doesn't give away how much matched.
+fn secrets_match(given: &str, secret: &str) -> bool {
+    let differences = given.bytes().zip(secret.bytes()).map(|(a, b)| a ^ b);
+    given.len() == secret.len() && differences.fold(0, |all, difference| all | difference) == 0
+}
+
+/// The event for a webhook, going by its `webhookEvent`, e.g. `jira:issue_updated` or
+/// `comment_created`. Webhooks for anything but issues and comments are ignored.
+fn webhook_event(payload: &Value) -> Option<Event> {
+    let kind = payload["webhookEvent"].as_str()?;
+    let key = payload["issue"]["key"].as_str()?.to_string();
+    if kind.starts_with("comment_") {
+        Some(Event::CommentsChanged(key))
+    } else if kind.starts_with("jira:issue_") {
+        Some(Event::IssueChanged(key))
+    } else {
+        None
+    }
+}